@@ -0,0 +1,255 @@
+#!/usr/bin/env python3
+# Duck chess perft counts, worked out without the engine
+#
+# A move generator of its own written from the rules of Duck chess, to check the Duck chess
+# counts within src/perft.rs against something that does not share any code with them:
+#
+# - every turn a piece moves as usual and then the duck moves to any other empty square, the
+#   first time to any empty square. Both parts are a ply of their own
+# - the duck blocks every piece and can not be captured
+# - there is no check, kings can move onto attacked squares and castle out of or through attacks
+# - the game ends once a king is captured, a position without a king has no moves
+#
+# python3 scripts/duck_perft.py [depth] ["fen"]
+# Without a FEN the positions of src/perft.rs are counted
+
+import sys
+
+KNIGHT_STEPS = [(1, 2), (2, 1), (2, -1), (1, -2), (-1, -2), (-2, -1), (-2, 1), (-1, 2)]
+KING_STEPS   = [(1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1), (0, -1), (1, -1)]
+BISHOP_LINES = [(1, 1), (1, -1), (-1, 1), (-1, -1)]
+ROOK_LINES   = [(1, 0), (-1, 0), (0, 1), (0, -1)]
+
+POSITIONS = [
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 4),
+    ("4k3/1P4P1/8/3q4/8/8/1p4p1/4K2R w - - 0 1", 3),
+    ("k7/8/8/8/8/8/8/7K w - - 0 1", 3),
+]
+
+
+# Squares are (file, rank) from (0, 0) = a1 to (7, 7) = h8
+def square_name(square):
+    return "abcdefgh"[square[0]] + str(square[1] + 1)
+
+
+def parse_square(name):
+    return ("abcdefgh".index(name[0]), int(name[1]) - 1)
+
+
+def is_white(piece):
+    return piece.isupper()
+
+
+class Position:
+    def __init__(self, fen):
+        fields = fen.split()
+        self.board = {}
+
+        for rank_index, row in enumerate(fields[0].split("/")):
+            rank = 7 - rank_index
+            file = 0
+
+            for letter in row:
+                if letter.isdigit():
+                    file += int(letter)
+                else:
+                    self.board[(file, rank)] = letter
+                    file += 1
+
+        self.white = fields[1] == "w"
+        self.castling = set(fields[2]) - {"-"}
+        self.en_passant = None if fields[3] == "-" else parse_square(fields[3])
+        self.placing_duck = False
+
+    def copy(self):
+        other = Position.__new__(Position)
+        other.board = dict(self.board)
+        other.white = self.white
+        other.castling = set(self.castling)
+        other.en_passant = self.en_passant
+        other.placing_duck = self.placing_duck
+        return other
+
+    def own(self, piece):
+        return piece != "*" and is_white(piece) == self.white
+
+    def enemy(self, piece):
+        return piece != "*" and is_white(piece) != self.white
+
+    def game_over(self):
+        pieces = self.board.values()
+        return "K" not in pieces or "k" not in pieces
+
+
+def on_board(file, rank):
+    return 0 <= file < 8 and 0 <= rank < 8
+
+
+# The moves of the pieces as (origin, target, promotion)
+def piece_moves(position):
+    moves = []
+
+    for origin, piece in position.board.items():
+        if not position.own(piece):
+            continue
+
+        kind = piece.upper()
+        file, rank = origin
+
+        if kind == "P":
+            step = 1 if position.white else -1
+            start_rank = 1 if position.white else 6
+            last_rank = 7 if position.white else 0
+            targets = []
+
+            ahead = (file, rank + step)
+
+            if on_board(*ahead) and ahead not in position.board:
+                targets.append(ahead)
+                double = (file, rank + 2 * step)
+
+                if rank == start_rank and double not in position.board:
+                    targets.append(double)
+
+            for side in (-1, 1):
+                target = (file + side, rank + step)
+
+                if not on_board(*target):
+                    continue
+
+                if position.enemy(position.board.get(target, "*")) or target == position.en_passant:
+                    targets.append(target)
+
+            for target in targets:
+                if target[1] == last_rank:
+                    for promotion in "QRBN":
+                        moves.append((origin, target, promotion))
+                else:
+                    moves.append((origin, target, None))
+
+            continue
+
+        if kind in "NK":
+            steps = KNIGHT_STEPS if kind == "N" else KING_STEPS
+
+            for file_step, rank_step in steps:
+                target = (file + file_step, rank + rank_step)
+
+                if on_board(*target) and (target not in position.board or position.enemy(position.board[target])):
+                    moves.append((origin, target, None))
+
+        lines = {"B": BISHOP_LINES, "R": ROOK_LINES, "Q": BISHOP_LINES + ROOK_LINES}.get(kind, [])
+
+        for file_step, rank_step in lines:
+            target = (file + file_step, rank + rank_step)
+
+            while on_board(*target):
+                if target in position.board:
+                    if position.enemy(position.board[target]):
+                        moves.append((origin, target, None))
+                    break
+
+                moves.append((origin, target, None))
+                target = (target[0] + file_step, target[1] + rank_step)
+
+    # Castling only needs the squares between the king and the rook to be empty
+    rank = 0 if position.white else 7
+    king = "K" if position.white else "k"
+    rook = "R" if position.white else "r"
+
+    for right, rook_file, between in (("K", 7, (5, 6)), ("Q", 0, (1, 2, 3))):
+        right = right if position.white else right.lower()
+
+        if (
+                right in position.castling
+            and position.board.get((4, rank)) == king
+            and position.board.get((rook_file, rank)) == rook
+            and all((file, rank) not in position.board for file in between)
+        ):
+            moves.append(((4, rank), (6 if rook_file == 7 else 2, rank), None))
+
+    return moves
+
+
+def make_piece_move(position, move):
+    origin, target, promotion = move
+    after = position.copy()
+    piece = after.board.pop(origin)
+
+    # En passant takes the pawn behind the target square
+    if piece.upper() == "P" and target == position.en_passant:
+        del after.board[(target[0], origin[1])]
+
+    after.board[target] = piece if promotion is None else (promotion if position.white else promotion.lower())
+
+    # Castling moves the rook next to the king
+    if piece.upper() == "K" and abs(target[0] - origin[0]) == 2:
+        rook_origin = (7 if target[0] == 6 else 0, origin[1])
+        rook_target = (5 if target[0] == 6 else 3, origin[1])
+        after.board[rook_target] = after.board.pop(rook_origin)
+
+    after.en_passant = None
+
+    if piece.upper() == "P" and abs(target[1] - origin[1]) == 2:
+        after.en_passant = (origin[0], (origin[1] + target[1]) // 2)
+
+    # Moving the king or a rook, or capturing a rook, loses the rights of its corner
+    corners = {(4, 0): "KQ", (0, 0): "Q", (7, 0): "K", (4, 7): "kq", (0, 7): "q", (7, 7): "k"}
+
+    for square in (origin, target):
+        after.castling -= set(corners.get(square, ""))
+
+    after.placing_duck = True
+    return after
+
+
+def duck_moves(position):
+    duck = next((square for square, piece in position.board.items() if piece == "*"), None)
+    return [(duck, (file, rank)) for file in range(8) for rank in range(8) if (file, rank) not in position.board]
+
+
+def make_duck_move(position, move):
+    origin, target = move
+    after = position.copy()
+
+    if origin is not None:
+        del after.board[origin]
+
+    after.board[target] = "*"
+    after.placing_duck = False
+    after.white = not position.white
+    return after
+
+
+def perft(position, depth):
+    if position.game_over():
+        return 0
+
+    if position.placing_duck:
+        moves = duck_moves(position)
+
+        if depth == 1:
+            return len(moves)
+
+        return sum(perft(make_duck_move(position, move), depth - 1) for move in moves)
+
+    moves = piece_moves(position)
+
+    if depth == 1:
+        return len(moves)
+
+    return sum(perft(make_piece_move(position, move), depth - 1) for move in moves)
+
+
+def main():
+    depth = int(sys.argv[1]) if len(sys.argv) > 1 else None
+    positions = [(sys.argv[2], depth or 3)] if len(sys.argv) > 2 else [(fen, depth or max_depth) for fen, max_depth in POSITIONS]
+
+    for fen, max_depth in positions:
+        position = Position(fen)
+        counts = [perft(position, d) for d in range(1, max_depth + 1)]
+        print(fen, counts)
+
+
+if __name__ == "__main__":
+    main()
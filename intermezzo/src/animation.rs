@@ -0,0 +1,188 @@
+// Animations of a game, every position is drawn as in board_image.rs and saved as a GIF or APNG
+//
+// The game is replayed from its first position: a frame for the start and one after every move.
+// What the frames look like is set with Animation_Options:
+//
+// image        -> the options of the board images (size, colors, flipped, highlights, ...), the
+//                 last move is highlighted when image.last_move is set
+// delay        -> how long every move is shown, in milliseconds
+// first_delay  -> how long the starting position is shown
+// last_delay   -> how long the final position is shown before the animation starts again
+// captions     -> a line below the board with the move in SAN, "12... Nf6"
+// evaluation   -> when set the engine searches every position with these limits and the score is
+//                 added to the caption from white's point of view, "+0.35" or "#-3"
+//
+// Only the part of the image that changed is saved for every frame after the first, a move
+// usually changes two or three squares so the files stay small
+
+use crate::board_image::*;
+use crate::chess::*;
+use crate::drawing::*;
+use crate::gif::encode_gif;
+use crate::png::{encode_apng, Animation_Frame};
+use crate::raster::Image;
+use crate::search::*;
+
+const CAPTION_BACKGROUND: Color = Color::rgb(48, 46, 43);
+const CAPTION_COLOR: Color = Color::rgb(255, 255, 255);
+
+#[derive(Clone, Debug)]
+pub struct Animation_Options
+{
+    pub image:       Image_Options,
+    pub delay:       u32,
+    pub first_delay: u32,
+    pub last_delay:  u32,
+    pub captions:    bool,
+    pub evaluation:  Option<Search_Limits>,
+}
+
+impl Default for Animation_Options
+{
+    fn default() -> Self
+    {
+        return Animation_Options
+        {
+            image: Image_Options::default(),
+            delay: 1000,
+            first_delay: 1000,
+            last_delay: 3000,
+            captions: true,
+            evaluation: None,
+        };
+    }
+}
+
+// The score of the position from white's point of view, the result when the game is over
+fn evaluation_text(board: &mut Chess_Board, search: &mut Search, limits: &Search_Limits) -> String
+{
+    let side = board.details.side_to_move;
+
+    if let Some(outcome) = board.outcome()
+    {
+        return match outcome.winner(side)
+        {
+            WHITE => String::from("1-0"),
+            BLACK => String::from("0-1"),
+            _     => String::from("1/2-1/2"),
+        };
+    }
+
+    let info = search.search(board, limits, &mut |_| {});
+    let score = info.score * side as i32;
+
+    if is_mate_score(score)
+    {
+        let moves = (MATE - score.abs() + 1) / 2;
+        return format!("#{}", if score > 0 { moves } else { -moves });
+    }
+
+    return format!("{:+.2}", score as f64 / 100.0);
+}
+
+impl Chess_Board
+{
+    // The images of every position of the game played on the board, each with how long it is shown
+    pub fn animation_frames(&self, options: &Animation_Options) -> Vec<(Image, u32)>
+    {
+        let mut replay = self.clone();
+
+        while !replay.moves.is_empty()
+        {
+            replay.unmake_move();
+        }
+
+        let mut search = Search::new();
+        let mut frames = vec![];
+
+        for index in 0..=self.moves.len()
+        {
+            let mut caption = String::from("Start");
+
+            if index > 0
+            {
+                let played = self.moves[index - 1];
+                let number = replay.details.fullmove_number;
+                let dots = if replay.details.side_to_move == WHITE { "." } else { "..." };
+
+                caption = format!("{}{} {}", number, dots, replay.move_to_san(played));
+                replay.make_move(played);
+            }
+
+            let mut drawing = replay.to_drawing(&options.image);
+
+            if options.captions
+            {
+                let evaluation = match &options.evaluation
+                {
+                    Some(limits) => evaluation_text(&mut replay, &mut search, limits),
+                    None         => String::new(),
+                };
+
+                Self::add_caption(&mut drawing, &caption, &evaluation);
+            }
+
+            let delay = match index
+            {
+                _ if index == self.moves.len() => options.last_delay,
+                0                              => options.first_delay,
+                _                              => options.delay,
+            };
+
+            frames.push((drawing.rasterize(), delay));
+        }
+
+        return frames;
+    }
+
+    // Adds a line below the board with the move on the left and the evaluation on the right
+    fn add_caption(drawing: &mut Drawing, caption: &str, evaluation: &str)
+    {
+        let board_size = drawing.width as f64;
+        let caption_height = (board_size / 10.0).round();
+        let text_height = caption_height * 0.4;
+        let margin = (caption_height - text_height) / 2.0;
+
+        drawing.height += caption_height as u32;
+        drawing.add_rectangle(0.0, board_size, board_size, caption_height, CAPTION_BACKGROUND);
+        drawing.add_text(margin, board_size + margin, text_height, CAPTION_COLOR, caption);
+
+        // Every character takes 6/7 of the text height
+        let evaluation_width = evaluation.chars().count() as f64 * text_height * 6.0 / 7.0;
+        drawing.add_text(board_size - margin - evaluation_width, board_size + margin, text_height, CAPTION_COLOR, evaluation);
+    }
+
+    // The frames with only the part that changed from the frame before, the first one is whole
+    fn changed_frames(frames: &[(Image, u32)]) -> Vec<Animation_Frame>
+    {
+        let mut result = vec![];
+
+        for (index, (image, delay)) in frames.iter().enumerate()
+        {
+            let area = if index == 0 { Some((0, 0, image.width, image.height)) } else { image.changed_area(&frames[index - 1].0) };
+
+            // A frame that looks the same as the one before still needs a pixel to hold its delay
+            let (x, y, width, height) = area.unwrap_or((0, 0, 1, 1));
+
+            result.push(Animation_Frame { x, y, width, height, pixels: image.crop(x, y, width, height).pixels, delay: *delay });
+        }
+
+        return result;
+    }
+
+    pub fn to_gif(&self, options: &Animation_Options) -> Vec<u8>
+    {
+        let frames = self.animation_frames(options);
+        let (width, height) = (frames[0].0.width, frames[0].0.height);
+
+        return encode_gif(width, height, &Self::changed_frames(&frames));
+    }
+
+    pub fn to_apng(&self, options: &Animation_Options) -> Vec<u8>
+    {
+        let frames = self.animation_frames(options);
+        let (width, height) = (frames[0].0.width, frames[0].0.height);
+
+        return encode_apng(width, height, &Self::changed_frames(&frames));
+    }
+}
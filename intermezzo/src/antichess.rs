@@ -0,0 +1,99 @@
+// Antichess (Losing chess)
+//
+// The goal is to lose every piece. Capturing is compulsory: when a capture is possible one of the
+// captures has to be played, the player can choose which one. The king is an ordinary piece that
+// can be captured, there is no check and no castling, and pawns can also promote to a king
+//
+// A player wins when they have no pieces left or when they can not move (stalemate)
+//
+// There are no draws because of material
+
+use crate::chess::*;
+use crate::variant::*;
+
+pub struct Antichess;
+
+impl Rules for Antichess
+{
+    fn name(&self) -> &'static str
+    {
+        return "Antichess";
+    }
+
+    fn aliases(&self) -> &'static [&'static str]
+    {
+        return &["Losing chess", "giveaway", "suicide"];
+    }
+
+    fn start_fen(&self) -> &'static str
+    {
+        return "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - - 0 1";
+    }
+
+    // The normal moves without castling, with promotions to a king
+    fn pseudo_legal_moves(&self, board: &Chess_Board, color: i8) -> Vec<Move>
+    {
+        let mut results = vec![];
+
+        for candidate in board.standard_pseudo_legal_moves(color)
+        {
+            match candidate.kind
+            {
+                Move_Kind::Short_Castle | Move_Kind::Long_Castle => continue,
+                Move_Kind::Promotion if candidate.promotion.abs() == QUEEN =>
+                {
+                    results.push(Move { promotion: color * KING, ..candidate });
+                },
+                _ => (),
+            }
+
+            results.push(candidate);
+        }
+
+        return results;
+    }
+
+    // Every move is legal as there is no check, but captures come first
+    fn legal_moves(&self, board: &mut Chess_Board) -> Vec<Move>
+    {
+        let moves = board.pseudo_legal_moves(board.details.side_to_move);
+
+        if moves.iter().any(|candidate| candidate.capture != 0)
+        {
+            return moves.into_iter().filter(|candidate| candidate.capture != 0).collect();
+        }
+
+        return moves;
+    }
+
+    fn in_check(&self, _board: &Chess_Board, _color: i8) -> bool
+    {
+        return false;
+    }
+
+    fn variant_outcome(&self, board: &Chess_Board) -> Option<Outcome>
+    {
+        for color in [WHITE, BLACK]
+        {
+            if board.board.iter().all(|&piece| piece.signum() != color)
+            {
+                return Some(Outcome::Variant_Win(color));
+            }
+        }
+
+        return None;
+    }
+
+    // Being stalemated wins
+    fn no_moves_outcome(&self, board: &Chess_Board) -> Outcome
+    {
+        return Outcome::Variant_Win(board.details.side_to_move);
+    }
+
+    fn material_draws(&self) -> bool
+    {
+        return false;
+    }
+}
+
+pub static ANTICHESS: Antichess = Antichess;
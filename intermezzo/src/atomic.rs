@@ -0,0 +1,163 @@
+// Atomic chess
+//
+// Every capture is an explosion: the capturing piece, the captured piece and every piece other
+// than a pawn on the eight squares around the capture are removed from the board. A player wins by
+// blowing up the enemy king, which is also how checkmate ends the game
+//
+// Because of the explosions:
+// - kings can not capture
+// - a move that blows up the own king is not legal, not even if it blows up the enemy king as well
+// - blowing up the enemy king is legal even when the own king is left in check
+// - kings standing next to each other can not be in check, taking one would blow up the other
+//
+// The board from before a capture is kept in saved_boards to put the exploded pieces back
+//
+// There are no draws because of material, a single piece can blow up a king next to another piece
+
+use crate::chess::*;
+use crate::variant::*;
+
+pub struct Atomic;
+
+impl Atomic
+{
+    fn kings_touch(board: &Chess_Board) -> bool
+    {
+        let (Some(white), Some(black)) = (board.king_square(WHITE), board.king_square(BLACK)) else { return false };
+
+        return    Chess_Board::get_row(white).abs_diff(Chess_Board::get_row(black)) <= 1
+               && Chess_Board::get_col(white).abs_diff(Chess_Board::get_col(black)) <= 1;
+    }
+
+    // The squares around a square, the square itself not included
+    fn neighbours(square: usize) -> Vec<usize>
+    {
+        let row = Chess_Board::get_row(square) as i8;
+        let col = Chess_Board::get_col(square) as i8;
+        let mut results = vec![];
+
+        for row_step in -1..=1
+        {
+            for col_step in -1..=1
+            {
+                let next_row = row + row_step;
+                let next_col = col + col_step;
+
+                if    (row_step != 0 || col_step != 0)
+                   && (0..8).contains(&next_row)
+                   && (0..8).contains(&next_col)
+                {
+                    results.push((next_row * 8 + next_col) as usize);
+                }
+            }
+        }
+
+        return results;
+    }
+}
+
+impl Rules for Atomic
+{
+    fn name(&self) -> &'static str
+    {
+        return "Atomic";
+    }
+
+    // Kings do not capture
+    fn pseudo_legal_moves(&self, board: &Chess_Board, color: i8) -> Vec<Move>
+    {
+        let mut results = board.standard_pseudo_legal_moves(color);
+        results.retain(|candidate| candidate.piece.abs() != KING || candidate.capture == 0);
+
+        return results;
+    }
+
+    fn make_move(&self, board: &mut Chess_Board, new_move: Move)
+    {
+        if new_move.capture == 0
+        {
+            board.standard_make_move(new_move);
+            return;
+        }
+
+        let color = new_move.piece.signum();
+        let before = board.board;
+
+        board.standard_make_move(new_move);
+        board.saved_boards.push(before);
+
+        // The capturing piece and everything around the capture except for pawns
+        board.board[new_move.target] = 0;
+
+        for square in Self::neighbours(new_move.target)
+        {
+            if board.board[square].abs() != PAWN
+            {
+                board.board[square] = 0;
+            }
+        }
+
+        // A rook that explodes can not castle anymore, a new rook could appear on the square later
+        for r_idx in 0..4
+        {
+            if board.board[board.castling_rooks[r_idx]] == 0
+            {
+                board.details.rooks_have_moved[r_idx] = true;
+            }
+        }
+
+        board.details.is_check = if board.in_check(-color) { color } else { 0 };
+    }
+
+    fn unmake_move(&self, board: &mut Chess_Board)
+    {
+        let Some(&last_move) = board.moves.last() else { return };
+
+        board.standard_unmake_move();
+
+        if last_move.capture != 0
+        {
+            if let Some(saved) = board.saved_boards.pop()
+            {
+                board.board = saved;
+            }
+        }
+    }
+
+    // A king that is gone counts as in check, so that moves blowing up the own king are not legal
+    fn in_check(&self, board: &Chess_Board, color: i8) -> bool
+    {
+        if board.king_square(color).is_none()
+        {
+            return true;
+        }
+
+        if    board.king_square(-color).is_none()
+           || Self::kings_touch(board)
+        {
+            return false;
+        }
+
+        return board.standard_in_check(color);
+    }
+
+    fn variant_outcome(&self, board: &Chess_Board) -> Option<Outcome>
+    {
+        for color in [WHITE, BLACK]
+        {
+            if board.king_square(color).is_none()
+            {
+                return Some(Outcome::Variant_Win(-color));
+            }
+        }
+
+        return None;
+    }
+
+    fn material_draws(&self) -> bool
+    {
+        return false;
+    }
+}
+
+pub static ATOMIC: Atomic = Atomic;
@@ -0,0 +1,403 @@
+// Drawing the board as an image, for diagrams and screenshots without the OpenGL window
+//
+// to_svg and to_png draw the same picture, what it looks like is set with Image_Options:
+//
+// size         -> the width and height of the image in pixels
+// light, dark  -> the colors of the squares
+// coordinates  -> the rank numbers and file letters, drawn in the corners of the edge squares
+// flipped      -> black at the bottom
+// last_move    -> highlights the origin and target of the last move
+// check        -> highlights the king that is in check
+// highlights   -> squares colored with a color of their own, usually partly transparent
+// arrows       -> arrows from one square to another, e.g. to show a plan or the best move
+//
+// The pieces are drawn from polygons and circles on a 45 x 45 grid, which is scaled to the size
+// of a square. White pieces are white with a black outline, black pieces black with white details
+// so the shapes inside them can still be seen
+
+use crate::chess::*;
+use crate::drawing::*;
+
+const LAST_MOVE_COLOR: Color = Color::rgba(155, 199, 0, 105);
+const CHECK_COLOR: Color = Color::rgba(220, 30, 30, 150);
+const DUCK_COLOR: Color = Color::rgb(255, 210, 40);
+
+// The size of the grid the pieces are drawn on
+const PIECE_GRID: f64 = 45.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Arrow
+{
+    pub origin: usize,
+    pub target: usize,
+    pub color:  Color,
+}
+
+impl Arrow
+{
+    pub fn new(origin: usize, target: usize) -> Self
+    {
+        return Arrow { origin, target, color: Color::rgba(21, 120, 27, 200) };
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Image_Options
+{
+    pub size:        u32,
+    pub light:       Color,
+    pub dark:        Color,
+    pub coordinates: bool,
+    pub flipped:     bool,
+    pub last_move:   bool,
+    pub check:       bool,
+    pub highlights:  Vec<(usize, Color)>,
+    pub arrows:      Vec<Arrow>,
+}
+
+impl Default for Image_Options
+{
+    fn default() -> Self
+    {
+        return Image_Options
+        {
+            size: 400,
+            light: Color::rgb(240, 217, 181),
+            dark: Color::rgb(181, 136, 99),
+            coordinates: true,
+            flipped: false,
+            last_move: true,
+            check: true,
+            highlights: vec![],
+            arrows: vec![],
+        };
+    }
+}
+
+// Where the pieces are drawn, in the units of the 45 x 45 grid
+struct Piece_Shape
+{
+    polygons: Vec<Vec<(f64, f64)>>,
+    // (center, radius)
+    circles:  Vec<((f64, f64), f64)>,
+    // Lines drawn over the piece in the color of the outline (white for black pieces)
+    details:  Vec<Vec<(f64, f64)>>,
+}
+
+fn piece_shape(kind: i8) -> Piece_Shape
+{
+    return match kind
+    {
+        PAWN => Piece_Shape
+        {
+            polygons: vec![vec![(18.0, 20.0), (27.0, 20.0), (29.0, 23.0), (26.0, 24.0), (30.0, 34.0), (34.0, 37.0), (34.0, 39.0),
+                                (11.0, 39.0), (11.0, 37.0), (15.0, 34.0), (19.0, 24.0), (16.0, 23.0)]],
+            circles: vec![((22.5, 15.5), 5.5)],
+            details: vec![],
+        },
+        KNIGHT => Piece_Shape
+        {
+            polygons: vec![vec![(21.0, 8.0), (24.0, 11.0), (29.0, 12.0), (34.0, 16.0), (37.0, 24.0), (37.0, 39.0), (14.0, 39.0), (14.0, 36.0),
+                                (17.0, 31.0), (21.0, 26.0), (19.0, 25.0), (14.0, 28.0), (11.0, 29.0), (9.0, 27.0), (9.0, 24.0), (14.0, 18.0),
+                                (18.0, 13.0), (19.0, 9.0)]],
+            circles: vec![((16.5, 18.0), 1.3)],
+            details: vec![vec![(24.0, 13.0), (30.0, 18.0), (33.0, 26.0), (33.0, 36.0)]],
+        },
+        BISHOP => Piece_Shape
+        {
+            polygons: vec![vec![(9.0, 36.0), (36.0, 36.0), (36.0, 39.0), (9.0, 39.0)],
+                           vec![(15.0, 29.0), (30.0, 29.0), (30.0, 33.0), (27.0, 33.0), (27.0, 36.0), (18.0, 36.0), (18.0, 33.0), (15.0, 33.0)],
+                           vec![(22.5, 9.0), (27.0, 13.0), (30.0, 18.0), (30.0, 24.0), (28.0, 29.0), (17.0, 29.0), (15.0, 24.0), (15.0, 18.0),
+                                (18.0, 13.0)]],
+            circles: vec![((22.5, 7.0), 2.5)],
+            details: vec![vec![(22.5, 15.0), (22.5, 23.0)], vec![(19.0, 19.0), (26.0, 19.0)]],
+        },
+        ROOK => Piece_Shape
+        {
+            polygons: vec![vec![(9.0, 39.0), (36.0, 39.0), (36.0, 36.0), (33.0, 36.0), (33.0, 33.0), (31.0, 30.0), (31.0, 17.0), (34.0, 14.0),
+                                (34.0, 9.0), (30.0, 9.0), (30.0, 11.0), (25.0, 11.0), (25.0, 9.0), (20.0, 9.0), (20.0, 11.0), (15.0, 11.0),
+                                (15.0, 9.0), (11.0, 9.0), (11.0, 14.0), (14.0, 17.0), (14.0, 30.0), (12.0, 33.0), (12.0, 36.0), (9.0, 36.0)]],
+            circles: vec![],
+            details: vec![vec![(14.0, 17.0), (31.0, 17.0)], vec![(14.0, 30.0), (31.0, 30.0)], vec![(12.0, 33.0), (33.0, 33.0)]],
+        },
+        QUEEN => Piece_Shape
+        {
+            polygons: vec![vec![(10.0, 39.0), (11.5, 31.0), (7.0, 14.0), (13.0, 26.0), (14.5, 11.0), (19.0, 25.0), (22.5, 10.0), (26.0, 25.0),
+                                (30.5, 11.0), (32.0, 26.0), (38.0, 14.0), (33.5, 31.0), (35.0, 39.0)]],
+            circles: vec![((7.0, 14.0), 2.5), ((14.5, 11.0), 2.5), ((22.5, 10.0), 2.5), ((30.5, 11.0), 2.5), ((38.0, 14.0), 2.5)],
+            details: vec![vec![(11.5, 31.0), (33.5, 31.0)], vec![(10.5, 35.0), (34.5, 35.0)]],
+        },
+        KING => Piece_Shape
+        {
+            polygons: vec![vec![(11.0, 38.0), (34.0, 38.0), (34.0, 34.0), (38.0, 27.0), (37.0, 21.0), (32.0, 18.0), (27.0, 20.0), (22.5, 25.0),
+                                (18.0, 20.0), (13.0, 18.0), (8.0, 21.0), (7.0, 27.0), (11.0, 34.0)],
+                           vec![(22.5, 25.0), (19.0, 19.0), (21.0, 15.0), (24.0, 15.0), (26.0, 19.0)],
+                           vec![(21.0, 4.0), (24.0, 4.0), (24.0, 6.5), (26.5, 6.5), (26.5, 9.5), (24.0, 9.5), (24.0, 14.0), (21.0, 14.0),
+                                (21.0, 9.5), (18.5, 9.5), (18.5, 6.5), (21.0, 6.5)]],
+            circles: vec![],
+            details: vec![vec![(11.0, 34.0), (34.0, 34.0)], vec![(22.5, 25.0), (22.5, 34.0)]],
+        },
+        _ => Piece_Shape
+        {
+            polygons: vec![],
+            circles: vec![((22.5, 22.5), 13.0)],
+            details: vec![],
+        },
+    };
+}
+
+impl Chess_Board
+{
+    // The top left corner of a square in the image
+    fn square_corner(square: usize, square_size: f64, flipped: bool) -> (f64, f64)
+    {
+        let (row, col) = (Self::get_row(square), Self::get_col(square));
+        let (x, y) = if flipped { (7 - col, row) } else { (col, 7 - row) };
+
+        return (x as f64 * square_size, y as f64 * square_size);
+    }
+
+    fn add_piece(drawing: &mut Drawing, piece: i8, corner: (f64, f64), square_size: f64)
+    {
+        let scale = square_size / PIECE_GRID;
+        let point = |(x, y): (f64, f64)| (corner.0 + x * scale, corner.1 + y * scale);
+
+        let (fill, outline) = match piece
+        {
+            DUCK           => (DUCK_COLOR, Color::rgb(0, 0, 0)),
+            _ if piece > 0 => (Color::rgb(255, 255, 255), Color::rgb(0, 0, 0)),
+            _              => (Color::rgb(0, 0, 0), Color::rgb(0, 0, 0)),
+        };
+
+        let detail_color = if piece < 0 { Color::rgb(255, 255, 255) } else { outline };
+        let stroke = Some((outline, 1.5 * scale));
+        let shape = piece_shape(piece.abs());
+
+        for polygon in &shape.polygons
+        {
+            drawing.shapes.push(Shape::Polygon { points: polygon.iter().map(|&corner| point(corner)).collect(), fill: Some(fill), stroke });
+        }
+
+        for &(center, radius) in &shape.circles
+        {
+            drawing.shapes.push(Shape::Circle { center: point(center), radius: radius * scale, fill: Some(fill), stroke });
+        }
+
+        for line in &shape.details
+        {
+            drawing.shapes.push(Shape::Line { points: line.iter().map(|&corner| point(corner)).collect(), color: detail_color, width: 1.5 * scale });
+        }
+    }
+
+    // An arrow from the center of one square to the center of the other, as one polygon
+    fn add_arrow(drawing: &mut Drawing, arrow: &Arrow, square_size: f64, flipped: bool)
+    {
+        let center = |square: usize| {
+            let (x, y) = Self::square_corner(square, square_size, flipped);
+            (x + square_size / 2.0, y + square_size / 2.0)
+        };
+
+        let (start, end) = (center(arrow.origin), center(arrow.target));
+        let length = ((end.0 - start.0).powi(2) + (end.1 - start.1).powi(2)).sqrt();
+
+        if length == 0.0
+        {
+            return;
+        }
+
+        // Along the arrow and across it
+        let (dx, dy) = ((end.0 - start.0) / length, (end.1 - start.1) / length);
+        let (nx, ny) = (-dy, dx);
+
+        let shaft = square_size * 0.1;
+        let head_width = square_size * 0.25;
+        let head_length = (square_size * 0.45).min(length);
+
+        let at = |along: f64, across: f64| (start.0 + dx * along + nx * across, start.1 + dy * along + ny * across);
+
+        drawing.shapes.push(Shape::Polygon
+        {
+            points: vec![at(0.0, -shaft), at(length - head_length, -shaft), at(length - head_length, -head_width), at(length, 0.0),
+                         at(length - head_length, head_width), at(length - head_length, shaft), at(0.0, shaft)],
+            fill: Some(arrow.color),
+            stroke: None,
+        });
+    }
+
+    pub fn to_drawing(&self, options: &Image_Options) -> Drawing
+    {
+        let square_size = options.size as f64 / 8.0;
+        let mut drawing = Drawing::new(options.size, options.size, options.light);
+
+        let mut highlights: Vec<(usize, Color)> = vec![];
+
+        if let Some(last) = self.moves.last().filter(|_| options.last_move)
+        {
+            highlights.push((last.origin, LAST_MOVE_COLOR));
+            highlights.push((last.target, LAST_MOVE_COLOR));
+        }
+
+        if let Some(king) = self.king_square(self.details.side_to_move).filter(|_| options.check && self.details.is_check != 0)
+        {
+            highlights.push((king, CHECK_COLOR));
+        }
+
+        highlights.extend(options.highlights.iter().copied());
+
+        for square in 0..64
+        {
+            let (x, y) = Self::square_corner(square, square_size, options.flipped);
+            let dark = (Self::get_row(square) + Self::get_col(square)) % 2 == 0;
+
+            if dark
+            {
+                drawing.add_rectangle(x, y, square_size, square_size, options.dark);
+            }
+
+            for &(_, color) in highlights.iter().filter(|(highlighted, _)| *highlighted == square)
+            {
+                drawing.add_rectangle(x, y, square_size, square_size, color);
+            }
+
+            if options.coordinates
+            {
+                let height = square_size * 0.18;
+                let margin = square_size * 0.05;
+                let color = if dark { options.light } else { options.dark };
+                let (row, col) = (Self::get_row(square), Self::get_col(square));
+
+                // Along the left edge and the bottom edge of the image
+                let (left_col, bottom_row) = if options.flipped { (7, 7) } else { (0, 0) };
+
+                if col == left_col
+                {
+                    drawing.add_text(x + margin, y + margin, height, color, &(row + 1).to_string());
+                }
+
+                if row == bottom_row
+                {
+                    let letter = (b'a' + col as u8) as char;
+                    drawing.add_text(x + square_size - margin - height * 5.0 / 7.0, y + square_size - margin - height, height, color, &letter.to_string());
+                }
+            }
+        }
+
+        for square in 0..64
+        {
+            if self.board[square] != 0
+            {
+                Self::add_piece(&mut drawing, self.board[square], Self::square_corner(square, square_size, options.flipped), square_size);
+            }
+        }
+
+        for arrow in &options.arrows
+        {
+            Self::add_arrow(&mut drawing, arrow, square_size, options.flipped);
+        }
+
+        return drawing;
+    }
+
+    pub fn to_svg(&self, options: &Image_Options) -> String
+    {
+        return self.to_drawing(options).to_svg();
+    }
+
+    pub fn to_png(&self, options: &Image_Options) -> Vec<u8>
+    {
+        return self.to_drawing(options).rasterize().to_png();
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    use std::fs;
+    use std::path::PathBuf;
+
+    // The expected images are kept in tests/images, after a change to the drawing that is meant
+    // to change them they are written again with:
+    // cargo test -- --ignored write_golden_images
+    fn golden_path(name: &str) -> PathBuf
+    {
+        return PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("images").join(name);
+    }
+
+    // The start position and a checkmate seen from black with the last move, an arrow and a
+    // highlighted square
+    fn golden_images() -> Vec<(&'static str, Chess_Board, Image_Options)>
+    {
+        let start = Chess_Board::new();
+
+        let mut mate = Chess_Board::new();
+
+        for san in ["e4", "e5", "Bc4", "Nc6", "Qh5", "Nf6", "Qxf7#"]
+        {
+            let san_move = mate.parse_san(san).unwrap();
+            mate.make_move(san_move);
+        }
+
+        let mate_options = Image_Options
+        {
+            size: 200,
+            flipped: true,
+            highlights: vec![(5 * 8 + 5, Color::rgba(20, 85, 200, 120))],
+            arrows: vec![Arrow::new(2 * 8 + 6, 3 * 8 + 4)],
+            ..Image_Options::default()
+        };
+
+        return vec![
+            ("start", start, Image_Options { size: 160, ..Image_Options::default() }),
+            ("scholars_mate", mate, mate_options),
+        ];
+    }
+
+    #[test]
+    fn svg_matches_golden_images()
+    {
+        for (name, board, options) in golden_images()
+        {
+            let expected = fs::read_to_string(golden_path(&format!("{}.svg", name))).unwrap();
+            assert!(board.to_svg(&options) == expected, "{}.svg differs", name);
+        }
+    }
+
+    #[test]
+    fn png_matches_golden_images()
+    {
+        for (name, board, options) in golden_images()
+        {
+            let expected = fs::read(golden_path(&format!("{}.png", name))).unwrap();
+            assert!(board.to_png(&options) == expected, "{}.png differs", name);
+        }
+    }
+
+    #[test]
+    fn image_shows_the_board()
+    {
+        let board = Chess_Board::new();
+        let image = board.to_drawing(&Image_Options { size: 80, coordinates: false, ..Image_Options::default() }).rasterize();
+
+        // The corners of a1 (dark) and h1 (light), both empty
+        assert_eq!(image.pixel(0, 79), [181, 136, 99]);
+        assert_eq!(image.pixel(79, 79), [240, 217, 181]);
+        // d5 (light) and e5 (dark) are empty
+        assert_eq!(image.pixel(35, 35), [240, 217, 181]);
+        assert_eq!(image.pixel(45, 35), [181, 136, 99]);
+    }
+
+    #[test]
+    #[ignore]
+    fn write_golden_images()
+    {
+        fs::create_dir_all(golden_path("")).unwrap();
+
+        for (name, board, options) in golden_images()
+        {
+            fs::write(golden_path(&format!("{}.svg", name)), board.to_svg(&options)).unwrap();
+            fs::write(golden_path(&format!("{}.png", name)), board.to_png(&options)).unwrap();
+        }
+    }
+}
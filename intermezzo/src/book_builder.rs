@@ -0,0 +1,223 @@
+// Building Polyglot opening books from PGN games
+//
+// Every game is replayed from its start position and for every position up to max_ply the move
+// that was played is recorded together with the result of the game. The weight of a move in the
+// book follows the convention of the original Polyglot tool: 2 points for every win and 1 point
+// for every draw, seen from the side that played the move
+
+use crate::chess::*;
+use crate::pgn::*;
+use crate::polyglot::*;
+
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+
+// Which games, or which side within a game, are used for the book
+// All         -> the moves of both sides in every finished game
+// Winner_Only -> only the moves of the side that won, draws are skipped
+// No_Losses   -> the moves of the side that won and the moves of both sides in a draw
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Result_Filter
+{
+    All,
+    Winner_Only,
+    No_Losses,
+}
+
+// The statistics collected for a single move in a single position
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Move_Statistics
+{
+    pub games:  u32,
+    pub wins:   u32,
+    pub draws:  u32,
+    pub losses: u32,
+}
+
+impl Move_Statistics
+{
+    // The Polyglot weight: 2 points per win and 1 point per draw
+    pub fn points(&self) -> u64
+    {
+        return 2 * self.wins as u64 + self.draws as u64;
+    }
+}
+
+pub struct Book_Builder
+{
+    // Only the first max_ply half moves of every game are used
+    pub max_ply:   usize,
+    // Moves played in fewer games than this are left out of the book
+    pub min_games: u32,
+    pub filter:    Result_Filter,
+
+    // Statistics by (polyglot key, polyglot move)
+    statistics: HashMap<(u64, u16), Move_Statistics>,
+
+    pub games_used:    usize,
+    pub games_skipped: usize,
+}
+
+impl Book_Builder
+{
+    pub fn new(max_ply: usize, min_games: u32, filter: Result_Filter) -> Self
+    {
+        return Book_Builder
+        {
+            max_ply,
+            min_games,
+            filter,
+            statistics: HashMap::new(),
+            games_used: 0,
+            games_skipped: 0,
+        };
+    }
+
+    // Adds every game of a PGN file, returns the number of games that were used
+    pub fn add_pgn_file<P: AsRef<Path>>(&mut self, path: P) -> io::Result<usize>
+    {
+        let games_before = self.games_used;
+
+        for game in read_pgn_file(path)?
+        {
+            // A game with a broken move is still used up to that move
+            let _ = self.add_game(&game);
+        }
+
+        return Ok(self.games_used - games_before);
+    }
+
+    // Adds the moves of a single game
+    // Unfinished games are skipped, an error is returned for a move that can not be played
+    pub fn add_game(&mut self, game: &Pgn_Game) -> Result<(), String>
+    {
+        let white_score = match game.white_score()
+        {
+            Some(score) => score,
+            None =>
+            {
+                self.games_skipped += 1;
+                return Ok(());
+            },
+        };
+
+        if    self.filter == Result_Filter::Winner_Only
+           && white_score == 0.5
+        {
+            self.games_skipped += 1;
+            return Ok(());
+        }
+
+        let mut board = game.start_board()?;
+        self.games_used += 1;
+
+        for (ply, san) in game.moves.iter().enumerate()
+        {
+            if ply >= self.max_ply
+            {
+                break;
+            }
+
+            let played = match board.parse_san(san)
+            {
+                Some(played) => played,
+                None => return Err(format!("illegal or ambiguous move \"{}\" at ply {}", san, ply + 1)),
+            };
+
+            // The score of the game from the point of view of the side to move
+            let score = if board.details.side_to_move == WHITE { white_score } else { 1.0 - white_score };
+
+            let include = match self.filter
+            {
+                Result_Filter::All         => true,
+                Result_Filter::Winner_Only => score == 1.0,
+                Result_Filter::No_Losses   => score > 0.0,
+            };
+
+            if include
+            {
+                let statistics = self.statistics.entry((polyglot_key(&board), encode_move(&played)))
+                                                .or_default();
+
+                statistics.games += 1;
+
+                if score == 1.0
+                {
+                    statistics.wins += 1;
+                } else if score == 0.5
+                {
+                    statistics.draws += 1;
+                } else
+                {
+                    statistics.losses += 1;
+                }
+            }
+
+            board.make_move(played);
+        }
+
+        return Ok(());
+    }
+
+    // Creates the book from the statistics collected so far
+    pub fn build(&self) -> Polyglot_Book
+    {
+        let mut entries: Vec<Book_Entry> = vec![];
+
+        for ((key, raw), statistics) in &self.statistics
+        {
+            if    statistics.games < self.min_games
+               || statistics.points() == 0
+            {
+                continue;
+            }
+
+            entries.push(Book_Entry
+            {
+                key: *key,
+                raw: *raw,
+                weight: 0,
+                learn: 0,
+            });
+        }
+
+        // Weights have to fit into 16 bits, so they are scaled down if any move has more points
+        let max_points = self.statistics.values()
+                                        .filter(|statistics| statistics.games >= self.min_games)
+                                        .map(|statistics| statistics.points())
+                                        .max()
+                                        .unwrap_or(0);
+
+        for entry in entries.iter_mut()
+        {
+            let points = self.statistics[&(entry.key, entry.raw)].points();
+
+            entry.weight = if max_points > u16::MAX as u64
+            {
+                std::cmp::max(points * u16::MAX as u64 / max_points, 1) as u16
+            } else
+            {
+                points as u16
+            };
+        }
+
+        // Sorted by key, the moves of a position with the highest weight first
+        entries.sort_by(|first, second| {
+            first.key.cmp(&second.key)
+                     .then(second.weight.cmp(&first.weight))
+                     .then(first.raw.cmp(&second.raw))
+        });
+
+        return Polyglot_Book { entries };
+    }
+
+    // Builds the book and saves it to a file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<usize>
+    {
+        let book = self.build();
+        book.save(path)?;
+
+        return Ok(book.entries.len());
+    }
+}
@@ -9,28 +9,116 @@
 #![allow(unused)]
 #![allow(non_camel_case_types)]
 
+use crate::notation::piece_to_char;
+use crate::render::Render_Options;
+use crate::variant::*;
+
+// Piece values used within the board array (negative values are the black pieces)
+pub const PAWN:   i8 = 1;
+pub const KNIGHT: i8 = 2;
+pub const BISHOP: i8 = 3;
+pub const ROOK:   i8 = 4;
+pub const QUEEN:  i8 = 5;
+pub const KING:   i8 = 6;
+
+// The duck of Duck chess belongs to neither side, it blocks every piece and can not be captured
+pub const DUCK:   i8 = 7;
+
+pub const WHITE: i8 =  1;
+pub const BLACK: i8 = -1;
+
+// The kind of a move, needed for moves that affect more than the origin and target squares
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Move_Kind
+{
+    Normal,
+    Double_Push,
+    En_Passant,
+    Short_Castle,
+    Long_Castle,
+    Promotion,
+    // A piece from the pocket is put on the board (Crazyhouse), origin and target are the same square
+    Drop,
+    // The duck is moved after the piece move of the same player (Duck chess), the first time it is
+    // put on the board origin and target are the same square
+    Duck,
+}
+
 // The Move struct which stores data related to a move made on the board
+// promotion is the (signed) piece a pawn is promoted to or 0
+// For castling origin and target are the squares of the king, the king always ends up on the
+// g file (short) or the c file (long), also in Chess960
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct Move
 {
-    piece:     i8,
-    origin:    usize,
-    target:    usize,
-    capture:   i8,
+    pub piece:     i8,
+    pub origin:    usize,
+    pub target:    usize,
+    pub capture:   i8,
+    pub promotion: i8,
+    pub kind:      Move_Kind,
+}
+
+impl Move
+{
+    // The move in the coordinate notation used by UCI, e.g. "e2e4", "e7e8q", "N@f3" for a drop or
+    // "*@d5" for the duck
+    pub fn to_uci(self) -> String
+    {
+        if self.kind == Move_Kind::Drop || self.kind == Move_Kind::Duck
+        {
+            return format!("{}@{}", piece_to_char(self.piece.abs()), Chess_Board::square_name(self.target));
+        }
+
+        let mut result = format!("{}{}", Chess_Board::square_name(self.origin), Chess_Board::square_name(self.target));
+
+        match self.promotion.abs()
+        {
+            KNIGHT => result.push('n'),
+            BISHOP => result.push('b'),
+            ROOK   => result.push('r'),
+            QUEEN  => result.push('q'),
+            KING   => result.push('k'),
+            _      => (),
+        }
+
+        return result;
+    }
 }
 
 // Board details that contains miscellaneous data that we would may want to use
 // rooks_have_moved and kings_have_moved are useful for determining whether castling
 // is a legal move
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct Board_Details
 {
     // rook index order: [0, 7, 56, 63]
-    rooks_have_moved: [bool; 4],
-    kings_have_moved: [bool; 2],
+    pub rooks_have_moved: [bool; 4],
+    // king index order: [black, white]
+    pub kings_have_moved: [bool; 2],
     // is_check < 0 -> white in check
     // is_check > 0 -> black in check
-    is_check: i8,
+    pub is_check: i8,
+    // 1 -> white to move, -1 -> black to move
+    pub side_to_move: i8,
+    // The square behind a pawn that has just moved forward two squares
+    pub en_passant: Option<usize>,
+    // The number of moves (plies) since the last capture or pawn move, used by the 50 move rule
+    pub halfmove_clock: u32,
+    // Starts at 1 and is incremented after every move by black (as in FEN)
+    pub fullmove_number: u32,
+    // The number of checks each side has given, [white, black], only counted in Three-check
+    pub checks_given: [u8; 2],
+    // The captured pieces that can be dropped (Crazyhouse), [white, black][pawn, knight, bishop, rook, queen]
+    pub pockets: [[u8; 5]; 2],
+    // The squares with a piece that was a pawn before promoting (one bit per square), these go back
+    // into the pocket as a pawn when they are captured
+    pub promoted: u64,
+    // Whether the player to move still has to move the duck after moving a piece (Duck chess)
+    pub placing_duck: bool,
 }
 
+#[derive(Clone)]
 pub struct Chess_Board
 {
     // The entire chess board is represented as a single 1d array of 64 integers
@@ -46,103 +134,172 @@ pub struct Chess_Board
     // A vector (resizable array) of Moves
     pub moves:       Vec<Move>,
     pub details:     Board_Details,
+
+    // The details from before every move in moves, used to take moves back
+    pub previous_details: Vec<Board_Details>,
+
+    // The starting squares of the rooks that can castle, in the same order as rooks_have_moved:
+    // [white long, white short, black long, black short]
+    // Always [0, 7, 56, 63] in normal chess, in Chess960 the rooks can start on any file
+    pub castling_rooks: [usize; 4],
+
+    // Whether the board is used for Chess960, only changes how castling moves are written in UCI
+    pub chess960: bool,
+
+    // The rules the game is played with, normal chess by default (see variant.rs)
+    pub rules: &'static dyn Rules,
+
+    // Copies of the board from before moves that change more squares than the move itself (the
+    // explosions of Atomic), put back when the move is taken back
+    pub saved_boards: Vec<[i8; 64]>,
 }
 
 // The impl block is used in Rust to implement methods for an object (struct)
 // The following functions are all methods belonging to the Chess_Board struct
 impl Chess_Board
 {
+    // Creates a board with the starting position that is ready for move generation
+    pub fn new() -> Self
+    {
+        return Chess_Board { ..Default::default() };
+    }
+
+    // The move in the coordinate notation used by UCI
+    // In Chess960 castling is written as the king capturing its own rook ("e1h1"), as the king
+    // might not move at all or the move could be mistaken for a normal king move
+    pub fn move_to_uci(&self, uci_move: Move) -> String
+    {
+        if    self.chess960
+           && (uci_move.kind == Move_Kind::Short_Castle || uci_move.kind == Move_Kind::Long_Castle)
+        {
+            let rook_square = self.castling_rooks[Self::castling_index(uci_move)];
+            return format!("{}{}", Self::square_name(uci_move.origin), Self::square_name(rook_square));
+        }
+
+        return uci_move.to_uci();
+    }
+
+    // The index of a castling move within castling_rooks and rooks_have_moved
+    pub fn castling_index(castle: Move) -> usize
+    {
+        let short = if castle.kind == Move_Kind::Short_Castle { 1 } else { 0 };
+        let black = if castle.piece < 0 { 2 } else { 0 };
+
+        return black + short;
+    }
+
+    // The squares the king and rook end up on after castling: (king, rook)
+    pub fn castling_targets(castle: Move) -> (usize, usize)
+    {
+        let back_rank = Self::get_row(castle.origin) * 8;
+
+        return if castle.kind == Move_Kind::Short_Castle
+        {
+            (back_rank + 6, back_rank + 5)
+        } else
+        {
+            (back_rank + 2, back_rank + 3)
+        };
+    }
+
+    // Converts a square index into its name in algebraic notation, 0 -> "a1", 63 -> "h8"
+    pub fn square_name(square: usize) -> String
+    {
+        let file = (b'a' + Self::get_col(square) as u8) as char;
+        let rank = (b'1' + Self::get_row(square) as u8) as char;
+
+        return format!("{}{}", file, rank);
+    }
+
+    // Converts the name of a square back into its index, "e4" -> 28
+    pub fn parse_square(name: &str) -> Option<usize>
+    {
+        let bytes = name.as_bytes();
+
+        if    bytes.len() != 2
+           || !(b'a'..=b'h').contains(&bytes[0])
+           || !(b'1'..=b'8').contains(&bytes[1])
+        {
+            return None;
+        }
+
+        return Some((bytes[1] - b'1') as usize * 8 + (bytes[0] - b'a') as usize);
+    }
+
     // Get row based on an index within the board array
-    fn get_row(index: usize) -> usize
+    pub fn get_row(index: usize) -> usize
     {
         return index / 8;
     }
 
     // Get column based on an index within the board array
-    fn get_col(index: usize) -> usize
+    pub fn get_col(index: usize) -> usize
     {
         return index % 8;
     }
 
     // Checks if two pieces have matching colors by checking if the two numbers have the same sign
-    fn match_color(origin_piece: i8, target_piece: i8) -> bool
+    // The duck counts as a piece of both colors, so no piece can move onto it
+    pub fn match_color(origin_piece: i8, target_piece: i8) -> bool
     {
-        return origin_piece * target_piece > 0;
+        return origin_piece * target_piece > 0 || target_piece == DUCK;
     }
 
-    // Prints the board into a visual form that can be easily read by a human (ONLY FOR DEBUG PURPOSES)
+    // Prints the board into a visual form that can be easily read by a human
     // Example of the result of the print_board function:
-    // + --- + --- + --- + --- + --- + --- + --- + --- +
-    // |  R  |  N  |  B  |  Q  |  K  |  B  |  N  |  R  |
-    // + --- + --- + --- + --- + --- + --- + --- + --- +
-    // |  P  |  P  |  P  |  P  |  P  |  P  |  P  |  P  |
-    // + --- + --- + --- + --- + --- + --- + --- + --- +
-    // |     |     |     |     |     |     |     |     |
-    // + --- + --- + --- + --- + --- + --- + --- + --- +
-    // |     |     |     |     |     |     |     |     |
-    // + --- + --- + --- + --- + --- + --- + --- + --- +
-    // |     |     |     |     |     |     |     |     |
-    // + --- + --- + --- + --- + --- + --- + --- + --- +
-    // |     |     |     |     |     |     |     |     |
-    // + --- + --- + --- + --- + --- + --- + --- + --- +
-    // |  p  |  p  |  p  |  p  |  p  |  p  |  p  |  p  |
-    // + --- + --- + --- + --- + --- + --- + --- + --- +
-    // |  r  |  n  |  b  |  q  |  k  |  b  |  n  |  r  |
-    // + --- + --- + --- + --- + --- + --- + --- + --- +
+    //   + --- + --- + --- + --- + --- + --- + --- + --- +
+    // 8 |  r  |  n  |  b  |  q  |  k  |  b  |  n  |  r  |
+    //   + --- + --- + --- + --- + --- + --- + --- + --- +
+    // 7 |  p  |  p  |  p  |  p  |  p  |  p  |  p  |  p  |
+    //   + --- + --- + --- + --- + --- + --- + --- + --- +
+    // 6 |     |     |     |     |     |     |     |     |
+    //   + --- + --- + --- + --- + --- + --- + --- + --- +
+    // 5 |     |     |     |     |     |     |     |     |
+    //   + --- + --- + --- + --- + --- + --- + --- + --- +
+    // 4 |     |     |     |     |  P  |     |     |     |
+    //   + --- + --- + --- + --- + --- + --- + --- + --- +
+    // 3 |     |     |     |     |     |     |     |     |
+    //   + --- + --- + --- + --- + --- + --- + --- + --- +
+    // 2 |  P  |  P  |  P  |  P  |     |  P  |  P  |  P  |
+    //   + --- + --- + --- + --- + --- + --- + --- + --- +
+    // 1 |  R  |  N  |  B  |  Q  |  K  |  B  |  N  |  R  |
+    //   + --- + --- + --- + --- + --- + --- + --- + --- +
+    //      a     b     c     d     e     f     g     h
+    // Drawn by render with Render_Options::plain(), other layouts (figurines, colors, black at the
+    // bottom) can be drawn with render itself, see render.rs
     pub fn print_board(&self)
     {
-        print!("{}{}{}", "+ --- + --- + --- + --- + --- + --- + --- + --- +", "\n", "|  ");
-
-        for (index, square) in self.board.iter().enumerate()
-        {
-            match square
-            {
-                -4 => print!("r"),
-                -2 => print!("n"),
-                -3 => print!("b"),
-                -5 => print!("q"),
-                -6 => print!("k"),
-                -1 => print!("p"),
-                 0 => print!(" "),
-                 4 => print!("R"),
-                 2 => print!("N"),
-                 3 => print!("B"),
-                 5 => print!("Q"),
-                 6 => print!("K"),
-                 1 => print!("P"),
-                 _ => (),
-            }
-            
-            print!("  |  ");
-            
-            if (index + 1) % 8 == 0
-            {
-                print!("{}{}{}", "\n", "+ --- + --- + --- + --- + --- + --- + --- + --- +", "\n");
-                
-                if (index != 63)
-                {
-                    print!("|  ");
-                }
-            }
-        } // for (index, square)
-    } // fn print_board
+        print!("{}", self.render(&Render_Options::plain()));
+    }
 
     // Moves the piece to the indicated index of the board and appends a Move to the vector of Moves
+    // Special moves (castling, en passant, promotions) are detected from the move generators,
+    // pawns reaching the last rank are promoted to a queen
     pub fn move_piece(&mut self, origin: usize, target: usize)
     {
-        let origin_piece = self.board[origin];
-        self.board[target] = self.board[origin];
-        self.board[origin] = 0;
-        
-        self.moves.push(
-            Move
+        let found = self.pseudo_legal_moves(self.board[origin].signum())
+                        .into_iter()
+                        .find(|candidate| {
+                               candidate.origin == origin
+                            && candidate.target == target
+                            && (candidate.promotion == 0 || candidate.promotion.abs() == QUEEN)
+                        });
+
+        let new_move = match found
+        {
+            Some(found_move) => found_move,
+            None => Move
             {
-                piece: self.board[target],
+                piece: self.board[origin],
                 origin,
                 target,
-                capture: origin_piece,
-            }
-        );
+                capture: self.board[target],
+                promotion: 0,
+                kind: Move_Kind::Normal,
+            },
+        };
+
+        self.make_move(new_move);
     }
 
     // The generate_distance function only needs to be run once on initialization
@@ -191,13 +348,14 @@ impl Chess_Board
         let directions: [i8; 2] = if color < 0 { [ -9, -7 ] } else { [ 9, 7 ] };
         let edge_count_index    = if color < 0 { [  4,  5 ] } else { [ 6, 7 ] };
 
-        // The index offsets for en passant (values depends on the color)
-        let ep_directions: [i8; 2] = if color < 0 { [ -9, -7 ] } else { [ 7,  9 ] };
-        let ep_neighbors:  [i8; 2] = if color < 0 { [ -1,  1 ] } else { [ 1, -1 ] };
-        let ep_edge_count_index    = [2, 3];
-
         let mut results: Vec<usize> = vec![];
 
+        // A pawn can never stand on the last row, but custom positions might contain one
+        if Self::get_row(square) == promotion_row
+        {
+            return results;
+        }
+
         // The index of the square in front of the pawn piece passed into the function
         let square_in_front = (square as i8 + offset) as usize;
 
@@ -232,6 +390,12 @@ impl Chess_Board
             {
                 let capture_square = (square as i8 + directions[i_idx]) as usize;
 
+                if    self.board[capture_square] == 0
+                   || Self::match_color(color, self.board[capture_square])
+                {
+                    continue;
+                }
+
                 if (Self::get_row(capture_square) == promotion_row)
                 {
                     results.push(200 + capture_square);
@@ -240,34 +404,39 @@ impl Chess_Board
                     results.push(500 + capture_square);
                 } else
                 {
-                    if    self.board[capture_square] != 0
-                       && !Self::match_color(color, self.board[capture_square])
-                    {
-                        results.push(capture_square);
-                    }
+                    results.push(capture_square);
                 }
             }
         }
 
         // En Passant in chess
-        if self.moves.len() >= 1
-        {
-            let last_move: &Move = &self.moves[self.moves.len() - 1];
+        results.extend(self.en_passant_captures(color, square));
+
+        return results;
+    }
+
+    // Returns the en passant capture for a pawn (if there is one)
+    // The square behind a pawn that has just moved two squares is stored in the board details
+    // and is only ever set for one move
+    fn en_passant_captures(&self, color: i8, square: usize) -> Vec<usize>
+    {
+        let mut results: Vec<usize> = vec![];
 
+        let directions: [i8; 2] = if color < 0 { [ -9, -7 ] } else { [ 9, 7 ] };
+        let edge_count_index    = if color < 0 { [  4,  5 ] } else { [ 6, 7 ] };
+        let ep_row:     usize   = if color < 0 {  2 } else { 5 };
+
+        if let Some(ep_square) = self.details.en_passant
+        {
             for i_idx in 0..2
             {
-                if self.edge_counts[square][ep_edge_count_index[i_idx]] > 0
+                if    self.edge_counts[square][edge_count_index[i_idx]] > 0
+                   && (square as i8 + directions[i_idx]) as usize == ep_square
+                   && Self::get_row(ep_square) == ep_row
+                   && self.board[ep_square] == 0
+                   && self.board[(ep_square as i8 - color * 8) as usize] == -color * PAWN
                 {
-                    let ep_square = (square as i8 + ep_directions[i_idx]) as usize;
-                    let neighbor_square = (square as i8 + ep_neighbors[i_idx]) as usize;
-
-                    if    last_move.piece.abs() == 1
-                       && !Self::match_color(last_move.piece, color)
-                       && last_move.target == neighbor_square
-                       && i8::abs(last_move.origin as i8 - last_move.target as i8) == 16
-                    {
-                        results.push(ep_square);
-                    }
+                    results.push(ep_square);
                 }
             }
         }
@@ -287,12 +456,14 @@ impl Chess_Board
         let directions: [i8; 2] = if color < 0 { [ -9, -7 ] } else { [ 9, 7 ] };
         let edge_count_index    = if color < 0 { [  4,  5 ] } else { [ 6, 7 ] };
 
-        let ep_directions: [i8; 2] = if color < 0 { [ -9, -7 ] } else { [ 7,  9 ] };
-        let ep_neighbors:  [i8; 2] = if color < 0 { [ -1,  1 ] } else { [ 1, -1 ] };
-        let ep_edge_count_index    = [2, 3];
-
         let promotion_row: usize = if color < 0 {  0 } else { 7 };
         let offset:        i8    = if color < 0 { -8 } else { 8 };
+
+        if Self::get_row(square) == promotion_row
+        {
+            return results;
+        }
+
         let square_in_front = (square as i8 + offset) as usize;
 
         for i_idx in 0..2
@@ -301,6 +472,12 @@ impl Chess_Board
             {
                 let capture_square = (square as i8 + directions[i_idx]) as usize;
 
+                if    self.board[capture_square] == 0
+                   || Self::match_color(color, self.board[capture_square])
+                {
+                    continue;
+                }
+
                 if (Self::get_row(capture_square) == promotion_row)
                 {
                     results.push(200 + capture_square);
@@ -309,36 +486,12 @@ impl Chess_Board
                     results.push(500 + capture_square);
                 } else
                 {
-                    if    self.board[capture_square] != 0
-                       && !Self::match_color(color, self.board[capture_square])
-                    {
-                        results.push(capture_square);
-                    }
+                    results.push(capture_square);
                 }
             }
         }
         
-        if self.moves.len() >= 1
-        {
-            let last_move: &Move = &self.moves[self.moves.len() - 1];
-
-            for i_idx in 0..2
-            {
-                if self.edge_counts[square][ep_edge_count_index[i_idx]] > 0
-                {
-                    let ep_square = (square as i8 + ep_directions[i_idx]) as usize;
-                    let neighbor_square = (square as i8 + ep_neighbors[i_idx]) as usize;
-
-                    if    last_move.piece.abs() == 1
-                       && !Self::match_color(last_move.piece, color)
-                       && last_move.target == neighbor_square
-                       && i8::abs(last_move.origin as i8 - last_move.target as i8) == 16
-                    {
-                        results.push(ep_square);
-                    }
-                }
-            }
-        }
+        results.extend(self.en_passant_captures(color, square));
 
         if self.board[square_in_front] == 0
         {
@@ -397,7 +550,8 @@ impl Chess_Board
     pub fn bishop_moves(&self, color: i8, square: usize) -> Vec<usize>
     {
         let mut results: Vec<usize> = vec![];
-        let directions = [-7, -9, 9, 7];
+        // The order matches the order of the diagonal directions within edge_counts
+        let directions = [-9, -7, 9, 7];
 
         for d_idx in 0..directions.len()
         {
@@ -492,14 +646,9 @@ impl Chess_Board
     pub fn king_moves(&self, color: i8, square: usize) -> Vec<usize>
     {
         let mut results: Vec<usize> = vec![];
-        
-        let king_detail_index = if color < 0 { 0 } else { 1 };
-        let rook_detail_index = if color < 0 { [ 2, 3 ] } else { [ 0, 1 ] };
-        let rook_squares = if color < 0 { [ 56, 63 ] } else { [ 0, 7 ] };
-        let king_squares = if color < 0 { 60 } else { 4 };
 
         // amounts are in the order of the directions stored in edge_counts
-        let directions = [8, -8, -1, 1, -9, -7, 9, 7];
+        let directions = [-8, 8, -1, 1, -9, -7, 9, 7];
         
         for i_idx in 0..8
         {
@@ -507,7 +656,7 @@ impl Chess_Board
             {
                 let new_square = (square as i8 + directions[i_idx]) as usize;
                 
-                if !Self::match_color(self.board[square], self.board[new_square])
+                if !Self::match_color(color, self.board[new_square])
                 {
                     results.push(new_square);
                 }
@@ -515,32 +664,37 @@ impl Chess_Board
         }
 
         // Castling
+        // index 0 -> short castle (rook towards the h file), index 1 -> long castle (rook towards the a file)
+        // The king moves to the g or c file and the rook next to it on the f or d file. Every square
+        // the king and the rook pass over has to be empty apart from the king and rook themselves
+        // Whether the king passes through an attacked square is checked within legal_moves
+        let king_detail_index = if color < 0 { 0 } else { 1 };
+        let rook_detail_index = if color < 0 { [ 3, 2 ] } else { [ 1, 0 ] };
+        let back_rank = if color < 0 { 56 } else { 0 };
+
         'castle: for i_idx in 0..2
         {
+            let rook_square = self.castling_rooks[rook_detail_index[i_idx]];
+            let (king_target, rook_target) = if i_idx == 0 { (back_rank + 6, back_rank + 5) } else { (back_rank + 2, back_rank + 3) };
+            let rook_on_side = if i_idx == 0 { rook_square > square } else { rook_square < square };
+
             if    self.details.rooks_have_moved[rook_detail_index[i_idx]] == false
                && self.details.kings_have_moved[king_detail_index] == false
+               && Self::get_row(square) == back_rank / 8
+               && rook_on_side
+               && self.board[rook_square] == color * ROOK
+               && self.board[square] == color * KING
             {
-                if    self.board[rook_squares[i_idx]].abs() == 4
-                   && self.board[king_squares].abs() == 6
+                let first = [square, rook_square, king_target, rook_target].into_iter().min().unwrap();
+                let last  = [square, rook_square, king_target, rook_target].into_iter().max().unwrap();
+
+                for s_idx in first..=last
                 {
-                    if rook_squares[i_idx] < king_squares
-                    {
-                        for s_idx in (rook_squares[i_idx] + 1)..=(king_squares - 1)
-                        {
-                            if self.board[s_idx] != 0
-                            {
-                                continue 'castle;
-                            }
-                        }
-                    } else
+                    if    s_idx != square
+                       && s_idx != rook_square
+                       && self.board[s_idx] != 0
                     {
-                        for s_idx in (king_squares + 1)..=(rook_squares[i_idx] - 1)
-                        {
-                            if self.board[s_idx] != 0
-                            {
-                                continue 'castle;
-                            }
-                        }
+                        continue 'castle;
                     }
                 }
 
@@ -559,32 +713,438 @@ impl Chess_Board
         return results;
     }
 
+    // Returns the castling rights in the order
+    // [white short, white long, black short, black long]
+    // The king has to be on its back rank and the rook on its starting square on the correct
+    // side of the king
+    pub fn castling_rights(&self) -> [bool; 4]
+    {
+        let mut rights = [false; 4];
+
+        // [king detail index, rook detail index, back rank, short castle, color]
+        let checks = [
+            [1, 1, 0, 1,  1],
+            [1, 0, 0, 0,  1],
+            [0, 3, 7, 1, -1],
+            [0, 2, 7, 0, -1],
+        ];
+
+        for c_idx in 0..4
+        {
+            let [king_index, rook_index, back_rank, short, color] = checks[c_idx];
+            let rook_square = self.castling_rooks[rook_index as usize];
+
+            let Some(king_square) = self.king_square(color as i8) else { continue };
+
+            let rook_on_side = if short == 1 { rook_square > king_square } else { rook_square < king_square };
+
+            rights[c_idx] =    !self.details.kings_have_moved[king_index as usize]
+                            && !self.details.rooks_have_moved[rook_index as usize]
+                            && Self::get_row(king_square) == back_rank as usize
+                            && rook_on_side
+                            && self.board[rook_square] == color as i8 * ROOK;
+        }
+
+        return rights;
+    }
+
+    // Returns true if any piece of by_color attacks the square
+    // Instead of generating the moves for every opponent piece, this looks outwards from the
+    // square itself in every direction a piece could be attacking from
+    pub fn square_attacked(&self, square: usize, by_color: i8) -> bool
+    {
+        // Pawns attack diagonally forwards, so look diagonally backwards from the square
+        let pawn_directions: [i8; 2] = if by_color < 0 { [ 9, 7 ] } else { [ -9, -7 ] };
+        let pawn_edge_index          = if by_color < 0 { [ 6, 7 ] } else { [  4,  5 ] };
+
+        for i_idx in 0..2
+        {
+            if    self.edge_counts[square][pawn_edge_index[i_idx]] > 0
+               && self.board[(square as i8 + pawn_directions[i_idx]) as usize] == by_color * PAWN
+            {
+                return true;
+            }
+        }
+
+        // Knights use the same bound checks as in knight_moves
+        let knight_directions = [-17, -15, -10, -6, 6, 10, 15, 17];
+        let bound_checks = [[0, 2], [0, 3], [2, 0], [3, 0], [2, 1], [3, 1], [1, 2], [1, 3]];
+
+        for d_idx in 0..8
+        {
+            if    self.edge_counts[square][bound_checks[d_idx][0]] >= 2
+               && self.edge_counts[square][bound_checks[d_idx][1]] >= 1
+               && self.board[(square as i8 + knight_directions[d_idx]) as usize] == by_color * KNIGHT
+            {
+                return true;
+            }
+        }
+
+        // Kings and sliding pieces, in the order of the directions stored in edge_counts
+        // The first four directions are orthogonal (rooks), the last four are diagonal (bishops)
+        let directions = [-8, 8, -1, 1, -9, -7, 9, 7];
+
+        for d_idx in 0..8
+        {
+            let slider = if d_idx < 4 { ROOK } else { BISHOP };
+
+            for s_idx in 0..self.edge_counts[square][d_idx]
+            {
+                let piece = self.board[(square as i8 + directions[d_idx] * (s_idx as i8 + 1)) as usize];
+
+                if piece == 0
+                {
+                    continue;
+                }
+
+                if    piece == by_color * slider
+                   || piece == by_color * QUEEN
+                   || (s_idx == 0 && piece == by_color * KING)
+                {
+                    return true;
+                }
+
+                break;
+            }
+        }
+
+        return false;
+    }
+
+    // Finds the square of the king of the given color
+    pub fn king_square(&self, color: i8) -> Option<usize>
+    {
+        return self.board.iter().position(|&piece| piece == color * KING);
+    }
+
+    // Returns true if the king of the given color is in check according to the rules of the board
+    pub fn in_check(&self, color: i8) -> bool
+    {
+        return self.rules.in_check(self, color);
+    }
+
+    // Returns true if the king of the given color is attacked
+    pub fn standard_in_check(&self, color: i8) -> bool
+    {
+        return match self.king_square(color)
+        {
+            Some(square) => self.square_attacked(square, -color),
+            None         => false,
+        };
+    }
+
     // A function that returns every square that the opponent pieces are attacking
-    // Very useful for when check will be implemented
-    fn check_opponent_attacks(&mut self, color: i8) -> Vec<usize>
+    pub fn check_opponent_attacks(&self, color: i8) -> Vec<usize>
     {
         let mut results: Vec<usize> = vec![];
 
         for i_idx in 0..64
         {
-            let current_piece = self.board[i_idx];
-            
-            if    current_piece != 0
-               && !Self::match_color(color, current_piece)
+            if self.square_attacked(i_idx, -color)
             {
-                match current_piece {
-                    4 | -4 => results.extend(self.rook_moves    (color, i_idx)),
-                    2 | -2 => results.extend(self.knight_moves  (color, i_idx)),
-                    3 | -3 => results.extend(self.bishop_moves  (color, i_idx)),
-                    5 | -5 => results.extend(self.queen_moves   (color, i_idx)),
-                    1 | -1 => results.extend(self.pawn_captures (color, i_idx)),
-                    _ => (),
-                }
+                results.push(i_idx);
             }
         }
         
         return results;
     }
+
+    // Calls the move generation function for the piece on the square
+    pub fn piece_moves(&self, square: usize) -> Vec<usize>
+    {
+        let piece = self.board[square];
+        let color = piece.signum();
+
+        return match piece.abs()
+        {
+            PAWN   => self.pawn_moves   (color, square),
+            KNIGHT => self.knight_moves (color, square),
+            BISHOP => self.bishop_moves (color, square),
+            ROOK   => self.rook_moves   (color, square),
+            QUEEN  => self.queen_moves  (color, square),
+            KING   => self.king_moves   (color, square),
+            _      => vec![],
+        };
+    }
+
+    // Turns the values returned by the move generation functions into a Move
+    // < 64            -> the target square
+    // 200, 300, ...   -> promotion to a knight, bishop, rook or queen + the target square
+    // 101             -> short castle
+    // 1001            -> long castle
+    pub fn decode_move(&self, origin: usize, value: usize) -> Move
+    {
+        let piece = self.board[origin];
+        let color = piece.signum();
+
+        let mut new_move = Move
+        {
+            piece,
+            origin,
+            target: value,
+            capture: 0,
+            promotion: 0,
+            kind: Move_Kind::Normal,
+        };
+
+        if value == 101
+        {
+            new_move.target = Self::get_row(origin) * 8 + 6;
+            new_move.kind = Move_Kind::Short_Castle;
+        } else if value == 1001
+        {
+            new_move.target = Self::get_row(origin) * 8 + 2;
+            new_move.kind = Move_Kind::Long_Castle;
+        } else if value >= 200
+        {
+            new_move.target = value % 100;
+            new_move.capture = self.board[new_move.target];
+            new_move.promotion = (value / 100) as i8 * color;
+            new_move.kind = Move_Kind::Promotion;
+        } else
+        {
+            new_move.capture = self.board[value];
+
+            if piece.abs() == PAWN
+            {
+                if i8::abs(origin as i8 - value as i8) == 16
+                {
+                    new_move.kind = Move_Kind::Double_Push;
+                } else if    Self::get_col(origin) != Self::get_col(value)
+                          && self.board[value] == 0
+                {
+                    new_move.kind = Move_Kind::En_Passant;
+                    new_move.capture = -color * PAWN;
+                }
+            }
+        }
+
+        return new_move;
+    }
+
+    // Generates every move for the given color without checking whether the king is left in check
+    pub fn pseudo_legal_moves(&self, color: i8) -> Vec<Move>
+    {
+        return self.rules.pseudo_legal_moves(self, color);
+    }
+
+    // The pseudo legal moves of normal chess
+    pub fn standard_pseudo_legal_moves(&self, color: i8) -> Vec<Move>
+    {
+        let mut results: Vec<Move> = vec![];
+
+        for square in 0..64
+        {
+            if !Self::match_color(color, self.board[square])
+            {
+                continue;
+            }
+
+            for value in self.piece_moves(square)
+            {
+                results.push(self.decode_move(square, value));
+            }
+        }
+
+        return results;
+    }
+
+    // Generates every legal move for the side to move according to the rules of the board
+    // There are no moves once the game is over according to the variant
+    pub fn legal_moves(&mut self) -> Vec<Move>
+    {
+        if self.variant_outcome().is_some()
+        {
+            return vec![];
+        }
+
+        return self.rules.legal_moves(self);
+    }
+
+    // The legal moves of normal chess
+    // Each move is made on the board and taken back again to check whether it leaves the king in check
+    pub fn standard_legal_moves(&mut self) -> Vec<Move>
+    {
+        let color = self.details.side_to_move;
+        let mut results: Vec<Move> = vec![];
+
+        for candidate in self.pseudo_legal_moves(color)
+        {
+            if    candidate.kind == Move_Kind::Short_Castle
+               || candidate.kind == Move_Kind::Long_Castle
+            {
+                // The king can not castle out of or through check
+                let first = std::cmp::min(candidate.origin, candidate.target);
+                let last  = std::cmp::max(candidate.origin, candidate.target);
+
+                if (first..=last).any(|path| self.square_attacked(path, -color))
+                {
+                    continue;
+                }
+            }
+
+            self.make_move(candidate);
+            let legal = !self.in_check(color);
+            self.unmake_move();
+
+            if legal
+            {
+                results.push(candidate);
+            }
+        }
+
+        return results;
+    }
+
+    // Plays a move on the board, the move is assumed to be legal
+    pub fn make_move(&mut self, new_move: Move)
+    {
+        self.rules.make_move(self, new_move);
+    }
+
+    pub fn standard_make_move(&mut self, new_move: Move)
+    {
+        let color = new_move.piece.signum();
+
+        self.previous_details.push(self.details);
+
+        self.board[new_move.origin] = 0;
+        self.board[new_move.target] = new_move.piece;
+
+        match new_move.kind
+        {
+            Move_Kind::En_Passant   => self.board[(new_move.target as i8 - color * 8) as usize] = 0,
+            Move_Kind::Promotion    => self.board[new_move.target] = new_move.promotion,
+            Move_Kind::Short_Castle | Move_Kind::Long_Castle =>
+            {
+                // In Chess960 the king can end up on the square of the rook or the other way
+                // around, so both pieces are taken off the board before being put back
+                let rook_square = self.castling_rooks[Self::castling_index(new_move)];
+                let (king_target, rook_target) = Self::castling_targets(new_move);
+
+                self.board[rook_square] = 0;
+                self.board[king_target] = new_move.piece;
+                self.board[rook_target] = color * ROOK;
+            },
+            _ => (),
+        }
+
+        // Update the castling details, a rook that is captured counts as moved
+        if new_move.piece.abs() == KING
+        {
+            self.details.kings_have_moved[if color < 0 { 0 } else { 1 }] = true;
+        }
+
+        for r_idx in 0..4
+        {
+            if    new_move.origin == self.castling_rooks[r_idx]
+               || new_move.target == self.castling_rooks[r_idx]
+            {
+                self.details.rooks_have_moved[r_idx] = true;
+            }
+        }
+
+        self.details.en_passant = if new_move.kind == Move_Kind::Double_Push
+        {
+            Some((new_move.origin + new_move.target) / 2)
+        } else
+        {
+            None
+        };
+
+        self.details.halfmove_clock = if new_move.capture != 0 || new_move.piece.abs() == PAWN
+        {
+            0
+        } else
+        {
+            self.details.halfmove_clock + 1
+        };
+
+        if color == BLACK
+        {
+            self.details.fullmove_number += 1;
+        }
+
+        self.details.side_to_move = -color;
+        self.details.is_check = if self.in_check(-color) { color } else { 0 };
+
+        self.moves.push(new_move);
+    }
+
+    // Takes back the last move that was played
+    pub fn unmake_move(&mut self)
+    {
+        self.rules.unmake_move(self);
+    }
+
+    pub fn standard_unmake_move(&mut self)
+    {
+        let last_move = match self.moves.pop()
+        {
+            Some(last_move) => last_move,
+            None => return,
+        };
+
+        let color = last_move.piece.signum();
+
+        if let Some(details) = self.previous_details.pop()
+        {
+            self.details = details;
+        }
+
+        self.board[last_move.origin] = last_move.piece;
+        self.board[last_move.target] = last_move.capture;
+
+        match last_move.kind
+        {
+            Move_Kind::En_Passant   =>
+            {
+                self.board[last_move.target] = 0;
+                self.board[(last_move.target as i8 - color * 8) as usize] = last_move.capture;
+            },
+            Move_Kind::Short_Castle | Move_Kind::Long_Castle =>
+            {
+                let rook_square = self.castling_rooks[Self::castling_index(last_move)];
+                let (king_target, rook_target) = Self::castling_targets(last_move);
+
+                self.board[king_target] = 0;
+                self.board[rook_target] = 0;
+                self.board[last_move.origin] = last_move.piece;
+                self.board[rook_square] = color * ROOK;
+            },
+            _ => (),
+        }
+    }
+
+    // Finds the legal move matching a move written in UCI coordinate notation ("e2e4", "e7e8q")
+    pub fn parse_uci_move(&mut self, text: &str) -> Option<Move>
+    {
+        return self.legal_moves()
+                   .into_iter()
+                   .find(|legal_move| self.move_to_uci(*legal_move) == text);
+    }
+
+    // Counts the number of leaf nodes of the move tree up to a depth
+    // Comparing the results to the known values for a position is the standard way to find
+    // bugs within move generation
+    pub fn perft(&mut self, depth: u32) -> u64
+    {
+        if depth == 0
+        {
+            return 1;
+        }
+
+        let mut nodes = 0;
+
+        for legal_move in self.legal_moves()
+        {
+            self.make_move(legal_move);
+            nodes += self.perft(depth - 1);
+            self.unmake_move();
+        }
+
+        return nodes;
+    }
 } // impl Chess_Board
 
 // Setting up the default values for the Chess_Board struct
@@ -592,12 +1152,12 @@ impl Default for Chess_Board
 {
     fn default() -> Self
     {
-        Chess_Board
+        let mut new_board = Chess_Board
         { 
             board:
             [
                 4,  2,  3,  5,  6,  3,  2,  4,
-                1,  1,  0,  1,  0,  1,  1,  1,
+                1,  1,  1,  1,  1,  1,  1,  1,
                 0,  0,  0,  0,  0,  0,  0,  0,
                 0,  0,  0,  0,  0,  0,  0,  0,
                 0,  0,  0,  0,  0,  0,  0,  0,
@@ -611,7 +1171,23 @@ impl Default for Chess_Board
                 rooks_have_moved: [false, false, false, false],
                 kings_have_moved: [false, false],
                 is_check: 0,
+                side_to_move: WHITE,
+                en_passant: None,
+                halfmove_clock: 0,
+                fullmove_number: 1,
+                checks_given: [0, 0],
+                pockets: [[0; 5]; 2],
+                promoted: 0,
+                placing_duck: false,
             },
-        }
+            previous_details: vec![],
+            castling_rooks: [0, 7, 56, 63],
+            chess960: false,
+            rules: &STANDARD,
+            saved_boards: vec![],
+        };
+
+        new_board.generate_distance();
+        return new_board;
     }
 }
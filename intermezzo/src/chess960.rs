@@ -0,0 +1,113 @@
+// Chess960 (Fischer Random Chess) starting positions
+//
+// The pieces on the back rank are shuffled, with the bishops on squares of opposite colors and the
+// king somewhere between the two rooks. The pawns stay where they are and black mirrors white
+// There are 960 such positions, numbered 0 to 959 as described by Reinhard Scharnagl:
+//
+// n = index
+// light square bishop -> file b, d, f or h, given by n % 4, then n = n / 4
+// dark square bishop  -> file a, c, e or g, given by n % 4, then n = n / 4
+// queen               -> one of the 6 empty squares, given by n % 6, then n = n / 6
+// knights             -> two of the 5 empty squares, n (0 - 9) is an index into KNIGHT_PLACEMENTS
+// rook, king, rook    -> the 3 squares that are left, from left to right
+//
+// Number 518 is the normal starting position
+//
+// Castling works as in normal chess apart from where the pieces start, see king_moves and
+// make_move within chess.rs
+
+use crate::chess::*;
+
+pub const CHESS960_POSITIONS: u32 = 960;
+pub const STANDARD_INDEX: u32 = 518;
+
+// The empty squares the two knights are put on, counted among the 5 squares left after the
+// bishops and the queen
+const KNIGHT_PLACEMENTS: [(usize, usize); 10] = [
+    (0, 1), (0, 2), (0, 3), (0, 4),
+    (1, 2), (1, 3), (1, 4),
+    (2, 3), (2, 4),
+    (3, 4),
+];
+
+impl Chess_Board
+{
+    // The Chess960 starting position with the given number
+    pub fn from_chess960_index(index: u32) -> Result<Self, String>
+    {
+        let Some(back_rank) = chess960_back_rank(index) else { return Err(format!("there is no Chess960 position {}, the numbers go from 0 to 959", index)) };
+
+        let mut new_board = Chess_Board::new();
+
+        for col in 0..8
+        {
+            new_board.board[col] = back_rank[col];
+            new_board.board[56 + col] = -back_rank[col];
+        }
+
+        // The rooks are the outermost pieces on either side of the king
+        let rooks: Vec<usize> = (0..8).filter(|&col| back_rank[col] == ROOK).collect();
+
+        new_board.castling_rooks = [rooks[0], rooks[1], 56 + rooks[0], 56 + rooks[1]];
+        new_board.details.rooks_have_moved = [false; 4];
+        new_board.details.kings_have_moved = [false; 2];
+        new_board.chess960 = true;
+
+        return Ok(new_board);
+    }
+
+    // The number of the Chess960 starting position the board is in, if any
+    // Only the pieces are compared, a position with moves played never matches
+    pub fn chess960_index(&self) -> Option<u32>
+    {
+        if !self.moves.is_empty()
+        {
+            return None;
+        }
+
+        return (0..CHESS960_POSITIONS).find(|&index| {
+            let Some(back_rank) = chess960_back_rank(index) else { return false };
+
+            return (0..8).all(|col| {
+                return    self.board[col] == back_rank[col]
+                       && self.board[8 + col] == PAWN
+                       && self.board[48 + col] == -PAWN
+                       && self.board[56 + col] == -back_rank[col];
+            }) && self.board[16..48].iter().all(|&piece| piece == 0);
+        });
+    }
+}
+
+// The white pieces on the first rank from the a file to the h file
+pub fn chess960_back_rank(index: u32) -> Option<[i8; 8]>
+{
+    if index >= CHESS960_POSITIONS
+    {
+        return None;
+    }
+
+    let mut back_rank = [0; 8];
+    let mut n = index as usize;
+
+    back_rank[2 * (n % 4) + 1] = BISHOP;
+    n /= 4;
+
+    back_rank[2 * (n % 4)] = BISHOP;
+    n /= 4;
+
+    let empty: Vec<usize> = (0..8).filter(|&col| back_rank[col] == 0).collect();
+    back_rank[empty[n % 6]] = QUEEN;
+    n /= 6;
+
+    let empty: Vec<usize> = (0..8).filter(|&col| back_rank[col] == 0).collect();
+    let (first, second) = KNIGHT_PLACEMENTS[n];
+    back_rank[empty[first]] = KNIGHT;
+    back_rank[empty[second]] = KNIGHT;
+
+    let empty: Vec<usize> = (0..8).filter(|&col| back_rank[col] == 0).collect();
+    back_rank[empty[0]] = ROOK;
+    back_rank[empty[1]] = KING;
+    back_rank[empty[2]] = ROOK;
+
+    return Some(back_rank);
+}
@@ -0,0 +1,916 @@
+// Playing games on the terminal, started when intermezzo is run without a command
+//
+// Commands are read line by line, anything that is not a command is played as a move, in SAN
+// ("Nf3", "O-O", "e8=Q") or in coordinates ("g1f3", "e7e8q"). The board is printed again after
+// every change. Both sides are played by humans until the engine is given a side with "engine"
+//
+// Some variants are not played on a single Chess_Board and only know part of the commands:
+// Capablanca, Gothic      -> played on a Fairy_Board (fairy.rs) searched by fairy_search.rs, without
+//                            pgn, animate, resign and draw. Only the draws that end the game
+//                            immediately (fivefold repetition, 75 moves, insufficient material) apply
+// Kriegspiel, Fog of War  -> two players on the same terminal, the board is shown as the player to
+//                            move sees it and the moves are tried in coordinates with the referee
+//                            (referee.rs), "announcements" repeats what the referee said so far
+// Bughouse                -> four players on two boards, every move starts with the number of its
+//                            board ("1 e4", "2 N@f3"), see crazyhouse.rs. There is no engine
+//
+// help                          -> the list of commands
+// new [variant]                 -> a new game, of a variant if given (see variant.rs and fairy.rs)
+// new 960 [number]              -> a Chess960 game from the numbered position, a random one without
+// odds white|black <odds>       -> a game where one side starts without some pieces (see handicap.rs)
+// fen [fen]                     -> prints the FEN, or starts a game from the FEN
+// moves [square]                -> the legal moves, or the moves of the piece on the square
+// undo                          -> takes back a move, against the engine until it is your turn again
+// flip                          -> turns the board around
+// style ascii|unicode|color     -> how the board is drawn, see render.rs
+// engine white|black|both|off   -> the sides the engine plays
+// go                            -> the engine plays a move for the side to move
+// depth <n> | time <seconds>    -> how deep or how long the engine searches
+// board | pgn [file]            -> prints the board, or the game as PGN (saved to a file if given)
+// animate <file>                -> saves the game as an animated GIF, or APNG for .png files
+// resign | draw                 -> resigns for the side to move or claims a draw
+// quit
+
+use crate::animation::*;
+use crate::board_image::*;
+use crate::chess::*;
+use crate::chess960::*;
+use crate::crazyhouse::*;
+use crate::fairy::*;
+use crate::fairy_search::*;
+use crate::fog_of_war::*;
+use crate::game::*;
+use crate::handicap::*;
+use crate::notation::piece_to_char;
+use crate::pgn::*;
+use crate::referee::*;
+use crate::render::*;
+use crate::search::*;
+use crate::time_manager::*;
+use crate::variant::*;
+
+use std::io::{BufRead, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const HELP: &str = "\
+commands:
+  <move>                       play a move in SAN (Nf3, O-O, e8=Q) or coordinates (g1f3, e7e8q)
+  new [variant]                start a new game
+  new 960 [number]             start a Chess960 game, from a random position without a number
+  odds white|black <odds>      start a game where one side gives odds (rook,knight or a square)
+  fen [fen]                    print the FEN, or start a game from a FEN
+  moves [square]               list the legal moves, or those of the piece on a square
+  undo                         take back a move
+  flip                         turn the board around
+  style ascii|unicode|color    draw the board with letters, figurines or colored squares
+  engine white|black|both|off  choose the sides the engine plays
+  go                           let the engine play a move now
+  depth <n>                    let the engine search to a fixed depth
+  time <seconds>               let the engine think for a fixed time per move
+  board                        print the board
+  pgn [file]                   print the game as PGN, or save it to a file
+  animate <file.gif|file.png>  save the game as an animation
+  resign                       resign for the side to move
+  draw                         claim a draw by repetition or the 50 move rule
+  quit                         leave
+
+In Kriegspiel and Fog of War the moves are tried as coordinates (e2e4) and \"announcements\"
+repeats what the referee said. In Bughouse every move starts with the number of its board: 1 e4,
+2 N@f3";
+
+pub struct Cli
+{
+    game:    Game,
+    // Set while a variant on a Fairy_Board is played instead of game
+    fairy:   Option<(&'static Fairy_Variant, Fairy_Board)>,
+    // Set while Kriegspiel or Fog of War is played, the referee keeps the real game
+    referee: Option<Referee>,
+    // Set while Bughouse is played on two boards
+    bughouse: Option<Bughouse_Game>,
+    // How the board is drawn, marked is set for every drawing
+    render:  Render_Options,
+    // Whether the engine plays [white, black]
+    engine:  [bool; 2],
+    limits:  Search_Limits,
+    search:  Search,
+}
+
+impl Cli
+{
+    pub fn new() -> Self
+    {
+        let limits = Search_Limits
+        {
+            time: Some(Time_Controls { move_time: Some(1000), ..Default::default() }),
+            ..Default::default()
+        };
+
+        return Cli
+        {
+            game: Game::new(),
+            fairy: None,
+            referee: None,
+            bughouse: None,
+            render: Render_Options { last_move: true, check: true, ..Render_Options::plain() },
+            engine: [false, false],
+            limits,
+            search: Search::new(),
+        };
+    }
+
+    fn engine_index(color: i8) -> usize
+    {
+        return if color == WHITE { 0 } else { 1 };
+    }
+
+    // Reads commands until "quit" or the end of the input
+    pub fn run(&mut self)
+    {
+        println!("intermezzo, type \"help\" for the list of commands");
+        self.show(0);
+
+        let stdin = std::io::stdin();
+        let mut lines = stdin.lock().lines();
+
+        loop
+        {
+            print!("> ");
+            std::io::stdout().flush().ok();
+
+            let Some(Ok(line)) = lines.next() else { break };
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+
+            let Some(&command) = tokens.first() else { continue };
+
+            if command == "quit" || command == "exit"
+            {
+                break;
+            }
+
+            match self.execute(command, &tokens[1..])
+            {
+                Ok(())     => self.play_engine_moves(),
+                Err(error) => println!("{}", error),
+            }
+        }
+    }
+
+    // Whether the game is over and whose turn it is, on the Fairy_Board while one is played
+    fn is_over(&self) -> bool
+    {
+        if let Some(referee) = &self.referee
+        {
+            return referee.is_over();
+        }
+
+        if let Some(bughouse) = &self.bughouse
+        {
+            return bughouse.is_over();
+        }
+
+        return match &self.fairy
+        {
+            Some((_, board)) => board.clone().outcome().is_some(),
+            None             => self.game.is_over(),
+        };
+    }
+
+    fn side_to_move(&self) -> i8
+    {
+        if let Some(referee) = &self.referee
+        {
+            return referee.side_to_move();
+        }
+
+        return match &self.fairy
+        {
+            Some((_, board)) => board.details.side_to_move,
+            None             => self.game.side_to_move(),
+        };
+    }
+
+    fn execute(&mut self, command: &str, args: &[&str]) -> Result<(), String>
+    {
+        let special = if self.fairy.is_some()
+        {
+            self.execute_fairy(command, args)
+        } else if self.referee.is_some()
+        {
+            self.execute_referee(command, args)
+        } else if self.bughouse.is_some()
+        {
+            self.execute_bughouse(command, args)
+        } else
+        {
+            None
+        };
+
+        if let Some(result) = special
+        {
+            return result;
+        }
+
+        match command
+        {
+            "help" =>
+            {
+                println!("{}", HELP);
+            },
+            "new" =>
+            {
+                self.new_game(args)?;
+                self.show(0);
+            },
+            "odds" =>
+            {
+                let usage = "usage: odds white|black <odds>, e.g. \"odds white rook,knight\" or \"odds black pawn and move\"";
+
+                let giver = match args.first().copied()
+                {
+                    Some("white") => WHITE,
+                    Some("black") => BLACK,
+                    _             => return Err(String::from(usage)),
+                };
+
+                if args.len() < 2
+                {
+                    return Err(String::from(usage));
+                }
+
+                let board = Chess_Board::with_odds(giver, &Odds::parse_list(&args[1..].join(" "))?)?;
+
+                self.leave_special_games();
+                self.game = Game::from_board(board);
+                self.show(0);
+            },
+            "fen" if args.is_empty() =>
+            {
+                let board = self.game.board();
+                println!("{}", board.to_fen());
+
+                // GUIs for Chess960 often only read Shredder-FEN
+                if board.chess960
+                {
+                    println!("{} (Shredder-FEN)", board.to_shredder_fen());
+                }
+
+                match board.chess960_index().filter(|_| board.chess960)
+                {
+                    Some(STANDARD_INDEX) => println!("Chess960 position {} (the normal starting position)", STANDARD_INDEX),
+                    Some(index)          => println!("Chess960 position {}", index),
+                    None                 => (),
+                }
+            },
+            "fen" =>
+            {
+                let board = Chess_Board::from_variant_fen(&args.join(" "), self.game.rules())?;
+
+                self.leave_special_games();
+                self.game = Game::from_board(board);
+                self.show(0);
+            },
+            "moves" =>
+            {
+                self.list_moves(args.first().copied())?;
+            },
+            "undo" =>
+            {
+                if self.game.undo().is_none()
+                {
+                    return Err(String::from("there is no move to take back"));
+                }
+
+                // Against the engine the engine's move is taken back as well
+                let human_playing = self.engine != [true, true];
+
+                while    human_playing
+                      && self.engine[Self::engine_index(self.game.side_to_move())]
+                      && self.game.undo().is_some()
+                {
+                }
+
+                self.show(0);
+            },
+            "flip" =>
+            {
+                self.render.flipped = !self.render.flipped;
+                self.show(0);
+            },
+            "style" =>
+            {
+                let (figurines, colors) = match args.first().copied()
+                {
+                    Some("ascii")   => (false, false),
+                    Some("unicode") => (true, false),
+                    Some("color")   => (true, true),
+                    _               => return Err(String::from("usage: style ascii|unicode|color")),
+                };
+
+                self.render.figurines = figurines;
+                self.render.colors = colors;
+                self.show(0);
+            },
+            "engine" =>
+            {
+                self.engine = match args.first().copied()
+                {
+                    Some("white") => [true, false],
+                    Some("black") => [false, true],
+                    Some("both")  => [true, true],
+                    Some("off")   => [false, false],
+                    _             => return Err(String::from("usage: engine white|black|both|off")),
+                };
+            },
+            "go" =>
+            {
+                self.engine_move()?;
+            },
+            "depth" =>
+            {
+                let depth = args.first().and_then(|depth| depth.parse::<u32>().ok()).ok_or("usage: depth <n>")?;
+
+                self.limits = Search_Limits { depth: Some(depth), ..Default::default() };
+            },
+            "time" =>
+            {
+                let seconds = args.first().and_then(|seconds| seconds.parse::<f64>().ok()).ok_or("usage: time <seconds>")?;
+                let move_time = (seconds * 1000.0).max(1.0) as u64;
+
+                self.limits = Search_Limits { time: Some(Time_Controls { move_time: Some(move_time), ..Default::default() }), ..Default::default() };
+            },
+            "board" =>
+            {
+                self.show(0);
+            },
+            "pgn" =>
+            {
+                let pgn_game = Pgn_Game::from_game(&self.game);
+
+                match args.first()
+                {
+                    Some(path) =>
+                    {
+                        write_pgn_file(path, &[pgn_game]).map_err(|error| format!("could not write {}: {}", path, error))?;
+                        println!("saved the game to {}", path);
+                    },
+                    None => println!("{}", pgn_game.to_pgn()),
+                }
+            },
+            "animate" =>
+            {
+                let Some(path) = args.first() else { return Err(String::from("usage: animate <file.gif|file.png>")) };
+
+                let options = Animation_Options
+                {
+                    image: Image_Options { flipped: self.render.flipped, ..Default::default() },
+                    ..Default::default()
+                };
+
+                let board = self.game.board();
+                let data = if path.to_lowercase().ends_with(".png") { board.to_apng(&options) } else { board.to_gif(&options) };
+
+                std::fs::write(path, data).map_err(|error| format!("could not write {}: {}", path, error))?;
+                println!("saved {} positions to {}", board.moves.len() + 1, path);
+            },
+            "resign" =>
+            {
+                self.game.resign(self.game.side_to_move())?;
+                self.show(0);
+            },
+            "draw" =>
+            {
+                self.game.claim_draw()?;
+                self.show(0);
+            },
+            _ =>
+            {
+                let text = [command].iter().chain(args).copied().collect::<Vec<&str>>().join("");
+                let board = self.game.board().clone();
+                let played = self.game.play_text(&text)?;
+
+                println!("{}", board.clone().move_to_san(played));
+                self.show(0);
+            },
+        }
+
+        return Ok(());
+    }
+
+    // Back to a game on a single Chess_Board
+    fn leave_special_games(&mut self)
+    {
+        self.fairy = None;
+        self.referee = None;
+        self.bughouse = None;
+    }
+
+    // The name of the variant played now, to start it again
+    fn variant_name(&self) -> String
+    {
+        if let Some((fairy, _)) = &self.fairy
+        {
+            return String::from(fairy.name);
+        }
+
+        if let Some(referee) = &self.referee
+        {
+            return String::from(if referee.mode == Referee_Mode::Kriegspiel { "Kriegspiel" } else { "Fog of War" });
+        }
+
+        if self.bughouse.is_some()
+        {
+            return String::from(BUGHOUSE.name());
+        }
+
+        if self.game.board().chess960
+        {
+            return String::from("Chess960");
+        }
+
+        return String::from(self.game.rules().name());
+    }
+
+    // Starts a new game of the named variant, without a name the variant played so far
+    fn new_game(&mut self, args: &[&str]) -> Result<(), String>
+    {
+        let name = if args.is_empty() { self.variant_name() } else { args.join(" ") };
+        let mut words = name.split_whitespace();
+
+        // Chess960 can be followed by the number of the position
+        if words.next().is_some_and(|first| matches!(variant_key_name(first).as_str(), "960" | "chess960"))
+        {
+            let index = match words.next()
+            {
+                Some(number) => number.parse::<u32>().map_err(|_| format!("{} is not a number", number))?,
+                None         => SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.subsec_nanos()).unwrap_or(0) % CHESS960_POSITIONS,
+            };
+
+            let board = Chess_Board::from_chess960_index(index)?;
+
+            self.leave_special_games();
+            self.game = Game::from_board(board);
+            println!("Chess960 position {}", index);
+
+            return Ok(());
+        }
+
+        if let Some(fairy) = find_fairy_variant(&name)
+        {
+            self.leave_special_games();
+            self.fairy = Some((fairy, Fairy_Board::from_fen(fairy.start_fen)?));
+
+            return Ok(());
+        }
+
+        let rules = if variant_key_name(&name) == "kriegspiel" { None } else { Some(find_variant(&name).ok_or(format!("unknown variant \"{}\"", name))?) };
+
+        self.leave_special_games();
+
+        // The hidden boards are played by two people and Bughouse by four, the engine only
+        // knows games on a single board where it sees everything
+        match rules
+        {
+            None =>
+            {
+                self.referee = Some(Referee::new(Referee_Mode::Kriegspiel));
+                self.engine = [false, false];
+            },
+            Some(rules) if rules.name() == FOG_OF_WAR.name() =>
+            {
+                self.referee = Some(Referee::new(Referee_Mode::Fog_Of_War));
+                self.engine = [false, false];
+            },
+            Some(rules) if rules.name() == BUGHOUSE.name() =>
+            {
+                self.bughouse = Some(Bughouse_Game::new());
+                self.engine = [false, false];
+            },
+            Some(rules) => self.game = Game::with_rules(rules),
+        }
+
+        return Ok(());
+    }
+
+    // The commands that work differently on a Fairy_Board, None for those that are the same
+    fn execute_fairy(&mut self, command: &str, args: &[&str]) -> Option<Result<(), String>>
+    {
+        let (fairy, board) = self.fairy.as_mut()?;
+
+        match command
+        {
+            "fen" if args.is_empty() =>
+            {
+                println!("{}", board.to_fen());
+            },
+            "fen" =>
+            {
+                match Fairy_Board::from_fen(&args.join(" "))
+                {
+                    Ok(new_board) => *board = new_board,
+                    Err(error)    => return Some(Err(error)),
+                }
+            },
+            "moves" =>
+            {
+                let origin = match args.first()
+                {
+                    Some(name) => match board.geometry.parse_square(name)
+                    {
+                        Some(square) => Some(square),
+                        None         => return Some(Err(format!("{} is not a square", name))),
+                    },
+                    None => None,
+                };
+
+                let moves: Vec<Move> = board.legal_moves()
+                                            .into_iter()
+                                            .filter(|legal_move| origin.is_none_or(|origin| legal_move.origin == origin))
+                                            .collect();
+
+                let names: Vec<String> = moves.iter().map(|legal_move| board.move_to_san(*legal_move)).collect();
+
+                println!("{}", if names.is_empty() { String::from("no legal moves") } else { names.join(" ") });
+                return Some(Ok(()));
+            },
+            "undo" =>
+            {
+                if board.moves.is_empty()
+                {
+                    return Some(Err(String::from("there is no move to take back")));
+                }
+
+                board.unmake_move();
+
+                // Against the engine the engine's move is taken back as well
+                let human_playing = self.engine != [true, true];
+
+                while    human_playing
+                      && self.engine[Self::engine_index(board.details.side_to_move)]
+                      && !board.moves.is_empty()
+                {
+                    board.unmake_move();
+                }
+            },
+            "go" =>
+            {
+                return Some(self.engine_move());
+            },
+            "pgn" | "animate" | "resign" | "draw" =>
+            {
+                return Some(Err(format!("{} is not available in {}", command, fairy.name)));
+            },
+            "help" | "new" | "flip" | "style" | "engine" | "depth" | "time" | "board" =>
+            {
+                return None;
+            },
+            _ =>
+            {
+                if board.clone().outcome().is_some()
+                {
+                    return Some(Err(String::from("the game is over")));
+                }
+
+                let text = [command].iter().chain(args).copied().collect::<Vec<&str>>().join("");
+
+                let Some(played) = board.parse_san(&text).or_else(|| board.parse_uci_move(&text)) else
+                {
+                    return Some(Err(format!("{} is not a legal move", text)));
+                };
+
+                println!("{}", board.move_to_san(played));
+                board.make_move(played);
+            },
+        }
+
+        self.show(0);
+
+        return Some(Ok(()));
+    }
+
+    // The commands of Kriegspiel and Fog of War, None for those that are the same
+    fn execute_referee(&mut self, command: &str, args: &[&str]) -> Option<Result<(), String>>
+    {
+        let referee = self.referee.as_mut()?;
+
+        match command
+        {
+            "help" | "new" | "flip" | "style" | "board" =>
+            {
+                return None;
+            },
+            // The moves are only shown once nothing can be given away anymore
+            "pgn" if referee.is_over() =>
+            {
+                println!("{}", Pgn_Game::from_game(referee.game()).to_pgn());
+                return Some(Ok(()));
+            },
+            // Both players hear every announcement, so they can be repeated to either of them
+            "announcements" =>
+            {
+                for announcement in referee.announcements()
+                {
+                    println!("{}", announcement.description());
+                }
+
+                return Some(Ok(()));
+            },
+            "pgn" | "fen" | "moves" | "undo" | "engine" | "go" | "depth" | "time" | "animate" | "resign" | "draw" =>
+            {
+                return Some(Err(format!("{} is not available while the board is hidden", command)));
+            },
+            _ =>
+            {
+                let text = [command].iter().chain(args).copied().collect::<Vec<&str>>().join("");
+                let color = referee.side_to_move();
+
+                let announcements = match referee.try_text(color, &text)
+                {
+                    Ok(announcements) => announcements,
+                    Err(error)        => return Some(Err(error)),
+                };
+
+                for announcement in &announcements
+                {
+                    println!("referee: {}", announcement.description());
+                }
+
+                // After an illegal try the same player tries again on the same board
+                if announcements.contains(&Announcement::Illegal)
+                {
+                    return Some(Ok(()));
+                }
+
+                if !referee.is_over()
+                {
+                    println!("hand the board to {}", if color == WHITE { "black" } else { "white" });
+                }
+            },
+        }
+
+        self.show(0);
+
+        return Some(Ok(()));
+    }
+
+    // The commands of Bughouse, None for those that are the same
+    fn execute_bughouse(&mut self, command: &str, args: &[&str]) -> Option<Result<(), String>>
+    {
+        let bughouse = self.bughouse.as_mut()?;
+
+        match command
+        {
+            "help" | "new" | "flip" | "style" | "board" =>
+            {
+                return None;
+            },
+            "1" | "2" =>
+            {
+                let b_idx = if command == "1" { 0 } else { 1 };
+                let text = args.join("");
+                let mut before = bughouse.boards[b_idx].clone();
+
+                match bughouse.play(b_idx, &text)
+                {
+                    Ok(played) => println!("board {}: {}", b_idx + 1, before.move_to_san(played)),
+                    Err(error) => return Some(Err(error)),
+                }
+            },
+            "fen" | "moves" | "undo" | "engine" | "go" | "depth" | "time" | "pgn" | "animate" | "resign" | "draw" =>
+            {
+                return Some(Err(format!("{} is not available in Bughouse", command)));
+            },
+            _ =>
+            {
+                return Some(Err(String::from("start the move with the number of its board: 1 e4, 2 N@f3")));
+            },
+        }
+
+        self.show(0);
+
+        return Some(Ok(()));
+    }
+
+    // The pieces in the pocket of a color, "N N P" or "-"
+    fn pocket_text(board: &Chess_Board, color: i8) -> String
+    {
+        let pocket = board.details.pockets[pocket_index(color)];
+
+        let pieces: Vec<String> = POCKET_PIECES.iter()
+                                               .zip(pocket)
+                                               .flat_map(|(&piece, count)| std::iter::repeat_n(piece_to_char(color * piece).to_string(), count as usize))
+                                               .collect();
+
+        return if pieces.is_empty() { String::from("-") } else { pieces.join(" ") };
+    }
+
+    // Prints the board, the squares in marked are drawn as the targets of a move, and below it
+    // whose turn it is or how the game ended
+    fn show(&self, marked: u64)
+    {
+        if let Some(referee) = &self.referee
+        {
+            self.show_view(referee);
+            return;
+        }
+
+        if let Some(bughouse) = &self.bughouse
+        {
+            self.show_bughouse(bughouse);
+            return;
+        }
+
+        if let Some((_, board)) = &self.fairy
+        {
+            print!("{}", board.render(&self.render));
+
+            match board.clone().outcome()
+            {
+                Some(outcome) =>
+                {
+                    let result = match outcome.winner(board.details.side_to_move)
+                    {
+                        WHITE => "1-0",
+                        BLACK => "0-1",
+                        _     => "1/2-1/2",
+                    };
+
+                    println!("game over: {} by {}", result, Termination::from_outcome(outcome).description());
+                },
+                None =>
+                {
+                    let side = if board.details.side_to_move == WHITE { "white" } else { "black" };
+                    println!("{} to move{}", side, if board.details.is_check != 0 { ", check" } else { "" });
+                },
+            }
+
+            return;
+        }
+
+        let board = self.game.board();
+
+        print!("{}", board.render(&Render_Options { marked, ..self.render }));
+
+        if let Some(termination) = self.game.termination()
+        {
+            println!("game over: {} by {}", self.game.result().to_pgn(), termination.description());
+            return;
+        }
+
+        let side = if board.details.side_to_move == WHITE { "white" } else { "black" };
+        let check = if board.details.is_check != 0 { ", check" } else { "" };
+
+        println!("{} to move{}", side, check);
+    }
+
+    // The board as the player to move sees it, the whole board once the game is over. In Fog of
+    // War the empty squares the player can see are marked
+    fn show_view(&self, referee: &Referee)
+    {
+        let game = referee.game();
+
+        if let Some(termination) = game.termination()
+        {
+            print!("{}", game.board().render(&self.render));
+            println!("game over: {} by {}", game.result().to_pgn(), termination.description());
+            return;
+        }
+
+        let color = referee.side_to_move();
+        let view = referee.view(color);
+
+        // Only the pieces of the view, and no last move as it would show where the other side moved
+        let mut shown = game.board().clone();
+        shown.board = view.board;
+        shown.moves.clear();
+
+        let marked = if referee.mode == Referee_Mode::Fog_Of_War { (0..64).filter(|&square| view.is_visible(square) && view.board[square] == 0).fold(0, |marked, square| marked | 1 << square) } else { 0 };
+
+        print!("{}", shown.render(&Render_Options { marked, ..self.render }));
+
+        let side = if color == WHITE { "white" } else { "black" };
+        let check = if game.board().details.is_check != 0 { ", check" } else { "" };
+
+        println!("{} to move{}, only what {} can see is shown", side, check, side);
+    }
+
+    // Both boards with their pockets, the teams and how the game ended
+    fn show_bughouse(&self, bughouse: &Bughouse_Game)
+    {
+        for (b_idx, board) in bughouse.boards.iter().enumerate()
+        {
+            println!("board {}: team {} has white, team {} black", b_idx + 1, Bughouse_Game::team(b_idx, WHITE) + 1, Bughouse_Game::team(b_idx, BLACK) + 1);
+            print!("{}", board.render(&self.render));
+
+            let side = if board.details.side_to_move == WHITE { "white" } else { "black" };
+            let check = if board.details.is_check != 0 { ", check" } else { "" };
+
+            println!("pockets: white {}, black {}", Self::pocket_text(board, WHITE), Self::pocket_text(board, BLACK));
+            println!("{} to move{}\n", side, check);
+        }
+
+        if let Some((b_idx, outcome)) = bughouse.result()
+        {
+            let ending = if outcome == Outcome::Checkmate { "checkmate" } else { "stalemate" };
+
+            match bughouse.winner()
+            {
+                Some(team) => println!("game over: team {} wins by {} on board {}", team + 1, ending, b_idx + 1),
+                None       => println!("game over: draw by {} on board {}", ending, b_idx + 1),
+            }
+        }
+    }
+
+    // Lists the legal moves in SAN, for a single square the targets are marked on the board
+    fn list_moves(&mut self, square: Option<&str>) -> Result<(), String>
+    {
+        let origin = match square
+        {
+            Some(name) => Some(Chess_Board::parse_square(name).ok_or(format!("{} is not a square", name))?),
+            None       => None,
+        };
+
+        let moves: Vec<Move> = self.game
+                                   .legal_moves()
+                                   .into_iter()
+                                   .filter(|legal_move| origin.is_none_or(|origin| legal_move.origin == origin))
+                                   .collect();
+
+        if moves.is_empty()
+        {
+            println!("no legal moves");
+            return Ok(());
+        }
+
+        let mut board = self.game.board().clone();
+        let names: Vec<String> = moves.iter().map(|legal_move| board.move_to_san(*legal_move)).collect();
+
+        if origin.is_some()
+        {
+            let marked = moves.iter().fold(0, |marked, legal_move| marked | 1 << legal_move.target);
+            self.show(marked);
+        }
+
+        println!("{}", names.join(" "));
+
+        return Ok(());
+    }
+
+    // Searches the position and plays the best move
+    fn engine_move(&mut self) -> Result<(), String>
+    {
+        if let Some((_, board)) = self.fairy.as_mut()
+        {
+            if board.clone().outcome().is_some()
+            {
+                return Err(String::from("the game is over"));
+            }
+
+            let info = Fairy_Search::new(board).search(&self.limits, &mut |_| {});
+            let Some(best_move) = info.best_move() else { return Err(String::from("the engine did not find a move")) };
+
+            println!("engine plays {} (depth {}, score {})", board.move_to_san(best_move), info.depth, Self::score_text(info.score));
+
+            board.make_move(best_move);
+            self.show(0);
+
+            return Ok(());
+        }
+
+        if self.game.is_over()
+        {
+            return Err(format!("the game is over ({})", self.game.result().to_pgn()));
+        }
+
+        let mut board = self.game.board().clone();
+        let info = self.search.search(&board, &self.limits, &mut |_| {});
+
+        let Some(best_move) = info.best_move() else { return Err(String::from("the engine did not find a move")) };
+
+        println!("engine plays {} (depth {}, score {})", board.move_to_san(best_move), info.depth, Self::score_text(info.score));
+
+        self.game.play(best_move)?;
+        self.show(0);
+
+        return Ok(());
+    }
+
+    // The score of a search for the side to move, "+0.35" or "mate 3"
+    fn score_text(score: i32) -> String
+    {
+        return if is_mate_score(score) { score_to_uci(score) } else { format!("{:+.2}", score as f64 / 100.0) };
+    }
+
+    // Lets the engine move for as long as it is the side to move
+    fn play_engine_moves(&mut self)
+    {
+        while    !self.is_over()
+              && self.engine[Self::engine_index(self.side_to_move())]
+        {
+            if let Err(error) = self.engine_move()
+            {
+                println!("{}", error);
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,415 @@
+// Crazyhouse and Bughouse
+//
+// Crazyhouse is normal chess where a captured piece changes sides and goes into the pocket of the
+// player who captured it. Instead of moving, a player can drop a piece from their pocket onto any
+// empty square, written "N@f3" in SAN and UCI. Pawns can not be dropped on the first or last rank.
+// A promoted piece goes back into the pocket as a pawn when it is captured
+//
+// In FEN the pockets follow the pieces in brackets and promoted pieces are marked with a '~':
+// r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R[Pp] w KQkq - 0 1
+// 4k3/1Q~6/8/8/8/8/8/4K3[] b - - 0 1
+// The pocket can also be written as a ninth rank after another '/'
+//
+// Bughouse is played by two teams of two on two boards. A captured piece goes to the partner of
+// the player who captured it, who plays the other color on the other board. The boards are linked
+// by Bughouse_Game, on its own a board with the Bughouse rules never gets anything in its pockets
+//
+// The pockets and promoted pieces are stored within the Board_Details, so they are taken back with
+// the moves. A piece can always be dropped, so there are no draws because of material
+
+use crate::chess::*;
+use crate::notation::*;
+use crate::variant::*;
+
+pub struct Crazyhouse
+{
+    // Captured pieces are not kept, Bughouse_Game hands them to the other board
+    bughouse: bool,
+}
+
+pub static CRAZYHOUSE: Crazyhouse = Crazyhouse { bughouse: false };
+pub static BUGHOUSE: Crazyhouse = Crazyhouse { bughouse: true };
+
+// The pieces that can be within a pocket, in the order of the pocket arrays
+pub const POCKET_PIECES: [i8; 5] = [PAWN, KNIGHT, BISHOP, ROOK, QUEEN];
+
+// The index of a color within the pockets
+pub fn pocket_index(color: i8) -> usize
+{
+    return if color == WHITE { 0 } else { 1 };
+}
+
+impl Rules for Crazyhouse
+{
+    fn name(&self) -> &'static str
+    {
+        return if self.bughouse { "Bughouse" } else { "Crazyhouse" };
+    }
+
+    fn aliases(&self) -> &'static [&'static str]
+    {
+        return if self.bughouse { &["bughouse"] } else { &["zh", "crazyhouse"] };
+    }
+
+    fn start_fen(&self) -> &'static str
+    {
+        return "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[] w KQkq - 0 1";
+    }
+
+    fn parse_fen(&self, fen: &str) -> Result<Chess_Board, String>
+    {
+        let mut fields: Vec<String> = fen.split_whitespace().map(String::from).collect();
+        let Some(placement) = fields.first().cloned() else { return Err(String::from("the FEN is empty")) };
+
+        // The pocket in brackets or as a ninth rank
+        let (pieces, pocket) = if let Some((pieces, pocket)) = placement.split_once('[')
+        {
+            (pieces.to_string(), pocket.trim_end_matches(']').to_string())
+        } else if placement.matches('/').count() == 8
+        {
+            let (pieces, pocket) = placement.rsplit_once('/').unwrap();
+            (pieces.to_string(), pocket.to_string())
+        } else
+        {
+            (placement.clone(), String::new())
+        };
+
+        // Promoted pieces are marked with a '~' after the letter, the square is found by counting
+        let mut promoted: u64 = 0;
+        let (mut row, mut col) = (7usize, 0usize);
+        let mut previous = ' ';
+
+        for letter in pieces.chars()
+        {
+            match letter
+            {
+                '/' =>
+                {
+                    row = row.checked_sub(1).ok_or_else(|| String::from("the FEN has more than 8 ranks"))?;
+                    col = 0;
+                },
+                '1'..='8' => col += letter as usize - '0' as usize,
+                '~' =>
+                {
+                    if !previous.is_ascii_alphabetic() || !(1..=8).contains(&col)
+                    {
+                        return Err(String::from("'~' has to follow a piece"));
+                    }
+
+                    promoted |= 1 << (row * 8 + col - 1);
+                },
+                _ => col += 1,
+            }
+
+            previous = letter;
+        }
+
+        fields[0] = pieces.replace('~', "");
+
+        let mut new_board = Chess_Board::from_fen(&fields.join(" "))?;
+
+        for letter in pocket.chars().filter(|&letter| letter != '-')
+        {
+            let piece = piece_from_char(letter).ok_or(format!("unknown piece '{}' within the pocket", letter))?;
+
+            let Some(p_idx) = POCKET_PIECES.iter().position(|&pocket_piece| pocket_piece == piece.abs()) else
+            {
+                return Err(format!("a {} can not be within the pocket", letter));
+            };
+
+            new_board.details.pockets[pocket_index(piece.signum())][p_idx] += 1;
+        }
+
+        new_board.details.promoted = promoted;
+
+        return Ok(new_board);
+    }
+
+    fn write_fen(&self, board: &Chess_Board, shredder: bool) -> String
+    {
+        let standard = board.standard_fen(shredder);
+        let (placement, rest) = standard.split_once(' ').unwrap();
+
+        // The '~' markers for the promoted pieces
+        let mut pieces = String::new();
+
+        // The squares in the order of the FEN: from a8 to h8, then a7 to h7 and so on
+        let mut fen_squares = (0..8).rev().flat_map(|row| (0..8).map(move |col| row * 8 + col));
+
+        for letter in placement.chars()
+        {
+            pieces.push(letter);
+
+            match letter
+            {
+                '/'       => {},
+                '1'..='8' =>
+                {
+                    for _ in 0..letter as usize - '0' as usize
+                    {
+                        fen_squares.next();
+                    }
+                },
+                _         =>
+                {
+                    if fen_squares.next().is_some_and(|square| board.details.promoted & (1 << square) != 0)
+                    {
+                        pieces.push('~');
+                    }
+                },
+            }
+        }
+
+        // White pieces first, the most valuable first
+        let mut pocket = String::new();
+
+        for color in [WHITE, BLACK]
+        {
+            for p_idx in (0..5).rev()
+            {
+                for _ in 0..board.details.pockets[pocket_index(color)][p_idx]
+                {
+                    pocket.push(piece_to_char(color * POCKET_PIECES[p_idx]));
+                }
+            }
+        }
+
+        return format!("{}[{}] {}", pieces, pocket, rest);
+    }
+
+    fn variant_key(&self, board: &Chess_Board) -> u64
+    {
+        // Every count gets a few bits of its own, multiplied into a random looking number
+        let mut counts: u64 = 0;
+
+        for color in 0..2
+        {
+            for p_idx in 0..5
+            {
+                counts = counts << 5 | board.details.pockets[color][p_idx] as u64;
+            }
+        }
+
+        return counts.wrapping_mul(0x9e37_79b9_7f4a_7c15) ^ board.details.promoted.wrapping_mul(0xc2b2_ae3d_27d4_eb4f);
+    }
+
+    // The normal moves and a drop of every piece in the pocket on every empty square
+    fn pseudo_legal_moves(&self, board: &Chess_Board, color: i8) -> Vec<Move>
+    {
+        let mut results = board.standard_pseudo_legal_moves(color);
+        let pocket = board.details.pockets[pocket_index(color)];
+
+        for p_idx in 0..5
+        {
+            if pocket[p_idx] == 0
+            {
+                continue;
+            }
+
+            let piece = POCKET_PIECES[p_idx];
+
+            for square in 0..64
+            {
+                let row = Chess_Board::get_row(square);
+
+                if    board.board[square] != 0
+                   || (piece == PAWN && (row == 0 || row == 7))
+                {
+                    continue;
+                }
+
+                results.push(Move
+                {
+                    piece: color * piece,
+                    origin: square,
+                    target: square,
+                    capture: 0,
+                    promotion: 0,
+                    kind: Move_Kind::Drop,
+                });
+            }
+        }
+
+        return results;
+    }
+
+    fn make_move(&self, board: &mut Chess_Board, new_move: Move)
+    {
+        let color = new_move.piece.signum();
+        let index = pocket_index(color);
+        let origin_bit: u64 = 1 << new_move.origin;
+        let target_bit: u64 = 1 << new_move.target;
+
+        let captured = captured_piece(board, new_move);
+
+        // The details are saved by standard_make_move, so they are changed afterwards
+        board.standard_make_move(new_move);
+
+        let details = &mut board.details;
+
+        if new_move.kind == Move_Kind::Drop
+        {
+            let p_idx = POCKET_PIECES.iter().position(|&piece| piece == new_move.piece.abs()).unwrap();
+            details.pockets[index][p_idx] -= 1;
+            return;
+        }
+
+        if captured != 0 && !self.bughouse
+        {
+            let p_idx = POCKET_PIECES.iter().position(|&piece| piece == captured.abs()).unwrap();
+            details.pockets[index][p_idx] += 1;
+        }
+
+        // The promoted piece moves along, the captured piece is gone
+        let moved = details.promoted & origin_bit != 0;
+        details.promoted &= !(origin_bit | target_bit);
+
+        if moved || new_move.kind == Move_Kind::Promotion
+        {
+            details.promoted |= target_bit;
+        }
+    }
+
+    fn material_draws(&self) -> bool
+    {
+        return false;
+    }
+}
+
+// The piece a move captures as it goes into a pocket: the color of the capturing side and a pawn
+// for a promoted piece, 0 without a capture
+pub fn captured_piece(board: &Chess_Board, capture: Move) -> i8
+{
+    if capture.capture == 0
+    {
+        return 0;
+    }
+
+    let color = capture.piece.signum();
+
+    if    capture.kind != Move_Kind::En_Passant
+       && board.details.promoted & (1 << capture.target) != 0
+    {
+        return color * PAWN;
+    }
+
+    return color * capture.capture.abs();
+}
+
+// Two Crazyhouse boards played by two teams, captures go into the pocket of the partner
+// Team 0 plays white on board 0 and black on board 1, team 1 the other colors
+// The game is over as soon as one of the boards is. Moves can not be taken back, as the pieces
+// handed to the other board would have to be taken back as well
+pub struct Bughouse_Game
+{
+    pub boards: [Chess_Board; 2],
+    // The board the game ended on and how
+    result: Option<(usize, Outcome)>,
+}
+
+impl Bughouse_Game
+{
+    pub fn new() -> Self
+    {
+        return Bughouse_Game
+        {
+            boards: [Chess_Board::with_rules(&BUGHOUSE), Chess_Board::with_rules(&BUGHOUSE)],
+            result: None,
+        };
+    }
+
+    // The team playing the given color on a board
+    pub fn team(board_index: usize, color: i8) -> usize
+    {
+        return if (board_index == 0) == (color == WHITE) { 0 } else { 1 };
+    }
+
+    // The winning team, None while the game is going on or after a draw
+    pub fn winner(&self) -> Option<usize>
+    {
+        let (board_index, outcome) = self.result?;
+        let winner = outcome.winner(self.boards[board_index].details.side_to_move);
+
+        return if winner == 0 { None } else { Some(Self::team(board_index, winner)) };
+    }
+
+    pub fn result(&self) -> Option<(usize, Outcome)>
+    {
+        return self.result;
+    }
+
+    pub fn is_over(&self) -> bool
+    {
+        return self.result.is_some();
+    }
+
+    // Plays a move on one of the boards, in SAN or UCI notation. A captured piece is added to the
+    // pocket of the partner on the other board
+    pub fn play(&mut self, board_index: usize, text: &str) -> Result<Move, String>
+    {
+        if self.is_over()
+        {
+            return Err(String::from("the game is over"));
+        }
+
+        let board = &mut self.boards[board_index];
+
+        let parsed = match board.parse_san(text)
+        {
+            Some(parsed) => Some(parsed),
+            None         => board.parse_uci_move(text),
+        };
+
+        let Some(played) = parsed else { return Err(format!("{} is not a legal move on board {}", text, board_index + 1)) };
+
+        let captured = captured_piece(board, played);
+        board.make_move(played);
+
+        // The partner plays the other color, the captured piece keeps its color
+        if captured != 0
+        {
+            let p_idx = POCKET_PIECES.iter().position(|&piece| piece == captured.abs()).unwrap();
+            self.boards[1 - board_index].details.pockets[pocket_index(-captured.signum())][p_idx] += 1;
+        }
+
+        for b_idx in [board_index, 1 - board_index]
+        {
+            if let Some(outcome) = self.boards[b_idx].outcome()
+            {
+                self.result = Some((b_idx, outcome));
+                break;
+            }
+        }
+
+        return Ok(played);
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn promoted_pieces_round_trip()
+    {
+        let fen = "4k3/1Q~6/8/8/4b3/8/Kpp5/8[] b - - 0 1";
+        let board = CRAZYHOUSE.parse_fen(fen).unwrap();
+
+        assert_eq!(board.details.promoted, 1 << (6 * 8 + 1));
+        assert_eq!(CRAZYHOUSE.write_fen(&board, false), fen);
+    }
+
+    #[test]
+    fn malformed_promotion_markers()
+    {
+        for fen in ["/rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[] w KQkq - 0 1",
+                    "~rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[] w KQkq - 0 1",
+                    "rnbqkbnr/pppppppp/8~/8/8/8/PPPPPPPP/RNBQKBNR[] w KQkq - 0 1",
+                    "rnbqkbnrr~/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[] w KQkq - 0 1",
+                    "8/8/8/8/8/8/8/8/8/8[] w - - 0 1"]
+        {
+            assert!(CRAZYHOUSE.parse_fen(fen).is_err(), "{}", fen);
+        }
+    }
+}
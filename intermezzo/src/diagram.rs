@@ -0,0 +1,372 @@
+// Reading a board back from a text diagram, e.g. print_board output pasted into a bug report
+//
+// Every line with 8 squares is a rank, every other line is skipped. These kinds of lines are read:
+//
+// 8 |  r  |  n  |  b  |  q  |  k  |  b  |  n  |  r  |   -> the grid of print_board and render, with
+//                                                          or without the rank numbers
+// 8 r n b q k b n r                                     -> letters separated by spaces
+// rnbqkbnr   r..qk..r   3p4                             -> a rank written in one go, '.', '-' and '_'
+//                                                          are empty squares, digits count empty
+//                                                          squares as in FEN
+//
+// 8 \x1b[48;5;180m\x1b[38;5;16m ♜ \x1b[0m...            -> the colored board of render, the color of a
+//                                                          piece is read from its foreground color
+//
+// Pieces can be letters or figurines, uppercase and outlined figurines are white. The highlights
+// of render ("[ P ]", "! K !", " (n) ", "  .  ") are ignored. The duck is '*'
+//
+// The ranks are read from the 8th down unless the diagram shows it is flipped: the first rank
+// number is 1 or the file letters below start with h. Black is to move when a line says "black to
+// move", as the status line of the CLI does, otherwise white. Castling is allowed for every king
+// and rook that stand on their starting squares and there is no en passant square
+//
+// parse_diagram only fails when the text can not be read as a board, position_problems lists what
+// is wrong with the position itself (a missing king, pawns on the first rank, ...)
+
+use crate::chess::*;
+use crate::notation::piece_from_char;
+
+// The piece of a letter or figurine, Some(0) for an empty square and None for anything else
+fn diagram_piece(letter: char) -> Option<i8>
+{
+    let figurines = "♔♕♖♗♘♙♚♛♜♝♞♟";
+
+    if let Some(index) = figurines.chars().position(|figurine| figurine == letter)
+    {
+        let piece = [KING, QUEEN, ROOK, BISHOP, KNIGHT, PAWN][index % 6];
+        return Some(if index < 6 { piece } else { -piece });
+    }
+
+    return match letter
+    {
+        '.' | '-' | '_' | ' ' => Some(0),
+        '*' | '●'             => Some(DUCK),
+        _                     => piece_from_char(letter),
+    };
+}
+
+// Removes the ANSI escape codes of a colored diagram
+fn strip_colors(line: &str) -> String
+{
+    let mut result = String::new();
+    let mut in_escape = false;
+
+    for letter in line.chars()
+    {
+        if letter == '\x1b'
+        {
+            in_escape = true;
+        } else if in_escape
+        {
+            // The codes end with a letter, e.g. "\x1b[48;5;180m"
+            in_escape = !letter.is_ascii_alphabetic();
+        } else
+        {
+            result.push(letter);
+        }
+    }
+
+    return result;
+}
+
+// The rank number at the start of a line and the rest of the line
+fn split_rank_number(line: &str) -> (Option<u32>, &str)
+{
+    let trimmed = line.trim_start();
+    let mut letters = trimmed.chars();
+
+    if let (Some(number @ '1'..='8'), Some(' ' | '|')) = (letters.next(), letters.next())
+    {
+        return (number.to_digit(10), &trimmed[1..]);
+    }
+
+    return (None, trimmed);
+}
+
+// The squares of a line of the grid, "|  r  |[ n ]|  .  |"
+fn grid_squares(line: &str) -> Option<Vec<i8>>
+{
+    let cells: Vec<&str> = line.split('|').collect();
+
+    // The text before the first '|' and after the last one are not squares
+    if cells.len() != 10
+    {
+        return None;
+    }
+
+    let mut squares = vec![];
+
+    for cell in &cells[1..9]
+    {
+        let pieces: Vec<i8> = cell.chars()
+                                  .filter(|letter| !"[]()!.".contains(*letter))
+                                  .filter_map(diagram_piece)
+                                  .filter(|&piece| piece != 0)
+                                  .collect();
+
+        match pieces[..]
+        {
+            []      => squares.push(0),
+            [piece] => squares.push(piece),
+            _       => return None,
+        }
+    }
+
+    return Some(squares);
+}
+
+// The squares of a line of a colored diagram, every square is written as its colors and three
+// characters, " r ", white pieces have the foreground color 231
+fn colored_squares(line: &str) -> Option<Vec<i8>>
+{
+    let mut squares = vec![];
+
+    for part in line.split("\x1b[0m")
+    {
+        // The rank number is in front of the first square
+        let Some(start) = part.find("\x1b[48;") else { continue };
+        let square = &part[start..];
+
+        let letters: Vec<char> = strip_colors(square).chars().collect();
+
+        if letters.len() != 3
+        {
+            return None;
+        }
+
+        let piece = diagram_piece(letters[1])?;
+
+        squares.push(match piece
+        {
+            0 | DUCK                         => piece,
+            _ if square.contains("38;5;231") => piece.abs(),
+            _                                => -piece.abs(),
+        });
+    }
+
+    return if squares.len() == 8 { Some(squares) } else { None };
+}
+
+// The squares of a line without a grid, "r n b q k b n r", "rnbqkbnr" or "3p4"
+fn plain_squares(line: &str) -> Option<Vec<i8>>
+{
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    let letters: Vec<char> = if tokens.len() == 8 { tokens.iter().map(|token| token.chars().next().unwrap()).collect() } else { tokens.concat().chars().collect() };
+
+    if tokens.len() == 8 && tokens.iter().any(|token| token.chars().count() != 1)
+    {
+        return None;
+    }
+
+    let mut squares = vec![];
+
+    for letter in letters
+    {
+        if let Some(empty) = letter.to_digit(10).filter(|&empty| (1..=8).contains(&empty))
+        {
+            squares.extend(std::iter::repeat_n(0, empty as usize));
+            continue;
+        }
+
+        squares.push(diagram_piece(letter)?);
+    }
+
+    return if squares.len() == 8 { Some(squares) } else { None };
+}
+
+impl Chess_Board
+{
+    // Reads a diagram into a board, see the top of this file for what can be read
+    pub fn parse_diagram(text: &str) -> Result<Chess_Board, String>
+    {
+        let mut ranks: Vec<Vec<i8>> = vec![];
+        let mut first_rank_number: Option<u32> = None;
+        let mut files_from_h = false;
+        let mut side_to_move = WHITE;
+
+        for raw_line in text.lines()
+        {
+            let line = strip_colors(raw_line);
+            let lower = line.to_lowercase();
+
+            if lower.contains("black to move")
+            {
+                side_to_move = BLACK;
+                continue;
+            }
+
+            if lower.contains("white to move")
+            {
+                continue;
+            }
+
+            // The file letters below the board
+            let letters: String = line.chars().filter(|letter| !letter.is_whitespace()).collect();
+
+            if    letters.len() == 8
+               && (letters == "abcdefgh" || letters == "hgfedcba")
+               && line.contains(' ')
+            {
+                files_from_h = letters.starts_with('h');
+                continue;
+            }
+
+            let (number, rest) = split_rank_number(&line);
+
+            // Colored diagrams have no grid, every square is three characters wide
+            let squares = if raw_line.contains('\x1b')
+            {
+                colored_squares(raw_line)
+            } else if rest.contains('|')
+            {
+                grid_squares(rest)
+            } else
+            {
+                plain_squares(rest)
+            };
+
+            let Some(squares) = squares else { continue };
+
+            if ranks.is_empty()
+            {
+                first_rank_number = number;
+            }
+
+            ranks.push(squares);
+        }
+
+        if ranks.len() != 8
+        {
+            return Err(format!("found {} ranks in the diagram instead of 8", ranks.len()));
+        }
+
+        let flipped = first_rank_number == Some(1) || files_from_h;
+
+        let mut new_board = Chess_Board::new();
+        new_board.board = [0; 64];
+
+        for (line_idx, squares) in ranks.iter().enumerate()
+        {
+            for (col_idx, &piece) in squares.iter().enumerate()
+            {
+                let (row, col) = if flipped { (line_idx, 7 - col_idx) } else { (7 - line_idx, col_idx) };
+                new_board.board[row * 8 + col] = piece;
+            }
+        }
+
+        // The duck is not part of a FEN, it is put back after the FEN has set up the rest
+        let duck = new_board.board.iter().position(|&piece| piece == DUCK);
+
+        if duck.is_some_and(|square| new_board.board[square + 1..].contains(&DUCK))
+        {
+            return Err(String::from("there can only be one duck"));
+        }
+
+        if let Some(square) = duck
+        {
+            new_board.board[square] = 0;
+        }
+
+        let placement = new_board.to_fen().split_whitespace().next().unwrap_or_default().to_string();
+        let color = if side_to_move == WHITE { "w" } else { "b" };
+
+        // A right for every king on e1 / e8 with a rook of its color in the corner
+        let mut castling = String::new();
+
+        for (king, rook, right, side) in [(4, 7, 'K', WHITE), (4, 0, 'Q', WHITE), (60, 63, 'k', BLACK), (60, 56, 'q', BLACK)]
+        {
+            if    new_board.board[king] == side * KING
+               && new_board.board[rook] == side * ROOK
+            {
+                castling.push(right);
+            }
+        }
+
+        if castling.is_empty()
+        {
+            castling.push('-');
+        }
+
+        let mut result = Chess_Board::from_fen(&format!("{} {} {} - 0 1", placement, color, castling))?;
+
+        if let Some(square) = duck
+        {
+            result.board[square] = DUCK;
+        }
+
+        return Ok(result);
+    }
+
+    // What makes the position impossible in standard chess, empty for a legal position
+    pub fn position_problems(&self) -> Vec<String>
+    {
+        let mut problems = vec![];
+
+        for (color, name) in [(WHITE, "white"), (BLACK, "black")]
+        {
+            let count = |piece: i8| self.board.iter().filter(|&&square| square == color * piece).count();
+
+            match count(KING)
+            {
+                0 => problems.push(format!("{} has no king", name)),
+                1 => {},
+                n => problems.push(format!("{} has {} kings", name, n)),
+            }
+
+            if count(PAWN) > 8
+            {
+                problems.push(format!("{} has {} pawns", name, count(PAWN)));
+            }
+
+            let pieces = self.board.iter().filter(|&&square| square != DUCK && square.signum() == color).count();
+
+            if pieces > 16
+            {
+                problems.push(format!("{} has {} pieces", name, pieces));
+            }
+        }
+
+        for square in (0..8).chain(56..64)
+        {
+            if self.board[square].abs() == PAWN
+            {
+                problems.push(format!("there is a pawn on {}", Chess_Board::square_name(square)));
+            }
+        }
+
+        // Only meaningful with one king on each side
+        let side = self.details.side_to_move;
+
+        if    problems.iter().all(|problem| !problem.contains("king"))
+           && self.square_attacked(self.king_square(-side).unwrap(), side)
+        {
+            let name = if side == WHITE { "black" } else { "white" };
+            problems.push(format!("{} is in check but it is not their move", name));
+        }
+
+        return problems;
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn castling_field(diagram: &str) -> String
+    {
+        let board = Chess_Board::parse_diagram(diagram).unwrap();
+        return board.to_fen().split_whitespace().nth(2).unwrap().to_string();
+    }
+
+    #[test]
+    fn castling_needs_king_and_rook_on_their_squares()
+    {
+        assert_eq!(castling_field("rnbqkbnr\npppppppp\n8\n8\n8\n8\nPPPPPPPP\nRNBQKBNR"), "KQkq");
+        // The rook on h1 belongs to a king on a1
+        assert_eq!(castling_field("k7\n8\n8\n8\n8\n8\n8\nK6R"), "-");
+        assert_eq!(castling_field("4k2r\n8\n8\n8\n8\n8\n8\nR3K2R"), "KQk");
+        // A black rook in a white corner does not count
+        assert_eq!(castling_field("r3k3\n8\n8\n8\n8\n8\n8\n4K2r"), "q");
+    }
+}
@@ -0,0 +1,467 @@
+// The rules that end a game in a draw without a stalemate
+//
+// Some draws have to be claimed by one of the players, the others end the game immediately
+// (FIDE Laws of Chess, article 9):
+//
+// Claimable:
+// Threefold_Repetition   -> the same position occurred for the third time
+// Fifty_Move_Rule        -> 50 moves by each side without a capture or pawn move
+//
+// Automatic:
+// Fivefold_Repetition    -> the same position occurred for the fifth time
+// Seventy_Five_Move_Rule -> 75 moves by each side without a capture or pawn move
+// Insufficient_Material  -> neither side has the pieces to ever checkmate
+// Dead_Position          -> no sequence of legal moves can lead to a checkmate
+//
+// Positions count as the same when the same pieces are on the same squares, the same side is to
+// move and both sides have the same castling and en passant rights. An en passant square only
+// counts when there is a pawn next to the pawn that moved, as in the Polyglot position key
+//
+// Only positions within moves can be compared, a game set up from a FEN does not know about the
+// positions before it
+//
+// The same rules are used on a Fairy_Board (Capablanca and Gothic), without dead positions
+//
+// A checkmate on the move that reaches the 50 / 75 move limit still counts, the caller is
+// expected to look for checkmate first
+
+use crate::chess::*;
+use crate::fairy::*;
+
+use std::collections::VecDeque;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Draw_Rule
+{
+    Threefold_Repetition,
+    Fifty_Move_Rule,
+    Fivefold_Repetition,
+    Seventy_Five_Move_Rule,
+    Insufficient_Material,
+    Dead_Position,
+}
+
+impl Draw_Rule
+{
+    // Whether the draw ends the game immediately, the other draws have to be claimed
+    pub fn is_automatic(self) -> bool
+    {
+        return !matches!(self, Draw_Rule::Threefold_Repetition | Draw_Rule::Fifty_Move_Rule);
+    }
+
+    pub fn is_claimable(self) -> bool
+    {
+        return !self.is_automatic();
+    }
+}
+
+// King against king, king and a single minor piece against king or kings and any number of
+// bishops that are all on squares of the same color. The pieces come with the color of their
+// square (0 or 1), any other piece (including the fairy pieces) can still checkmate
+pub fn insufficient_material(pieces: impl Iterator<Item = (i8, usize)>) -> bool
+{
+    let mut knights = 0;
+    let mut bishop_colors = [0, 0];
+
+    for (piece, square_color) in pieces
+    {
+        match piece.abs()
+        {
+            0 | KING => (),
+            KNIGHT   => knights += 1,
+            BISHOP   => bishop_colors[square_color] += 1,
+            _        => return false,
+        }
+    }
+
+    let minor_pieces = knights + bishop_colors[0] + bishop_colors[1];
+
+    if minor_pieces <= 1
+    {
+        return true;
+    }
+
+    // Only bishops on one color of squares
+    return    knights == 0
+           && (bishop_colors[0] == 0 || bishop_colors[1] == 0);
+}
+
+// The draw rules in the order of Chess_Board::draw_rules, from the number of times the position
+// occurred, the half move clock and the material rule if one applies
+pub fn draw_rules(repetitions: u32, halfmove_clock: u32, material: Option<Draw_Rule>) -> Vec<Draw_Rule>
+{
+    let mut rules = vec![];
+
+    if repetitions >= 5
+    {
+        rules.push(Draw_Rule::Fivefold_Repetition);
+    }
+
+    if halfmove_clock >= 150
+    {
+        rules.push(Draw_Rule::Seventy_Five_Move_Rule);
+    }
+
+    rules.extend(material);
+
+    if repetitions >= 3
+    {
+        rules.push(Draw_Rule::Threefold_Repetition);
+    }
+
+    if halfmove_clock >= 100
+    {
+        rules.push(Draw_Rule::Fifty_Move_Rule);
+    }
+
+    return rules;
+}
+
+impl Chess_Board
+{
+    // The en passant square if a pawn of the side to move stands next to the pawn that moved
+    fn relevant_en_passant(&self) -> Option<usize>
+    {
+        let square = self.details.en_passant?;
+        let color = self.details.side_to_move;
+
+        // The pawn that moved is in front of the en passant square from the point of view of
+        // the side that moved it, there is none for a square on the first or last rank
+        let pawn_square = square.checked_add_signed(-(color as isize) * 8).filter(|&pawn_square| pawn_square < 64)?;
+        let col = Self::get_col(pawn_square);
+
+        let beside = [col.checked_sub(1), if col < 7 { Some(col + 1) } else { None }];
+
+        for neighbour in beside.into_iter().flatten()
+        {
+            if self.board[pawn_square - col + neighbour] == color * PAWN
+            {
+                return Some(square);
+            }
+        }
+
+        return None;
+    }
+
+    // How often the current position has occurred, counting the current position itself
+    // Only the moves since the last capture or pawn move are looked at, a position from
+    // before that can not occur again
+    pub fn repetition_count(&self) -> u32
+    {
+        let rights = self.castling_rights();
+        let en_passant = self.relevant_en_passant();
+
+        let mut previous = self.clone();
+        let mut count = 1;
+        let plies = std::cmp::min(self.details.halfmove_clock as usize, self.moves.len());
+
+        for p_idx in 1..=plies
+        {
+            previous.unmake_move();
+
+            if    p_idx % 2 == 0
+               && previous.board == self.board
+               && previous.castling_rights() == rights
+               && previous.relevant_en_passant() == en_passant
+            {
+                count += 1;
+            }
+        }
+
+        return count;
+    }
+
+    // Neither side has enough material left to checkmate with any sequence of moves, see
+    // insufficient_material below
+    pub fn insufficient_material(&self) -> bool
+    {
+        let pieces = (0..64).map(|square| (self.board[square], (Self::get_row(square) + Self::get_col(square)) % 2));
+
+        return insufficient_material(pieces);
+    }
+
+    // Positions where no checkmate is possible even though there is enough material
+    // Only the most common case is recognised: nothing but kings and pawns, every pawn is
+    // blocked by another pawn and neither king can ever get to a pawn it could capture
+    pub fn is_dead_position(&self) -> bool
+    {
+        if self.insufficient_material()
+        {
+            return true;
+        }
+
+        let mut pawn_attacks = [[false; 64]; 2];
+
+        for square in 0..64
+        {
+            let piece = self.board[square];
+
+            match piece.abs()
+            {
+                0 | KING => continue,
+                PAWN     => (),
+                _        => return false,
+            }
+
+            let color = piece.signum();
+            let row = Self::get_row(square) as i8;
+            let col = Self::get_col(square) as i8;
+
+            // A pawn on the first or last rank (possible in a position from a FEN) has no square
+            // in front of it, it is not blocked and the position is not looked at any further
+            if !(0..8).contains(&(row + color))
+            {
+                return false;
+            }
+
+            // A pawn that is not blocked by a pawn may be able to move at some point
+            let front = (square as i8 + color * 8) as usize;
+
+            if self.board[front].abs() != PAWN
+            {
+                return false;
+            }
+
+            for side in [-1, 1]
+            {
+                if !(0..8).contains(&(col + side))
+                {
+                    continue;
+                }
+
+                let target = ((row + color) * 8 + col + side) as usize;
+
+                // A pawn that can capture another pawn changes the structure
+                if self.board[target] == -color * PAWN
+                {
+                    return false;
+                }
+
+                pawn_attacks[if color == WHITE { 0 } else { 1 }][target] = true;
+            }
+        }
+
+        for color in [WHITE, BLACK]
+        {
+            if self.king_can_reach_pawn(color, &pawn_attacks)
+            {
+                return false;
+            }
+        }
+
+        return true;
+    }
+
+    // Whether the king of the given color can walk to an enemy pawn that is not protected by
+    // another pawn, the squares attacked by enemy pawns and its own pawns are in the way
+    fn king_can_reach_pawn(&self, color: i8, pawn_attacks: &[[bool; 64]; 2]) -> bool
+    {
+        let Some(start) = self.king_square(color) else { return false };
+        let enemy_attacks = &pawn_attacks[if color == WHITE { 1 } else { 0 }];
+
+        let mut visited = [false; 64];
+        let mut queue = VecDeque::from([start]);
+        visited[start] = true;
+
+        while let Some(square) = queue.pop_front()
+        {
+            if self.board[square] == -color * PAWN
+            {
+                return true;
+            }
+
+            // Pawns are not passed through, the king stops on the pawn it captures
+            if square != start && self.board[square] != 0
+            {
+                continue;
+            }
+
+            let row = Self::get_row(square) as i8;
+            let col = Self::get_col(square) as i8;
+
+            for row_step in -1..=1
+            {
+                for col_step in -1..=1
+                {
+                    let next_row = row + row_step;
+                    let next_col = col + col_step;
+
+                    if    !(0..8).contains(&next_row)
+                       || !(0..8).contains(&next_col)
+                    {
+                        continue;
+                    }
+
+                    let next = (next_row * 8 + next_col) as usize;
+
+                    if    visited[next]
+                       || enemy_attacks[next]
+                       || self.board[next] == color * PAWN
+                    {
+                        continue;
+                    }
+
+                    visited[next] = true;
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        return false;
+    }
+
+    // Every draw rule that applies to the current position, the automatic ones first
+    pub fn draw_rules(&self) -> Vec<Draw_Rule>
+    {
+        // Only for variants that are won by checkmate
+        let material = if !self.rules.material_draws()
+        {
+            None
+        } else if self.insufficient_material()
+        {
+            Some(Draw_Rule::Insufficient_Material)
+        } else if self.is_dead_position()
+        {
+            Some(Draw_Rule::Dead_Position)
+        } else
+        {
+            None
+        };
+
+        return draw_rules(self.repetition_count(), self.details.halfmove_clock, material);
+    }
+
+    // The rule that ends the game in a draw right now, if any
+    pub fn automatic_draw(&self) -> Option<Draw_Rule>
+    {
+        return self.draw_rules().into_iter().find(|rule| rule.is_automatic());
+    }
+
+    // The rule a player can claim a draw with, if any
+    pub fn claimable_draw(&self) -> Option<Draw_Rule>
+    {
+        return self.draw_rules().into_iter().find(|rule| rule.is_claimable());
+    }
+}
+
+// The same rules on a Fairy_Board, dead positions are only recognised on a normal board
+impl Fairy_Board
+{
+    // How often the current position has occurred, as Chess_Board::repetition_count
+    pub fn repetition_count(&self) -> u32
+    {
+        let key = self.position_key();
+
+        let mut previous = self.clone();
+        let mut count = 1;
+        let plies = std::cmp::min(self.details.halfmove_clock as usize, self.moves.len());
+
+        for p_idx in 1..=plies
+        {
+            previous.unmake_move();
+
+            if    p_idx % 2 == 0
+               && previous.position_key() == key
+            {
+                count += 1;
+            }
+        }
+
+        return count;
+    }
+
+    pub fn insufficient_material(&self) -> bool
+    {
+        let geometry = self.geometry;
+        let pieces = (0..geometry.size()).map(|square| (self.board[square], (geometry.get_rank(square) + geometry.get_file(square)) % 2));
+
+        return insufficient_material(pieces);
+    }
+
+    pub fn draw_rules(&self) -> Vec<Draw_Rule>
+    {
+        let material = if self.insufficient_material() { Some(Draw_Rule::Insufficient_Material) } else { None };
+
+        return draw_rules(self.repetition_count(), self.details.halfmove_clock, material);
+    }
+
+    pub fn automatic_draw(&self) -> Option<Draw_Rule>
+    {
+        return self.draw_rules().into_iter().find(|rule| rule.is_automatic());
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::game::Game;
+    use crate::variant::Outcome;
+
+    #[test]
+    fn pawns_on_the_first_or_last_rank()
+    {
+        for fen in ["P3k3/8/8/8/8/8/8/4K3 w - - 0 1", "4k3/8/8/8/8/8/8/p3K3 w - - 0 1"]
+        {
+            let board = Chess_Board::from_fen(fen).unwrap();
+
+            assert!(!board.is_dead_position(), "{}", fen);
+            assert_eq!(board.draw_rules(), vec![], "{}", fen);
+            assert!(!Game::from_fen(fen).unwrap().is_over(), "{}", fen);
+        }
+    }
+
+    #[test]
+    fn blocked_pawns_are_a_dead_position()
+    {
+        let board = Chess_Board::from_fen("k7/8/p1p1p1p1/P1P1P1P1/8/8/8/K7 w - - 0 1").unwrap();
+        assert_eq!(board.draw_rules(), vec![Draw_Rule::Dead_Position]);
+
+        // Without the pawns on the g file the white king walks around them to the pawn on e6
+        let board = Chess_Board::from_fen("k7/8/p1p1p3/P1P1P3/8/8/8/K7 w - - 0 1").unwrap();
+        assert!(!board.is_dead_position());
+    }
+
+    #[test]
+    fn fairy_board_repetitions()
+    {
+        let mut board = Fairy_Board::from_fen(FAIRY_VARIANTS[0].start_fen).unwrap();
+
+        for round in 1..=4
+        {
+            for text in ["b1c3", "b8c6", "c3b1", "c6b8"]
+            {
+                let knight_move = board.parse_uci_move(text).unwrap();
+                board.make_move(knight_move);
+            }
+
+            assert_eq!(board.repetition_count(), round + 1);
+
+            match round
+            {
+                1 => assert_eq!(board.outcome(), None),
+                2 => assert_eq!(board.draw_rules(), vec![Draw_Rule::Threefold_Repetition]),
+                _ => (),
+            }
+        }
+
+        assert_eq!(board.outcome(), Some(Outcome::Draw(Draw_Rule::Fivefold_Repetition)));
+    }
+
+    #[test]
+    fn fairy_board_move_rules_and_material()
+    {
+        let board = Fairy_Board::from_fen("4k5/10/10/10/10/10/10/4K4R w - - 100 80").unwrap();
+        assert_eq!(board.draw_rules(), vec![Draw_Rule::Fifty_Move_Rule]);
+
+        let mut board = Fairy_Board::from_fen("4k5/10/10/10/10/10/10/4K4R w - - 150 100").unwrap();
+        assert_eq!(board.outcome(), Some(Outcome::Draw(Draw_Rule::Seventy_Five_Move_Rule)));
+
+        let mut board = Fairy_Board::from_fen("4k5/10/10/10/10/10/10/4K4N w - - 0 1").unwrap();
+        assert_eq!(board.outcome(), Some(Outcome::Draw(Draw_Rule::Insufficient_Material)));
+
+        // The archbishop can checkmate on its own
+        let mut board = Fairy_Board::from_fen("4k5/10/10/10/10/10/10/4K4A w - - 0 1").unwrap();
+        assert_eq!(board.outcome(), None);
+    }
+}
@@ -0,0 +1,218 @@
+// A picture made of simple shapes that can be written as SVG or rasterized into pixels
+//
+// The board images (board_image.rs) are built as a Drawing so the SVG and the PNG come from the
+// same shapes and look the same. Coordinates are in pixels with y going down, the shapes are
+// drawn in order so later shapes cover earlier ones
+//
+// Polygon  -> a closed shape, filled and / or outlined
+// Circle   -> the same for a circle
+// Line     -> a line through a list of points with a width
+// Text     -> a line of text, (x, y) is its top left corner and height the height of a capital
+//             letter. The SVG uses a monospace font, the rasterizer its own bitmap font so the
+//             letters look a bit different but take the same space
+
+use crate::raster::*;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Color
+{
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    // 255 for opaque colors
+    pub a: u8,
+}
+
+impl Color
+{
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self
+    {
+        return Color { r, g, b, a: 255 };
+    }
+
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self
+    {
+        return Color { r, g, b, a };
+    }
+
+    // Reads "#rrggbb" or "#rrggbbaa", the '#' may be left out
+    pub fn parse(text: &str) -> Option<Self>
+    {
+        let hex = text.trim().trim_start_matches('#');
+
+        if !(hex.len() == 6 || hex.len() == 8) || !hex.chars().all(|letter| letter.is_ascii_hexdigit())
+        {
+            return None;
+        }
+
+        let byte = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16).ok();
+
+        let alpha = if hex.len() == 8 { byte(6)? } else { 255 };
+
+        return Some(Color::rgba(byte(0)?, byte(2)?, byte(4)?, alpha));
+    }
+
+    // "#rrggbb" without the alpha, which SVG gives as a separate opacity
+    pub fn to_hex(self) -> String
+    {
+        return format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b);
+    }
+
+    fn svg_paint(self, property: &str) -> String
+    {
+        if self.a == 255
+        {
+            return format!("{}=\"{}\"", property, self.to_hex());
+        }
+
+        return format!("{}=\"{}\" {}-opacity=\"{:.3}\"", property, self.to_hex(), property, self.a as f64 / 255.0);
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Shape
+{
+    Polygon { points: Vec<(f64, f64)>, fill: Option<Color>, stroke: Option<(Color, f64)> },
+    Circle { center: (f64, f64), radius: f64, fill: Option<Color>, stroke: Option<(Color, f64)> },
+    Line { points: Vec<(f64, f64)>, color: Color, width: f64 },
+    Text { position: (f64, f64), height: f64, color: Color, text: String },
+}
+
+#[derive(Clone, Debug)]
+pub struct Drawing
+{
+    pub width:      u32,
+    pub height:     u32,
+    pub background: Color,
+    pub shapes:     Vec<Shape>,
+}
+
+// Escapes the characters that have a meaning in XML
+fn escape_xml(text: &str) -> String
+{
+    return text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;");
+}
+
+fn svg_points(points: &[(f64, f64)]) -> String
+{
+    return points.iter().map(|(x, y)| format!("{:.2},{:.2}", x, y)).collect::<Vec<String>>().join(" ");
+}
+
+fn svg_fill_and_stroke(fill: Option<Color>, stroke: Option<(Color, f64)>) -> String
+{
+    let mut result = match fill
+    {
+        Some(color) => color.svg_paint("fill"),
+        None        => String::from("fill=\"none\""),
+    };
+
+    if let Some((color, width)) = stroke
+    {
+        result.push_str(&format!(" {} stroke-width=\"{:.2}\" stroke-linejoin=\"round\"", color.svg_paint("stroke"), width));
+    }
+
+    return result;
+}
+
+impl Drawing
+{
+    pub fn new(width: u32, height: u32, background: Color) -> Self
+    {
+        return Drawing { width, height, background, shapes: vec![] };
+    }
+
+    // A rectangle is a polygon with four corners
+    pub fn add_rectangle(&mut self, x: f64, y: f64, width: f64, height: f64, color: Color)
+    {
+        self.shapes.push(Shape::Polygon
+        {
+            points: vec![(x, y), (x + width, y), (x + width, y + height), (x, y + height)],
+            fill: Some(color),
+            stroke: None,
+        });
+    }
+
+    pub fn add_text(&mut self, x: f64, y: f64, height: f64, color: Color, text: &str)
+    {
+        self.shapes.push(Shape::Text { position: (x, y), height, color, text: String::from(text) });
+    }
+
+    pub fn to_svg(&self) -> String
+    {
+        let mut result = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n", self.width, self.height, self.width, self.height);
+
+        result.push_str(&format!("<rect width=\"{}\" height=\"{}\" {}/>\n", self.width, self.height, self.background.svg_paint("fill")));
+
+        for shape in &self.shapes
+        {
+            let element = match shape
+            {
+                Shape::Polygon { points, fill, stroke } =>
+                {
+                    format!("<polygon points=\"{}\" {}/>", svg_points(points), svg_fill_and_stroke(*fill, *stroke))
+                },
+                Shape::Circle { center, radius, fill, stroke } =>
+                {
+                    format!("<circle cx=\"{:.2}\" cy=\"{:.2}\" r=\"{:.2}\" {}/>", center.0, center.1, radius, svg_fill_and_stroke(*fill, *stroke))
+                },
+                Shape::Line { points, color, width } =>
+                {
+                    format!("<polyline points=\"{}\" fill=\"none\" {} stroke-width=\"{:.2}\" stroke-linecap=\"round\" stroke-linejoin=\"round\"/>", svg_points(points), color.svg_paint("stroke"), width)
+                },
+                Shape::Text { position, height, color, text } =>
+                {
+                    // A capital letter of a monospace font is about 0.7 of the font size
+                    format!("<text x=\"{:.2}\" y=\"{:.2}\" font-family=\"monospace\" font-weight=\"bold\" font-size=\"{:.2}\" {}>{}</text>",
+                            position.0, position.1 + height, height / 0.7, color.svg_paint("fill"), escape_xml(text))
+                },
+            };
+
+            result.push_str(&element);
+            result.push('\n');
+        }
+
+        result.push_str("</svg>\n");
+
+        return result;
+    }
+
+    pub fn rasterize(&self) -> Image
+    {
+        let mut image = Image::new(self.width, self.height, self.background);
+
+        for shape in &self.shapes
+        {
+            match shape
+            {
+                Shape::Polygon { points, fill, stroke } =>
+                {
+                    if let Some(color) = fill
+                    {
+                        image.fill_polygon(points, *color);
+                    }
+
+                    if let Some((color, width)) = stroke
+                    {
+                        image.stroke_lines(points, true, *width, *color);
+                    }
+                },
+                Shape::Circle { center, radius, fill, stroke } =>
+                {
+                    if let Some(color) = fill
+                    {
+                        image.fill_circle(*center, *radius, *color);
+                    }
+
+                    if let Some((color, width)) = stroke
+                    {
+                        image.stroke_circle(*center, *radius, *width, *color);
+                    }
+                },
+                Shape::Line { points, color, width } => image.stroke_lines(points, false, *width, *color),
+                Shape::Text { position, height, color, text } => image.draw_text(*position, *height, *color, text),
+            }
+        }
+
+        return image;
+    }
+}
@@ -0,0 +1,199 @@
+// Duck chess
+//
+// Every turn has two parts: the player moves one of their pieces as usual and then moves the duck
+// to any other empty square, the first time it can go on any empty square. The duck belongs to
+// nobody, it blocks every piece and can not be captured
+//
+// There is no check, the king can move onto an attacked square and castle out of or through an
+// attack. The game is won by capturing the enemy king. A player that can not move wins, which
+// can only happen when every piece is blocked
+//
+// The two parts of a turn are two moves on the board, the duck is moved with a move of the kind
+// Move_Kind::Duck while details.placing_duck is set. The side to move only changes after the duck
+// has moved, so perft counts the piece moves and the duck moves as separate plies
+//
+// In FEN the duck is written as '*':
+// rnbqkbnr/pppp1ppp/8/4p3/4P3/2*5/PPPP1PPP/RNBQKBNR w KQkq - 0 2
+// A FEN is always written for the start of a turn, placing_duck is not part of it
+
+use crate::chess::*;
+use crate::variant::*;
+
+pub struct Duck_Chess;
+
+impl Duck_Chess
+{
+    fn duck_square(board: &Chess_Board) -> Option<usize>
+    {
+        return board.board.iter().position(|&piece| piece == DUCK);
+    }
+}
+
+impl Rules for Duck_Chess
+{
+    fn name(&self) -> &'static str
+    {
+        return "Duck chess";
+    }
+
+    fn aliases(&self) -> &'static [&'static str]
+    {
+        return &["duck"];
+    }
+
+    fn parse_fen(&self, fen: &str) -> Result<Chess_Board, String>
+    {
+        let mut fields: Vec<String> = fen.split_whitespace().map(String::from).collect();
+        let Some(placement) = fields.first().cloned() else { return Err(String::from("the FEN is empty")) };
+
+        // The duck is read as an empty square, every digit is added up so "3*4" becomes "314"
+        let mut duck: Option<usize> = None;
+        let mut square = 56;
+
+        for letter in placement.chars()
+        {
+            match letter
+            {
+                '/'       => square -= 16,
+                '1'..='8' => square += letter as usize - '0' as usize,
+                '*'       =>
+                {
+                    if duck.is_some()
+                    {
+                        return Err(String::from("there can only be one duck"));
+                    }
+
+                    duck = Some(square);
+                    square += 1;
+                },
+                _         => square += 1,
+            }
+        }
+
+        fields[0] = placement.replace('*', "1");
+
+        let mut new_board = Chess_Board::from_fen(&fields.join(" "))?;
+
+        if let Some(square) = duck
+        {
+            new_board.board[square] = DUCK;
+        }
+
+        return Ok(new_board);
+    }
+
+    fn variant_key(&self, board: &Chess_Board) -> u64
+    {
+        let duck = match Self::duck_square(board)
+        {
+            Some(square) => square as u64 + 1,
+            None         => 0,
+        };
+
+        return (duck << 1 | board.details.placing_duck as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    }
+
+    // The piece moves, or the duck moves after a piece has moved
+    fn pseudo_legal_moves(&self, board: &Chess_Board, color: i8) -> Vec<Move>
+    {
+        if !board.details.placing_duck
+        {
+            return board.standard_pseudo_legal_moves(color);
+        }
+
+        let mut results = vec![];
+
+        for square in 0..64
+        {
+            if board.board[square] != 0
+            {
+                continue;
+            }
+
+            results.push(Move
+            {
+                piece: DUCK,
+                origin: Self::duck_square(board).unwrap_or(square),
+                target: square,
+                capture: 0,
+                promotion: 0,
+                kind: Move_Kind::Duck,
+            });
+        }
+
+        return results;
+    }
+
+    // Every move is legal as there is no check
+    fn legal_moves(&self, board: &mut Chess_Board) -> Vec<Move>
+    {
+        return board.pseudo_legal_moves(board.details.side_to_move);
+    }
+
+    // A piece move keeps the turn with the same player until the duck has moved, the details are
+    // saved by standard_make_move so they are changed afterwards
+    fn make_move(&self, board: &mut Chess_Board, new_move: Move)
+    {
+        let color = board.details.side_to_move;
+
+        if new_move.kind != Move_Kind::Duck
+        {
+            board.standard_make_move(new_move);
+
+            if color == BLACK
+            {
+                board.details.fullmove_number -= 1;
+            }
+
+            board.details.side_to_move = color;
+            board.details.placing_duck = true;
+            return;
+        }
+
+        board.previous_details.push(board.details);
+
+        board.board[new_move.origin] = 0;
+        board.board[new_move.target] = DUCK;
+
+        if color == BLACK
+        {
+            board.details.fullmove_number += 1;
+        }
+
+        board.details.side_to_move = -color;
+        board.details.placing_duck = false;
+
+        board.moves.push(new_move);
+    }
+
+    fn in_check(&self, _board: &Chess_Board, _color: i8) -> bool
+    {
+        return false;
+    }
+
+    fn variant_outcome(&self, board: &Chess_Board) -> Option<Outcome>
+    {
+        for color in [WHITE, BLACK]
+        {
+            if board.king_square(color).is_none()
+            {
+                return Some(Outcome::Variant_Win(-color));
+            }
+        }
+
+        return None;
+    }
+
+    // Being stalemated wins
+    fn no_moves_outcome(&self, board: &Chess_Board) -> Outcome
+    {
+        return Outcome::Variant_Win(board.details.side_to_move);
+    }
+
+    fn material_draws(&self) -> bool
+    {
+        return false;
+    }
+}
+
+pub static DUCK_CHESS: Duck_Chess = Duck_Chess;
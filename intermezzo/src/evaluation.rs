@@ -0,0 +1,160 @@
+// Static evaluation of a position
+//
+// The evaluation is a sum of the material and a bonus or penalty for the square every piece is
+// on (piece-square tables). The tables are the ones from the "Simplified Evaluation Function" by
+// Tomasz Michniewski, the king uses a separate table in the endgame where it should move to the
+// center. The two king tables are blended depending on how much material is left on the board
+//
+// All scores are in centipawns
+
+use crate::chess::*;
+
+// Indexed by the piece value (PAWN = 1 ... KING = 6, DUCK = 7)
+pub const PIECE_VALUES: [i32; 8] = [0, 100, 320, 330, 500, 900, 0, 0];
+
+// The amount of material that counts towards the game phase, 24 at the start of the game
+const PHASE_VALUES: [i32; 8] = [0, 0, 1, 1, 2, 4, 0, 0];
+const MAX_PHASE: i32 = 24;
+
+// The tables are written from white's point of view as seen on a printed board, so the
+// first row is the 8th rank
+const PAWN_TABLE: [i32; 64] =
+[
+     0,  0,  0,  0,  0,  0,  0,  0,
+    50, 50, 50, 50, 50, 50, 50, 50,
+    10, 10, 20, 30, 30, 20, 10, 10,
+     5,  5, 10, 25, 25, 10,  5,  5,
+     0,  0,  0, 20, 20,  0,  0,  0,
+     5, -5,-10,  0,  0,-10, -5,  5,
+     5, 10, 10,-20,-20, 10, 10,  5,
+     0,  0,  0,  0,  0,  0,  0,  0,
+];
+
+const KNIGHT_TABLE: [i32; 64] =
+[
+   -50,-40,-30,-30,-30,-30,-40,-50,
+   -40,-20,  0,  0,  0,  0,-20,-40,
+   -30,  0, 10, 15, 15, 10,  0,-30,
+   -30,  5, 15, 20, 20, 15,  5,-30,
+   -30,  0, 15, 20, 20, 15,  0,-30,
+   -30,  5, 10, 15, 15, 10,  5,-30,
+   -40,-20,  0,  5,  5,  0,-20,-40,
+   -50,-40,-30,-30,-30,-30,-40,-50,
+];
+
+const BISHOP_TABLE: [i32; 64] =
+[
+   -20,-10,-10,-10,-10,-10,-10,-20,
+   -10,  0,  0,  0,  0,  0,  0,-10,
+   -10,  0,  5, 10, 10,  5,  0,-10,
+   -10,  5,  5, 10, 10,  5,  5,-10,
+   -10,  0, 10, 10, 10, 10,  0,-10,
+   -10, 10, 10, 10, 10, 10, 10,-10,
+   -10,  5,  0,  0,  0,  0,  5,-10,
+   -20,-10,-10,-10,-10,-10,-10,-20,
+];
+
+const ROOK_TABLE: [i32; 64] =
+[
+     0,  0,  0,  0,  0,  0,  0,  0,
+     5, 10, 10, 10, 10, 10, 10,  5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+     0,  0,  0,  5,  5,  0,  0,  0,
+];
+
+const QUEEN_TABLE: [i32; 64] =
+[
+   -20,-10,-10, -5, -5,-10,-10,-20,
+   -10,  0,  0,  0,  0,  0,  0,-10,
+   -10,  0,  5,  5,  5,  5,  0,-10,
+    -5,  0,  5,  5,  5,  5,  0, -5,
+     0,  0,  5,  5,  5,  5,  0, -5,
+   -10,  5,  5,  5,  5,  5,  0,-10,
+   -10,  0,  5,  0,  0,  0,  0,-10,
+   -20,-10,-10, -5, -5,-10,-10,-20,
+];
+
+const KING_MIDDLE_TABLE: [i32; 64] =
+[
+   -30,-40,-40,-50,-50,-40,-40,-30,
+   -30,-40,-40,-50,-50,-40,-40,-30,
+   -30,-40,-40,-50,-50,-40,-40,-30,
+   -30,-40,-40,-50,-50,-40,-40,-30,
+   -20,-30,-30,-40,-40,-30,-30,-20,
+   -10,-20,-20,-20,-20,-20,-20,-10,
+    20, 20,  0,  0,  0,  0, 20, 20,
+    20, 30, 10,  0,  0, 10, 30, 20,
+];
+
+const KING_END_TABLE: [i32; 64] =
+[
+   -50,-40,-30,-20,-20,-30,-40,-50,
+   -30,-20,-10,  0,  0,-10,-20,-30,
+   -30,-10, 20, 30, 30, 20,-10,-30,
+   -30,-10, 30, 40, 40, 30,-10,-30,
+   -30,-10, 30, 40, 40, 30,-10,-30,
+   -30,-10, 20, 30, 30, 20,-10,-30,
+   -30,-30,  0,  0,  0,  0,-30,-30,
+   -50,-30,-30,-30,-30,-30,-30,-50,
+];
+
+// The index within the tables above for a piece of the given color
+// White pieces are flipped vertically as the tables start with the 8th rank
+fn table_index(square: usize, color: i8) -> usize
+{
+    if color == WHITE
+    {
+        return (7 - Chess_Board::get_row(square)) * 8 + Chess_Board::get_col(square);
+    }
+
+    return square;
+}
+
+// The game phase from MAX_PHASE (all pieces on the board) down to 0 (only pawns and kings)
+pub fn game_phase(board: &Chess_Board) -> i32
+{
+    let phase: i32 = board.board.iter()
+                                .map(|&piece| PHASE_VALUES[piece.unsigned_abs() as usize])
+                                .sum();
+
+    return std::cmp::min(phase, MAX_PHASE);
+}
+
+// Evaluates the position from the point of view of the side to move
+pub fn evaluate(board: &Chess_Board) -> i32
+{
+    let phase = game_phase(board);
+    let mut score = 0;
+
+    for square in 0..64
+    {
+        let piece = board.board[square];
+
+        if piece == 0
+        {
+            continue;
+        }
+
+        let color = piece.signum();
+        let index = table_index(square, color);
+
+        let value = PIECE_VALUES[piece.unsigned_abs() as usize] + match piece.abs()
+        {
+            PAWN   => PAWN_TABLE[index],
+            KNIGHT => KNIGHT_TABLE[index],
+            BISHOP => BISHOP_TABLE[index],
+            ROOK   => ROOK_TABLE[index],
+            QUEEN  => QUEEN_TABLE[index],
+            KING   => (KING_MIDDLE_TABLE[index] * phase + KING_END_TABLE[index] * (MAX_PHASE - phase)) / MAX_PHASE,
+            _      => 0,
+        };
+
+        score += color as i32 * value;
+    }
+
+    return score * board.details.side_to_move as i32;
+}
@@ -0,0 +1,979 @@
+// Fairy_Board, a board of any size with the compound fairy pieces
+//
+// Archbishop (A) -> moves as a bishop and a knight
+// Chancellor (C) -> moves as a rook and a knight
+//
+// It is used for the chess variants on a 10x8 board:
+// Capablanca -> rnabqkbcnr/pppppppppp/10/10/10/10/PPPPPPPPPP/RNABQKBCNR w KQkq - 0 1
+// Gothic     -> rnbqckabnr/pppppppppp/10/10/10/10/PPPPPPPPPP/RNBQCKABNR w KQkq - 0 1
+//
+// The rules are those of normal chess apart from the board and the pieces. Pawns can also promote
+// to an archbishop or chancellor. When castling the king moves to the second file from the corner
+// (i1 on a 10x8 board, g1 on a normal board) or to the c file, the rook jumps over it as usual
+//
+// The draw rules of draw.rs apply as well, apart from dead positions which are only looked for
+// on a normal board
+//
+// The size of the board is read from the FEN, numbers can have more than one digit ("10")
+// The moves and their details are the same Move and Board_Details as on Chess_Board, only the
+// square numbers depend on the Board_Geometry. A Fairy_Board is searched by fairy_search.rs, drawn
+// by render.rs and played in the CLI and over UCI like the variants of variant.rs
+
+use crate::chess::*;
+use crate::geometry::*;
+use crate::notation::{piece_from_char, piece_to_char};
+use crate::variant::*;
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+// After the duck (DUCK = 7 in chess.rs), so that every piece has its own value
+pub const ARCHBISHOP: i8 = 8;
+pub const CHANCELLOR: i8 = 9;
+
+const KNIGHT_STEPS:    [(i32, i32); 8] = [(1, 2), (2, 1), (2, -1), (1, -2), (-1, -2), (-2, -1), (-2, 1), (-1, 2)];
+const DIAGONAL_STEPS:  [(i32, i32); 4] = [(1, 1), (1, -1), (-1, -1), (-1, 1)];
+const STRAIGHT_STEPS:  [(i32, i32); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+
+const PROMOTION_PIECES: [i8; 6] = [KNIGHT, BISHOP, ROOK, QUEEN, ARCHBISHOP, CHANCELLOR];
+
+pub struct Fairy_Variant
+{
+    pub name:      &'static str,
+    pub start_fen: &'static str,
+}
+
+pub const FAIRY_VARIANTS: [Fairy_Variant; 2] = [
+    Fairy_Variant { name: "Capablanca", start_fen: "rnabqkbcnr/pppppppppp/10/10/10/10/PPPPPPPPPP/RNABQKBCNR w KQkq - 0 1" },
+    Fairy_Variant { name: "Gothic",     start_fen: "rnbqckabnr/pppppppppp/10/10/10/10/PPPPPPPPPP/RNBQCKABNR w KQkq - 0 1" },
+];
+
+// Finds a fairy variant by name in the same way as find_variant
+pub fn find_fairy_variant(name: &str) -> Option<&'static Fairy_Variant>
+{
+    let name = variant_key_name(name);
+
+    return FAIRY_VARIANTS.iter().find(|fairy| variant_key_name(fairy.name) == name);
+}
+
+// piece_from_char with the fairy pieces
+pub fn fairy_piece_from_char(letter: char) -> Option<i8>
+{
+    let piece = match letter.to_ascii_lowercase()
+    {
+        'a' => ARCHBISHOP,
+        'c' => CHANCELLOR,
+        _   => return piece_from_char(letter),
+    };
+
+    return Some(if letter.is_ascii_uppercase() { piece } else { -piece });
+}
+
+// piece_to_char with the fairy pieces
+pub fn fairy_piece_to_char(piece: i8) -> char
+{
+    let letter = match piece.abs()
+    {
+        ARCHBISHOP => 'a',
+        CHANCELLOR => 'c',
+        _          => return piece_to_char(piece),
+    };
+
+    return if piece > 0 { letter.to_ascii_uppercase() } else { letter };
+}
+
+// What a piece can do, the compound pieces combine the moves of two normal pieces
+fn leaps(piece: i8) -> bool
+{
+    return matches!(piece.abs(), KNIGHT | ARCHBISHOP | CHANCELLOR);
+}
+
+fn slides_diagonally(piece: i8) -> bool
+{
+    return matches!(piece.abs(), BISHOP | QUEEN | ARCHBISHOP);
+}
+
+fn slides_straight(piece: i8) -> bool
+{
+    return matches!(piece.abs(), ROOK | QUEEN | CHANCELLOR);
+}
+
+#[derive(Clone)]
+pub struct Fairy_Board
+{
+    pub geometry:         Board_Geometry,
+    pub board:            Vec<i8>,
+    pub moves:            Vec<Move>,
+    pub details:          Board_Details,
+    pub previous_details: Vec<Board_Details>,
+}
+
+impl Fairy_Board
+{
+    // An empty board of the given size, white to move and no castling rights
+    pub fn empty(geometry: Board_Geometry) -> Self
+    {
+        let mut details = Chess_Board::new().details;
+        details.rooks_have_moved = [true; 4];
+
+        return Fairy_Board
+        {
+            geometry,
+            board: vec![0; geometry.size()],
+            moves: vec![],
+            details,
+            previous_details: vec![],
+        };
+    }
+
+    // The rook squares in the order of rooks_have_moved: [white long, white short, black long, black short]
+    pub fn castling_rooks(&self) -> [usize; 4]
+    {
+        let last_rank = self.geometry.ranks - 1;
+        let last_file = self.geometry.files - 1;
+
+        return [
+            self.geometry.square(0, 0),
+            self.geometry.square(last_file, 0),
+            self.geometry.square(0, last_rank),
+            self.geometry.square(last_file, last_rank),
+        ];
+    }
+
+    // Whether each rook can still castle, in the order of rooks_have_moved
+    pub fn castling_rights(&self) -> [bool; 4]
+    {
+        return std::array::from_fn(|r_idx| !self.details.rooks_have_moved[r_idx] && !self.details.kings_have_moved[if r_idx < 2 { 1 } else { 0 }]);
+    }
+
+    // The en passant square if a pawn of the side to move stands next to the pawn that moved,
+    // as Chess_Board::relevant_en_passant in draw.rs
+    pub fn relevant_en_passant(&self) -> Option<usize>
+    {
+        let square = self.details.en_passant?;
+        let color = self.details.side_to_move;
+
+        for side in [-1, 1]
+        {
+            if self.geometry.offset(square, side, -color as i32).is_some_and(|neighbour| self.board[neighbour] == color * PAWN)
+            {
+                return Some(square);
+            }
+        }
+
+        return None;
+    }
+
+    // The key of the position used for repetitions, a hash of what makes two positions the same
+    // (see draw.rs)
+    pub fn position_key(&self) -> u64
+    {
+        let mut hasher = DefaultHasher::new();
+        (&self.board, self.details.side_to_move, self.castling_rights(), self.relevant_en_passant()).hash(&mut hasher);
+
+        return hasher.finish();
+    }
+
+    pub fn king_square(&self, color: i8) -> Option<usize>
+    {
+        return self.board.iter().position(|&piece| piece == color * KING);
+    }
+
+    // Whether a piece of the given color attacks the square, looking outwards from the square
+    pub fn square_attacked(&self, square: usize, color: i8) -> bool
+    {
+        let geometry = self.geometry;
+
+        // Pawns of the color attack from the rank behind the square
+        for file_step in [-1, 1]
+        {
+            if let Some(from) = geometry.offset(square, file_step, -color as i32)
+            {
+                if self.board[from] == color * PAWN
+                {
+                    return true;
+                }
+            }
+        }
+
+        for (file_step, rank_step) in KNIGHT_STEPS
+        {
+            if let Some(from) = geometry.offset(square, file_step, rank_step)
+            {
+                if self.board[from].signum() == color && leaps(self.board[from])
+                {
+                    return true;
+                }
+            }
+        }
+
+        for (steps, slides) in [(DIAGONAL_STEPS, slides_diagonally as fn(i8) -> bool), (STRAIGHT_STEPS, slides_straight)]
+        {
+            for (file_step, rank_step) in steps
+            {
+                let mut current = square;
+                let mut distance = 0;
+
+                while let Some(next) = geometry.offset(current, file_step, rank_step)
+                {
+                    current = next;
+                    distance += 1;
+
+                    let piece = self.board[current];
+
+                    if piece == 0
+                    {
+                        continue;
+                    }
+
+                    if    piece.signum() == color
+                       && (slides(piece) || (distance == 1 && piece.abs() == KING))
+                    {
+                        return true;
+                    }
+
+                    break;
+                }
+            }
+        }
+
+        return false;
+    }
+
+    pub fn in_check(&self, color: i8) -> bool
+    {
+        return match self.king_square(color)
+        {
+            Some(square) => self.square_attacked(square, -color),
+            None         => false,
+        };
+    }
+
+    fn new_move(&self, origin: usize, target: usize, kind: Move_Kind) -> Move
+    {
+        return Move
+        {
+            piece: self.board[origin],
+            origin,
+            target,
+            capture: self.board[target],
+            promotion: 0,
+            kind,
+        };
+    }
+
+    fn pawn_moves(&self, square: usize, results: &mut Vec<Move>)
+    {
+        let geometry = self.geometry;
+        let color = self.board[square].signum();
+        let rank_step = color as i32;
+        let start_rank = if color > 0 { 1 } else { geometry.ranks - 2 };
+        let promotion_rank = geometry.back_rank(-color);
+
+        let mut add = |new_move: Move| {
+            if geometry.get_rank(new_move.target) == promotion_rank
+            {
+                for piece in PROMOTION_PIECES
+                {
+                    results.push(Move { promotion: color * piece, kind: Move_Kind::Promotion, ..new_move });
+                }
+            } else
+            {
+                results.push(new_move);
+            }
+        };
+
+        if let Some(front) = geometry.offset(square, 0, rank_step)
+        {
+            if self.board[front] == 0
+            {
+                add(self.new_move(square, front, Move_Kind::Normal));
+
+                if let Some(double) = geometry.offset(front, 0, rank_step)
+                {
+                    if    geometry.get_rank(square) == start_rank
+                       && self.board[double] == 0
+                    {
+                        add(self.new_move(square, double, Move_Kind::Double_Push));
+                    }
+                }
+            }
+        }
+
+        for file_step in [-1, 1]
+        {
+            let Some(target) = geometry.offset(square, file_step, rank_step) else { continue };
+
+            if self.board[target].signum() == -color
+            {
+                add(self.new_move(square, target, Move_Kind::Normal));
+            } else if self.details.en_passant == Some(target)
+            {
+                add(Move { capture: -color * PAWN, ..self.new_move(square, target, Move_Kind::En_Passant) });
+            }
+        }
+    }
+
+    // The squares the king and rook end up on after castling: (king, rook)
+    pub fn castling_targets(&self, castle: Move) -> (usize, usize)
+    {
+        let back_rank = self.geometry.get_rank(castle.origin);
+
+        return if castle.kind == Move_Kind::Short_Castle
+        {
+            let files = self.geometry.files;
+            (self.geometry.square(files - 2, back_rank), self.geometry.square(files - 3, back_rank))
+        } else
+        {
+            (self.geometry.square(2, back_rank), self.geometry.square(3, back_rank))
+        };
+    }
+
+    fn castling_moves(&self, square: usize, results: &mut Vec<Move>)
+    {
+        let color = self.board[square].signum();
+        let rooks = self.castling_rooks();
+
+        if    self.geometry.get_rank(square) != self.geometry.back_rank(color)
+           || self.details.kings_have_moved[if color < 0 { 0 } else { 1 }]
+           || self.in_check(color)
+        {
+            return;
+        }
+
+        for (kind, r_idx) in [(Move_Kind::Long_Castle, 0), (Move_Kind::Short_Castle, 1)]
+        {
+            let r_idx = if color < 0 { r_idx + 2 } else { r_idx };
+            let rook_square = rooks[r_idx];
+
+            if    self.details.rooks_have_moved[r_idx]
+               || self.board[rook_square] != color * ROOK
+            {
+                continue;
+            }
+
+            let castle = Move { piece: color * KING, origin: square, target: 0, capture: 0, promotion: 0, kind };
+            let (king_target, rook_target) = self.castling_targets(castle);
+
+            // Every square the king and rook cross or land on is empty, apart from the two pieces
+            let squares = [square, rook_square, king_target, rook_target];
+            let lowest = *squares.iter().min().unwrap();
+            let highest = *squares.iter().max().unwrap();
+
+            let blocked = (lowest..=highest).any(|between| between != square && between != rook_square && self.board[between] != 0);
+
+            // The king can not cross an attacked square
+            let crossed = if king_target > square { square..=king_target } else { king_target..=square };
+            let attacked = crossed.into_iter().any(|between| self.square_attacked(between, -color));
+
+            if !blocked && !attacked
+            {
+                results.push(Move { target: king_target, ..castle });
+            }
+        }
+    }
+
+    // Every move for the side to move without checking whether the king is left in check
+    pub fn pseudo_legal_moves(&self) -> Vec<Move>
+    {
+        let color = self.details.side_to_move;
+        let geometry = self.geometry;
+        let mut results = vec![];
+
+        for square in 0..geometry.size()
+        {
+            let piece = self.board[square];
+
+            if piece.signum() != color
+            {
+                continue;
+            }
+
+            if piece.abs() == PAWN
+            {
+                self.pawn_moves(square, &mut results);
+                continue;
+            }
+
+            let mut add_steps = |steps: &[(i32, i32)], slides: bool| {
+                for &(file_step, rank_step) in steps
+                {
+                    let mut current = square;
+
+                    while let Some(next) = geometry.offset(current, file_step, rank_step)
+                    {
+                        current = next;
+
+                        if self.board[current].signum() == color
+                        {
+                            break;
+                        }
+
+                        results.push(self.new_move(square, current, Move_Kind::Normal));
+
+                        if !slides || self.board[current] != 0
+                        {
+                            break;
+                        }
+                    }
+                }
+            };
+
+            if piece.abs() == KING
+            {
+                add_steps(&DIAGONAL_STEPS, false);
+                add_steps(&STRAIGHT_STEPS, false);
+                self.castling_moves(square, &mut results);
+                continue;
+            }
+
+            if leaps(piece)
+            {
+                add_steps(&KNIGHT_STEPS, false);
+            }
+
+            if slides_diagonally(piece)
+            {
+                add_steps(&DIAGONAL_STEPS, true);
+            }
+
+            if slides_straight(piece)
+            {
+                add_steps(&STRAIGHT_STEPS, true);
+            }
+        }
+
+        return results;
+    }
+
+    pub fn legal_moves(&mut self) -> Vec<Move>
+    {
+        let color = self.details.side_to_move;
+        let mut results = vec![];
+
+        for candidate in self.pseudo_legal_moves()
+        {
+            self.make_move(candidate);
+            let legal = !self.in_check(color);
+            self.unmake_move();
+
+            if legal
+            {
+                results.push(candidate);
+            }
+        }
+
+        return results;
+    }
+
+    // Plays a move on the board, the move is assumed to be legal
+    pub fn make_move(&mut self, new_move: Move)
+    {
+        let color = new_move.piece.signum();
+
+        self.previous_details.push(self.details);
+        self.moves.push(new_move);
+
+        self.board[new_move.origin] = 0;
+        self.board[new_move.target] = if new_move.promotion != 0 { new_move.promotion } else { new_move.piece };
+
+        match new_move.kind
+        {
+            Move_Kind::En_Passant =>
+            {
+                let captured = self.geometry.offset(new_move.target, 0, -color as i32).unwrap();
+                self.board[captured] = 0;
+            },
+            Move_Kind::Short_Castle | Move_Kind::Long_Castle =>
+            {
+                let rook_square = self.castling_rooks()[Chess_Board::castling_index(new_move)];
+                let (_, rook_target) = self.castling_targets(new_move);
+
+                self.board[rook_square] = 0;
+                self.board[rook_target] = color * ROOK;
+            },
+            _ => (),
+        }
+
+        if new_move.piece.abs() == KING
+        {
+            self.details.kings_have_moved[if color < 0 { 0 } else { 1 }] = true;
+        }
+
+        // A rook that moves or is captured can not castle anymore
+        let rooks = self.castling_rooks();
+
+        for r_idx in 0..4
+        {
+            if new_move.origin == rooks[r_idx] || new_move.target == rooks[r_idx]
+            {
+                self.details.rooks_have_moved[r_idx] = true;
+            }
+        }
+
+        self.details.en_passant = if new_move.kind == Move_Kind::Double_Push
+        {
+            Some((new_move.origin + new_move.target) / 2)
+        } else
+        {
+            None
+        };
+
+        self.details.halfmove_clock = if new_move.capture != 0 || new_move.piece.abs() == PAWN
+        {
+            0
+        } else
+        {
+            self.details.halfmove_clock + 1
+        };
+
+        if color == BLACK
+        {
+            self.details.fullmove_number += 1;
+        }
+
+        self.details.side_to_move = -color;
+        self.details.is_check = if self.in_check(-color) { color } else { 0 };
+    }
+
+    pub fn unmake_move(&mut self)
+    {
+        let (Some(last_move), Some(details)) = (self.moves.pop(), self.previous_details.pop()) else { return };
+
+        let color = last_move.piece.signum();
+
+        self.details = details;
+        self.board[last_move.origin] = last_move.piece;
+        self.board[last_move.target] = if last_move.kind == Move_Kind::En_Passant { 0 } else { last_move.capture };
+
+        match last_move.kind
+        {
+            Move_Kind::En_Passant =>
+            {
+                let captured = self.geometry.offset(last_move.target, 0, -color as i32).unwrap();
+                self.board[captured] = last_move.capture;
+            },
+            Move_Kind::Short_Castle | Move_Kind::Long_Castle =>
+            {
+                let rook_square = self.castling_rooks()[Chess_Board::castling_index(last_move)];
+                let (_, rook_target) = self.castling_targets(last_move);
+
+                self.board[rook_target] = 0;
+                self.board[rook_square] = color * ROOK;
+                self.board[last_move.origin] = last_move.piece;
+            },
+            _ => (),
+        }
+    }
+
+    // Checkmate, stalemate or a draw that ends the game immediately (see draw.rs), None while
+    // the game goes on
+    pub fn outcome(&mut self) -> Option<Outcome>
+    {
+        if self.legal_moves().is_empty()
+        {
+            return Some(if self.in_check(self.details.side_to_move) { Outcome::Checkmate } else { Outcome::Stalemate });
+        }
+
+        return self.automatic_draw().map(Outcome::Draw);
+    }
+
+    // The same as Chess_Board::perft
+    pub fn perft(&mut self, depth: u32) -> u64
+    {
+        if depth == 0
+        {
+            return 1;
+        }
+
+        let mut nodes = 0;
+
+        for legal_move in self.legal_moves()
+        {
+            self.make_move(legal_move);
+            nodes += self.perft(depth - 1);
+            self.unmake_move();
+        }
+
+        return nodes;
+    }
+
+    // Reads a FEN with any number of files and ranks, the castling rights as KQkq
+    pub fn from_fen(fen: &str) -> Result<Self, String>
+    {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+
+        if fields.len() < 4
+        {
+            return Err(format!("expected at least 4 fields in FEN \"{}\"", fen));
+        }
+
+        // Every row is read first, as the number of files is only known afterwards
+        let mut rows: Vec<Vec<i8>> = vec![];
+
+        for row in fields[0].split('/')
+        {
+            let mut pieces = vec![];
+            let mut empty = 0;
+
+            for letter in row.chars()
+            {
+                if let Some(digit) = letter.to_digit(10)
+                {
+                    empty = empty * 10 + digit as usize;
+                    continue;
+                }
+
+                pieces.extend(std::iter::repeat_n(0, empty));
+                empty = 0;
+
+                match fairy_piece_from_char(letter)
+                {
+                    Some(piece) => pieces.push(piece),
+                    None        => return Err(format!("unknown piece '{}' in FEN", letter)),
+                }
+            }
+
+            pieces.extend(std::iter::repeat_n(0, empty));
+            rows.push(pieces);
+        }
+
+        let files = rows[0].len();
+
+        let Some(geometry) = Board_Geometry::new(files, rows.len()) else { return Err(format!("a board of {}x{} squares is not supported", files, rows.len())) };
+
+        let mut new_board = Fairy_Board::empty(geometry);
+
+        // The FEN lists the rows starting from the last rank
+        for (r_idx, row) in rows.iter().enumerate()
+        {
+            if row.len() != files
+            {
+                return Err(format!("row {} of the FEN does not have {} squares", r_idx + 1, files));
+            }
+
+            let rank = geometry.ranks - 1 - r_idx;
+
+            for (file, &piece) in row.iter().enumerate()
+            {
+                new_board.board[geometry.square(file, rank)] = piece;
+            }
+        }
+
+        new_board.details.side_to_move = match fields[1]
+        {
+            "w" => WHITE,
+            "b" => BLACK,
+            _   => return Err(format!("unknown side to move \"{}\"", fields[1])),
+        };
+
+        // Rights without the king on its back rank or the rook in its corner are ignored
+        let rooks = new_board.castling_rooks();
+
+        for letter in fields[2].chars().filter(|&letter| letter != '-')
+        {
+            let r_idx = match letter
+            {
+                'Q' => 0,
+                'K' => 1,
+                'q' => 2,
+                'k' => 3,
+                _   => return Err(format!("unknown castling right '{}'", letter)),
+            };
+
+            let color = if r_idx < 2 { WHITE } else { BLACK };
+
+            if    new_board.board[rooks[r_idx]] == color * ROOK
+               && new_board.king_square(color).is_some_and(|square| geometry.get_rank(square) == geometry.back_rank(color))
+            {
+                new_board.details.rooks_have_moved[r_idx] = false;
+            }
+        }
+
+        new_board.details.en_passant = match fields[3]
+        {
+            "-" => None,
+            square => match geometry.parse_square(square)
+            {
+                Some(index) => Some(index),
+                None => return Err(format!("invalid en passant square \"{}\"", square)),
+            },
+        };
+
+        if let Some(clock) = fields.get(4)
+        {
+            new_board.details.halfmove_clock = clock.parse::<u32>().map_err(|_| format!("invalid half move clock \"{}\"", clock))?;
+        }
+
+        if let Some(number) = fields.get(5)
+        {
+            new_board.details.fullmove_number = std::cmp::max(number.parse::<u32>().map_err(|_| format!("invalid full move number \"{}\"", number))?, 1);
+        }
+
+        let color = new_board.details.side_to_move;
+        new_board.details.is_check = if new_board.in_check(color) { -color } else { 0 };
+
+        return Ok(new_board);
+    }
+
+    pub fn to_fen(&self) -> String
+    {
+        let geometry = self.geometry;
+        let mut rows = vec![];
+
+        for rank in (0..geometry.ranks).rev()
+        {
+            let mut row = String::new();
+            let mut empty = 0;
+
+            for file in 0..geometry.files
+            {
+                let piece = self.board[geometry.square(file, rank)];
+
+                if piece == 0
+                {
+                    empty += 1;
+                    continue;
+                }
+
+                if empty > 0
+                {
+                    row.push_str(&empty.to_string());
+                    empty = 0;
+                }
+
+                row.push(fairy_piece_to_char(piece));
+            }
+
+            if empty > 0
+            {
+                row.push_str(&empty.to_string());
+            }
+
+            rows.push(row);
+        }
+
+        let rights = self.castling_rights();
+        let mut castling: String = ['K', 'Q', 'k', 'q'].iter()
+                                                       .zip([1, 0, 3, 2])
+                                                       .filter(|&(_, r_idx)| rights[r_idx])
+                                                       .map(|(&letter, _)| letter)
+                                                       .collect();
+
+        if castling.is_empty()
+        {
+            castling.push('-');
+        }
+
+        let en_passant = match self.details.en_passant
+        {
+            Some(square) => geometry.square_name(square),
+            None         => String::from("-"),
+        };
+
+        return format!("{} {} {} {} {} {}",
+                       rows.join("/"),
+                       if self.details.side_to_move == WHITE { "w" } else { "b" },
+                       castling,
+                       en_passant,
+                       self.details.halfmove_clock,
+                       self.details.fullmove_number);
+    }
+
+    // The move in UCI notation, "e2e4", "b7b8c" for a promotion to a chancellor
+    pub fn move_to_uci(&self, uci_move: Move) -> String
+    {
+        let mut result = format!("{}{}", self.geometry.square_name(uci_move.origin), self.geometry.square_name(uci_move.target));
+
+        if uci_move.promotion != 0
+        {
+            result.push(fairy_piece_to_char(-uci_move.promotion.abs()));
+        }
+
+        return result;
+    }
+
+    pub fn parse_uci_move(&mut self, text: &str) -> Option<Move>
+    {
+        return self.legal_moves()
+                   .into_iter()
+                   .find(|legal_move| self.move_to_uci(*legal_move) == text);
+    }
+
+    // Writes a legal move in SAN in the same way as Chess_Board::move_to_san: Ah3, Cxe5, b8=A
+    pub fn move_to_san(&mut self, san_move: Move) -> String
+    {
+        let geometry = self.geometry;
+        let mut result = String::new();
+
+        match san_move.kind
+        {
+            Move_Kind::Short_Castle => result.push_str("O-O"),
+            Move_Kind::Long_Castle  => result.push_str("O-O-O"),
+            _ if san_move.piece.abs() == PAWN =>
+            {
+                if geometry.get_file(san_move.origin) != geometry.get_file(san_move.target)
+                {
+                    result.push(geometry.square_name(san_move.origin).as_bytes()[0] as char);
+                    result.push('x');
+                }
+
+                result.push_str(&geometry.square_name(san_move.target));
+
+                if san_move.promotion != 0
+                {
+                    result.push('=');
+                    result.push(fairy_piece_to_char(san_move.promotion.abs()));
+                }
+            },
+            _ =>
+            {
+                result.push(fairy_piece_to_char(san_move.piece.abs()));
+
+                // Other pieces of the same type that can move to the same square
+                let others: Vec<Move> = self.legal_moves()
+                                            .into_iter()
+                                            .filter(|other| {
+                                                   other.piece == san_move.piece
+                                                && other.target == san_move.target
+                                                && other.origin != san_move.origin
+                                            })
+                                            .collect();
+
+                let origin_name = geometry.square_name(san_move.origin);
+
+                if !others.is_empty()
+                {
+                    let same_file = others.iter().any(|other| geometry.get_file(other.origin) == geometry.get_file(san_move.origin));
+                    let same_rank = others.iter().any(|other| geometry.get_rank(other.origin) == geometry.get_rank(san_move.origin));
+
+                    if !same_file
+                    {
+                        result.push_str(&origin_name[0..1]);
+                    } else if !same_rank
+                    {
+                        result.push_str(&origin_name[1..2]);
+                    } else
+                    {
+                        result.push_str(&origin_name);
+                    }
+                }
+
+                if san_move.capture != 0
+                {
+                    result.push('x');
+                }
+
+                result.push_str(&geometry.square_name(san_move.target));
+            },
+        }
+
+        self.make_move(san_move);
+
+        if self.in_check(self.details.side_to_move)
+        {
+            result.push(if self.outcome() == Some(Outcome::Checkmate) { '#' } else { '+' });
+        }
+
+        self.unmake_move();
+
+        return result;
+    }
+
+    // Finds the legal move written in SAN, with the same deviations as Chess_Board::parse_san
+    pub fn parse_san(&mut self, text: &str) -> Option<Move>
+    {
+        let geometry = self.geometry;
+
+        let cleaned: String = text.trim()
+                                  .trim_end_matches(|letter| "+#!?".contains(letter))
+                                  .chars()
+                                  .filter(|letter| *letter != 'x' && *letter != ':' && *letter != '-' && *letter != '=')
+                                  .collect();
+
+        let legal = self.legal_moves();
+
+        if cleaned == "OO" || cleaned == "00"
+        {
+            return legal.into_iter().find(|legal_move| legal_move.kind == Move_Kind::Short_Castle);
+        }
+
+        if cleaned == "OOO" || cleaned == "000"
+        {
+            return legal.into_iter().find(|legal_move| legal_move.kind == Move_Kind::Long_Castle);
+        }
+
+        let mut letters: Vec<char> = cleaned.chars().collect();
+
+        // Piece type, pawns do not have a letter
+        let piece_type = match letters.first()
+        {
+            Some(letter) if "NBRQKAC".contains(*letter) =>
+            {
+                let piece = fairy_piece_from_char(*letter).unwrap();
+                letters.remove(0);
+                piece
+            },
+            Some(_) => PAWN,
+            None    => return None,
+        };
+
+        // Promotion piece at the end, files can be lowercase letters as well so only uppercase
+        // letters are read here
+        let mut promotion = 0;
+
+        if let Some(letter) = letters.last()
+        {
+            if piece_type == PAWN && "NBRQAC".contains(*letter)
+            {
+                promotion = fairy_piece_from_char(*letter).unwrap();
+                letters.pop();
+            }
+        }
+
+        if letters.len() < 2
+        {
+            return None;
+        }
+
+        let target_name: String = letters[letters.len() - 2..].iter().collect();
+        let target = geometry.parse_square(&target_name)?;
+
+        let hints = &letters[..letters.len() - 2];
+        let mut origin_file: Option<usize> = None;
+        let mut origin_rank: Option<usize> = None;
+
+        for &hint in hints
+        {
+            if hint.is_ascii_digit()
+            {
+                origin_rank = Some((hint as u8 - b'1') as usize);
+            } else if hint.is_ascii_lowercase() && ((hint as u8 - b'a') as usize) < geometry.files
+            {
+                origin_file = Some((hint as u8 - b'a') as usize);
+            } else
+            {
+                return None;
+            }
+        }
+
+        let candidates: Vec<Move> = legal.into_iter()
+                                         .filter(|legal_move| {
+                                                legal_move.piece.abs() == piece_type
+                                             && legal_move.target == target
+                                             && legal_move.promotion.abs() == promotion
+                                             && legal_move.kind != Move_Kind::Short_Castle
+                                             && legal_move.kind != Move_Kind::Long_Castle
+                                             && origin_file.is_none_or(|file| geometry.get_file(legal_move.origin) == file)
+                                             && origin_rank.is_none_or(|rank| geometry.get_rank(legal_move.origin) == rank)
+                                         })
+                                         .collect();
+
+        if candidates.len() == 1
+        {
+            return Some(candidates[0]);
+        }
+
+        return None;
+    }
+}
@@ -0,0 +1,476 @@
+// Searching a Fairy_Board (Capablanca and Gothic chess)
+//
+// The search of search.rs only works on Chess_Board, the variants on a 10x8 board get this
+// smaller one: iterative deepening with alpha-beta, the captures first (most valuable victim,
+// least valuable attacker) and a quiescence search of the captures. It takes the same
+// Search_Limits and returns the same Search_Info, so the CLI and UCI report both the same way
+//
+// Repetitions and the 50 move rule score as a draw in the same way as in search.rs. There is no
+// transposition table, MultiPV or pondering. The evaluation is the material and a small bonus for
+// pieces near the center and pawns that moved up the board
+
+use crate::chess::*;
+use crate::fairy::*;
+use crate::search::*;
+use crate::time_manager::*;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+// Indexed by the piece value (PAWN = 1 ... KING = 6, DUCK = 7, ARCHBISHOP = 8, CHANCELLOR = 9)
+const FAIRY_PIECE_VALUES: [i32; 10] = [0, 100, 300, 350, 500, 950, 0, 0, 875, 900];
+
+// Per step closer to the center, for pieces other than pawns and kings
+const CENTER_BONUS: i32 = 4;
+// Per rank a pawn moved up the board
+const PAWN_ADVANCE_BONUS: i32 = 6;
+
+// The material and position from the point of view of the side to move
+pub fn evaluate_fairy(board: &Fairy_Board) -> i32
+{
+    let geometry = board.geometry;
+    let mut score = 0;
+
+    for square in 0..geometry.size()
+    {
+        let piece = board.board[square];
+
+        if piece == 0
+        {
+            continue;
+        }
+
+        let file = geometry.get_file(square) as i32;
+        let rank = geometry.get_rank(square) as i32;
+
+        let mut value = FAIRY_PIECE_VALUES[piece.unsigned_abs() as usize];
+
+        match piece.abs()
+        {
+            PAWN =>
+            {
+                let advanced = if piece > 0 { rank - 1 } else { geometry.ranks as i32 - 2 - rank };
+                value += advanced * PAWN_ADVANCE_BONUS;
+            },
+            KING => (),
+            _ =>
+            {
+                // Twice the distance, so that the center of a board with an even size is whole
+                let file_distance = (2 * file - (geometry.files as i32 - 1)).abs();
+                let rank_distance = (2 * rank - (geometry.ranks as i32 - 1)).abs();
+                let largest = geometry.files as i32 + geometry.ranks as i32 - 2;
+
+                value += (largest - file_distance - rank_distance) / 2 * CENTER_BONUS;
+            },
+        }
+
+        score += piece.signum() as i32 * value;
+    }
+
+    return score * board.details.side_to_move as i32;
+}
+
+// Captures of valuable pieces with cheap pieces first, promotions before other quiet moves
+fn move_order(candidate: &Move) -> i32
+{
+    if candidate.capture != 0
+    {
+        return 10 * FAIRY_PIECE_VALUES[candidate.capture.unsigned_abs() as usize] - FAIRY_PIECE_VALUES[candidate.piece.unsigned_abs() as usize];
+    }
+
+    return FAIRY_PIECE_VALUES[candidate.promotion.unsigned_abs() as usize];
+}
+
+pub struct Fairy_Search
+{
+    // Set from the outside to stop the search as soon as possible
+    pub stop:          Arc<AtomicBool>,
+    pub move_overhead: u64,
+
+    board:        Fairy_Board,
+    start:        Instant,
+    nodes:        u64,
+    node_limit:   Option<u64>,
+    time_manager: Option<Time_Manager>,
+    aborted:      bool,
+
+    // The keys of the positions since the last capture or pawn move, as in search.rs
+    keys: Vec<u64>,
+
+    // The principal variation found at every ply
+    pv: Vec<Vec<Move>>,
+}
+
+impl Fairy_Search
+{
+    pub fn new(board: &Fairy_Board) -> Self
+    {
+        return Fairy_Search
+        {
+            stop: Arc::new(AtomicBool::new(false)),
+            move_overhead: DEFAULT_MOVE_OVERHEAD,
+            board: board.clone(),
+            start: Instant::now(),
+            nodes: 0,
+            node_limit: None,
+            time_manager: None,
+            aborted: false,
+            keys: vec![],
+            pv: vec![vec![]; MAX_PLY + 1],
+        };
+    }
+
+    // Searches the position until one of the limits is reached or the search is stopped
+    // report is called after every completed iteration
+    pub fn search(&mut self, limits: &Search_Limits, report: &mut dyn FnMut(&Search_Info)) -> Search_Info
+    {
+        self.start = Instant::now();
+        self.nodes = 0;
+        self.aborted = false;
+        self.node_limit = limits.nodes;
+        self.time_manager = limits.time.map(|controls| Time_Manager::new(&controls, self.move_overhead));
+        self.keys = Self::history_keys(&self.board);
+
+        let mut root_moves = self.board.legal_moves();
+        let mut result = Search_Info::default();
+
+        if root_moves.is_empty()
+        {
+            result.score = if self.board.in_check(self.board.details.side_to_move) { -MATE } else { 0 };
+            return result;
+        }
+
+        if root_moves.len() == 1
+        {
+            if let Some(time_manager) = self.time_manager.as_mut()
+            {
+                time_manager.set_forced();
+            }
+        }
+
+        root_moves.sort_by_key(|candidate| std::cmp::Reverse(move_order(candidate)));
+
+        let max_depth = limits.depth.unwrap_or(MAX_PLY as u32 - 1).clamp(1, MAX_PLY as u32 - 1);
+
+        for depth in 1..=max_depth
+        {
+            let mut score = self.search_root(&mut root_moves, depth);
+
+            // A partial iteration is only used if nothing has been found yet
+            if self.aborted && depth > 1
+            {
+                break;
+            }
+
+            // Stopped before even the first move was searched, there is no score yet
+            if self.pv[0].is_empty()
+            {
+                self.pv[0].push(root_moves[0]);
+                score = 0;
+            }
+
+            let previous_best = result.best_move();
+            let pv = self.pv[0].clone();
+
+            result = Search_Info
+            {
+                depth,
+                score,
+                nodes: self.nodes,
+                time: self.start.elapsed().as_millis() as u64,
+                tb_hits: 0,
+                pv: pv.clone(),
+                lines: vec![Search_Line { score, pv }],
+            };
+
+            report(&result);
+
+            if self.aborted
+            {
+                break;
+            }
+
+            if let Some(time_manager) = self.time_manager.as_mut()
+            {
+                time_manager.update(depth, previous_best != result.best_move(), score);
+
+                if time_manager.should_stop()
+                {
+                    break;
+                }
+            }
+
+            // No point in searching deeper once a mate has been found
+            if    is_mate_score(score)
+               && MATE - score.abs() <= depth as i32
+            {
+                break;
+            }
+        }
+
+        return result;
+    }
+
+    // The keys of the positions that can still be repeated, ending with the current position
+    fn history_keys(board: &Fairy_Board) -> Vec<u64>
+    {
+        let mut previous = board.clone();
+        let mut keys = vec![previous.position_key()];
+        let plies = std::cmp::min(board.details.halfmove_clock as usize, board.moves.len());
+
+        for _ in 0..plies
+        {
+            previous.unmake_move();
+            keys.push(previous.position_key());
+        }
+
+        keys.reverse();
+
+        return keys;
+    }
+
+    // Checked at every node, the clock is only looked at every 1024 nodes
+    fn should_abort(&mut self) -> bool
+    {
+        if self.aborted
+        {
+            return true;
+        }
+
+        if self.stop.load(Ordering::Relaxed)
+        {
+            self.aborted = true;
+        }
+
+        if self.nodes % 1024 == 0
+        {
+            if    self.node_limit.is_some_and(|limit| self.nodes >= limit)
+               || self.time_manager.as_ref().is_some_and(|time_manager| time_manager.should_abort())
+            {
+                self.aborted = true;
+            }
+        }
+
+        return self.aborted;
+    }
+
+    // Searches every root move, the best one is moved to the front for the next iteration
+    fn search_root(&mut self, root_moves: &mut [Move], depth: u32) -> i32
+    {
+        let mut alpha = -INFINITY;
+        let mut best_index = 0;
+
+        self.pv[0].clear();
+
+        for m_idx in 0..root_moves.len()
+        {
+            self.board.make_move(root_moves[m_idx]);
+            let score = -self.alpha_beta(depth - 1, 1, -INFINITY, -alpha);
+            self.board.unmake_move();
+
+            if self.aborted
+            {
+                break;
+            }
+
+            if score > alpha
+            {
+                alpha = score;
+                best_index = m_idx;
+                self.update_pv(0, root_moves[m_idx]);
+            }
+        }
+
+        root_moves[..=best_index].rotate_right(1);
+
+        return alpha;
+    }
+
+    fn update_pv(&mut self, ply: usize, best_move: Move)
+    {
+        let (current, rest) = self.pv.split_at_mut(ply + 1);
+
+        current[ply].clear();
+        current[ply].push(best_move);
+        current[ply].extend_from_slice(&rest[0]);
+    }
+
+    // The moves for the side to move sorted by move_order, only captures and promotions when
+    // quiet is false. Moves that leave the king in check are filtered while searching them
+    fn ordered_moves(&self, quiet: bool) -> Vec<Move>
+    {
+        let mut moves: Vec<Move> = self.board
+                                       .pseudo_legal_moves()
+                                       .into_iter()
+                                       .filter(|candidate| quiet || candidate.capture != 0 || candidate.promotion != 0)
+                                       .collect();
+
+        moves.sort_by_key(|candidate| std::cmp::Reverse(move_order(candidate)));
+
+        return moves;
+    }
+
+    fn alpha_beta(&mut self, depth: u32, ply: usize, mut alpha: i32, beta: i32) -> i32
+    {
+        self.nodes += 1;
+        self.pv[ply].clear();
+
+        if self.should_abort()
+        {
+            return 0;
+        }
+
+        let key = self.board.position_key();
+
+        if    self.board.details.halfmove_clock >= 100
+           || is_repetition(&self.keys, self.board.details.halfmove_clock, key)
+        {
+            return 0;
+        }
+
+        if depth == 0 || ply >= MAX_PLY - 1
+        {
+            return self.quiescence(ply, alpha, beta);
+        }
+
+        let color = self.board.details.side_to_move;
+        let mut any_legal = false;
+
+        self.keys.push(key);
+
+        for candidate in self.ordered_moves(true)
+        {
+            self.board.make_move(candidate);
+
+            if self.board.in_check(color)
+            {
+                self.board.unmake_move();
+                continue;
+            }
+
+            any_legal = true;
+
+            let score = -self.alpha_beta(depth - 1, ply + 1, -beta, -alpha);
+            self.board.unmake_move();
+
+            if self.aborted
+            {
+                self.keys.pop();
+                return 0;
+            }
+
+            if score > alpha
+            {
+                alpha = score;
+                self.update_pv(ply, candidate);
+
+                if alpha >= beta
+                {
+                    break;
+                }
+            }
+        }
+
+        self.keys.pop();
+
+        if !any_legal
+        {
+            return if self.board.in_check(color) { -MATE + ply as i32 } else { 0 };
+        }
+
+        return alpha;
+    }
+
+    // Only captures and promotions are searched, the side to move can always stand pat
+    fn quiescence(&mut self, ply: usize, mut alpha: i32, beta: i32) -> i32
+    {
+        self.nodes += 1;
+        self.pv[ply].clear();
+
+        if self.should_abort()
+        {
+            return 0;
+        }
+
+        let stand_pat = evaluate_fairy(&self.board);
+
+        if stand_pat >= beta || ply >= MAX_PLY - 1
+        {
+            return stand_pat;
+        }
+
+        alpha = std::cmp::max(alpha, stand_pat);
+
+        let color = self.board.details.side_to_move;
+
+        for candidate in self.ordered_moves(false)
+        {
+            self.board.make_move(candidate);
+
+            if self.board.in_check(color)
+            {
+                self.board.unmake_move();
+                continue;
+            }
+
+            let score = -self.quiescence(ply + 1, -beta, -alpha);
+            self.board.unmake_move();
+
+            if self.aborted
+            {
+                return 0;
+            }
+
+            if score > alpha
+            {
+                alpha = score;
+                self.update_pv(ply, candidate);
+
+                if alpha >= beta
+                {
+                    break;
+                }
+            }
+        }
+
+        return alpha;
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn start_positions_are_even()
+    {
+        for fairy in FAIRY_VARIANTS
+        {
+            assert_eq!(evaluate_fairy(&Fairy_Board::from_fen(fairy.start_fen).unwrap()), 0, "{}", fairy.name);
+        }
+    }
+
+    #[test]
+    fn finds_mate_in_one()
+    {
+        // The chancellor mates on a8 as a rook, the white king covers the squares in front of the black one
+        let board = Fairy_Board::from_fen("5k4/10/5K4/10/10/10/10/C9 w - - 0 1").unwrap();
+        let mut search = Fairy_Search::new(&board);
+        let info = search.search(&Search_Limits { depth: Some(3), ..Default::default() }, &mut |_| {});
+
+        assert_eq!(board.move_to_uci(info.best_move().unwrap()), "a1a8");
+        assert_eq!(info.score, MATE - 1);
+    }
+
+    #[test]
+    fn takes_the_hanging_archbishop()
+    {
+        let board = Fairy_Board::from_fen("4k5/10/10/4a5/10/10/4R5/4K5 w - - 0 1").unwrap();
+        let mut search = Fairy_Search::new(&board);
+        let info = search.search(&Search_Limits { depth: Some(2), ..Default::default() }, &mut |_| {});
+
+        assert_eq!(board.move_to_uci(info.best_move().unwrap()), "e2e5");
+    }
+}
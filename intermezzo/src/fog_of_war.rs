@@ -0,0 +1,92 @@
+// Fog of War (Dark chess)
+//
+// The players only see their own pieces and the squares those pieces can move to, Referee
+// (referee.rs) keeps the whole board and gives every player their view
+//
+// As nobody knows whether their king is attacked there is no check: the king can move onto an
+// attacked square, stay attacked and castle out of or through an attack. The game is won by
+// capturing the enemy king
+
+use crate::chess::*;
+use crate::variant::*;
+
+pub struct Fog_Of_War;
+
+impl Rules for Fog_Of_War
+{
+    fn name(&self) -> &'static str
+    {
+        return "Fog of War";
+    }
+
+    fn aliases(&self) -> &'static [&'static str]
+    {
+        return &["fow", "Dark chess"];
+    }
+
+    // Every move is legal, leaving the king attacked just risks losing it
+    fn legal_moves(&self, board: &mut Chess_Board) -> Vec<Move>
+    {
+        return board.pseudo_legal_moves(board.details.side_to_move);
+    }
+
+    fn in_check(&self, _board: &Chess_Board, _color: i8) -> bool
+    {
+        return false;
+    }
+
+    fn variant_outcome(&self, board: &Chess_Board) -> Option<Outcome>
+    {
+        for color in [WHITE, BLACK]
+        {
+            if board.king_square(color).is_none()
+            {
+                return Some(Outcome::Variant_Win(-color));
+            }
+        }
+
+        return None;
+    }
+
+    // Even a lone king can still capture the other one
+    fn material_draws(&self) -> bool
+    {
+        return false;
+    }
+}
+
+pub static FOG_OF_WAR: Fog_Of_War = Fog_Of_War;
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::draw::Draw_Rule;
+    use crate::game::Game;
+
+    #[test]
+    fn no_material_draws()
+    {
+        for fen in ["4k3/8/8/8/8/8/8/4K3 w - - 0 1", "4k3/8/8/8/8/8/8/2B1K3 w - - 0 1"]
+        {
+            let board = Chess_Board::from_variant_fen(fen, &FOG_OF_WAR).unwrap();
+
+            assert_eq!(board.draw_rules(), vec![], "{}", fen);
+            assert!(!Game::from_board(board).is_over(), "{}", fen);
+        }
+
+        // The same positions are drawn in normal chess
+        let board = Chess_Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(board.draw_rules(), vec![Draw_Rule::Insufficient_Material]);
+    }
+
+    #[test]
+    fn capturing_the_king_wins()
+    {
+        let mut board = Chess_Board::from_variant_fen("8/8/8/8/8/8/3k4/4K3 w - - 0 1", &FOG_OF_WAR).unwrap();
+        let capture = board.legal_moves().into_iter().find(|candidate| candidate.capture == -KING).unwrap();
+
+        board.make_move(capture);
+        assert_eq!(board.outcome(), Some(Outcome::Variant_Win(WHITE)));
+    }
+}
@@ -0,0 +1,429 @@
+// A game of chess on top of a Chess_Board
+//
+// The board only knows about the position and the moves, the Game also knows whether the game
+// is still going on and how it ended. Games end on the board (checkmate, stalemate, automatic
+// draws) or because of something that happens off the board (resignation, running out of time,
+// agreeing to a draw, claiming a draw or abandoning the game)
+//
+// Once the game is over no more moves are accepted. Taking a move back (undo) continues the game
+
+use crate::chess::*;
+use crate::draw::*;
+use crate::variant::*;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Game_Result
+{
+    White_Wins,
+    Black_Wins,
+    Draw,
+    Ongoing,
+}
+
+impl Game_Result
+{
+    // The result as written in PGN files
+    pub fn to_pgn(self) -> &'static str
+    {
+        return match self
+        {
+            Game_Result::White_Wins => "1-0",
+            Game_Result::Black_Wins => "0-1",
+            Game_Result::Draw       => "1/2-1/2",
+            Game_Result::Ongoing    => "*",
+        };
+    }
+
+    pub fn from_pgn(text: &str) -> Self
+    {
+        return match text
+        {
+            "1-0"     => Game_Result::White_Wins,
+            "0-1"     => Game_Result::Black_Wins,
+            "1/2-1/2" => Game_Result::Draw,
+            _         => Game_Result::Ongoing,
+        };
+    }
+
+    // The result when the player of the given color wins
+    pub fn win_for(color: i8) -> Self
+    {
+        return if color == WHITE { Game_Result::White_Wins } else { Game_Result::Black_Wins };
+    }
+}
+
+// Why the game ended
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Termination
+{
+    Checkmate,
+    Stalemate,
+    Resignation,
+    Timeout,
+    Agreement,
+    Repetition,
+    Fifty_Move_Rule,
+    Insufficient_Material,
+    Dead_Position,
+    Abandonment,
+    // A win or draw by a rule of the variant, e.g. the king reaching the center in King of the Hill
+    Variant_End,
+}
+
+impl Termination
+{
+    // The value of the PGN "Termination" tag
+    pub fn to_pgn(self) -> &'static str
+    {
+        return match self
+        {
+            Termination::Timeout     => "time forfeit",
+            Termination::Abandonment => "abandoned",
+            _                        => "normal",
+        };
+    }
+
+    // A short description for the players, e.g. "checkmate" or "fifty move rule"
+    pub fn description(self) -> &'static str
+    {
+        return match self
+        {
+            Termination::Checkmate             => "checkmate",
+            Termination::Stalemate             => "stalemate",
+            Termination::Resignation           => "resignation",
+            Termination::Timeout               => "timeout",
+            Termination::Agreement             => "agreement",
+            Termination::Repetition            => "repetition",
+            Termination::Fifty_Move_Rule       => "fifty move rule",
+            Termination::Insufficient_Material => "insufficient material",
+            Termination::Dead_Position         => "dead position",
+            Termination::Abandonment           => "abandonment",
+            Termination::Variant_End           => "variant rules",
+        };
+    }
+
+    pub fn from_outcome(outcome: Outcome) -> Self
+    {
+        return match outcome
+        {
+            Outcome::Checkmate  => Termination::Checkmate,
+            Outcome::Stalemate  => Termination::Stalemate,
+            Outcome::Draw(rule) => Self::from_draw_rule(rule),
+            _                   => Termination::Variant_End,
+        };
+    }
+
+    fn from_draw_rule(rule: Draw_Rule) -> Self
+    {
+        return match rule
+        {
+            Draw_Rule::Threefold_Repetition | Draw_Rule::Fivefold_Repetition => Termination::Repetition,
+            Draw_Rule::Fifty_Move_Rule | Draw_Rule::Seventy_Five_Move_Rule   => Termination::Fifty_Move_Rule,
+            Draw_Rule::Insufficient_Material                                 => Termination::Insufficient_Material,
+            Draw_Rule::Dead_Position                                         => Termination::Dead_Position,
+        };
+    }
+}
+
+#[derive(Clone)]
+pub struct Game
+{
+    board:       Chess_Board,
+    start_fen:   String,
+    result:      Game_Result,
+    termination: Option<Termination>,
+
+    // Information about the game for the PGN writer, e.g. ("White", "Magnus Carlsen")
+    pub tags: Vec<(String, String)>,
+}
+
+impl Game
+{
+    // A new game from the starting position
+    pub fn new() -> Self
+    {
+        return Self::from_board(Chess_Board::new());
+    }
+
+    // A new game of a variant from its starting position
+    pub fn with_rules(rules: &'static dyn Rules) -> Self
+    {
+        return Self::from_board(Chess_Board::with_rules(rules));
+    }
+
+    pub fn from_fen(fen: &str) -> Result<Self, String>
+    {
+        return Ok(Self::from_board(Chess_Board::from_fen(fen)?));
+    }
+
+    // A game continuing from a board, the moves already played on the board are kept
+    pub fn from_board(board: Chess_Board) -> Self
+    {
+        let mut start = board.clone();
+
+        while !start.moves.is_empty()
+        {
+            start.unmake_move();
+        }
+
+        let start_fen = start.to_fen();
+
+        let mut game = Game
+        {
+            board,
+            start_fen,
+            result: Game_Result::Ongoing,
+            termination: None,
+            tags: vec![],
+        };
+
+        game.update_status();
+
+        return game;
+    }
+
+    pub fn board(&self) -> &Chess_Board
+    {
+        return &self.board;
+    }
+
+    // The position the game started from
+    pub fn start_fen(&self) -> &str
+    {
+        return &self.start_fen;
+    }
+
+    // Whether the game started from the starting position of its variant
+    pub fn is_standard_start(&self) -> bool
+    {
+        return self.start_fen == self.board.rules.start_fen();
+    }
+
+    pub fn rules(&self) -> &'static dyn Rules
+    {
+        return self.board.rules;
+    }
+
+    pub fn result(&self) -> Game_Result
+    {
+        return self.result;
+    }
+
+    pub fn termination(&self) -> Option<Termination>
+    {
+        return self.termination;
+    }
+
+    pub fn is_over(&self) -> bool
+    {
+        return self.result != Game_Result::Ongoing;
+    }
+
+    pub fn side_to_move(&self) -> i8
+    {
+        return self.board.details.side_to_move;
+    }
+
+    // The legal moves, none once the game is over
+    pub fn legal_moves(&mut self) -> Vec<Move>
+    {
+        if self.is_over()
+        {
+            return vec![];
+        }
+
+        return self.board.legal_moves();
+    }
+
+    // Plays a move, fails if the move is not legal or the game is already over
+    pub fn play(&mut self, new_move: Move) -> Result<(), String>
+    {
+        if self.is_over()
+        {
+            return Err(format!("the game is over ({})", self.result.to_pgn()));
+        }
+
+        if !self.board.legal_moves().contains(&new_move)
+        {
+            return Err(format!("{} is not a legal move", new_move.to_uci()));
+        }
+
+        self.board.make_move(new_move);
+        self.update_status();
+
+        return Ok(());
+    }
+
+    // Plays a move written in SAN ("Nf3") or in the coordinate notation of UCI ("g1f3")
+    pub fn play_text(&mut self, text: &str) -> Result<Move, String>
+    {
+        if self.is_over()
+        {
+            return Err(format!("the game is over ({})", self.result.to_pgn()));
+        }
+
+        let parsed = match self.board.parse_san(text)
+        {
+            Some(parsed) => Some(parsed),
+            None         => self.board.parse_uci_move(text),
+        };
+
+        let Some(parsed) = parsed else { return Err(format!("{} is not a legal move", text)) };
+
+        self.play(parsed)?;
+
+        return Ok(parsed);
+    }
+
+    // Takes back the last move, the game continues even if it was over
+    pub fn undo(&mut self) -> Option<Move>
+    {
+        let last_move = *self.board.moves.last()?;
+
+        self.board.unmake_move();
+        self.result = Game_Result::Ongoing;
+        self.termination = None;
+        self.update_status();
+
+        return Some(last_move);
+    }
+
+    // The moves of the game in SAN
+    pub fn san_moves(&self) -> Vec<String>
+    {
+        let mut replay = self.board.clone();
+        let mut moves = vec![];
+
+        while let Some(&last_move) = replay.moves.last()
+        {
+            replay.unmake_move();
+            moves.push(replay.move_to_san(last_move));
+        }
+
+        moves.reverse();
+
+        return moves;
+    }
+
+    // Ends the game for a reason that is not on the board, used by the functions below and when
+    // reading finished games from PGN
+    pub fn finish(&mut self, result: Game_Result, termination: Termination) -> Result<(), String>
+    {
+        if self.is_over()
+        {
+            return Err(format!("the game is over ({})", self.result.to_pgn()));
+        }
+
+        self.result = result;
+        self.termination = Some(termination);
+
+        return Ok(());
+    }
+
+    // Ends the game after a move if the position is checkmate, stalemate, the end of the game
+    // according to the variant or an automatic draw
+    fn update_status(&mut self)
+    {
+        let color = self.board.details.side_to_move;
+
+        if let Some(outcome) = self.board.outcome()
+        {
+            self.result = match outcome.winner(color)
+            {
+                0      => Game_Result::Draw,
+                winner => Game_Result::win_for(winner),
+            };
+
+            self.termination = Some(Termination::from_outcome(outcome));
+
+            return;
+        }
+
+        if let Some(rule) = self.board.automatic_draw()
+        {
+            self.result = Game_Result::Draw;
+            self.termination = Some(Termination::from_draw_rule(rule));
+        }
+    }
+
+    pub fn resign(&mut self, color: i8) -> Result<(), String>
+    {
+        return self.finish(Game_Result::win_for(-color), Termination::Resignation);
+    }
+
+    pub fn agree_draw(&mut self) -> Result<(), String>
+    {
+        return self.finish(Game_Result::Draw, Termination::Agreement);
+    }
+
+    // The player of the given color ran out of time. The game is still a draw if the opponent
+    // could never checkmate, e.g. with only a king left
+    pub fn timeout(&mut self, color: i8) -> Result<(), String>
+    {
+        if self.can_never_mate(-color)
+        {
+            return self.finish(Game_Result::Draw, Termination::Timeout);
+        }
+
+        return self.finish(Game_Result::win_for(-color), Termination::Timeout);
+    }
+
+    // Claims a draw by threefold repetition or the 50 move rule for the side to move
+    pub fn claim_draw(&mut self) -> Result<Draw_Rule, String>
+    {
+        let Some(rule) = self.board.claimable_draw() else { return Err(String::from("there is no draw to claim")) };
+
+        self.finish(Game_Result::Draw, Termination::from_draw_rule(rule))?;
+
+        return Ok(rule);
+    }
+
+    // A lone king can never checkmate, neither can a king with a single minor piece against a
+    // lone king. The duck (stored as a white piece) belongs to neither side
+    fn can_never_mate(&self, color: i8) -> bool
+    {
+        let own: Vec<i8> = self.board.board.iter()
+                                           .filter(|&&piece| piece.signum() == color && piece != DUCK && piece.abs() != KING)
+                                           .map(|&piece| piece.abs())
+                                           .collect();
+
+        let opponent_alone = self.board.board.iter().all(|&piece| piece.signum() != -color || piece == DUCK || piece.abs() == KING);
+
+        return    own.is_empty()
+               || (   opponent_alone
+                   && own.len() == 1
+                   && (own[0] == KNIGHT || own[0] == BISHOP));
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::duck::DUCK_CHESS;
+
+    fn timeout_result(fen: &str, rules: &'static dyn Rules, color: i8) -> Game_Result
+    {
+        let mut game = Game::from_board(Chess_Board::from_variant_fen(fen, rules).unwrap());
+        game.timeout(color).unwrap();
+
+        return game.result();
+    }
+
+    #[test]
+    fn timeout_against_a_side_that_can_never_mate()
+    {
+        assert_eq!(timeout_result("4k3/7p/8/8/8/8/8/4K3 w - - 0 1", &STANDARD, BLACK), Game_Result::Draw);
+        assert_eq!(timeout_result("4k3/7p/8/8/8/8/8/2N1K3 w - - 0 1", &STANDARD, BLACK), Game_Result::White_Wins);
+        assert_eq!(timeout_result("4k3/8/8/8/8/8/8/2R1K3 w - - 0 1", &STANDARD, BLACK), Game_Result::White_Wins);
+    }
+
+    #[test]
+    fn the_duck_belongs_to_neither_side()
+    {
+        // Only a king or a king and a knight against a lone king, the duck does not count for either side
+        assert_eq!(timeout_result("4k3/8/8/3*4/8/8/8/4K3 w - - 0 1", &DUCK_CHESS, BLACK), Game_Result::Draw);
+        assert_eq!(timeout_result("4k3/8/8/3*4/8/8/8/2N1K3 w - - 0 1", &DUCK_CHESS, BLACK), Game_Result::Draw);
+        assert_eq!(timeout_result("2n1k3/8/8/3*4/8/8/8/4K3 w - - 0 1", &DUCK_CHESS, WHITE), Game_Result::Draw);
+    }
+}
@@ -0,0 +1,108 @@
+// The shape of a board that is not necessarily 8x8
+//
+// Chess_Board is fixed to 64 squares, Fairy_Board (fairy.rs) uses a Board_Geometry so that it can
+// be played on wider boards such as the 10x8 board of Capablanca chess. Squares are numbered the
+// same way as on Chess_Board: a1 is 0, then along the first rank and up the board
+//
+// 10x8:
+// 70 71 72 73 74 75 76 77 78 79   <- rank 8
+// ...
+// 0  1  2  3  4  5  6  7  8  9    <- rank 1
+//
+// Instead of the edge_counts of Chess_Board, moves are made one step at a time with offset, which
+// returns None when the step would leave the board
+
+// Files are named with a single letter and ranks with a single digit
+pub const MAX_FILES: usize = 16;
+pub const MAX_RANKS: usize = 9;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Board_Geometry
+{
+    pub files: usize,
+    pub ranks: usize,
+}
+
+impl Board_Geometry
+{
+    pub fn new(files: usize, ranks: usize) -> Option<Self>
+    {
+        if    !(1..=MAX_FILES).contains(&files)
+           || !(2..=MAX_RANKS).contains(&ranks)
+        {
+            return None;
+        }
+
+        return Some(Board_Geometry { files, ranks });
+    }
+
+    pub fn size(&self) -> usize
+    {
+        return self.files * self.ranks;
+    }
+
+    pub fn square(&self, file: usize, rank: usize) -> usize
+    {
+        return rank * self.files + file;
+    }
+
+    pub fn get_file(&self, square: usize) -> usize
+    {
+        return square % self.files;
+    }
+
+    pub fn get_rank(&self, square: usize) -> usize
+    {
+        return square / self.files;
+    }
+
+    // The first rank for white, the last one for black
+    pub fn back_rank(&self, color: i8) -> usize
+    {
+        return if color > 0 { 0 } else { self.ranks - 1 };
+    }
+
+    // The square a number of files and ranks away, None if that is off the board
+    pub fn offset(&self, square: usize, file_step: i32, rank_step: i32) -> Option<usize>
+    {
+        let file = self.get_file(square) as i32 + file_step;
+        let rank = self.get_rank(square) as i32 + rank_step;
+
+        if    file < 0 || file >= self.files as i32
+           || rank < 0 || rank >= self.ranks as i32
+        {
+            return None;
+        }
+
+        return Some(self.square(file as usize, rank as usize));
+    }
+
+    // 0 -> "a1", on a 10x8 board 79 -> "j8"
+    pub fn square_name(&self, square: usize) -> String
+    {
+        let file = (b'a' + self.get_file(square) as u8) as char;
+        let rank = (b'1' + self.get_rank(square) as u8) as char;
+
+        return format!("{}{}", file, rank);
+    }
+
+    pub fn parse_square(&self, name: &str) -> Option<usize>
+    {
+        let bytes = name.as_bytes();
+
+        if bytes.len() != 2
+        {
+            return None;
+        }
+
+        let file = bytes[0].wrapping_sub(b'a') as usize;
+        let rank = bytes[1].wrapping_sub(b'1') as usize;
+
+        if file >= self.files || rank >= self.ranks
+        {
+            return None;
+        }
+
+        return Some(self.square(file, rank));
+    }
+}
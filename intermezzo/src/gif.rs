@@ -0,0 +1,211 @@
+// Writing animated GIF files
+//
+// A GIF has one palette of at most 256 colors for every frame, each pixel is an index into it:
+// header          -> "GIF89a", the size of the image and the palette
+// NETSCAPE2.0     -> an application extension that makes the animation repeat forever
+// for every frame -> a graphic control extension with the delay (in 1/100 s), the position and
+//                    size of the frame and its pixel indices compressed with LZW
+// trailer         -> the end of the file
+//
+// The palette is made from the colors that are used most in all frames together, the other colors
+// (mostly the smoothed edges of the pieces) get the nearest color of the palette. Board images only
+// use a few colors over large areas so this looks almost the same as the PNG
+
+use crate::png::Animation_Frame;
+
+use std::collections::HashMap;
+
+const PALETTE_SIZE: usize = 256;
+// LZW codes start at 9 bits (the 256 colors, the clear code and the end code) and grow to 12
+const MIN_CODE_SIZE: u32 = 8;
+const MAX_CODES: u16 = 4096;
+const CLEAR_CODE: u16 = 256;
+const END_CODE: u16 = 257;
+
+// The most used colors of all frames, padded to 256 colors
+fn build_palette(frames: &[Animation_Frame]) -> Vec<[u8; 3]>
+{
+    let mut counts: HashMap<[u8; 3], usize> = HashMap::new();
+
+    for frame in frames
+    {
+        for pixel in frame.pixels.chunks(3)
+        {
+            *counts.entry([pixel[0], pixel[1], pixel[2]]).or_insert(0) += 1;
+        }
+    }
+
+    let mut colors: Vec<([u8; 3], usize)> = counts.into_iter().collect();
+
+    // The color itself breaks ties so the palette is the same every time
+    colors.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    let mut palette: Vec<[u8; 3]> = colors.into_iter().take(PALETTE_SIZE).map(|(color, _)| color).collect();
+    palette.resize(PALETTE_SIZE, [0, 0, 0]);
+
+    return palette;
+}
+
+fn nearest_color(palette: &[[u8; 3]], color: [u8; 3]) -> u8
+{
+    let distance = |entry: &[u8; 3]| (0..3).map(|channel| (entry[channel] as i32 - color[channel] as i32).pow(2)).sum::<i32>();
+
+    return (0..palette.len()).min_by_key(|&index| distance(&palette[index])).unwrap() as u8;
+}
+
+// Writes the codes of the LZW stream, which are packed starting from the lowest bit of every byte
+struct Code_Writer
+{
+    bytes:      Vec<u8>,
+    bit_buffer: u32,
+    bit_count:  u32,
+}
+
+impl Code_Writer
+{
+    fn write(&mut self, code: u16, size: u32)
+    {
+        self.bit_buffer |= (code as u32) << self.bit_count;
+        self.bit_count += size;
+
+        while self.bit_count >= 8
+        {
+            self.bytes.push(self.bit_buffer as u8);
+            self.bit_buffer >>= 8;
+            self.bit_count -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8>
+    {
+        if self.bit_count > 0
+        {
+            self.bytes.push(self.bit_buffer as u8);
+        }
+
+        return self.bytes;
+    }
+}
+
+// Compresses the palette indices of a frame with the LZW variant of GIF
+pub fn lzw_compress(indices: &[u8]) -> Vec<u8>
+{
+    let mut writer = Code_Writer { bytes: vec![], bit_buffer: 0, bit_count: 0 };
+
+    // (code of the prefix, next index) -> code of the longer string
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next_code = END_CODE + 1;
+    let mut code_size = MIN_CODE_SIZE + 1;
+
+    writer.write(CLEAR_CODE, code_size);
+
+    let Some((&first, rest)) = indices.split_first() else
+    {
+        writer.write(END_CODE, code_size);
+        return writer.finish();
+    };
+
+    let mut prefix = first as u16;
+
+    for &index in rest
+    {
+        if let Some(&code) = table.get(&(prefix, index))
+        {
+            prefix = code;
+            continue;
+        }
+
+        writer.write(prefix, code_size);
+
+        if next_code < MAX_CODES
+        {
+            table.insert((prefix, index), next_code);
+            next_code += 1;
+
+            // The decoder adds its codes one step behind, so the size grows once the code after
+            // the largest one that fits has been added
+            if next_code > 1 << code_size && code_size < 12
+            {
+                code_size += 1;
+            }
+        } else
+        {
+            // The table is full, start over
+            writer.write(CLEAR_CODE, code_size);
+
+            table.clear();
+            next_code = END_CODE + 1;
+            code_size = MIN_CODE_SIZE + 1;
+        }
+
+        prefix = index as u16;
+    }
+
+    writer.write(prefix, code_size);
+    writer.write(END_CODE, code_size);
+
+    return writer.finish();
+}
+
+// An animated GIF that repeats forever, the first frame has to cover the whole image
+pub fn encode_gif(width: u32, height: u32, frames: &[Animation_Frame]) -> Vec<u8>
+{
+    let palette = build_palette(frames);
+    let mut nearest: HashMap<[u8; 3], u8> = HashMap::new();
+
+    let mut output = b"GIF89a".to_vec();
+
+    output.extend((width as u16).to_le_bytes());
+    output.extend((height as u16).to_le_bytes());
+    // A global palette of 256 colors with 8 bits per channel, background color 0 and square pixels
+    output.extend([0xf7, 0, 0]);
+
+    for color in &palette
+    {
+        output.extend(color);
+    }
+
+    output.extend([0x21, 0xff, 11]);
+    output.extend(b"NETSCAPE2.0");
+    // Repeat forever
+    output.extend([3, 1, 0, 0, 0]);
+
+    for frame in frames
+    {
+        let indices: Vec<u8> = frame.pixels
+                                    .chunks(3)
+                                    .map(|pixel| [pixel[0], pixel[1], pixel[2]])
+                                    .map(|color| *nearest.entry(color).or_insert_with(|| nearest_color(&palette, color)))
+                                    .collect();
+
+        // Graphic control extension: keep the frame when the next one is drawn, no transparency
+        output.extend([0x21, 0xf9, 4, 0x04]);
+        output.extend(((frame.delay / 10).min(u16::MAX as u32) as u16).to_le_bytes());
+        output.extend([0, 0]);
+
+        // Image descriptor, the frame uses the global palette
+        output.push(0x2c);
+
+        for value in [frame.x, frame.y, frame.width, frame.height]
+        {
+            output.extend((value as u16).to_le_bytes());
+        }
+
+        output.push(0);
+
+        output.push(MIN_CODE_SIZE as u8);
+
+        // The compressed data is split into blocks of at most 255 bytes, ending with an empty one
+        for block in lzw_compress(&indices).chunks(255)
+        {
+            output.push(block.len() as u8);
+            output.extend(block);
+        }
+
+        output.push(0);
+    }
+
+    output.push(0x3b);
+
+    return output;
+}
@@ -0,0 +1,116 @@
+// Odds games, where the stronger player starts without some of their pieces
+//
+// pawn and move -> the f pawn is removed and the other player moves first
+// pawn          -> the f pawn
+// knight        -> the queen's knight (b1 / b8)
+// rook          -> the queen's rook (a1 / a8), which also takes away long castling
+// queen         -> the queen
+//
+// Any other piece can be removed by naming its square, "h1" removes the king's rook. Odds can be
+// combined: rook and knight odds are written "rook,knight"
+//
+// The positions are normal chess positions, a removed castling rook is marked as having moved so
+// that the castling rights in the FEN are correct
+
+use crate::chess::*;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Odds
+{
+    Pawn_And_Move,
+    Pawn,
+    Knight,
+    Rook,
+    Queen,
+    // Any piece other than the king, the square is on the board as it is (not mirrored for black)
+    Square(usize),
+}
+
+impl Odds
+{
+    // Parses a single odds name or a square name, ignoring case, spaces and dashes
+    pub fn parse(text: &str) -> Option<Odds>
+    {
+        let name: String = text.chars()
+                               .filter(|letter| letter.is_ascii_alphanumeric())
+                               .map(|letter| letter.to_ascii_lowercase())
+                               .collect();
+
+        return match name.as_str()
+        {
+            "pawnandmove" | "pm" => Some(Odds::Pawn_And_Move),
+            "pawn"   | "p"       => Some(Odds::Pawn),
+            "knight" | "n"       => Some(Odds::Knight),
+            "rook"   | "r"       => Some(Odds::Rook),
+            "queen"  | "q"       => Some(Odds::Queen),
+            _                    => Chess_Board::parse_square(&name).map(Odds::Square),
+        };
+    }
+
+    // Parses a comma separated list of odds, "rook,knight"
+    pub fn parse_list(text: &str) -> Result<Vec<Odds>, String>
+    {
+        return text.split(',')
+                   .map(|part| Odds::parse(part).ok_or(format!("unknown odds '{}'", part.trim())))
+                   .collect();
+    }
+
+    // The square the removed piece stands on for the given color in the starting position
+    fn square(&self, giver: i8) -> usize
+    {
+        let square = match self
+        {
+            Odds::Pawn_And_Move | Odds::Pawn => 13,
+            Odds::Knight                     => 1,
+            Odds::Rook                       => 0,
+            Odds::Queen                      => 3,
+            Odds::Square(square)             => return *square,
+        };
+
+        // The same square from the side of black
+        return if giver == WHITE { square } else { square ^ 56 };
+    }
+}
+
+impl Chess_Board
+{
+    // The starting position with the pieces of the odds removed from the side of the giver
+    pub fn with_odds(giver: i8, odds: &[Odds]) -> Result<Self, String>
+    {
+        let mut new_board = Chess_Board::new();
+
+        for &removed in odds
+        {
+            let square = removed.square(giver);
+            let piece = new_board.board[square];
+
+            if piece.signum() != giver
+            {
+                return Err(format!("there is no piece of the giver on {}", Self::square_name(square)));
+            }
+
+            if piece.abs() == KING
+            {
+                return Err(String::from("the king can not be given as odds"));
+            }
+
+            new_board.board[square] = 0;
+
+            for r_idx in 0..4
+            {
+                if new_board.castling_rooks[r_idx] == square
+                {
+                    new_board.details.rooks_have_moved[r_idx] = true;
+                }
+            }
+
+            // The player receiving the odds makes the first move
+            if removed == Odds::Pawn_And_Move
+            {
+                new_board.details.side_to_move = -giver;
+            }
+        }
+
+        return Ok(new_board);
+    }
+}
@@ -0,0 +1,80 @@
+// Horde
+//
+// White has no king and 36 pawns against the normal black army:
+// rnbqkbnr/pppppppp/8/1PP2PP1/PPPPPPPP/PPPPPPPP/PPPPPPPP/PPPPPPPP w kq - 0 1
+//
+// Black wins by capturing every white piece, white wins by checkmating the black king. The white
+// pawns on the first rank can also move forward two squares, but they can not be taken en passant
+// after doing so. White can not be in check, a white side that can not move is stalemated
+//
+// There are no draws because of material, black always has enough to take the horde
+
+use crate::chess::*;
+use crate::variant::*;
+
+pub struct Horde;
+
+impl Rules for Horde
+{
+    fn name(&self) -> &'static str
+    {
+        return "Horde";
+    }
+
+    fn start_fen(&self) -> &'static str
+    {
+        return "rnbqkbnr/pppppppp/8/1PP2PP1/PPPPPPPP/PPPPPPPP/PPPPPPPP/PPPPPPPP w kq - 0 1";
+    }
+
+    // The normal moves and the double pushes from the first rank
+    fn pseudo_legal_moves(&self, board: &Chess_Board, color: i8) -> Vec<Move>
+    {
+        let mut results = board.standard_pseudo_legal_moves(color);
+
+        if color != WHITE
+        {
+            return results;
+        }
+
+        for square in 0..8
+        {
+            if    board.board[square] == PAWN
+               && board.board[square + 8] == 0
+               && board.board[square + 16] == 0
+            {
+                results.push(board.decode_move(square, square + 16));
+            }
+        }
+
+        return results;
+    }
+
+    fn make_move(&self, board: &mut Chess_Board, new_move: Move)
+    {
+        board.standard_make_move(new_move);
+
+        // No en passant after a double push from the first rank
+        if    new_move.kind == Move_Kind::Double_Push
+           && Chess_Board::get_row(new_move.origin) == 0
+        {
+            board.details.en_passant = None;
+        }
+    }
+
+    fn variant_outcome(&self, board: &Chess_Board) -> Option<Outcome>
+    {
+        if board.board.iter().all(|&piece| piece <= 0)
+        {
+            return Some(Outcome::Variant_Win(BLACK));
+        }
+
+        return None;
+    }
+
+    fn material_draws(&self) -> bool
+    {
+        return false;
+    }
+}
+
+pub static HORDE: Horde = Horde;
@@ -0,0 +1,48 @@
+// King of the Hill
+//
+// Normal chess, but a player also wins by bringing their king to one of the four center squares
+// (d4, e4, d5, e5). The king can not move into check on the way, so reaching the center is only
+// possible with a legal move. Checkmate and stalemate work as usual
+//
+// A king alone can always walk to the center, so there are no draws because of material
+
+use crate::chess::*;
+use crate::variant::*;
+
+// d4, e4, d5, e5
+pub const CENTER_SQUARES: [usize; 4] = [27, 28, 35, 36];
+
+pub struct King_Of_The_Hill;
+
+impl Rules for King_Of_The_Hill
+{
+    fn name(&self) -> &'static str
+    {
+        return "King of the Hill";
+    }
+
+    fn aliases(&self) -> &'static [&'static str]
+    {
+        return &["koth"];
+    }
+
+    fn variant_outcome(&self, board: &Chess_Board) -> Option<Outcome>
+    {
+        for square in CENTER_SQUARES
+        {
+            if board.board[square].abs() == KING
+            {
+                return Some(Outcome::Variant_Win(board.board[square].signum()));
+            }
+        }
+
+        return None;
+    }
+
+    fn material_draws(&self) -> bool
+    {
+        return false;
+    }
+}
+
+pub static KING_OF_THE_HILL: King_Of_The_Hill = King_Of_The_Hill;
@@ -1,59 +1,589 @@
+// Configure Rust tooling settings for excluding certain warnings and errors
+// The clippy lints below conflict with the code style used throughout the project
+// (explicit returns, index based loops and aligned if/else blocks)
+
+#![allow(non_camel_case_types)]
+#![allow(clippy::needless_return)]
+#![allow(clippy::needless_range_loop)]
+#![allow(clippy::suspicious_else_formatting)]
+#![allow(clippy::bool_comparison)]
+#![allow(clippy::collapsible_if)]
+#![allow(clippy::collapsible_else_if)]
+#![allow(clippy::len_zero)]
+#![allow(clippy::print_literal)]
+#![allow(clippy::manual_is_multiple_of)]
+
+mod animation;
+mod antichess;
+mod atomic;
+mod board_image;
+mod book_builder;
 mod chess;
+mod chess960;
+mod cli;
+mod crazyhouse;
+mod diagram;
+mod draw;
+mod drawing;
+mod duck;
+mod evaluation;
+mod fairy;
+mod fairy_search;
+mod fog_of_war;
+mod game;
+mod geometry;
+mod gif;
+mod handicap;
+mod horde;
+mod king_of_the_hill;
+mod notation;
+mod perft;
+mod pgn;
+mod png;
+mod polyglot;
+mod racing_kings;
+mod raster;
+mod referee;
+mod render;
+mod search;
+mod syzygy;
+mod tablebase;
+mod three_check;
+mod thread_pool;
+mod time_manager;
+mod transposition;
+mod tui;
+mod uci;
+mod variant;
 
-// Testing the move generation functions and Chess_Board struct provided by the chess module
+// Runs the command given on the command line (uci, tui, perft, ...), without one a game is played
+// on the terminal, see cli.rs
 fn main()
 {
-    // Creates a Chess_Board object with default values
-    let mut sample_board = chess::Chess_Board { ..Default::default() };
-    sample_board.generate_distance();
-    // Configures the board with custom values
-    sample_board.configure_board(
-        [
-            4,  0,  3,  5,  6,  3,  2,  4,
-            0,  0,  1,  0,  0,  1,  1,  1,
-            0,  0,  0,  0,  1,  1, -1,  0,
-            0, -1,  0,  0, -2,  0,  0,  0,
-            0,  0,  0,  0,  0,  0,  0,  0,
-            0,  0,  0,  0,  0,  0,  0,  0,
-           -1, -1, -1, -1, -1,  1, -1,  1,
-           -4, -2, -3, -5, -6,  0, -2,  0,
-        ]
-    );
-    sample_board.print_board();
-
-    // The following lines of code tests the move generation functions for various pieces
-    // and prints the results
-
-    // Bishops, rooks, queens, knights, and pawns at various locations on the configued board
-    // are all tested
-    
-    let mut sample_results = sample_board.bishop_moves(sample_board.board[2], 2);
-    println!("{:?}", sample_results);
-
-    sample_results = sample_board.rook_moves(sample_board.board[0], 0);
-    println!("{:?}", sample_results);
-
-    sample_results = sample_board.queen_moves(sample_board.board[3], 3);
-    println!("{:?}", sample_results);
-
-    sample_results = sample_board.knight_moves(sample_board.board[28], 28);
-    println!("{:?}", sample_results);
-
-    sample_results = sample_board.knight_moves(sample_board.board[6], 6);
-    println!("{:?}", sample_results);
-
-    sample_results = sample_board.pawn_moves(sample_board.board[13], 13);
-    println!("{:?}", sample_results);
-
-    sample_results = sample_board.pawn_moves(sample_board.board[55], 55);
-    println!("{:?}", sample_results);
-
-    sample_results = sample_board.pawn_moves(sample_board.board[53], 53);
-    println!("{:?}", sample_results);
-
-    // Tests the move_piece function which also adds a Move object to the array of moves within the Chess_Board object
-    sample_board.move_piece(10, 26);
-    sample_board.print_board();
-    sample_results = sample_board.pawn_moves(sample_board.board[25], 25);
-    println!("{:?}", sample_results);
+    let args: Vec<String> = std::env::args().collect();
+
+    if args.len() > 1 && args[1] == "uci"
+    {
+        uci::Uci::new().run();
+        return;
+    }
+
+    if args.len() > 1 && args[1] == "make-book"
+    {
+        make_book(&args[2..]);
+        return;
+    }
+
+    if args.len() > 1 && args[1] == "generate-tb"
+    {
+        generate_tablebases(&args[2..]);
+        return;
+    }
+
+    if args.len() > 1 && args[1] == "probe-tb"
+    {
+        probe_tablebase(&args[2..]);
+        return;
+    }
+
+    if args.len() > 1 && args[1] == "probe-syzygy"
+    {
+        probe_syzygy(&args[2..]);
+        return;
+    }
+
+    if args.len() > 1 && args[1] == "perft"
+    {
+        run_perft(&args[2..]);
+        return;
+    }
+
+    if args.len() > 1 && args[1] == "diagram"
+    {
+        read_diagram(&args[2..]);
+        return;
+    }
+
+    if args.len() > 1 && args[1] == "image"
+    {
+        write_image(&args[2..]);
+        return;
+    }
+
+    if args.len() > 1 && args[1] == "animate"
+    {
+        write_animation(&args[2..]);
+        return;
+    }
+
+    if args.len() > 1 && args[1] == "tui"
+    {
+        tui::Tui::run_from_args(&args[2..]);
+        return;
+    }
+
+    // Without a command (or with "play") a game is played on the terminal, see cli.rs
+    cli::Cli::new().run();
+}
+
+// Turns a board diagram (e.g. pasted print_board output) into a FEN, see diagram.rs
+// intermezzo diagram [file], the diagram is read from the input without a file
+fn read_diagram(args: &[String])
+{
+    let text = match args.first()
+    {
+        Some(path) => std::fs::read_to_string(path),
+        None       => std::io::read_to_string(std::io::stdin()),
+    };
+
+    let text = match text
+    {
+        Ok(text) => text,
+        Err(error) =>
+        {
+            println!("could not read the diagram: {}", error);
+            std::process::exit(1);
+        },
+    };
+
+    let board = match chess::Chess_Board::parse_diagram(&text)
+    {
+        Ok(board) => board,
+        Err(error) =>
+        {
+            println!("{}", error);
+            std::process::exit(1);
+        },
+    };
+
+    println!("{}", board.to_fen());
+
+    for problem in board.position_problems()
+    {
+        println!("warning: {}", problem);
+    }
+}
+
+// Draws a position as an SVG or PNG file, see board_image.rs
+// intermezzo image <output.svg|output.png> [fen] [--moves "e4 e5 Nf3"] [--size <pixels>] [--flip]
+//                  [--no-coordinates] [--arrow e2e4[:color]]... [--highlight e4[:color]]...
+//                  [--light <color>] [--dark <color>]
+// Colors are written as #rrggbb or #rrggbbaa, the moves are played from the FEN (or the start)
+fn write_image(args: &[String])
+{
+    let usage = "usage: intermezzo image <output.svg|output.png> [fen] [--moves <moves>] [--size <pixels>] [--flip] [--no-coordinates] \
+                 [--arrow e2e4[:color]] [--highlight e4[:color]] [--light <color>] [--dark <color>]";
+
+    let Some(output) = args.first() else
+    {
+        println!("{}", usage);
+        std::process::exit(1);
+    };
+
+    match image_from_args(&args[1..])
+    {
+        Ok((board, options)) =>
+        {
+            let written = if output.to_lowercase().ends_with(".png")
+            {
+                std::fs::write(output, board.to_png(&options))
+            } else
+            {
+                std::fs::write(output, board.to_svg(&options))
+            };
+
+            if let Err(error) = written
+            {
+                println!("could not write {}: {}", output, error);
+                std::process::exit(1);
+            }
+        },
+        Err(error) =>
+        {
+            println!("{}\n{}", error, usage);
+            std::process::exit(1);
+        },
+    }
+}
+
+// The position and options of the image command
+fn image_from_args(args: &[String]) -> Result<(chess::Chess_Board, board_image::Image_Options), String>
+{
+    let mut options = board_image::Image_Options::default();
+    let mut fen_parts: Vec<&str> = vec![];
+    let mut moves: Vec<&str> = vec![];
+
+    // A square or move followed by an optional color, "e4:#ff000080"
+    let with_color = |text: &str| -> Result<(String, Option<drawing::Color>), String> {
+        match text.split_once(':')
+        {
+            Some((squares, color)) => Ok((squares.to_string(), Some(drawing::Color::parse(color).ok_or(format!("{} is not a color", color))?))),
+            None                   => Ok((text.to_string(), None)),
+        }
+    };
+
+    let mut index = 0;
+
+    while index < args.len()
+    {
+        let arg = args[index].as_str();
+        let value = args.get(index + 1).map(|value| value.as_str());
+
+        // The options that take a value skip it
+        index += if arg.starts_with("--") && !matches!(arg, "--flip" | "--no-coordinates") { 2 } else { 1 };
+
+        if arg.starts_with("--") && !matches!(arg, "--flip" | "--no-coordinates") && value.is_none()
+        {
+            return Err(format!("{} needs a value", arg));
+        }
+
+        match arg
+        {
+            "--flip"           => options.flipped = true,
+            "--no-coordinates" => options.coordinates = false,
+            "--moves"          => moves.extend(value.unwrap().split(|letter: char| letter == ',' || letter.is_whitespace()).filter(|text| !text.is_empty())),
+            "--size" =>
+            {
+                options.size = value.unwrap().parse::<u32>().ok().filter(|&size| (16..=4096).contains(&size)).ok_or("the size must be between 16 and 4096")?;
+            },
+            "--light" | "--dark" =>
+            {
+                let color = drawing::Color::parse(value.unwrap()).ok_or(format!("{} is not a color", value.unwrap()))?;
+
+                if arg == "--light" { options.light = color; } else { options.dark = color; }
+            },
+            "--highlight" =>
+            {
+                let (name, color) = with_color(value.unwrap())?;
+                let square = chess::Chess_Board::parse_square(&name).ok_or(format!("{} is not a square", name))?;
+
+                options.highlights.push((square, color.unwrap_or(drawing::Color::rgba(255, 255, 0, 110))));
+            },
+            "--arrow" =>
+            {
+                let (squares, color) = with_color(value.unwrap())?;
+
+                let (origin, target) = match (squares.get(0..2).and_then(chess::Chess_Board::parse_square), squares.get(2..).and_then(chess::Chess_Board::parse_square))
+                {
+                    (Some(origin), Some(target)) => (origin, target),
+                    _                            => return Err(format!("{} is not an arrow, write it as e2e4", squares)),
+                };
+
+                let mut arrow = board_image::Arrow::new(origin, target);
+                arrow.color = color.unwrap_or(arrow.color);
+                options.arrows.push(arrow);
+            },
+            _ if arg.starts_with("--") => return Err(format!("unknown option {}", arg)),
+            _                          => fen_parts.push(arg),
+        }
+    }
+
+    let mut game = if fen_parts.is_empty()
+    {
+        game::Game::new()
+    } else
+    {
+        game::Game::from_fen(&fen_parts.join(" "))?
+    };
+
+    for text in moves
+    {
+        game.play_text(text)?;
+    }
+
+    return Ok((game.board().clone(), options));
+}
+
+// Saves a game from a PGN file as an animated GIF, or an animated PNG for .png and .apng, see
+// animation.rs
+// intermezzo animate <output.gif|output.png> [games.pgn] [--game <n>] [--delay <ms>] [--last-delay <ms>]
+//                    [--size <pixels>] [--flip] [--no-captions] [--eval <depth>]
+// The PGN is read from the input without a file, the first game is used unless --game is given
+fn write_animation(args: &[String])
+{
+    let usage = "usage: intermezzo animate <output.gif|output.png> [games.pgn] [--game <n>] [--delay <ms>] [--last-delay <ms>] \
+                 [--size <pixels>] [--flip] [--no-captions] [--eval <depth>]";
+
+    let Some(output) = args.first() else
+    {
+        println!("{}", usage);
+        std::process::exit(1);
+    };
+
+    let mut options = animation::Animation_Options::default();
+    let mut path: Option<&str> = None;
+    let mut game_number = 1;
+
+    let mut index = 1;
+
+    while index < args.len()
+    {
+        let arg = args[index].as_str();
+        let number = args.get(index + 1).and_then(|value| value.parse::<u32>().ok());
+
+        index += 1;
+
+        match (arg, number)
+        {
+            ("--flip", _)                  => options.image.flipped = true,
+            ("--no-captions", _)           => options.captions = false,
+            ("--game", Some(number))       => game_number = number.max(1) as usize,
+            ("--delay", Some(delay))       => options.delay = delay,
+            ("--last-delay", Some(delay))  => options.last_delay = delay,
+            ("--size", Some(size))         => options.image.size = size.clamp(16, 4096),
+            ("--eval", Some(depth))        => options.evaluation = Some(search::Search_Limits { depth: Some(depth), ..Default::default() }),
+            _ if arg.starts_with("--")     =>
+            {
+                println!("{} needs a number or is not an option\n{}", arg, usage);
+                std::process::exit(1);
+            },
+            _                              => path = Some(arg),
+        }
+
+        // The options with a number skip it
+        if arg.starts_with("--") && !matches!(arg, "--flip" | "--no-captions")
+        {
+            index += 1;
+        }
+    }
+
+    let text = match path
+    {
+        Some(path) => std::fs::read_to_string(path),
+        None       => std::io::read_to_string(std::io::stdin()),
+    };
+
+    let games = match text
+    {
+        Ok(text) => pgn::parse_pgn(&text),
+        Err(error) =>
+        {
+            println!("could not read the PGN: {}", error);
+            std::process::exit(1);
+        },
+    };
+
+    let Some(pgn_game) = games.get(game_number - 1) else
+    {
+        println!("found {} games, there is no game {}", games.len(), game_number);
+        std::process::exit(1);
+    };
+
+    let board = match pgn_game.to_game()
+    {
+        Ok(game) => game.board().clone(),
+        Err(error) =>
+        {
+            println!("could not replay the game: {}", error);
+            std::process::exit(1);
+        },
+    };
+
+    let lower = output.to_lowercase();
+    let data = if lower.ends_with(".png") || lower.ends_with(".apng") { board.to_apng(&options) } else { board.to_gif(&options) };
+
+    if let Err(error) = std::fs::write(output, data)
+    {
+        println!("could not write {}: {}", output, error);
+        std::process::exit(1);
+    }
+}
+
+// Checks the move generation against positions with known perft results
+// intermezzo perft [variant] [max nodes]
+fn run_perft(args: &[String])
+{
+    let variant = args.iter().find(|arg| arg.parse::<u64>().is_err()).map(|arg| arg.as_str());
+    let max_nodes = args.iter().find_map(|arg| arg.parse::<u64>().ok()).unwrap_or(1_000_000);
+
+    if variant.is_some_and(|name| perft::perft_variant_name(name).is_none())
+    {
+        let names: Vec<&str> = variant::VARIANTS.iter()
+                                                .map(|rules| rules.name())
+                                                .chain(fairy::FAIRY_VARIANTS.iter().map(|fairy| fairy.name))
+                                                .collect();
+
+        println!("unknown variant, the variants are: {}", names.join(", "));
+        return;
+    }
+
+    if perft::run_perft_suite(variant, max_nodes)
+    {
+        println!("all perft results match");
+    } else
+    {
+        println!("some perft results do not match");
+        std::process::exit(1);
+    }
+}
+
+// Builds a Polyglot book from PGN files
+// intermezzo make-book <output.bin> <max ply> <min games> [--winner-only | --no-losses] <games.pgn>...
+fn make_book(args: &[String])
+{
+    let usage = "usage: intermezzo make-book <output.bin> <max ply> <min games> [--winner-only | --no-losses] <games.pgn>...";
+
+    if args.len() < 4
+    {
+        println!("{}", usage);
+        return;
+    }
+
+    let (max_ply, min_games) = match (args[1].parse::<usize>(), args[2].parse::<u32>())
+    {
+        (Ok(max_ply), Ok(min_games)) => (max_ply, min_games),
+        _ =>
+        {
+            println!("{}", usage);
+            return;
+        },
+    };
+
+    let mut filter = book_builder::Result_Filter::All;
+    let mut files: Vec<&String> = vec![];
+
+    for arg in &args[3..]
+    {
+        match arg.as_str()
+        {
+            "--winner-only" => filter = book_builder::Result_Filter::Winner_Only,
+            "--no-losses"   => filter = book_builder::Result_Filter::No_Losses,
+            _               => files.push(arg),
+        }
+    }
+
+    let mut builder = book_builder::Book_Builder::new(max_ply, min_games, filter);
+
+    for file in files
+    {
+        match builder.add_pgn_file(file)
+        {
+            Ok(count) => println!("{}: {} games", file, count),
+            Err(error) => println!("{}: {}", file, error),
+        }
+    }
+
+    match builder.save(&args[0])
+    {
+        Ok(count) => println!("wrote {} entries to {}", count, args[0]),
+        Err(error) => println!("could not write {}: {}", args[0], error),
+    }
+}
+
+// Generates endgame tables (and the smaller tables they depend on) into a directory
+// intermezzo generate-tb <directory> <signature>...
+fn generate_tablebases(args: &[String])
+{
+    if args.len() < 2
+    {
+        println!("usage: intermezzo generate-tb <directory> <signature>...   (e.g. KQvK KRvK KPvK KBNvK)");
+        return;
+    }
+
+    let mut tablebase = tablebase::Endgame_Tablebase::new(Some(std::path::PathBuf::from(&args[0])));
+
+    for signature in &args[1..]
+    {
+        match tablebase.generate(signature)
+        {
+            Ok(()) => println!("{}: done", signature),
+            Err(error) => println!("{}: {}", signature, error),
+        }
+    }
+}
+
+// Looks up a position within the endgame tables of tablebase.rs and prints the best move, the
+// table is loaded from the directory or generated into it first
+// intermezzo probe-tb <directory> <fen>
+fn probe_tablebase(args: &[String])
+{
+    if args.len() < 2
+    {
+        println!("usage: intermezzo probe-tb <directory> <fen>");
+        return;
+    }
+
+    let mut board = match chess::Chess_Board::from_fen(&args[1..].join(" "))
+    {
+        Ok(board) => board,
+        Err(error) =>
+        {
+            println!("{}", error);
+            return;
+        },
+    };
+
+    let mut tablebase = tablebase::Endgame_Tablebase::new(Some(std::path::PathBuf::from(&args[0])));
+    let (signature, _) = tablebase::material_signature(&board.board);
+
+    if let Err(error) = tablebase.generate(&signature)
+    {
+        println!("{}: {}", signature, error);
+        return;
+    }
+
+    let value = match tablebase.probe(&board)
+    {
+        Some(value) => value,
+        None =>
+        {
+            println!("the position can not be probed");
+            return;
+        },
+    };
+
+    match value.moves_to_mate()
+    {
+        Some(moves) => println!("{:?}, mate in {}", value, moves),
+        None        => println!("{:?}", value),
+    }
+
+    if let Some(best_move) = tablebase.best_move(&mut board)
+    {
+        println!("best move: {}", board.move_to_san(best_move));
+    }
+}
+
+// Looks up a position within Syzygy tablebases and prints the result of every legal move
+// intermezzo probe-syzygy <directory> <fen>
+fn probe_syzygy(args: &[String])
+{
+    if args.len() < 2
+    {
+        println!("usage: intermezzo probe-syzygy <directory> <fen>");
+        return;
+    }
+
+    let tablebase = syzygy::Syzygy_Tablebase::open(&args[0]);
+    println!("{} tables, up to {} pieces", tablebase.table_count(), tablebase.max_pieces());
+
+    let mut board = match chess::Chess_Board::from_fen(&args[1..].join(" "))
+    {
+        Ok(board) => board,
+        Err(error) =>
+        {
+            println!("{}", error);
+            return;
+        },
+    };
+
+    // Without the DTZ table of the material only the WDL value is known
+    match (tablebase.probe(&mut board), tablebase.probe_wdl(&mut board))
+    {
+        (Some(result), _) => println!("wdl: {:?}\ndtz: {}", result.wdl, result.dtz),
+        (None, Some(wdl)) => println!("wdl: {:?}\ndtz: not available", wdl),
+        (None, None) =>
+        {
+            println!("the position can not be probed");
+            return;
+        },
+    }
+
+    if let Some((ranked, _)) = tablebase.rank_root_moves(&mut board)
+    {
+        for (ranked_move, rank) in ranked
+        {
+            println!("{:8} {}", board.move_to_san(ranked_move), rank);
+        }
+    }
 }
@@ -0,0 +1,538 @@
+// Reading and writing positions and moves in the standard text formats
+//
+// FEN (Forsyth-Edwards Notation) describes a whole position in a single line:
+// rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1
+//
+// For Chess960 two ways of writing the castling rights are read:
+// X-FEN         -> KQkq stand for the outermost rook on that side of the king, if another rook is
+//                  further out the file of the castling rook is written instead ("Bkq")
+// Shredder-FEN  -> always the file of the castling rook, uppercase for white ("HAha")
+// to_fen writes X-FEN, which is the same as normal FEN for normal chess
+//
+// SAN (Standard Algebraic Notation) is the notation used within PGN files and books:
+// e4, Nf3, exd5, Raxd1, O-O, e8=Q+
+
+use crate::chess::*;
+use crate::variant::*;
+
+pub const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+// Converts a FEN / SAN piece letter into the value used within the board array
+// Uppercase letters are white pieces, lowercase letters are black pieces
+pub fn piece_from_char(letter: char) -> Option<i8>
+{
+    let piece = match letter.to_ascii_lowercase()
+    {
+        'p' => PAWN,
+        'n' => KNIGHT,
+        'b' => BISHOP,
+        'r' => ROOK,
+        'q' => QUEEN,
+        'k' => KING,
+        _   => return None,
+    };
+
+    return Some(if letter.is_ascii_uppercase() { piece } else { -piece });
+}
+
+// The opposite of piece_from_char, empty squares are returned as a space and the duck as a '*'
+pub fn piece_to_char(piece: i8) -> char
+{
+    let letter = match piece.abs()
+    {
+        PAWN   => 'p',
+        KNIGHT => 'n',
+        BISHOP => 'b',
+        ROOK   => 'r',
+        QUEEN  => 'q',
+        KING   => 'k',
+        DUCK   => return '*',
+        _      => return ' ',
+    };
+
+    return if piece > 0 { letter.to_ascii_uppercase() } else { letter };
+}
+
+impl Chess_Board
+{
+    // Creates a board from a FEN string
+    // The half move clock and full move number may be left out
+    pub fn from_fen(fen: &str) -> Result<Self, String>
+    {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+
+        if fields.len() < 4
+        {
+            return Err(format!("expected at least 4 fields in FEN \"{}\"", fen));
+        }
+
+        let mut new_board = Chess_Board::new();
+        new_board.board = [0; 64];
+
+        // The FEN lists the rows starting from the 8th rank
+        let rows: Vec<&str> = fields[0].split('/').collect();
+
+        if rows.len() != 8
+        {
+            return Err(format!("expected 8 rows in FEN \"{}\"", fen));
+        }
+
+        for (r_idx, row) in rows.iter().enumerate()
+        {
+            let mut col = 0;
+
+            for letter in row.chars()
+            {
+                if let Some(empty) = letter.to_digit(10)
+                {
+                    col += empty as usize;
+                    continue;
+                }
+
+                let piece = match piece_from_char(letter)
+                {
+                    Some(piece) => piece,
+                    None => return Err(format!("unknown piece '{}' in FEN", letter)),
+                };
+
+                if col >= 8
+                {
+                    return Err(format!("row {} of the FEN is too long", r_idx + 1));
+                }
+
+                new_board.board[(7 - r_idx) * 8 + col] = piece;
+                col += 1;
+            }
+
+            if col != 8
+            {
+                return Err(format!("row {} of the FEN does not have 8 squares", r_idx + 1));
+            }
+        }
+
+        new_board.details.side_to_move = match fields[1]
+        {
+            "w" => WHITE,
+            "b" => BLACK,
+            _   => return Err(format!("unknown side to move \"{}\"", fields[1])),
+        };
+
+        new_board.parse_castling(fields[2])?;
+
+        // The en passant square is behind a pawn of the other side that has just moved two
+        // squares, on the 6th rank when white is to move and on the 3rd when black is
+        let en_passant_row = if new_board.details.side_to_move == WHITE { 5 } else { 2 };
+
+        new_board.details.en_passant = match fields[3]
+        {
+            "-" => None,
+            square => match Chess_Board::parse_square(square)
+            {
+                Some(index) if Chess_Board::get_row(index) == en_passant_row => Some(index),
+                Some(_) => return Err(format!("en passant square \"{}\" is not on the right rank", square)),
+                None => return Err(format!("invalid en passant square \"{}\"", square)),
+            },
+        };
+
+        if let Some(clock) = fields.get(4)
+        {
+            new_board.details.halfmove_clock = match clock.parse::<u32>()
+            {
+                Ok(clock) => clock,
+                Err(_) => return Err(format!("invalid half move clock \"{}\"", clock)),
+            };
+        }
+
+        if let Some(number) = fields.get(5)
+        {
+            new_board.details.fullmove_number = match number.parse::<u32>()
+            {
+                Ok(number) => std::cmp::max(number, 1),
+                Err(_) => return Err(format!("invalid full move number \"{}\"", number)),
+            };
+        }
+
+        let color = new_board.details.side_to_move;
+        new_board.details.is_check = if new_board.in_check(color) { -color } else { 0 };
+
+        return Ok(new_board);
+    }
+
+    // Reads the castling field of a FEN in normal, X-FEN or Shredder-FEN notation
+    // Castling rights that are missing are stored as the rook having moved
+    // rook index order: [white long, white short, black long, black short]
+    fn parse_castling(&mut self, castling: &str) -> Result<(), String>
+    {
+        self.details.rooks_have_moved = [true; 4];
+        self.castling_rooks = [0, 7, 56, 63];
+
+        if castling == "-"
+        {
+            return Ok(());
+        }
+
+        // Rights without a king or rook to castle with are ignored
+        for letter in castling.chars()
+        {
+            if !"KQkqABCDEFGHabcdefgh".contains(letter)
+            {
+                return Err(format!("unknown castling right '{}'", letter));
+            }
+
+            let color = if letter.is_ascii_uppercase() { WHITE } else { BLACK };
+            let back_rank = if color == WHITE { 0 } else { 56 };
+
+            let king_square = match self.king_square(color)
+            {
+                Some(square) if Self::get_row(square) * 8 == back_rank => square,
+                _ => continue,
+            };
+
+            let rook_square = match letter.to_ascii_lowercase()
+            {
+                // The outermost rook on that side of the king
+                'k' => (king_square + 1..back_rank + 8).rev().find(|&square| self.board[square] == color * ROOK),
+                'q' => (back_rank..king_square).find(|&square| self.board[square] == color * ROOK),
+                file => Some(back_rank + (file as u8 - b'a') as usize),
+            };
+
+            let rook_square = match rook_square
+            {
+                Some(square) if self.board[square] == color * ROOK && square != king_square => square,
+                _ => continue,
+            };
+
+            let short = if rook_square > king_square { 1 } else { 0 };
+            let index = if color == WHITE { short } else { 2 + short };
+
+            self.castling_rooks[index] = rook_square;
+            self.details.rooks_have_moved[index] = false;
+        }
+
+        // Anything but the king on the e file and the rooks in the corners can only be Chess960
+        let rights = self.castling_rights();
+
+        for (c_idx, &rook_index) in [1, 0, 3, 2].iter().enumerate()
+        {
+            let color = if c_idx < 2 { WHITE } else { BLACK };
+
+            if    rights[c_idx]
+               && (   self.castling_rooks[rook_index] != [0, 7, 56, 63][rook_index]
+                   || self.king_square(color) != Some(if color == WHITE { 4 } else { 60 }))
+            {
+                self.chess960 = true;
+            }
+        }
+
+        return Ok(());
+    }
+
+    // The castling field of a FEN, with file letters for every right in Shredder-FEN and only
+    // where KQkq would be ambiguous in X-FEN
+    fn castling_text(&self, shredder: bool) -> String
+    {
+        let rights = self.castling_rights();
+        let mut castling = String::new();
+
+        // The order of the rights matches castling_rights: [white short, white long, black short, black long]
+        for (c_idx, &rook_index) in [1, 0, 3, 2].iter().enumerate()
+        {
+            if !rights[c_idx]
+            {
+                continue;
+            }
+
+            let color = if c_idx < 2 { WHITE } else { BLACK };
+            let rook_square = self.castling_rooks[rook_index];
+            let back_rank = Self::get_row(rook_square) * 8;
+
+            // Another rook further out on the same side makes KQkq point to the wrong rook
+            let outer = if c_idx % 2 == 0 { rook_square + 1..back_rank + 8 } else { back_rank..rook_square };
+            let ambiguous = outer.into_iter().any(|square| self.board[square] == color * ROOK);
+
+            let letter = if shredder || ambiguous
+            {
+                (b'a' + Self::get_col(rook_square) as u8) as char
+            } else if c_idx % 2 == 0
+            {
+                'k'
+            } else
+            {
+                'q'
+            };
+
+            castling.push(if color == WHITE { letter.to_ascii_uppercase() } else { letter });
+        }
+
+        if castling.is_empty()
+        {
+            castling.push('-');
+        }
+
+        return castling;
+    }
+
+    // Writes the position as a FEN string, the castling rights in X-FEN
+    // Variants can add fields of their own
+    pub fn to_fen(&self) -> String
+    {
+        return self.rules.write_fen(self, false);
+    }
+
+    // Writes the position as a FEN string with the castling rights in Shredder-FEN
+    pub fn to_shredder_fen(&self) -> String
+    {
+        return self.rules.write_fen(self, true);
+    }
+
+    // The FEN fields of normal chess
+    pub fn standard_fen(&self, shredder: bool) -> String
+    {
+        let mut result = String::new();
+
+        for row in (0..8).rev()
+        {
+            let mut empty = 0;
+
+            for col in 0..8
+            {
+                let piece = self.board[row * 8 + col];
+
+                if piece == 0
+                {
+                    empty += 1;
+                    continue;
+                }
+
+                if empty > 0
+                {
+                    result.push_str(&empty.to_string());
+                    empty = 0;
+                }
+
+                result.push(piece_to_char(piece));
+            }
+
+            if empty > 0
+            {
+                result.push_str(&empty.to_string());
+            }
+
+            if row > 0
+            {
+                result.push('/');
+            }
+        }
+
+        result.push_str(if self.details.side_to_move == WHITE { " w " } else { " b " });
+
+        result.push_str(&self.castling_text(shredder));
+
+        match self.details.en_passant
+        {
+            Some(square) => result.push_str(&format!(" {}", Chess_Board::square_name(square))),
+            None         => result.push_str(" -"),
+        }
+
+        result.push_str(&format!(" {} {}", self.details.halfmove_clock, self.details.fullmove_number));
+
+        return result;
+    }
+
+    // Writes a legal move in SAN, including the + or # suffix for check and checkmate
+    pub fn move_to_san(&mut self, san_move: Move) -> String
+    {
+        let mut result = String::new();
+
+        match san_move.kind
+        {
+            Move_Kind::Short_Castle => result.push_str("O-O"),
+            Move_Kind::Long_Castle  => result.push_str("O-O-O"),
+            Move_Kind::Drop         => result.push_str(&san_move.to_uci()),
+            Move_Kind::Duck         => result.push_str(&san_move.to_uci()),
+            _ if san_move.piece.abs() == PAWN =>
+            {
+                let is_capture = Chess_Board::get_col(san_move.origin) != Chess_Board::get_col(san_move.target);
+
+                if is_capture
+                {
+                    result.push(Chess_Board::square_name(san_move.origin).as_bytes()[0] as char);
+                    result.push('x');
+                }
+
+                result.push_str(&Chess_Board::square_name(san_move.target));
+
+                if san_move.promotion != 0
+                {
+                    result.push('=');
+                    result.push(piece_to_char(san_move.promotion.abs()));
+                }
+            },
+            _ =>
+            {
+                result.push(piece_to_char(san_move.piece.abs()));
+
+                // Other pieces of the same type that can move to the same square
+                let others: Vec<Move> = self.legal_moves()
+                                            .into_iter()
+                                            .filter(|other| {
+                                                   other.piece == san_move.piece
+                                                && other.target == san_move.target
+                                                && other.origin != san_move.origin
+                                            })
+                                            .collect();
+
+                let origin_name = Chess_Board::square_name(san_move.origin);
+
+                if !others.is_empty()
+                {
+                    let same_col = others.iter().any(|other| Chess_Board::get_col(other.origin) == Chess_Board::get_col(san_move.origin));
+                    let same_row = others.iter().any(|other| Chess_Board::get_row(other.origin) == Chess_Board::get_row(san_move.origin));
+
+                    if !same_col
+                    {
+                        result.push_str(&origin_name[0..1]);
+                    } else if !same_row
+                    {
+                        result.push_str(&origin_name[1..2]);
+                    } else
+                    {
+                        result.push_str(&origin_name);
+                    }
+                }
+
+                if san_move.capture != 0
+                {
+                    result.push('x');
+                }
+
+                result.push_str(&Chess_Board::square_name(san_move.target));
+            },
+        }
+
+        self.make_move(san_move);
+
+        if self.in_check(self.details.side_to_move)
+        {
+            result.push(if self.outcome() == Some(Outcome::Checkmate) { '#' } else { '+' });
+        }
+
+        self.unmake_move();
+
+        return result;
+    }
+
+    // Finds the legal move written in SAN
+    // Some common deviations are accepted as well: 0-0 for castling, missing '=' before the
+    // promotion piece, missing 'x' for captures and unnecessary disambiguation
+    pub fn parse_san(&mut self, text: &str) -> Option<Move>
+    {
+        let cleaned: String = text.trim()
+                                  .trim_end_matches(|letter| "+#!?".contains(letter))
+                                  .chars()
+                                  .filter(|letter| *letter != 'x' && *letter != ':' && *letter != '-' && *letter != '=')
+                                  .collect();
+
+        let legal = self.legal_moves();
+
+        // Castling, the dashes have already been removed
+        if cleaned == "OO" || cleaned == "00"
+        {
+            return legal.into_iter().find(|legal_move| legal_move.kind == Move_Kind::Short_Castle);
+        }
+
+        if cleaned == "OOO" || cleaned == "000"
+        {
+            return legal.into_iter().find(|legal_move| legal_move.kind == Move_Kind::Long_Castle);
+        }
+
+        // Drops, "N@f3", pawns with or without a letter ("P@e4", "@e4"), and the duck ("*@d5")
+        if let Some((piece, square)) = cleaned.split_once('@')
+        {
+            let piece_type = match piece
+            {
+                ""     => PAWN,
+                "*"    => DUCK,
+                letter => piece_from_char(letter.chars().next()?.to_ascii_uppercase())?,
+            };
+
+            let target = Chess_Board::parse_square(square)?;
+
+            return legal.into_iter().find(|legal_move| {
+                return    (legal_move.kind == Move_Kind::Drop || legal_move.kind == Move_Kind::Duck)
+                       && legal_move.piece.abs() == piece_type
+                       && legal_move.target == target;
+            });
+        }
+
+        let mut letters: Vec<char> = cleaned.chars().collect();
+
+        // Piece type, pawns do not have a letter
+        let piece_type = match letters.first()
+        {
+            Some(letter) if "NBRQK".contains(*letter) =>
+            {
+                let piece = piece_from_char(*letter).unwrap();
+                letters.remove(0);
+                piece
+            },
+            Some(_) => PAWN,
+            None    => return None,
+        };
+
+        // Promotion piece at the end
+        let mut promotion = 0;
+
+        if let Some(letter) = letters.last()
+        {
+            if piece_type == PAWN && "NBRQKnbrqk".contains(*letter)
+            {
+                promotion = piece_from_char(letter.to_ascii_uppercase()).unwrap();
+                letters.pop();
+            }
+        }
+
+        if letters.len() < 2
+        {
+            return None;
+        }
+
+        let target_name: String = letters[letters.len() - 2..].iter().collect();
+        let target = Chess_Board::parse_square(&target_name)?;
+
+        // Anything in front of the target square is used to find the origin square
+        let hints = &letters[..letters.len() - 2];
+        let mut origin_col: Option<usize> = None;
+        let mut origin_row: Option<usize> = None;
+
+        for hint in hints
+        {
+            match hint
+            {
+                'a'..='h' => origin_col = Some((*hint as u8 - b'a') as usize),
+                '1'..='8' => origin_row = Some((*hint as u8 - b'1') as usize),
+                _         => return None,
+            }
+        }
+
+        let candidates: Vec<Move> = legal.into_iter()
+                                         .filter(|legal_move| {
+                                                legal_move.piece.abs() == piece_type
+                                             && legal_move.target == target
+                                             && legal_move.promotion.abs() == promotion
+                                             && legal_move.kind != Move_Kind::Short_Castle
+                                             && legal_move.kind != Move_Kind::Long_Castle
+                                             && legal_move.kind != Move_Kind::Drop
+                                             && origin_col.is_none_or(|col| Chess_Board::get_col(legal_move.origin) == col)
+                                             && origin_row.is_none_or(|row| Chess_Board::get_row(legal_move.origin) == row)
+                                         })
+                                         .collect();
+
+        if candidates.len() != 1
+        {
+            return None;
+        }
+
+        return Some(candidates[0]);
+    }
+}
@@ -0,0 +1,252 @@
+// Positions with known perft results, used to check the move generation of every variant
+// intermezzo perft [variant] [max nodes]
+//
+// counts[0] is the number of positions after one move (perft 1), counts[1] after two moves and so
+// on. Positions where the game is over according to the variant have no moves, as in the perft
+// results published for lichess variants. In Duck chess moving the duck is a ply of its own
+//
+// Sources:
+// Standard     -> the Chess Programming Wiki perft results (start position, Kiwipete, positions 3 - 5)
+// Chess960     -> the Chess960 perft results by Reinhard Scharnagl and H.G. Muller
+// Variants     -> the variant perft files of python-chess, with positions that can be counted by hand
+// 10x8 boards  -> the Capablanca and Gothic results of Fairy-Stockfish
+// Duck chess   -> no published results, counted with scripts/duck_perft.py which has a move generator
+//                 of its own. The lone kings can be counted by hand: 3 king moves, each followed by
+//                 62 squares for the duck, and black has 3 replies unless the duck is on a7, b7 or b8
+
+use crate::chess::*;
+use crate::fairy::*;
+use crate::variant::*;
+
+use std::time::Instant;
+
+pub struct Perft_Position
+{
+    pub variant: &'static str,
+    pub fen:     &'static str,
+    pub counts:  &'static [u64],
+}
+
+pub const PERFT_POSITIONS: &[Perft_Position] = &[
+    // Standard
+    Perft_Position { variant: "Standard", fen: "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", counts: &[20, 400, 8902, 197281, 4865609] },
+    Perft_Position { variant: "Standard", fen: "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", counts: &[48, 2039, 97862, 4085603] },
+    Perft_Position { variant: "Standard", fen: "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", counts: &[14, 191, 2812, 43238, 674624] },
+    Perft_Position { variant: "Standard", fen: "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1", counts: &[6, 264, 9467, 422333] },
+    Perft_Position { variant: "Standard", fen: "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8", counts: &[44, 1486, 62379, 2103487] },
+
+    // Chess960, castling rights in Shredder-FEN
+    Perft_Position { variant: "Standard", fen: "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9", counts: &[21, 528, 12189, 326672] },
+    Perft_Position { variant: "Standard", fen: "2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9", counts: &[21, 807, 18002, 667366] },
+    Perft_Position { variant: "Standard", fen: "b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GEge - 1 9", counts: &[20, 479, 10471, 273318] },
+    Perft_Position { variant: "Standard", fen: "qbbnnrkr/2pp2pp/p7/1p2pp2/8/P3PP2/1PPP1KPP/QBBNNR1R w hf - 0 9", counts: &[22, 593, 13440, 382958] },
+
+    // King of the Hill, in the second position the white king can step onto d4 or e4 which ends the game
+    Perft_Position { variant: "King of the Hill", fen: "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", counts: &[20, 400, 8902, 197281] },
+    Perft_Position { variant: "King of the Hill", fen: "8/8/8/8/8/3K4/8/k7 w - - 0 1", counts: &[8, 15] },
+
+    // Three-check, in Kiwipete with one check left for both sides every check ends the game
+    Perft_Position { variant: "Three-check", fen: "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 3+3 0 1", counts: &[20, 400, 8902, 197281] },
+    Perft_Position { variant: "Three-check", fen: "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 1+1 0 1", counts: &[48, 2039, 97848] },
+
+    // Racing Kings
+    Perft_Position { variant: "Racing Kings", fen: "8/8/8/8/8/8/krbnNBRK/qrbnNBRQ w - - 0 1", counts: &[21, 421, 11264, 296242] },
+
+    // Crazyhouse, the last position has a promoted queen that goes back into the pocket as a pawn
+    Perft_Position { variant: "Crazyhouse", fen: "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[] w KQkq - 0 1", counts: &[20, 400, 8902, 197281, 4888832] },
+    Perft_Position { variant: "Crazyhouse", fen: "2k5/8/8/8/8/8/8/4K3[QRBNPqrbnp] w - - 0 1", counts: &[301, 75353] },
+    Perft_Position { variant: "Crazyhouse", fen: "r1bqk2r/pppp1ppp/2n1p3/4P3/1b1Pn3/2NB1N2/PPP2PPP/R1BQK2R[] b KQkq - 0 1", counts: &[42, 1347, 58057] },
+    Perft_Position { variant: "Crazyhouse", fen: "4k3/1Q~6/8/8/4b3/8/Kpp5/8/ b - - 0 1", counts: &[20, 360, 5445, 132758] },
+
+    // Atomic
+    Perft_Position { variant: "Atomic", fen: "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", counts: &[20, 400, 8902, 197326] },
+    Perft_Position { variant: "Atomic", fen: "rn2kb1r/1pp1p2p/p2q1pp1/3P4/2P3b1/4PN2/PP3PPP/R2QKB1R b KQkq - 0 1", counts: &[40, 1238, 45237, 1434825] },
+    Perft_Position { variant: "Atomic", fen: "rn1qkb1r/p5pp/2p5/3p4/N3P3/5P2/PPP4P/R1BQK3 w Qkq - 0 1", counts: &[28, 833, 23353, 714499] },
+
+    // Antichess, the pawn endings end when a side loses its last pawn, the last position promotes to every piece including a king
+    Perft_Position { variant: "Antichess", fen: "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - - 0 1", counts: &[20, 400, 8067, 153299] },
+    Perft_Position { variant: "Antichess", fen: "8/1p6/8/8/8/8/P7/8 w - - 0 1", counts: &[2, 4, 4, 3, 1, 0] },
+    Perft_Position { variant: "Antichess", fen: "8/2p5/8/8/8/8/P7/8 w - - 0 1", counts: &[2, 4, 4, 4, 4, 4] },
+    Perft_Position { variant: "Antichess", fen: "8/P7/8/8/8/8/7p/8 w - - 0 1", counts: &[5, 25, 105] },
+
+    // Horde
+    Perft_Position { variant: "Horde", fen: "rnbqkbnr/pppppppp/8/1PP2PP1/PPPPPPPP/PPPPPPPP/PPPPPPPP/PPPPPPPP w kq - 0 1", counts: &[8, 128, 1274, 23310, 265223] },
+    Perft_Position { variant: "Horde", fen: "4k3/pp4q1/3P2p1/8/P3PP2/PPP2r2/PPP5/PPPP4 b - - 0 1", counts: &[30, 241, 6633, 56539] },
+    Perft_Position { variant: "Horde", fen: "k7/5p2/4p2P/3p2P1/2p2P2/1p2P2P/p2P2P1/2P2P2 w - - 0 1", counts: &[13, 172, 2205, 33781] },
+
+    // Duck chess, the second position has promotions and kings that can be captured
+    Perft_Position { variant: "Duck chess", fen: "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", counts: &[20, 640, 12240, 379440, 8060996] },
+    Perft_Position { variant: "Duck chess", fen: "4k3/1P4P1/8/3q4/8/8/1p4p1/4K2R w - - 0 1", counts: &[22, 1232, 47523, 2599955] },
+    Perft_Position { variant: "Duck chess", fen: "k7/8/8/8/8/8/8/7K w - - 0 1", counts: &[3, 186, 549] },
+
+    // Capablanca and Gothic, played on a Fairy_Board
+    Perft_Position { variant: "Capablanca", fen: "rnabqkbcnr/pppppppppp/10/10/10/10/PPPPPPPPPP/RNABQKBCNR w KQkq - 0 1", counts: &[28, 784, 25228, 805128] },
+    Perft_Position { variant: "Gothic", fen: "rnbqckabnr/pppppppppp/10/10/10/10/PPPPPPPPPP/RNBQCKABNR w KQkq - 0 1", counts: &[28, 784, 25283, 808984] },
+];
+
+// The variants on a board other than 8x8 are played on a Fairy_Board
+enum Perft_Board
+{
+    Chess(Box<Chess_Board>),
+    Fairy(Fairy_Board),
+}
+
+impl Perft_Board
+{
+    fn perft(&mut self, depth: u32) -> u64
+    {
+        return match self
+        {
+            Perft_Board::Chess(board) => board.perft(depth),
+            Perft_Board::Fairy(board) => board.perft(depth),
+        };
+    }
+}
+
+// The name of a variant that can be counted, found with find_variant or find_fairy_variant
+pub fn perft_variant_name(name: &str) -> Option<&'static str>
+{
+    if let Some(rules) = find_variant(name)
+    {
+        return Some(rules.name());
+    }
+
+    return find_fairy_variant(name).map(|fairy| fairy.name);
+}
+
+// Runs every position of the given variant (or all of them), counts above max_nodes are skipped
+// Returns whether every count matched
+pub fn run_perft_suite(variant: Option<&str>, max_nodes: u64) -> bool
+{
+    let mut all_passed = true;
+
+    for position in PERFT_POSITIONS
+    {
+        let Some(name) = perft_variant_name(position.variant) else { continue };
+
+        if variant.is_some_and(|wanted| perft_variant_name(wanted) != Some(name))
+        {
+            continue;
+        }
+
+        let board = match find_variant(position.variant)
+        {
+            Some(rules) => Chess_Board::from_variant_fen(position.fen, rules).map(|board| Perft_Board::Chess(Box::new(board))),
+            None        => Fairy_Board::from_fen(position.fen).map(Perft_Board::Fairy),
+        };
+
+        let mut board = match board
+        {
+            Ok(board) => board,
+            Err(error) =>
+            {
+                println!("{} {}: {}", name, position.fen, error);
+                all_passed = false;
+                continue;
+            },
+        };
+
+        println!("{} {}", name, position.fen);
+
+        for (d_idx, &expected) in position.counts.iter().enumerate()
+        {
+            if expected > max_nodes
+            {
+                break;
+            }
+
+            let start = Instant::now();
+            let nodes = board.perft(d_idx as u32 + 1);
+            let passed = nodes == expected;
+
+            println!("    perft {} = {} ({} expected) {:.2?} {}", d_idx + 1, nodes, expected, start.elapsed(), if passed { "ok" } else { "FAILED" });
+
+            all_passed &= passed;
+        }
+    }
+
+    return all_passed;
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    // Debug builds are slow, the larger counts are left to `intermezzo perft`
+    const MAX_NODES: u64 = 250_000;
+
+    fn check_variant(variant: &str)
+    {
+        let name = perft_variant_name(variant);
+
+        assert!(PERFT_POSITIONS.iter().any(|position| perft_variant_name(position.variant) == name), "no positions for {}", variant);
+        assert!(run_perft_suite(Some(variant), MAX_NODES), "{}", variant);
+    }
+
+    #[test]
+    fn perft_standard()
+    {
+        check_variant("Standard");
+    }
+
+    #[test]
+    fn perft_king_of_the_hill()
+    {
+        check_variant("King of the Hill");
+    }
+
+    #[test]
+    fn perft_three_check()
+    {
+        check_variant("Three-check");
+    }
+
+    #[test]
+    fn perft_racing_kings()
+    {
+        check_variant("Racing Kings");
+    }
+
+    #[test]
+    fn perft_crazyhouse()
+    {
+        check_variant("Crazyhouse");
+    }
+
+    #[test]
+    fn perft_atomic()
+    {
+        check_variant("Atomic");
+    }
+
+    #[test]
+    fn perft_antichess()
+    {
+        check_variant("Antichess");
+    }
+
+    #[test]
+    fn perft_horde()
+    {
+        check_variant("Horde");
+    }
+
+    #[test]
+    fn perft_duck_chess()
+    {
+        check_variant("Duck chess");
+    }
+
+    #[test]
+    fn perft_capablanca()
+    {
+        check_variant("Capablanca");
+    }
+
+    #[test]
+    fn perft_gothic()
+    {
+        check_variant("Gothic");
+    }
+}
@@ -0,0 +1,474 @@
+// Reading and writing games in PGN (Portable Game Notation)
+//
+// A PGN file is a list of games, every game starts with a list of tags followed by the moves:
+//
+// [Event "Club Championship"]
+// [White "Daniel"]
+// [Black "Isaac"]
+// [Result "1-0"]
+//
+// 1. e4 e5 2. Nf3 {a comment} Nc6 (2... d6 3. d4) 3. Bb5 $1 a6 1-0
+//
+// Only the main line is kept, comments, variations and annotations are skipped
+//
+// Written games always start with the seven tag roster (Event, Site, Date, Round, White, Black
+// and Result), games that do not start from the normal starting position get the SetUp and FEN
+// tags, games of other variants a Variant tag and finished games a Termination tag
+
+use crate::chess::*;
+use crate::game::*;
+use crate::variant::*;
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// The seven tag roster and the values used when a tag is unknown
+const SEVEN_TAG_ROSTER: [(&str, &str); 7] =
+[
+    ("Event",  "?"),
+    ("Site",   "?"),
+    ("Date",   "????.??.??"),
+    ("Round",  "?"),
+    ("White",  "?"),
+    ("Black",  "?"),
+    ("Result", "*"),
+];
+
+// Tags that are written from the game itself and not copied from the game's tags
+const GENERATED_TAGS: [&str; 5] = ["Result", "Variant", "SetUp", "FEN", "Termination"];
+
+// The movetext is wrapped to lines of at most this many characters
+const PGN_LINE_LENGTH: usize = 80;
+
+#[derive(Clone, Debug, Default)]
+pub struct Pgn_Game
+{
+    pub tags:   Vec<(String, String)>,
+    // The moves of the main line in SAN
+    pub moves:  Vec<String>,
+    // "1-0", "0-1", "1/2-1/2" or "*"
+    pub result: String,
+}
+
+impl Pgn_Game
+{
+    // Returns the value of a tag, e.g. game.tag("White")
+    pub fn tag(&self, name: &str) -> Option<&str>
+    {
+        return self.tags.iter()
+                        .find(|(tag_name, _)| tag_name == name)
+                        .map(|(_, value)| value.as_str());
+    }
+
+    // The position the game starts from, games can start from a custom position with the FEN tag
+    // and be played with the rules of a variant with the Variant tag
+    pub fn start_board(&self) -> Result<Chess_Board, String>
+    {
+        let rules = match self.tag("Variant")
+        {
+            Some(name) => match find_variant(name)
+            {
+                Some(rules) => rules,
+                None => return Err(format!("unknown variant \"{}\"", name)),
+            },
+            None => &STANDARD,
+        };
+
+        return Chess_Board::from_variant_fen(self.tag("FEN").unwrap_or(rules.start_fen()), rules);
+    }
+
+    // The result of the game from white's point of view
+    // 1.0 for a white win, 0.5 for a draw, 0.0 for a black win and None for an unfinished game
+    pub fn white_score(&self) -> Option<f32>
+    {
+        return match self.result.as_str()
+        {
+            "1-0"     => Some(1.0),
+            "0-1"     => Some(0.0),
+            "1/2-1/2" => Some(0.5),
+            _         => None,
+        };
+    }
+
+    // The PGN of a Game, the tags of the game are copied over
+    pub fn from_game(game: &Game) -> Self
+    {
+        let mut tags: Vec<(String, String)> = game.tags.iter()
+                                                       .filter(|(name, _)| !GENERATED_TAGS.contains(&name.as_str()))
+                                                       .cloned()
+                                                       .collect();
+
+        tags.push((String::from("Result"), game.result().to_pgn().to_string()));
+
+        if !game.board().is_standard()
+        {
+            tags.push((String::from("Variant"), game.rules().name().to_string()));
+        }
+
+        if !game.is_standard_start()
+        {
+            tags.push((String::from("SetUp"), String::from("1")));
+            tags.push((String::from("FEN"), game.start_fen().to_string()));
+        }
+
+        if let Some(termination) = game.termination()
+        {
+            tags.push((String::from("Termination"), termination.to_pgn().to_string()));
+        }
+
+        return Pgn_Game
+        {
+            tags,
+            moves: game.san_moves(),
+            result: game.result().to_pgn().to_string(),
+        };
+    }
+
+    // Replays the game, a result that was not reached on the board (resignation, time forfeit,
+    // draw offer) is taken from the result and the Termination tag
+    pub fn to_game(&self) -> Result<Game, String>
+    {
+        let mut game = Game::from_board(self.start_board()?);
+
+        game.tags = self.tags.iter()
+                             .filter(|(name, _)| !GENERATED_TAGS.contains(&name.as_str()))
+                             .cloned()
+                             .collect();
+
+        for (m_idx, san) in self.moves.iter().enumerate()
+        {
+            if let Err(error) = game.play_text(san)
+            {
+                return Err(format!("move {} ({}): {}", m_idx / 2 + 1, san, error));
+            }
+        }
+
+        let result = Game_Result::from_pgn(&self.result);
+
+        if    game.is_over()
+           || result == Game_Result::Ongoing
+        {
+            return Ok(game);
+        }
+
+        if result == Game_Result::Draw
+        {
+            if game.claim_draw().is_err()
+            {
+                game.agree_draw()?;
+            }
+
+            return Ok(game);
+        }
+
+        let termination = match self.tag("Termination")
+        {
+            Some("time forfeit") => Termination::Timeout,
+            Some("abandoned")    => Termination::Abandonment,
+            _                    => Termination::Resignation,
+        };
+
+        game.finish(result, termination)?;
+
+        return Ok(game);
+    }
+
+    // Writes the game as PGN text, ending with an empty line so games can be appended
+    pub fn to_pgn(&self) -> String
+    {
+        let mut result = String::new();
+
+        for (name, default) in SEVEN_TAG_ROSTER
+        {
+            let value = if name == "Result" { self.result.as_str() } else { self.tag(name).unwrap_or(default) };
+            result.push_str(&format!("[{} \"{}\"]\n", name, escape_tag(value)));
+        }
+
+        for (name, value) in self.tags.iter()
+        {
+            if SEVEN_TAG_ROSTER.iter().all(|(roster_name, _)| roster_name != name)
+            {
+                result.push_str(&format!("[{} \"{}\"]\n", name, escape_tag(value)));
+            }
+        }
+
+        result.push('\n');
+
+        // The move numbers depend on the position the game started from
+        let (mut number, mut white_to_move) = match self.start_board()
+        {
+            Ok(board) => (board.details.fullmove_number, board.details.side_to_move == WHITE),
+            Err(_)    => (1, true),
+        };
+
+        let mut tokens: Vec<String> = vec![];
+
+        for (m_idx, san) in self.moves.iter().enumerate()
+        {
+            if white_to_move
+            {
+                tokens.push(format!("{}.", number));
+            } else if m_idx == 0
+            {
+                tokens.push(format!("{}...", number));
+            }
+
+            tokens.push(san.clone());
+
+            if !white_to_move
+            {
+                number += 1;
+            }
+
+            white_to_move = !white_to_move;
+        }
+
+        tokens.push(self.result.clone());
+
+        let mut line_length = 0;
+
+        for token in tokens
+        {
+            if    line_length > 0
+               && line_length + 1 + token.len() > PGN_LINE_LENGTH
+            {
+                result.push('\n');
+                line_length = 0;
+            }
+
+            if line_length > 0
+            {
+                result.push(' ');
+                line_length += 1;
+            }
+
+            line_length += token.len();
+            result.push_str(&token);
+        }
+
+        result.push_str("\n\n");
+
+        return result;
+    }
+}
+
+// Quotes and backslashes within tag values are escaped with a backslash
+fn escape_tag(value: &str) -> String
+{
+    return value.replace('\\', "\\\\").replace('"', "\\\"");
+}
+
+// Writes games to a PGN file
+pub fn write_pgn_file<P: AsRef<Path>>(path: P, games: &[Pgn_Game]) -> io::Result<()>
+{
+    let text: String = games.iter().map(|game| game.to_pgn()).collect();
+
+    return fs::write(path, text);
+}
+
+// Reads every game from a PGN file
+pub fn read_pgn_file<P: AsRef<Path>>(path: P) -> io::Result<Vec<Pgn_Game>>
+{
+    let bytes = fs::read(path)?;
+
+    // Older PGN files are often not valid UTF-8 (latin-1 player names), which is only a problem
+    // within the tags and comments
+    return Ok(parse_pgn(&String::from_utf8_lossy(&bytes)));
+}
+
+// Parses the text of a PGN file into games
+pub fn parse_pgn(text: &str) -> Vec<Pgn_Game>
+{
+    let mut games: Vec<Pgn_Game> = vec![];
+    let mut current = Pgn_Game::default();
+    let mut has_content = false;
+
+    let characters: Vec<char> = text.chars().collect();
+    let mut index = 0;
+    let mut variation_depth = 0;
+
+    while index < characters.len()
+    {
+        let letter = characters[index];
+
+        // Lines starting with % are escaped and ignored completely
+        if    letter == '%'
+           && (index == 0 || characters[index - 1] == '\n')
+        {
+            while index < characters.len() && characters[index] != '\n'
+            {
+                index += 1;
+            }
+
+            continue;
+        }
+
+        match letter
+        {
+            '{' =>
+            {
+                while index < characters.len() && characters[index] != '}'
+                {
+                    index += 1;
+                }
+
+                index += 1;
+            },
+            ';' =>
+            {
+                while index < characters.len() && characters[index] != '\n'
+                {
+                    index += 1;
+                }
+            },
+            '(' =>
+            {
+                variation_depth += 1;
+                index += 1;
+            },
+            ')' =>
+            {
+                if variation_depth > 0
+                {
+                    variation_depth -= 1;
+                }
+
+                index += 1;
+            },
+            '[' if variation_depth == 0 =>
+            {
+                // A tag after the moves of a game without a result starts a new game
+                if !current.moves.is_empty()
+                {
+                    current.result = current.tag("Result").unwrap_or("*").to_string();
+                    games.push(std::mem::take(&mut current));
+                }
+
+                let (tag, next) = parse_tag(&characters, index);
+
+                if let Some(tag) = tag
+                {
+                    current.tags.push(tag);
+                    has_content = true;
+                }
+
+                index = next;
+            },
+            _ if letter.is_whitespace() =>
+            {
+                index += 1;
+            },
+            _ =>
+            {
+                let start = index;
+
+                while    index < characters.len()
+                      && !characters[index].is_whitespace()
+                      && !"{}();[".contains(characters[index])
+                {
+                    index += 1;
+                }
+
+                if variation_depth > 0
+                {
+                    continue;
+                }
+
+                let token: String = characters[start..index].iter().collect();
+
+                match token.as_str()
+                {
+                    "1-0" | "0-1" | "1/2-1/2" | "*" =>
+                    {
+                        current.result = token;
+                        games.push(std::mem::take(&mut current));
+                        has_content = false;
+                    },
+                    _ =>
+                    {
+                        // Annotations ($1) and move numbers (12. or 12...) are skipped,
+                        // a move number can also be written directly in front of the move (12.e4)
+                        if token.starts_with('$')
+                        {
+                            continue;
+                        }
+
+                        let san = token.trim_start_matches(|letter: char| letter.is_ascii_digit() || letter == '.');
+
+                        if !san.is_empty()
+                        {
+                            current.moves.push(san.to_string());
+                            has_content = true;
+                        }
+                    },
+                }
+            },
+        }
+    }
+
+    // The last game might not have a result at the end
+    if has_content
+    {
+        current.result = current.tag("Result").unwrap_or("*").to_string();
+        games.push(current);
+    }
+
+    return games;
+}
+
+// Parses a tag of the form [Name "value"] starting at the [ and returns the index after the ]
+fn parse_tag(characters: &[char], start: usize) -> (Option<(String, String)>, usize)
+{
+    let mut index = start + 1;
+    let mut name = String::new();
+    let mut value = String::new();
+
+    while index < characters.len() && characters[index].is_whitespace()
+    {
+        index += 1;
+    }
+
+    while    index < characters.len()
+          && !characters[index].is_whitespace()
+          && characters[index] != '"'
+          && characters[index] != ']'
+    {
+        name.push(characters[index]);
+        index += 1;
+    }
+
+    while index < characters.len() && characters[index] != '"' && characters[index] != ']'
+    {
+        index += 1;
+    }
+
+    if index < characters.len() && characters[index] == '"'
+    {
+        index += 1;
+
+        while index < characters.len() && characters[index] != '"'
+        {
+            // Quotes and backslashes within the value are escaped with a backslash
+            if characters[index] == '\\' && index + 1 < characters.len()
+            {
+                index += 1;
+            }
+
+            value.push(characters[index]);
+            index += 1;
+        }
+    }
+
+    while index < characters.len() && characters[index] != ']'
+    {
+        index += 1;
+    }
+
+    if name.is_empty()
+    {
+        return (None, index + 1);
+    }
+
+    return (Some((name, value)), index + 1);
+}
@@ -0,0 +1,526 @@
+// Writing PNG files
+//
+// A PNG is a signature followed by chunks, every chunk is its length, a 4 letter type, the data
+// and a CRC of the type and data:
+// IHDR -> the width, height and kind of pixels, here always 8 bit RGB
+// IDAT -> the rows of pixels compressed with zlib, every row starts with a filter byte
+// IEND -> the end of the file
+//
+// Animated PNGs (APNG) add an acTL chunk with the number of frames and for every frame an fcTL
+// chunk with its area and delay. The pixels of the first frame are in IDAT, those of the other
+// frames in fdAT chunks, which are IDAT with a sequence number in front. Programs without APNG
+// support show the first frame
+//
+// The zlib stream is compressed with deflate using the fixed Huffman codes and LZ77 matches found
+// through a hash table of the last 32 KB. Diagrams are mostly large areas of one color so this
+// already gets most of what a full deflate implementation would
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+// The LZ77 window and the longest match deflate allows
+const WINDOW_SIZE: usize = 32768;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+// How many earlier positions with the same hash are tried for every match
+const MAX_CHAIN: usize = 64;
+const HASH_BITS: usize = 15;
+
+const LENGTH_BASE: [u16; 29] = [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+const LENGTH_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+
+const DISTANCE_BASE: [u16; 30] = [1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577];
+const DISTANCE_EXTRA: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+
+// Writes the bits of a deflate stream, which are packed starting from the lowest bit of every byte
+struct Bit_Writer
+{
+    bytes: Vec<u8>,
+    bit_buffer: u32,
+    bit_count: u32,
+}
+
+impl Bit_Writer
+{
+    fn new() -> Self
+    {
+        return Bit_Writer { bytes: vec![], bit_buffer: 0, bit_count: 0 };
+    }
+
+    // Writes the lowest count bits of value, lowest bit first
+    fn write_bits(&mut self, value: u32, count: u32)
+    {
+        self.bit_buffer |= value << self.bit_count;
+        self.bit_count += count;
+
+        while self.bit_count >= 8
+        {
+            self.bytes.push(self.bit_buffer as u8);
+            self.bit_buffer >>= 8;
+            self.bit_count -= 8;
+        }
+    }
+
+    // Huffman codes are written starting from their highest bit
+    fn write_code(&mut self, code: u32, length: u32)
+    {
+        let mut reversed = 0;
+
+        for bit in 0..length
+        {
+            reversed |= (code >> bit & 1) << (length - 1 - bit);
+        }
+
+        self.write_bits(reversed, length);
+    }
+
+    fn finish(mut self) -> Vec<u8>
+    {
+        if self.bit_count > 0
+        {
+            self.bytes.push(self.bit_buffer as u8);
+        }
+
+        return self.bytes;
+    }
+}
+
+// The fixed Huffman code of a literal byte, the end of block (256) or a length code (257 to 285)
+fn write_literal_code(writer: &mut Bit_Writer, symbol: u32)
+{
+    match symbol
+    {
+        0..=143   => writer.write_code(0x30 + symbol, 8),
+        144..=255 => writer.write_code(0x190 + symbol - 144, 9),
+        256..=279 => writer.write_code(symbol - 256, 7),
+        _         => writer.write_code(0xc0 + symbol - 280, 8),
+    }
+}
+
+fn write_match(writer: &mut Bit_Writer, length: usize, distance: usize)
+{
+    let length_index = LENGTH_BASE.iter().rposition(|&base| base as usize <= length).unwrap();
+
+    write_literal_code(writer, 257 + length_index as u32);
+    writer.write_bits((length - LENGTH_BASE[length_index] as usize) as u32, LENGTH_EXTRA[length_index] as u32);
+
+    let distance_index = DISTANCE_BASE.iter().rposition(|&base| base as usize <= distance).unwrap();
+
+    writer.write_code(distance_index as u32, 5);
+    writer.write_bits((distance - DISTANCE_BASE[distance_index] as usize) as u32, DISTANCE_EXTRA[distance_index] as u32);
+}
+
+fn hash(data: &[u8], position: usize) -> usize
+{
+    let value = (data[position] as usize) << 16 | (data[position + 1] as usize) << 8 | data[position + 2] as usize;
+    return value.wrapping_mul(2654435761) >> (32 - HASH_BITS) & ((1 << HASH_BITS) - 1);
+}
+
+// Compresses the data into a single deflate block with the fixed Huffman codes
+pub fn deflate(data: &[u8]) -> Vec<u8>
+{
+    let mut writer = Bit_Writer::new();
+
+    // The last block and the fixed Huffman codes
+    writer.write_bits(1, 1);
+    writer.write_bits(1, 2);
+
+    // The last position with every hash and for every position the one before it with the same hash
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    let mut previous = vec![usize::MAX; WINDOW_SIZE];
+
+    let mut position = 0;
+
+    let insert = |head: &mut Vec<usize>, previous: &mut Vec<usize>, position: usize| {
+        if position + MIN_MATCH <= data.len()
+        {
+            let key = hash(data, position);
+            previous[position % WINDOW_SIZE] = head[key];
+            head[key] = position;
+        }
+    };
+
+    while position < data.len()
+    {
+        let mut best_length = 0;
+        let mut best_distance = 0;
+
+        if position + MIN_MATCH <= data.len()
+        {
+            let mut candidate = head[hash(data, position)];
+            let max_length = std::cmp::min(MAX_MATCH, data.len() - position);
+
+            for _ in 0..MAX_CHAIN
+            {
+                if candidate == usize::MAX || position - candidate > WINDOW_SIZE - 1
+                {
+                    break;
+                }
+
+                let mut length = 0;
+
+                while length < max_length && data[candidate + length] == data[position + length]
+                {
+                    length += 1;
+                }
+
+                if length > best_length
+                {
+                    best_length = length;
+                    best_distance = position - candidate;
+
+                    if length == max_length
+                    {
+                        break;
+                    }
+                }
+
+                let next = previous[candidate % WINDOW_SIZE];
+
+                // The chain can point to a position that was overwritten by a newer one
+                if next == usize::MAX || next >= candidate
+                {
+                    break;
+                }
+
+                candidate = next;
+            }
+        }
+
+        if best_length >= MIN_MATCH
+        {
+            write_match(&mut writer, best_length, best_distance);
+
+            for skipped in position..position + best_length
+            {
+                insert(&mut head, &mut previous, skipped);
+            }
+
+            position += best_length;
+        } else
+        {
+            write_literal_code(&mut writer, data[position] as u32);
+            insert(&mut head, &mut previous, position);
+            position += 1;
+        }
+    }
+
+    // The end of the block
+    write_literal_code(&mut writer, 256);
+
+    return writer.finish();
+}
+
+fn adler32(data: &[u8]) -> u32
+{
+    let mut a: u32 = 1;
+    let mut b: u32 = 0;
+
+    for chunk in data.chunks(5552)
+    {
+        for &byte in chunk
+        {
+            a += byte as u32;
+            b += a;
+        }
+
+        a %= 65521;
+        b %= 65521;
+    }
+
+    return b << 16 | a;
+}
+
+// The deflate data with the zlib header and checksum around it
+pub fn zlib_compress(data: &[u8]) -> Vec<u8>
+{
+    let mut result = vec![0x78, 0x01];
+
+    result.extend(deflate(data));
+    result.extend(adler32(data).to_be_bytes());
+
+    return result;
+}
+
+pub fn crc32(data: &[u8]) -> u32
+{
+    let mut crc: u32 = 0xffff_ffff;
+
+    for &byte in data
+    {
+        crc ^= byte as u32;
+
+        for _ in 0..8
+        {
+            crc = if crc & 1 != 0 { crc >> 1 ^ 0xedb8_8320 } else { crc >> 1 };
+        }
+    }
+
+    return !crc;
+}
+
+// Appends a chunk with its length and CRC
+pub fn encode_chunk(output: &mut Vec<u8>, kind: &[u8; 4], data: &[u8])
+{
+    output.extend((data.len() as u32).to_be_bytes());
+
+    let start = output.len();
+
+    output.extend(kind);
+    output.extend(data);
+
+    let crc = crc32(&output[start..]);
+    output.extend(crc.to_be_bytes());
+}
+
+// The data of the IHDR chunk for 8 bit RGB
+pub fn header_data(width: u32, height: u32) -> Vec<u8>
+{
+    let mut data = vec![];
+
+    data.extend(width.to_be_bytes());
+    data.extend(height.to_be_bytes());
+    // Bit depth, RGB, deflate, the standard filters and no interlacing
+    data.extend([8, 2, 0, 0, 0]);
+
+    return data;
+}
+
+// The rows of RGB pixels compressed for an IDAT (or fdAT) chunk, without filtering the rows
+pub fn compress_pixels(width: u32, height: u32, pixels: &[u8]) -> Vec<u8>
+{
+    let stride = width as usize * 3;
+    let mut raw = Vec::with_capacity((stride + 1) * height as usize);
+
+    for row in pixels.chunks(stride)
+    {
+        raw.push(0);
+        raw.extend(row);
+    }
+
+    return zlib_compress(&raw);
+}
+
+// A PNG file of RGB pixels, three bytes for every pixel and the rows from the top
+pub fn encode_png(width: u32, height: u32, pixels: &[u8]) -> Vec<u8>
+{
+    let mut output = SIGNATURE.to_vec();
+
+    encode_chunk(&mut output, b"IHDR", &header_data(width, height));
+    encode_chunk(&mut output, b"IDAT", &compress_pixels(width, height, pixels));
+    encode_chunk(&mut output, b"IEND", &[]);
+
+    return output;
+}
+
+// A frame of an animation (APNG here, GIF in gif.rs), the RGB pixels cover the area at (x, y) and
+// are drawn over the frame before it, delay is in milliseconds
+#[derive(Clone, Debug)]
+pub struct Animation_Frame
+{
+    pub x:      u32,
+    pub y:      u32,
+    pub width:  u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+    pub delay:  u32,
+}
+
+// An animated PNG that repeats forever, the first frame has to cover the whole image
+pub fn encode_apng(width: u32, height: u32, frames: &[Animation_Frame]) -> Vec<u8>
+{
+    let mut output = SIGNATURE.to_vec();
+
+    encode_chunk(&mut output, b"IHDR", &header_data(width, height));
+
+    let mut animation_control = vec![];
+    animation_control.extend((frames.len() as u32).to_be_bytes());
+    // The number of times the animation is played, 0 for forever
+    animation_control.extend(0u32.to_be_bytes());
+
+    encode_chunk(&mut output, b"acTL", &animation_control);
+
+    // fcTL and fdAT chunks share the sequence numbers
+    let mut sequence: u32 = 0;
+
+    for (index, frame) in frames.iter().enumerate()
+    {
+        let mut frame_control = vec![];
+
+        frame_control.extend(sequence.to_be_bytes());
+        frame_control.extend(frame.width.to_be_bytes());
+        frame_control.extend(frame.height.to_be_bytes());
+        frame_control.extend(frame.x.to_be_bytes());
+        frame_control.extend(frame.y.to_be_bytes());
+        // The delay as a fraction of a second, in milliseconds
+        frame_control.extend((frame.delay.min(u16::MAX as u32) as u16).to_be_bytes());
+        frame_control.extend(1000u16.to_be_bytes());
+        // Keep the frame when the next one is drawn and draw the next one over it
+        frame_control.extend([0, 0]);
+
+        encode_chunk(&mut output, b"fcTL", &frame_control);
+        sequence += 1;
+
+        let data = compress_pixels(frame.width, frame.height, &frame.pixels);
+
+        if index == 0
+        {
+            encode_chunk(&mut output, b"IDAT", &data);
+        } else
+        {
+            let mut frame_data = sequence.to_be_bytes().to_vec();
+            frame_data.extend(data);
+
+            encode_chunk(&mut output, b"fdAT", &frame_data);
+            sequence += 1;
+        }
+    }
+
+    encode_chunk(&mut output, b"IEND", &[]);
+
+    return output;
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    // Reads the bits of a deflate stream, the counterpart of Bit_Writer
+    struct Bit_Reader<'a>
+    {
+        data:     &'a [u8],
+        position: usize,
+    }
+
+    impl Bit_Reader<'_>
+    {
+        fn bits(&mut self, count: u32) -> u32
+        {
+            let mut value = 0;
+
+            for bit in 0..count
+            {
+                value |= (((self.data[self.position / 8] >> (self.position % 8)) & 1) as u32) << bit;
+                self.position += 1;
+            }
+
+            return value;
+        }
+
+        // Huffman codes start from their highest bit
+        fn code(&mut self, length: u32) -> u32
+        {
+            return (0..length).fold(0, |code, _| code << 1 | self.bits(1));
+        }
+    }
+
+    // Decompresses a single block with the fixed Huffman codes, the only kind deflate writes
+    fn inflate(data: &[u8]) -> Vec<u8>
+    {
+        let mut reader = Bit_Reader { data, position: 0 };
+        let mut output: Vec<u8> = vec![];
+
+        assert_eq!(reader.bits(1), 1, "not the last block");
+        assert_eq!(reader.bits(2), 1, "not the fixed Huffman codes");
+
+        loop
+        {
+            let mut code = reader.code(7);
+
+            let symbol = if code <= 0x17
+            {
+                256 + code
+            } else
+            {
+                code = code << 1 | reader.bits(1);
+
+                match code
+                {
+                    0x30..=0xbf => code - 0x30,
+                    0xc0..=0xc7 => 280 + code - 0xc0,
+                    _           => 144 + (code << 1 | reader.bits(1)) - 0x190,
+                }
+            };
+
+            match symbol
+            {
+                0..=255 => output.push(symbol as u8),
+                256     => return output,
+                _ =>
+                {
+                    let l_idx = (symbol - 257) as usize;
+                    let length = LENGTH_BASE[l_idx] as usize + reader.bits(LENGTH_EXTRA[l_idx] as u32) as usize;
+
+                    let d_idx = reader.code(5) as usize;
+                    let distance = DISTANCE_BASE[d_idx] as usize + reader.bits(DISTANCE_EXTRA[d_idx] as u32) as usize;
+
+                    for _ in 0..length
+                    {
+                        output.push(output[output.len() - distance]);
+                    }
+                },
+            }
+        }
+    }
+
+    #[test]
+    fn checksums()
+    {
+        // The check values of both algorithms
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn deflate_round_trip()
+    {
+        // Pseudo random bytes do not compress, long runs use the longest matches and distances
+        let mut state: u32 = 12345;
+        let random: Vec<u8> = (0..50_000).map(|_| { state = state.wrapping_mul(1_103_515_245).wrapping_add(12345); (state >> 16) as u8 }).collect();
+
+        let mut far = random[..40_000].to_vec();
+        far.extend(&random[..1000]);
+
+        let inputs: Vec<Vec<u8>> = vec![
+            vec![],
+            b"a".to_vec(),
+            b"abcabcabcabcabcabcabcabcabcabcabcabc".to_vec(),
+            vec![0; 100_000],
+            random,
+            far,
+        ];
+
+        for input in inputs
+        {
+            assert!(inflate(&deflate(&input)) == input, "{} bytes", input.len());
+        }
+
+        // Runs of one color shrink to almost nothing
+        assert!(deflate(&[7; 100_000]).len() < 1000);
+    }
+
+    #[test]
+    fn png_chunks()
+    {
+        let png = encode_png(2, 1, &[255, 0, 0, 0, 0, 255]);
+
+        assert_eq!(png[..8], SIGNATURE);
+
+        // IHDR: 2 x 1, 8 bit RGB
+        assert_eq!(png[8..16], [0, 0, 0, 13, b'I', b'H', b'D', b'R']);
+        assert_eq!(png[16..29], [0, 0, 0, 2, 0, 0, 0, 1, 8, 2, 0, 0, 0]);
+        assert_eq!(png[29..33], crc32(&png[12..29]).to_be_bytes());
+
+        // IDAT: zlib around the rows, every row starts with filter 0
+        let length = u32::from_be_bytes([png[33], png[34], png[35], png[36]]) as usize;
+        let idat = &png[41..41 + length];
+
+        assert_eq!(png[37..41], *b"IDAT");
+        assert_eq!(idat[..2], [0x78, 0x01]);
+        assert_eq!(inflate(&idat[2..length - 4]), [0, 255, 0, 0, 0, 0, 255]);
+        assert_eq!(idat[length - 4..], adler32(&[0, 255, 0, 0, 0, 0, 255]).to_be_bytes());
+
+        // IEND always ends the same way
+        assert_eq!(png[png.len() - 12..], [0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]);
+    }
+}
@@ -0,0 +1,101 @@
+// Racing Kings
+//
+// Both sides start on the first two ranks and race their king to the eighth rank:
+// 8/8/8/8/8/8/krbnNBRK/qrbnNBRQ w - - 0 1
+//
+// Checks are not allowed at all, neither giving check nor moving into check. There are no pawns
+// and no castling. The first king to reach the eighth rank wins, but as white moves first black
+// gets one more move to reach it as well, which makes the game a draw
+//
+// Stalemate is a draw, there are no draws because of material as the kings can always race
+
+use crate::chess::*;
+use crate::variant::*;
+
+pub struct Racing_Kings;
+
+impl Racing_Kings
+{
+    fn on_last_rank(board: &Chess_Board, color: i8) -> bool
+    {
+        return board.king_square(color).is_some_and(|square| Chess_Board::get_row(square) == 7);
+    }
+}
+
+impl Rules for Racing_Kings
+{
+    fn name(&self) -> &'static str
+    {
+        return "Racing Kings";
+    }
+
+    fn start_fen(&self) -> &'static str
+    {
+        return "8/8/8/8/8/8/krbnNBRK/qrbnNBRQ w - - 0 1";
+    }
+
+    // The normal legal moves without the ones that give check
+    fn legal_moves(&self, board: &mut Chess_Board) -> Vec<Move>
+    {
+        let mut results = vec![];
+
+        for candidate in board.standard_legal_moves()
+        {
+            board.make_move(candidate);
+            let gives_check = board.details.is_check != 0;
+            board.unmake_move();
+
+            if !gives_check
+            {
+                results.push(candidate);
+            }
+        }
+
+        return results;
+    }
+
+    fn variant_outcome(&self, board: &Chess_Board) -> Option<Outcome>
+    {
+        let white = Self::on_last_rank(board, WHITE);
+        let black = Self::on_last_rank(board, BLACK);
+
+        if white && black
+        {
+            return Some(Outcome::Variant_Draw);
+        }
+
+        if black
+        {
+            return Some(Outcome::Variant_Win(BLACK));
+        }
+
+        if !white
+        {
+            return None;
+        }
+
+        // White got there first, the game goes on if black can reach the last rank with the next move
+        if board.details.side_to_move == BLACK
+        {
+            let mut copy = board.clone();
+
+            let catches_up = self.legal_moves(&mut copy)
+                                 .iter()
+                                 .any(|candidate| candidate.piece == -KING && Chess_Board::get_row(candidate.target) == 7);
+
+            if catches_up
+            {
+                return None;
+            }
+        }
+
+        return Some(Outcome::Variant_Win(WHITE));
+    }
+
+    fn material_draws(&self) -> bool
+    {
+        return false;
+    }
+}
+
+pub static RACING_KINGS: Racing_Kings = Racing_Kings;
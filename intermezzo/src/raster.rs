@@ -0,0 +1,374 @@
+// Drawing shapes into pixels, used to turn a Drawing (drawing.rs) into a PNG
+//
+// Every pixel is split into 4 x 4 samples and a shape covers the pixel by the share of samples
+// inside it, which smooths the edges. Colors are blended with what is already there using their
+// alpha and that share
+//
+// Text is drawn with a built in 5 x 7 bitmap font, scaled to the height of a capital letter
+
+use crate::drawing::Color;
+use crate::png::encode_png;
+
+const SAMPLES: usize = 4;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Image
+{
+    pub width:  u32,
+    pub height: u32,
+    // Three bytes (red, green, blue) for every pixel, the rows from the top
+    pub pixels: Vec<u8>,
+}
+
+// The rows of a character of the bitmap font, the lowest 5 bits of every row from left to right
+fn glyph(letter: char) -> [u8; 7]
+{
+    return match letter
+    {
+        '0' => [0x0e, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0e],
+        '1' => [0x04, 0x0c, 0x04, 0x04, 0x04, 0x04, 0x0e],
+        '2' => [0x0e, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1f],
+        '3' => [0x1f, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0e],
+        '4' => [0x02, 0x06, 0x0a, 0x12, 0x1f, 0x02, 0x02],
+        '5' => [0x1f, 0x10, 0x1e, 0x01, 0x01, 0x11, 0x0e],
+        '6' => [0x06, 0x08, 0x10, 0x1e, 0x11, 0x11, 0x0e],
+        '7' => [0x1f, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0e, 0x11, 0x11, 0x0e, 0x11, 0x11, 0x0e],
+        '9' => [0x0e, 0x11, 0x11, 0x0f, 0x01, 0x02, 0x0c],
+        'A' => [0x0e, 0x11, 0x11, 0x11, 0x1f, 0x11, 0x11],
+        'B' => [0x1e, 0x11, 0x11, 0x1e, 0x11, 0x11, 0x1e],
+        'C' => [0x0e, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0e],
+        'D' => [0x1c, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1c],
+        'E' => [0x1f, 0x10, 0x10, 0x1e, 0x10, 0x10, 0x1f],
+        'F' => [0x1f, 0x10, 0x10, 0x1e, 0x10, 0x10, 0x10],
+        'G' => [0x0e, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0f],
+        'H' => [0x11, 0x11, 0x11, 0x1f, 0x11, 0x11, 0x11],
+        'I' => [0x0e, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0e],
+        'J' => [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0c],
+        'K' => [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+        'L' => [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1f],
+        'M' => [0x11, 0x1b, 0x15, 0x15, 0x11, 0x11, 0x11],
+        'N' => [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+        'O' => [0x0e, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0e],
+        'P' => [0x1e, 0x11, 0x11, 0x1e, 0x10, 0x10, 0x10],
+        'Q' => [0x0e, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0d],
+        'R' => [0x1e, 0x11, 0x11, 0x1e, 0x14, 0x12, 0x11],
+        'S' => [0x0f, 0x10, 0x10, 0x0e, 0x01, 0x01, 0x1e],
+        'T' => [0x1f, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+        'U' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0e],
+        'V' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x0a, 0x04],
+        'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0a],
+        'X' => [0x11, 0x11, 0x0a, 0x04, 0x0a, 0x11, 0x11],
+        'Y' => [0x11, 0x11, 0x11, 0x0a, 0x04, 0x04, 0x04],
+        'Z' => [0x1f, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1f],
+        'a' => [0x00, 0x00, 0x0e, 0x01, 0x0f, 0x11, 0x0f],
+        'b' => [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x1e],
+        'c' => [0x00, 0x00, 0x0e, 0x10, 0x10, 0x11, 0x0e],
+        'd' => [0x01, 0x01, 0x0d, 0x13, 0x11, 0x11, 0x0f],
+        'e' => [0x00, 0x00, 0x0e, 0x11, 0x1f, 0x10, 0x0e],
+        'f' => [0x06, 0x09, 0x08, 0x1c, 0x08, 0x08, 0x08],
+        'g' => [0x00, 0x0f, 0x11, 0x11, 0x0f, 0x01, 0x0e],
+        'h' => [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x11],
+        'i' => [0x04, 0x00, 0x0c, 0x04, 0x04, 0x04, 0x0e],
+        'j' => [0x02, 0x00, 0x06, 0x02, 0x02, 0x12, 0x0c],
+        'k' => [0x10, 0x10, 0x12, 0x14, 0x18, 0x14, 0x12],
+        'l' => [0x0c, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0e],
+        'm' => [0x00, 0x00, 0x1a, 0x15, 0x15, 0x11, 0x11],
+        'n' => [0x00, 0x00, 0x16, 0x19, 0x11, 0x11, 0x11],
+        'o' => [0x00, 0x00, 0x0e, 0x11, 0x11, 0x11, 0x0e],
+        'p' => [0x00, 0x00, 0x1e, 0x11, 0x1e, 0x10, 0x10],
+        'q' => [0x00, 0x00, 0x0d, 0x13, 0x0f, 0x01, 0x01],
+        'r' => [0x00, 0x00, 0x16, 0x19, 0x10, 0x10, 0x10],
+        's' => [0x00, 0x00, 0x0e, 0x10, 0x0e, 0x01, 0x1e],
+        't' => [0x08, 0x08, 0x1c, 0x08, 0x08, 0x09, 0x06],
+        'u' => [0x00, 0x00, 0x11, 0x11, 0x11, 0x13, 0x0d],
+        'v' => [0x00, 0x00, 0x11, 0x11, 0x11, 0x0a, 0x04],
+        'w' => [0x00, 0x00, 0x11, 0x11, 0x15, 0x15, 0x0a],
+        'x' => [0x00, 0x00, 0x11, 0x0a, 0x04, 0x0a, 0x11],
+        'y' => [0x00, 0x00, 0x11, 0x11, 0x0f, 0x01, 0x0e],
+        'z' => [0x00, 0x00, 0x1f, 0x02, 0x04, 0x08, 0x1f],
+        '+' => [0x00, 0x04, 0x04, 0x1f, 0x04, 0x04, 0x00],
+        '-' => [0x00, 0x00, 0x00, 0x1f, 0x00, 0x00, 0x00],
+        '=' => [0x00, 0x00, 0x1f, 0x00, 0x1f, 0x00, 0x00],
+        '#' => [0x0a, 0x0a, 0x1f, 0x0a, 0x1f, 0x0a, 0x0a],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0c, 0x0c],
+        ',' => [0x00, 0x00, 0x00, 0x00, 0x0c, 0x04, 0x08],
+        ':' => [0x00, 0x0c, 0x0c, 0x00, 0x0c, 0x0c, 0x00],
+        '(' => [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02],
+        ')' => [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08],
+        '[' => [0x0e, 0x08, 0x08, 0x08, 0x08, 0x08, 0x0e],
+        ']' => [0x0e, 0x02, 0x02, 0x02, 0x02, 0x02, 0x0e],
+        '/' => [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00],
+        '!' => [0x04, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04],
+        '?' => [0x0e, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04],
+        '%' => [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03],
+        '*' => [0x00, 0x04, 0x15, 0x0e, 0x15, 0x04, 0x00],
+        '@' => [0x0e, 0x11, 0x01, 0x0d, 0x15, 0x15, 0x0e],
+        '\'' => [0x0c, 0x04, 0x08, 0x00, 0x00, 0x00, 0x00],
+        _   => [0; 7],
+    };
+}
+
+// The shortest distance from a point to a line segment
+fn distance_to_segment(point: (f64, f64), start: (f64, f64), end: (f64, f64)) -> f64
+{
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    let length_squared = dx * dx + dy * dy;
+
+    let t = if length_squared == 0.0 { 0.0 } else { (((point.0 - start.0) * dx + (point.1 - start.1) * dy) / length_squared).clamp(0.0, 1.0) };
+
+    let (closest_x, closest_y) = (start.0 + t * dx, start.1 + t * dy);
+
+    return ((point.0 - closest_x).powi(2) + (point.1 - closest_y).powi(2)).sqrt();
+}
+
+// Whether the point is inside the polygon, with the nonzero winding rule
+fn inside_polygon(point: (f64, f64), points: &[(f64, f64)]) -> bool
+{
+    let mut winding = 0;
+
+    for index in 0..points.len()
+    {
+        let start = points[index];
+        let end = points[(index + 1) % points.len()];
+
+        // Which side of the edge the point is on
+        let side = (end.0 - start.0) * (point.1 - start.1) - (point.0 - start.0) * (end.1 - start.1);
+
+        if start.1 <= point.1
+        {
+            if end.1 > point.1 && side > 0.0
+            {
+                winding += 1;
+            }
+        } else if end.1 <= point.1 && side < 0.0
+        {
+            winding -= 1;
+        }
+    }
+
+    return winding != 0;
+}
+
+impl Image
+{
+    pub fn new(width: u32, height: u32, background: Color) -> Self
+    {
+        let pixels = [background.r, background.g, background.b].repeat((width * height) as usize);
+
+        return Image { width, height, pixels };
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 3]
+    {
+        let index = ((y * self.width + x) * 3) as usize;
+        return [self.pixels[index], self.pixels[index + 1], self.pixels[index + 2]];
+    }
+
+    pub fn to_png(&self) -> Vec<u8>
+    {
+        return encode_png(self.width, self.height, &self.pixels);
+    }
+
+    // The smallest rectangle (x, y, width, height) holding every pixel that differs from the
+    // other image of the same size, None when they are the same
+    pub fn changed_area(&self, other: &Image) -> Option<(u32, u32, u32, u32)>
+    {
+        let mut area: Option<(u32, u32, u32, u32)> = None;
+
+        for y in 0..self.height
+        {
+            for x in 0..self.width
+            {
+                if self.pixel(x, y) == other.pixel(x, y)
+                {
+                    continue;
+                }
+
+                // Kept as (left, top, right, bottom) until the end
+                area = Some(match area
+                {
+                    Some((left, top, right, bottom)) => (left.min(x), top.min(y), right.max(x), bottom.max(y)),
+                    None                             => (x, y, x, y),
+                });
+            }
+        }
+
+        return area.map(|(left, top, right, bottom)| (left, top, right - left + 1, bottom - top + 1));
+    }
+
+    pub fn crop(&self, x: u32, y: u32, width: u32, height: u32) -> Image
+    {
+        let mut pixels = Vec::with_capacity((width * height * 3) as usize);
+
+        for row in y..y + height
+        {
+            let start = ((row * self.width + x) * 3) as usize;
+            pixels.extend(&self.pixels[start..start + (width * 3) as usize]);
+        }
+
+        return Image { width, height, pixels };
+    }
+
+    // Mixes the color into the pixel, coverage is the share of the pixel covered from 0 to 1
+    fn blend(&mut self, x: usize, y: usize, color: Color, coverage: f64)
+    {
+        let alpha = coverage * color.a as f64 / 255.0;
+
+        if alpha <= 0.0
+        {
+            return;
+        }
+
+        let index = (y * self.width as usize + x) * 3;
+
+        for (channel, value) in [color.r, color.g, color.b].iter().enumerate()
+        {
+            let old = self.pixels[index + channel] as f64;
+            self.pixels[index + channel] = (old + (*value as f64 - old) * alpha).round() as u8;
+        }
+    }
+
+    // Covers every pixel in the box by the share of its samples for which inside is true
+    fn fill_samples(&mut self, left: f64, top: f64, right: f64, bottom: f64, color: Color, inside: impl Fn((f64, f64)) -> bool)
+    {
+        let x_start = left.floor().max(0.0) as usize;
+        let y_start = top.floor().max(0.0) as usize;
+        let x_end = (right.ceil().max(0.0) as usize).min(self.width as usize);
+        let y_end = (bottom.ceil().max(0.0) as usize).min(self.height as usize);
+
+        for y in y_start..y_end
+        {
+            for x in x_start..x_end
+            {
+                let mut count = 0;
+
+                for sample_y in 0..SAMPLES
+                {
+                    for sample_x in 0..SAMPLES
+                    {
+                        let point = (x as f64 + (sample_x as f64 + 0.5) / SAMPLES as f64, y as f64 + (sample_y as f64 + 0.5) / SAMPLES as f64);
+
+                        if inside(point)
+                        {
+                            count += 1;
+                        }
+                    }
+                }
+
+                self.blend(x, y, color, count as f64 / (SAMPLES * SAMPLES) as f64);
+            }
+        }
+    }
+
+    pub fn fill_polygon(&mut self, points: &[(f64, f64)], color: Color)
+    {
+        if points.len() < 3
+        {
+            return;
+        }
+
+        let left = points.iter().map(|point| point.0).fold(f64::MAX, f64::min);
+        let right = points.iter().map(|point| point.0).fold(f64::MIN, f64::max);
+        let top = points.iter().map(|point| point.1).fold(f64::MAX, f64::min);
+        let bottom = points.iter().map(|point| point.1).fold(f64::MIN, f64::max);
+
+        self.fill_samples(left, top, right, bottom, color, |point| inside_polygon(point, points));
+    }
+
+    // A line of the given width through the points, closed draws the line back to the first point
+    pub fn stroke_lines(&mut self, points: &[(f64, f64)], closed: bool, width: f64, color: Color)
+    {
+        if points.is_empty()
+        {
+            return;
+        }
+
+        let half = width / 2.0;
+
+        let left = points.iter().map(|point| point.0).fold(f64::MAX, f64::min) - half;
+        let right = points.iter().map(|point| point.0).fold(f64::MIN, f64::max) + half;
+        let top = points.iter().map(|point| point.1).fold(f64::MAX, f64::min) - half;
+        let bottom = points.iter().map(|point| point.1).fold(f64::MIN, f64::max) + half;
+
+        let segments = if closed { points.len() } else { points.len() - 1 };
+
+        self.fill_samples(left, top, right, bottom, color, |point| {
+            (0..segments.max(1)).any(|index| distance_to_segment(point, points[index], points[(index + 1) % points.len()]) <= half)
+        });
+    }
+
+    pub fn fill_circle(&mut self, center: (f64, f64), radius: f64, color: Color)
+    {
+        self.fill_samples(center.0 - radius, center.1 - radius, center.0 + radius, center.1 + radius, color, |point| {
+            (point.0 - center.0).powi(2) + (point.1 - center.1).powi(2) <= radius * radius
+        });
+    }
+
+    pub fn stroke_circle(&mut self, center: (f64, f64), radius: f64, width: f64, color: Color)
+    {
+        let outer = radius + width / 2.0;
+
+        self.fill_samples(center.0 - outer, center.1 - outer, center.0 + outer, center.1 + outer, color, |point| {
+            let distance = ((point.0 - center.0).powi(2) + (point.1 - center.1).powi(2)).sqrt();
+            (distance - radius).abs() <= width / 2.0
+        });
+    }
+
+    // Every character is 5 font pixels wide with one pixel between them, height is 7 font pixels
+    pub fn draw_text(&mut self, position: (f64, f64), height: f64, color: Color, text: &str)
+    {
+        let scale = height / 7.0;
+
+        for (index, letter) in text.chars().enumerate()
+        {
+            let rows = glyph(letter);
+            let left = position.0 + index as f64 * 6.0 * scale;
+
+            self.fill_samples(left, position.1, left + 5.0 * scale, position.1 + height, color, |point| {
+                let col = ((point.0 - left) / scale) as usize;
+                let row = ((point.1 - position.1) / scale) as usize;
+
+                row < 7 && col < 5 && rows[row] >> (4 - col) & 1 != 0
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    const WHITE_COLOR: Color = Color::rgb(255, 255, 255);
+    const BLACK_COLOR: Color = Color::rgb(0, 0, 0);
+
+    #[test]
+    fn polygon_on_whole_pixels()
+    {
+        let mut image = Image::new(8, 8, WHITE_COLOR);
+        image.fill_polygon(&[(2.0, 2.0), (6.0, 2.0), (6.0, 6.0), (2.0, 6.0)], BLACK_COLOR);
+
+        for y in 0..8
+        {
+            for x in 0..8
+            {
+                let inside = (2..6).contains(&x) && (2..6).contains(&y);
+                assert_eq!(image.pixel(x, y), if inside { [0, 0, 0] } else { [255, 255, 255] }, "({}, {})", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn edges_are_smoothed()
+    {
+        // Half of the middle column is covered, a transparent color only mixes in partly
+        let mut image = Image::new(3, 1, WHITE_COLOR);
+        image.fill_polygon(&[(0.0, 0.0), (1.5, 0.0), (1.5, 1.0), (0.0, 1.0)], BLACK_COLOR);
+        image.fill_polygon(&[(2.0, 0.0), (3.0, 0.0), (3.0, 1.0), (2.0, 1.0)], Color::rgba(0, 0, 0, 51));
+
+        assert_eq!(image.pixel(0, 0), [0, 0, 0]);
+        assert_eq!(image.pixel(1, 0), [128, 128, 128]);
+        assert_eq!(image.pixel(2, 0), [204, 204, 204]);
+    }
+}
@@ -0,0 +1,365 @@
+// A referee for the variants where the players can not see the whole board
+//
+// Kriegspiel  -> the players only see their own pieces. A player tries moves until the referee
+//                accepts one, every try that is not legal is announced as "illegal". After a move
+//                the referee announces captures (the square and whether a pawn or a piece was
+//                taken), checks (along the rank, file, long diagonal, short diagonal or by a
+//                knight) and how many captures with pawns ("pawn tries") the next player has
+// Fog of War  -> the players see their own pieces and every square those pieces can move to, see
+//                fog_of_war.rs for the rules. There is nothing to announce apart from the end of
+//                the game
+//
+// The referee keeps the real game, the players (or the GUI drawing the board for each of them)
+// ask for a Player_View with only what that player is allowed to know
+//
+// Moves are given as the origin and target square, as the SAN of a move depends on pieces the
+// player can not see (captures, checks and disambiguation)
+
+use crate::chess::*;
+use crate::fog_of_war::*;
+use crate::game::*;
+use crate::notation::piece_from_char;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Referee_Mode
+{
+    Kriegspiel,
+    Fog_Of_War,
+}
+
+// The line a check comes from, seen from the king that is in check
+// Every square is on two diagonals of different lengths, the longer one is the long diagonal
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Check_Direction
+{
+    Rank,
+    File,
+    Long_Diagonal,
+    Short_Diagonal,
+    Knight,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Announcement
+{
+    // The move that was tried is not legal, the same player has to try another one
+    Illegal,
+    Capture { square: usize, pawn: bool },
+    Check(Check_Direction),
+    // The number of captures with a pawn the player to move can make, only announced when there are any
+    Pawn_Tries(u32),
+    Game_Over(Game_Result, Termination),
+}
+
+impl Announcement
+{
+    // What the referee says, e.g. "pawn captured on e4" or "check on the long diagonal"
+    pub fn description(self) -> String
+    {
+        return match self
+        {
+            Announcement::Illegal                   => String::from("illegal"),
+            Announcement::Capture { square, pawn }  => format!("{} captured on {}", if pawn { "pawn" } else { "piece" }, Chess_Board::square_name(square)),
+            Announcement::Check(direction)          =>
+            {
+                let line = match direction
+                {
+                    Check_Direction::Rank           => "on the rank",
+                    Check_Direction::File           => "on the file",
+                    Check_Direction::Long_Diagonal  => "on the long diagonal",
+                    Check_Direction::Short_Diagonal => "on the short diagonal",
+                    Check_Direction::Knight         => "by a knight",
+                };
+
+                format!("check {}", line)
+            },
+            Announcement::Pawn_Tries(1)             => String::from("1 pawn try"),
+            Announcement::Pawn_Tries(count)         => format!("{} pawn tries", count),
+            Announcement::Game_Over(result, ending) => format!("{} by {}", result.to_pgn(), ending.description()),
+        };
+    }
+}
+
+// What one player can see of the board
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Player_View
+{
+    pub color: i8,
+    // The pieces on the visible squares, 0 on every other square
+    pub board: [i8; 64],
+    // A bit for every visible square, a1 is bit 0
+    pub visible: u64,
+}
+
+impl Player_View
+{
+    pub fn is_visible(&self, square: usize) -> bool
+    {
+        return self.visible & (1 << square) != 0;
+    }
+}
+
+pub struct Referee
+{
+    pub mode: Referee_Mode,
+    game: Game,
+    // Everything that was announced, both players hear every announcement
+    announcements: Vec<Announcement>,
+}
+
+impl Referee
+{
+    pub fn new(mode: Referee_Mode) -> Self
+    {
+        let game = match mode
+        {
+            Referee_Mode::Kriegspiel => Game::new(),
+            Referee_Mode::Fog_Of_War => Game::with_rules(&FOG_OF_WAR),
+        };
+
+        return Referee { mode, game, announcements: vec![] };
+    }
+
+    // The real game, only to be shown to the players once it is over
+    pub fn game(&self) -> &Game
+    {
+        return &self.game;
+    }
+
+    pub fn announcements(&self) -> &[Announcement]
+    {
+        return &self.announcements;
+    }
+
+    pub fn side_to_move(&self) -> i8
+    {
+        return self.game.side_to_move();
+    }
+
+    pub fn is_over(&self) -> bool
+    {
+        return self.game.is_over();
+    }
+
+    // The board as the player of the given color sees it
+    pub fn view(&self, color: i8) -> Player_View
+    {
+        let board = self.game.board();
+        let mut visible: u64 = 0;
+
+        for square in 0..64
+        {
+            if board.board[square].signum() == color
+            {
+                visible |= 1 << square;
+            }
+        }
+
+        // In Fog of War the squares the pieces can move to, including the pawn taken en passant
+        if self.mode == Referee_Mode::Fog_Of_War
+        {
+            for candidate in board.pseudo_legal_moves(color)
+            {
+                visible |= 1 << candidate.target;
+
+                if candidate.kind == Move_Kind::En_Passant
+                {
+                    visible |= 1 << (candidate.target as i8 - color * 8) as usize;
+                }
+            }
+        }
+
+        let mut view = Player_View { color, board: [0; 64], visible };
+
+        for square in 0..64
+        {
+            if view.is_visible(square)
+            {
+                view.board[square] = board.board[square];
+            }
+        }
+
+        return view;
+    }
+
+    // Tries a move in coordinates ("e2e4", "e7e8q"), see try_move
+    pub fn try_text(&mut self, color: i8, text: &str) -> Result<Vec<Announcement>, String>
+    {
+        let text = text.trim();
+
+        let (Some(origin), Some(target)) = (text.get(0..2).and_then(Chess_Board::parse_square), text.get(2..4).and_then(Chess_Board::parse_square)) else
+        {
+            return Err(format!("{} is not a move, moves are written as the origin and target square (e2e4)", text));
+        };
+
+        let promotion = match text.get(4..)
+        {
+            None | Some("") => 0,
+            Some(letter)    => match piece_from_char(letter.chars().next().unwrap().to_ascii_uppercase())
+            {
+                Some(piece) if (KNIGHT..=QUEEN).contains(&piece) => piece,
+                _ => return Err(format!("{} is not a piece to promote to", letter)),
+            },
+        };
+
+        return self.try_move(color, origin, target, promotion);
+    }
+
+    // Tries a move for the player of the given color, a pawn reaching the last rank becomes a
+    // queen unless another promotion piece is given
+    // Returns what the referee announces, Err for moves the player could have known to be
+    // impossible (not their turn, no piece of theirs on the origin or a piece that can not move
+    // that way even on an empty board). In Fog of War every move that is not legal is an Err
+    pub fn try_move(&mut self, color: i8, origin: usize, target: usize, promotion: i8) -> Result<Vec<Announcement>, String>
+    {
+        if self.game.is_over()
+        {
+            return Err(format!("the game is over ({})", self.game.result().to_pgn()));
+        }
+
+        if color != self.game.side_to_move()
+        {
+            return Err(String::from("it is not your turn"));
+        }
+
+        if self.game.board().board[origin].signum() != color
+        {
+            return Err(format!("there is no piece of yours on {}", Chess_Board::square_name(origin)));
+        }
+
+        let wanted = if promotion == 0 { QUEEN } else { promotion.abs() };
+
+        let found = self.game
+                        .legal_moves()
+                        .into_iter()
+                        .find(|legal_move| {
+                               legal_move.origin == origin
+                            && legal_move.target == target
+                            && (legal_move.promotion == 0 || legal_move.promotion.abs() == wanted)
+                        });
+
+        let Some(played) = found else
+        {
+            if    self.mode == Referee_Mode::Kriegspiel
+               && self.possible_on_view(color, origin, target)
+            {
+                self.announcements.push(Announcement::Illegal);
+                return Ok(vec![Announcement::Illegal]);
+            }
+
+            return Err(format!("{}{} is not possible", Chess_Board::square_name(origin), Chess_Board::square_name(target)));
+        };
+
+        self.game.play(played)?;
+
+        let mut results = vec![];
+
+        if self.mode == Referee_Mode::Kriegspiel
+        {
+            if played.capture != 0
+            {
+                let square = if played.kind == Move_Kind::En_Passant { (target as i8 - color * 8) as usize } else { target };
+                results.push(Announcement::Capture { square, pawn: played.capture.abs() == PAWN });
+            }
+
+            for direction in check_directions(self.game.board(), -color)
+            {
+                results.push(Announcement::Check(direction));
+            }
+
+            let pawn_tries = self.game
+                                 .legal_moves()
+                                 .iter()
+                                 .filter(|legal_move| legal_move.piece.abs() == PAWN && legal_move.capture != 0)
+                                 .count() as u32;
+
+            if pawn_tries > 0
+            {
+                results.push(Announcement::Pawn_Tries(pawn_tries));
+            }
+        }
+
+        if let Some(termination) = self.game.termination()
+        {
+            results.push(Announcement::Game_Over(self.game.result(), termination));
+        }
+
+        self.announcements.extend(results.iter().copied());
+
+        return Ok(results);
+    }
+
+    // Whether the move could be legal as far as the player knows: it is a move on a board with only
+    // their own pieces, or a pawn trying to capture
+    fn possible_on_view(&self, color: i8, origin: usize, target: usize) -> bool
+    {
+        let mut own = self.game.board().clone();
+
+        for square in 0..64
+        {
+            if own.board[square].signum() != color
+            {
+                own.board[square] = 0;
+            }
+        }
+
+        if own.pseudo_legal_moves(color).iter().any(|candidate| candidate.origin == origin && candidate.target == target)
+        {
+            return true;
+        }
+
+        return    own.board[origin] == color * PAWN
+               && own.board[target] == 0
+               && Chess_Board::get_row(target) as i8 == Chess_Board::get_row(origin) as i8 + color
+               && Chess_Board::get_col(target).abs_diff(Chess_Board::get_col(origin)) == 1;
+    }
+}
+
+// The directions the king of the given color is checked from, two for a double check
+pub fn check_directions(board: &Chess_Board, color: i8) -> Vec<Check_Direction>
+{
+    let mut results = vec![];
+
+    let Some(king) = board.king_square(color) else { return results };
+
+    let king_row = Chess_Board::get_row(king) as i32;
+    let king_col = Chess_Board::get_col(king) as i32;
+
+    // The lengths of the diagonal going up to the right and the one going up to the left
+    let rising_length  = 8 - (king_col - king_row).abs();
+    let falling_length = 8 - (king_col + king_row - 7).abs();
+
+    for square in 0..64
+    {
+        let piece = board.board[square];
+
+        if    piece.signum() != -color
+           || !board.piece_moves(square).iter().any(|&value| board.decode_move(square, value).target == king)
+        {
+            continue;
+        }
+
+        let row = Chess_Board::get_row(square) as i32;
+        let col = Chess_Board::get_col(square) as i32;
+
+        let direction = if piece.abs() == KNIGHT
+        {
+            Check_Direction::Knight
+        } else if row == king_row
+        {
+            Check_Direction::Rank
+        } else if col == king_col
+        {
+            Check_Direction::File
+        } else
+        {
+            let (length, other) = if row - king_row == col - king_col { (rising_length, falling_length) } else { (falling_length, rising_length) };
+
+            if length > other { Check_Direction::Long_Diagonal } else { Check_Direction::Short_Diagonal }
+        };
+
+        results.push(direction);
+    }
+
+    return results;
+}
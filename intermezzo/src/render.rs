@@ -0,0 +1,255 @@
+// Drawing the board as text
+//
+// render returns the board as a String, what it looks like is set with Render_Options:
+//
+// figurines    -> Unicode chess pieces (♔ ♚) instead of letters (K k)
+// colors       -> light and dark squares and the highlights as ANSI background colors, the board
+//                 is then drawn without the grid lines
+// coordinates  -> the rank numbers on the left and the files below the board
+// flipped      -> black at the bottom
+// last_move    -> highlights the origin and target of the last move
+// check        -> highlights the king that is in check
+// marked       -> a bit for every square to highlight, e.g. where a piece can move to
+//
+// Without colors the highlights are drawn inside the grid: "[ P ]" for the last move, "! K !" for
+// the king in check and "  .  " or " (n) " for the marked squares
+//
+// Render_Options::plain() (no colors or figurines, with coordinates) gives the grid that
+// print_board prints and Display writes, both go through render
+//   + --- + --- + --- + --- + --- + --- + --- + --- +
+// 8 |  r  |  n  |  b  |  q  |  k  |  b  |  n  |  r  |
+//   + --- + --- + --- + --- + --- + --- + --- + --- +
+// ...
+//
+// With colors and figurines every square is three characters wide:
+// 8  ♜  ♞  ♝  ♛  ♚  ♝  ♞  ♜
+// ...
+//    a  b  c  d  e  f  g  h
+//
+// A Fairy_Board (fairy.rs) is drawn the same way with its own number of files and ranks, the
+// archbishop and chancellor as letters (A, C) as there are no figurines for them
+
+use crate::chess::*;
+use crate::fairy::*;
+use crate::geometry::*;
+use crate::notation::piece_to_char;
+
+use std::fmt;
+
+// Background colors from the 256 color palette
+const LIGHT_SQUARE: u8 = 180;
+const DARK_SQUARE: u8 = 137;
+const LAST_MOVE_LIGHT: u8 = 186;
+const LAST_MOVE_DARK: u8 = 143;
+const MARKED_SQUARE: u8 = 107;
+const CHECK_SQUARE: u8 = 160;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Render_Options
+{
+    pub figurines:   bool,
+    pub colors:      bool,
+    pub coordinates: bool,
+    pub flipped:     bool,
+    pub last_move:   bool,
+    pub check:       bool,
+    pub marked:      u64,
+}
+
+impl Render_Options
+{
+    // The plain grid with coordinates, as printed by print_board
+    pub fn plain() -> Self
+    {
+        return Render_Options { coordinates: true, ..Default::default() };
+    }
+}
+
+// The Unicode figurine of a piece, filled is used for both colors when the color of the piece
+// is shown some other way (a foreground color), otherwise white pieces are the outlined ones
+pub fn figurine(piece: i8, filled: bool) -> char
+{
+    let index = match piece.abs()
+    {
+        KING   => 0,
+        QUEEN  => 1,
+        ROOK   => 2,
+        BISHOP => 3,
+        KNIGHT => 4,
+        PAWN   => 5,
+        DUCK   => return '●',
+        _      => return piece_to_char(piece),
+    };
+
+    let outlined = ['♔', '♕', '♖', '♗', '♘', '♙'];
+    let solid    = ['♚', '♛', '♜', '♝', '♞', '♟'];
+
+    return if piece > 0 && !filled { outlined[index] } else { solid[index] };
+}
+
+// The squares drawn highlighted when the options ask for them
+struct Highlights<'a>
+{
+    last_move:    Option<(usize, usize)>,
+    checked_king: Option<usize>,
+    marked:       &'a dyn Fn(usize) -> bool,
+}
+
+// The squares of a board of any size, the square numbers are those of the geometry. letter is
+// the letter of a piece, also used for the pieces without a figurine
+fn render_squares(geometry: Board_Geometry, pieces: &[i8], highlights: &Highlights, letter: fn(i8) -> char, options: &Render_Options) -> String
+{
+    let rows: Vec<usize> = if options.flipped { (0..geometry.ranks).collect() } else { (0..geometry.ranks).rev().collect() };
+    let cols: Vec<usize> = if options.flipped { (0..geometry.files).rev().collect() } else { (0..geometry.files).collect() };
+
+    let mut result = String::new();
+
+    let grid_line = if options.coordinates { "  +" } else { "+" }.to_string() + &" --- +".repeat(geometry.files) + "\n";
+
+    if !options.colors
+    {
+        result.push_str(&grid_line);
+    }
+
+    for &row in &rows
+    {
+        match (options.coordinates, options.colors)
+        {
+            (true, true)  => result.push_str(&format!("{} ", row + 1)),
+            (true, false) => result.push_str(&format!("{} |", row + 1)),
+            (false, true) => {},
+            (false, false) => result.push('|'),
+        }
+
+        for &col in &cols
+        {
+            let square = geometry.square(col, row);
+            let piece = pieces[square];
+
+            let glyph = if options.figurines && piece.abs() <= DUCK { figurine(piece, options.colors) } else { letter(piece) };
+
+            let checked = highlights.checked_king == Some(square);
+            let marked = (highlights.marked)(square);
+            let last_move = highlights.last_move.is_some_and(|(origin, target)| square == origin || square == target);
+
+            if options.colors
+            {
+                let light = (row + col) % 2 == 1;
+
+                let background = if checked
+                {
+                    CHECK_SQUARE
+                } else if marked
+                {
+                    MARKED_SQUARE
+                } else if last_move
+                {
+                    if light { LAST_MOVE_LIGHT } else { LAST_MOVE_DARK }
+                } else
+                {
+                    if light { LIGHT_SQUARE } else { DARK_SQUARE }
+                };
+
+                let foreground = match piece
+                {
+                    DUCK           => "\x1b[38;5;226m",
+                    _ if piece > 0 => "\x1b[1;38;5;231m",
+                    _              => "\x1b[38;5;16m",
+                };
+
+                result.push_str(&format!("\x1b[48;5;{}m{} {} \x1b[0m", background, foreground, glyph));
+                continue;
+            }
+
+            let cell = if checked
+            {
+                format!("! {} !", glyph)
+            } else if marked
+            {
+                if piece == 0 { String::from("  .  ") } else { format!(" ({}) ", glyph) }
+            } else if last_move
+            {
+                format!("[ {} ]", glyph)
+            } else
+            {
+                format!("  {}  ", glyph)
+            };
+
+            result.push_str(&cell);
+            result.push('|');
+        }
+
+        result.push('\n');
+
+        if !options.colors
+        {
+            result.push_str(&grid_line);
+        }
+    }
+
+    if options.coordinates
+    {
+        let width = if options.colors { 3 } else { 6 };
+        result.push_str(if options.colors { "  " } else { "   " });
+
+        for &col in &cols
+        {
+            let file = (b'a' + col as u8) as char;
+            result.push_str(&format!("{:^width$}", file, width = width));
+        }
+
+        result.push('\n');
+    }
+
+    return result;
+}
+
+impl Chess_Board
+{
+    pub fn render(&self, options: &Render_Options) -> String
+    {
+        let highlights = Highlights
+        {
+            last_move: self.moves.last().filter(|_| options.last_move).map(|last| (last.origin, last.target)),
+            checked_king: self.king_square(self.details.side_to_move).filter(|_| options.check && self.details.is_check != 0),
+            marked: &|square| options.marked & 1u64 << square != 0,
+        };
+
+        return render_squares(Board_Geometry::new(8, 8).unwrap(), &self.board, &highlights, piece_to_char, options);
+    }
+}
+
+impl Fairy_Board
+{
+    // The same drawing as Chess_Board::render, the archbishop and chancellor are always letters
+    // and marked is not used as the board can have more than 64 squares
+    pub fn render(&self, options: &Render_Options) -> String
+    {
+        let color = self.details.side_to_move;
+
+        let highlights = Highlights
+        {
+            last_move: self.moves.last().filter(|_| options.last_move).map(|last| (last.origin, last.target)),
+            checked_king: self.king_square(color).filter(|_| options.check && self.details.is_check != 0),
+            marked: &|_| false,
+        };
+
+        return render_squares(self.geometry, &self.board, &highlights, fairy_piece_to_char, options);
+    }
+}
+
+impl fmt::Display for Chess_Board
+{
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result
+    {
+        return write!(formatter, "{}", self.render(&Render_Options::plain()));
+    }
+}
+
+impl fmt::Display for Fairy_Board
+{
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result
+    {
+        return write!(formatter, "{}", self.render(&Render_Options::plain()));
+    }
+}
@@ -0,0 +1,909 @@
+// The search of the engine
+//
+// An alpha-beta (negamax) search with iterative deepening: the position is searched to depth 1,
+// then depth 2 and so on until the time runs out. Every iteration starts with the best move of
+// the previous one, which makes the alpha-beta cutoffs a lot more effective
+//
+// At depth 0 a quiescence search is started that only looks at captures and promotions, so the
+// position is never evaluated in the middle of an exchange
+//
+// Moves are ordered by:
+// 1. The best move of the previous iteration (at the root) or from the transposition table
+// 2. Captures, most valuable victim first and least valuable attacker first (MVV-LVA)
+// 3. Killer moves, quiet moves that caused a cutoff at the same ply in another position
+// 4. History, quiet moves that caused cutoffs anywhere in the search
+//
+// With Syzygy tablebases the moves at the root are filtered down to the ones that keep the best
+// result, and positions within the tables are scored without being searched
+//
+// With MultiPV the root moves are searched once for every line: the best move of a line is moved
+// to the front and the next line is searched among the remaining moves
+//
+// Repeating a position or reaching the 50 move rule scores as a draw. Within the search a
+// position only has to occur twice, the side that could have avoided the repetition would have
+// done so the first time
+//
+// While pondering (thinking on the opponent's time) the time limits are ignored. When the
+// opponent plays the expected move the search simply continues and the clock is started at that
+// moment, so nothing that has been searched so far is lost
+//
+// Every searched position is stored in the transposition table. Its best move is tried before
+// all other moves and deep enough results are reused without searching the position again. The
+// table can be shared with other searches running at the same time (see thread_pool.rs)
+
+use crate::chess::*;
+use crate::evaluation::*;
+use crate::syzygy::*;
+use crate::time_manager::*;
+use crate::transposition::*;
+use crate::variant::*;
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+pub const INFINITY: i32 = 32000;
+pub const MATE:     i32 = 31000;
+pub const MAX_PLY:  usize = 100;
+
+// Positions won according to the tablebases score below any mate
+pub const TB_WIN: i32 = 20000;
+
+// What to search for, a search without any limits runs until it is stopped
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Search_Limits
+{
+    pub depth: Option<u32>,
+    pub nodes: Option<u64>,
+    pub time:  Option<Time_Controls>,
+}
+
+// A line found by the search, with MultiPV there is one for every searched line
+#[derive(Clone, Debug, Default)]
+pub struct Search_Line
+{
+    pub score: i32,
+    pub pv:    Vec<Move>,
+}
+
+// The result of an iteration of the search
+// score is from the point of view of the side to move, time is in milliseconds
+// score and pv are the ones of the best line, lines contains all lines from best to worst
+#[derive(Clone, Debug, Default)]
+pub struct Search_Info
+{
+    pub depth:   u32,
+    pub score:   i32,
+    pub nodes:   u64,
+    pub time:    u64,
+    pub tb_hits: u64,
+    pub pv:      Vec<Move>,
+    pub lines:   Vec<Search_Line>,
+}
+
+impl Search_Info
+{
+    pub fn best_move(&self) -> Option<Move>
+    {
+        return self.pv.first().copied();
+    }
+}
+
+// Returns true for scores that are a forced mate
+pub fn is_mate_score(score: i32) -> bool
+{
+    return score.abs() >= MATE - MAX_PLY as i32;
+}
+
+// Whether the position with the given key occurred before, keys ends with the position before it
+// Only positions with the same side to move since the last capture or pawn move are looked at
+// Also used by fairy_search.rs
+pub fn is_repetition(keys: &[u64], halfmove_clock: u32, key: u64) -> bool
+{
+    let window = std::cmp::min(halfmove_clock as usize, keys.len());
+    let mut distance = 2;
+
+    while distance <= window
+    {
+        if keys[keys.len() - distance] == key
+        {
+            return true;
+        }
+
+        distance += 2;
+    }
+
+    return false;
+}
+
+// The score of a finished game for the side to move, wins and losses as a mate at the given ply
+fn outcome_score(outcome: Outcome, side_to_move: i8, ply: usize) -> i32
+{
+    let winner = outcome.winner(side_to_move);
+
+    return if winner == 0
+    {
+        0
+    } else if winner == side_to_move
+    {
+        MATE - ply as i32
+    } else
+    {
+        -MATE + ply as i32
+    };
+}
+
+// The score as used within UCI info lines: "cp 35" or "mate -3" (in moves, not plies)
+pub fn score_to_uci(score: i32) -> String
+{
+    if is_mate_score(score)
+    {
+        let plies = MATE - score.abs();
+        let moves = (plies + 1) / 2;
+
+        return format!("mate {}", if score > 0 { moves } else { -moves });
+    }
+
+    return format!("cp {}", score);
+}
+
+// Mate and tablebase scores depend on the ply they were found at, within the transposition table
+// they are stored relative to the position instead of the root
+fn score_to_tt(score: i32, ply: usize) -> i32
+{
+    if score >= TB_WIN - MAX_PLY as i32
+    {
+        return score + ply as i32;
+    }
+
+    if score <= -TB_WIN + MAX_PLY as i32
+    {
+        return score - ply as i32;
+    }
+
+    return score;
+}
+
+fn score_from_tt(score: i32, ply: usize) -> i32
+{
+    if score >= TB_WIN - MAX_PLY as i32
+    {
+        return score - ply as i32;
+    }
+
+    if score <= -TB_WIN + MAX_PLY as i32
+    {
+        return score + ply as i32;
+    }
+
+    return score;
+}
+
+// Helper threads of a Lazy SMP search skip some of the depths so that not every thread searches
+// the same depth at the same time. Thread n uses entry (n - 1) % 20 of these tables and skips a
+// depth when ((depth + SKIP_PHASE) / SKIP_SIZE) is odd
+const SKIP_SIZE:  [u32; 20] = [1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 3, 3, 4, 4, 4, 4, 4, 4, 4, 4];
+const SKIP_PHASE: [u32; 20] = [0, 1, 0, 1, 2, 3, 0, 1, 2, 3, 4, 5, 0, 1, 2, 3, 4, 5, 6, 7];
+
+fn skip_depth(thread_id: usize, depth: u32) -> bool
+{
+    if thread_id == 0 || depth == 1
+    {
+        return false;
+    }
+
+    let s_idx = (thread_id - 1) % SKIP_SIZE.len();
+
+    return ((depth + SKIP_PHASE[s_idx]) / SKIP_SIZE[s_idx]) % 2 == 1;
+}
+
+pub struct Search
+{
+    // Set from the outside to stop the search as soon as possible
+    pub stop:          Arc<AtomicBool>,
+    // Set while pondering, cleared from the outside when the expected move was played
+    pub ponder:        Arc<AtomicBool>,
+    pub syzygy:        Option<Arc<Syzygy_Tablebase>>,
+    pub move_overhead: u64,
+    pub tt:            Arc<Transposition_Table>,
+    // The number of lines to search (UCI "MultiPV"), 1 for a normal search
+    pub multi_pv:      usize,
+
+    // 0 for the main thread, helper threads skip some depths
+    pub thread_id:    usize,
+    // The nodes searched by all threads together, the nodes of this thread are added every 1024 nodes
+    pub shared_nodes: Arc<AtomicU64>,
+
+    board:         Chess_Board,
+    start:         Instant,
+    nodes:         u64,
+    flushed_nodes: u64,
+    tb_hits:      u64,
+    node_limit:   Option<u64>,
+    time_manager: Option<Time_Manager>,
+    aborted:      bool,
+    pondering:    bool,
+
+    // Whether the tablebases are probed within the search, not needed when the root moves
+    // have already been filtered with the DTZ tables
+    probe_in_search: bool,
+
+    // The keys of the positions since the last capture or pawn move, from the game before the
+    // search and along the line that is currently searched
+    keys: Vec<u64>,
+
+    // The principal variation found at every ply
+    pv: Vec<Vec<Move>>,
+    killers: [[Option<Move>; 2]; MAX_PLY],
+    // Indexed by [piece + 6][target square], the last row is for the duck
+    history: [[i32; 64]; 14],
+}
+
+impl Search
+{
+    pub fn new() -> Self
+    {
+        return Self::with_table(Arc::new(Transposition_Table::new(DEFAULT_HASH_MB)));
+    }
+
+    pub fn with_table(tt: Arc<Transposition_Table>) -> Self
+    {
+        return Search
+        {
+            stop: Arc::new(AtomicBool::new(false)),
+            ponder: Arc::new(AtomicBool::new(false)),
+            syzygy: None,
+            move_overhead: DEFAULT_MOVE_OVERHEAD,
+            tt,
+            multi_pv: 1,
+            thread_id: 0,
+            shared_nodes: Arc::new(AtomicU64::new(0)),
+            board: Chess_Board::new(),
+            start: Instant::now(),
+            nodes: 0,
+            flushed_nodes: 0,
+            tb_hits: 0,
+            node_limit: None,
+            time_manager: None,
+            aborted: false,
+            pondering: false,
+            probe_in_search: false,
+            keys: vec![],
+            pv: vec![vec![]; MAX_PLY + 1],
+            killers: [[None; 2]; MAX_PLY],
+            history: [[0; 64]; 14],
+        };
+    }
+
+    // Searches the position until one of the limits is reached or the search is stopped
+    // report is called after every completed iteration
+    pub fn search(&mut self, board: &Chess_Board, limits: &Search_Limits, report: &mut dyn FnMut(&Search_Info)) -> Search_Info
+    {
+        self.board = board.clone();
+        self.start = Instant::now();
+        self.nodes = 0;
+        self.flushed_nodes = 0;
+        self.tb_hits = 0;
+        self.aborted = false;
+        self.pondering = self.ponder.load(Ordering::Relaxed);
+        self.node_limit = limits.nodes;
+        self.time_manager = limits.time.map(|controls| Time_Manager::new(&controls, self.move_overhead));
+        self.killers = [[None; 2]; MAX_PLY];
+        self.keys = Self::history_keys(board);
+        self.history = [[0; 64]; 14];
+
+        let mut root_moves = self.board.legal_moves();
+        let mut result = Search_Info::default();
+
+        if let Some(outcome) = self.board.variant_outcome()
+        {
+            result.score = outcome_score(outcome, self.board.details.side_to_move, 0);
+            return result;
+        }
+
+        if root_moves.is_empty()
+        {
+            result.score = outcome_score(self.board.no_moves_outcome(), self.board.details.side_to_move, 0);
+            return result;
+        }
+
+        // Only keep the moves that keep the best result according to the tablebases
+        // The tablebases only know about normal chess
+        let mut tb_score: Option<i32> = None;
+        self.probe_in_search = self.syzygy.is_some() && self.board.is_standard();
+
+        if let Some(syzygy) = self.syzygy.clone().filter(|_| self.probe_in_search)
+        {
+            if let Some((moves, wdl, used_dtz)) = syzygy.filter_root_moves(&mut self.board)
+            {
+                root_moves = moves;
+                self.tb_hits += 1;
+
+                tb_score = Some(match wdl
+                {
+                    Wdl::Win          =>  TB_WIN - MAX_PLY as i32,
+                    Wdl::Loss         => -TB_WIN + MAX_PLY as i32,
+                    other             =>  other.value(),
+                });
+
+                // When winning without DTZ tables the search still needs the tables to find
+                // its way towards the win
+                self.probe_in_search = !used_dtz && wdl > Wdl::Draw;
+            }
+        }
+
+        if root_moves.len() == 1
+        {
+            if let Some(time_manager) = self.time_manager.as_mut()
+            {
+                time_manager.set_forced();
+            }
+        }
+
+        let max_depth = limits.depth.unwrap_or(MAX_PLY as u32 - 1).clamp(1, MAX_PLY as u32 - 1);
+
+        for depth in 1..=max_depth
+        {
+            if skip_depth(self.thread_id, depth)
+            {
+                continue;
+            }
+
+            let mut lines = self.search_root(&mut root_moves, depth);
+            self.flush_nodes();
+
+            // A partial iteration is only used if nothing has been found yet
+            if self.aborted && depth > 1
+            {
+                break;
+            }
+
+            // Stopped before even the first move was searched, there is no score yet
+            if lines.is_empty()
+            {
+                lines.push(Search_Line { score: 0, pv: vec![root_moves[0]] });
+            }
+
+            // The tablebase result is more reliable than the search unless a mate was found
+            if let Some(tb_score) = tb_score
+            {
+                for line in lines.iter_mut()
+                {
+                    if !is_mate_score(line.score)
+                    {
+                        line.score = tb_score;
+                    }
+                }
+            }
+
+            let score = lines[0].score;
+            let previous_best = result.best_move();
+
+            result = Search_Info
+            {
+                depth,
+                score,
+                nodes: self.shared_nodes.load(Ordering::Relaxed),
+                time: self.elapsed(),
+                tb_hits: self.tb_hits,
+                pv: lines[0].pv.clone(),
+                lines,
+            };
+
+            report(&result);
+
+            if self.aborted
+            {
+                break;
+            }
+
+            self.check_ponderhit();
+
+            if let Some(time_manager) = self.time_manager.as_mut()
+            {
+                time_manager.update(depth, previous_best != result.best_move(), score);
+
+                if    !self.pondering
+                   && time_manager.should_stop()
+                {
+                    break;
+                }
+            }
+
+            // No point in searching deeper once a mate has been found
+            if    is_mate_score(score)
+               && MATE - score.abs() <= depth as i32
+            {
+                break;
+            }
+        }
+
+        return result;
+    }
+
+    // The keys of the positions that can still be repeated, ending with the current position
+    fn history_keys(board: &Chess_Board) -> Vec<u64>
+    {
+        let mut previous = board.clone();
+        let mut keys = vec![previous.position_key()];
+        let plies = std::cmp::min(board.details.halfmove_clock as usize, board.moves.len());
+
+        for _ in 0..plies
+        {
+            previous.unmake_move();
+            keys.push(previous.position_key());
+        }
+
+        keys.reverse();
+
+        return keys;
+    }
+
+    fn is_repetition(&self, key: u64) -> bool
+    {
+        return is_repetition(&self.keys, self.board.details.halfmove_clock, key);
+    }
+
+    fn elapsed(&self) -> u64
+    {
+        return self.start.elapsed().as_millis() as u64;
+    }
+
+    // Once the expected move has been played the clock starts running
+    fn check_ponderhit(&mut self)
+    {
+        if    self.pondering
+           && !self.ponder.load(Ordering::Relaxed)
+        {
+            self.pondering = false;
+
+            if let Some(time_manager) = self.time_manager.as_mut()
+            {
+                time_manager.restart();
+            }
+        }
+    }
+
+    fn flush_nodes(&mut self)
+    {
+        self.shared_nodes.fetch_add(self.nodes - self.flushed_nodes, Ordering::Relaxed);
+        self.flushed_nodes = self.nodes;
+    }
+
+    // Checked at every node, the clock and the nodes of the other threads are only looked at
+    // every 1024 nodes
+    fn should_abort(&mut self) -> bool
+    {
+        if self.aborted
+        {
+            return true;
+        }
+
+        if self.stop.load(Ordering::Relaxed)
+        {
+            self.aborted = true;
+        }
+
+        if self.nodes % 1024 == 0
+        {
+            self.flush_nodes();
+            self.check_ponderhit();
+
+            if self.node_limit.is_some_and(|limit| self.shared_nodes.load(Ordering::Relaxed) >= limit)
+            {
+                self.aborted = true;
+            }
+
+            if    !self.pondering
+               && self.time_manager.as_ref().is_some_and(|time_manager| time_manager.should_abort())
+            {
+                self.aborted = true;
+            }
+        }
+
+        return self.aborted;
+    }
+
+    // Searches the lines one after another, returns the completed lines
+    fn search_root(&mut self, root_moves: &mut [Move], depth: u32) -> Vec<Search_Line>
+    {
+        let line_count = self.multi_pv.clamp(1, root_moves.len());
+        let mut lines = Vec::with_capacity(line_count);
+
+        for l_idx in 0..line_count
+        {
+            let mut alpha = -INFINITY;
+            let beta = INFINITY;
+            let mut best_index = l_idx;
+
+            self.pv[0].clear();
+
+            for m_idx in l_idx..root_moves.len()
+            {
+                let root_move = root_moves[m_idx];
+
+                let color = self.board.details.side_to_move;
+
+                self.board.make_move(root_move);
+                let score = self.child_score(color, depth - 1, alpha, beta, 1);
+                self.board.unmake_move();
+
+                if self.aborted
+                {
+                    break;
+                }
+
+                if score > alpha
+                {
+                    alpha = score;
+                    best_index = m_idx;
+                    self.update_pv(0, root_move);
+                }
+            }
+
+            if self.pv[0].is_empty()
+            {
+                break;
+            }
+
+            // The best move of the line is searched first in the next iteration
+            root_moves[l_idx..=best_index].rotate_right(1);
+
+            let mut pv = self.pv[0].clone();
+            self.extend_pv(&mut pv, depth as usize);
+            lines.push(Search_Line { score: alpha, pv });
+
+            if self.aborted
+            {
+                break;
+            }
+        }
+
+        // Scores taken from the transposition table (which the other threads fill as well) can
+        // make a later line score higher than an earlier one. The lines are sorted by score, the
+        // stable sort keeps the earlier line (the previous best move) first on equal scores
+        lines.sort_by_key(|line| std::cmp::Reverse(line.score));
+
+        for (l_idx, line) in lines.iter().enumerate()
+        {
+            root_moves[l_idx] = line.pv[0];
+        }
+
+        if    !self.aborted
+           && !lines.is_empty()
+        {
+            self.tt.store(self.board.position_key(), pack_move(&root_moves[0]), score_to_tt(lines[0].score, 0), depth, Bound::Exact);
+        }
+
+        return lines;
+    }
+
+    // Positions taken from the transposition table end the principal variation early, the rest of
+    // the line is filled in by following the best moves stored in the table
+    fn extend_pv(&mut self, pv: &mut Vec<Move>, length: usize)
+    {
+        for &pv_move in pv.iter()
+        {
+            self.board.make_move(pv_move);
+        }
+
+        let mut played = pv.len();
+
+        while pv.len() < length
+        {
+            let Some(entry) = self.tt.probe(self.board.position_key()) else { break };
+            let Some(next_move) = find_move(&self.board.legal_moves(), entry.best_move) else { break };
+
+            self.board.make_move(next_move);
+            pv.push(next_move);
+            played += 1;
+        }
+
+        for _ in 0..played
+        {
+            self.board.unmake_move();
+        }
+    }
+
+    fn update_pv(&mut self, ply: usize, best_move: Move)
+    {
+        let mut line = vec![best_move];
+        line.extend_from_slice(&self.pv[ply + 1]);
+        self.pv[ply] = line;
+    }
+
+    // The score after a move for the player that made it. Normally the other player moves next and
+    // the score is negated, in Duck chess the same player still has to move the duck
+    fn child_score(&mut self, color: i8, depth: u32, alpha: i32, beta: i32, ply: usize) -> i32
+    {
+        if self.board.details.side_to_move == color
+        {
+            return self.negamax(depth, alpha, beta, ply);
+        }
+
+        return -self.negamax(depth, -beta, -alpha, ply);
+    }
+
+    fn negamax(&mut self, depth: u32, mut alpha: i32, beta: i32, ply: usize) -> i32
+    {
+        self.pv[ply].clear();
+
+        if self.should_abort()
+        {
+            return 0;
+        }
+
+        self.nodes += 1;
+
+        if ply >= MAX_PLY - 1
+        {
+            return evaluate(&self.board);
+        }
+
+        let key = self.board.position_key();
+
+        if self.is_repetition(key)
+        {
+            return 0;
+        }
+
+        if let Some(outcome) = self.board.variant_outcome()
+        {
+            return outcome_score(outcome, self.board.details.side_to_move, ply);
+        }
+
+        // Positions within the tablebases are only probed right after a capture or pawn move,
+        // the tables do not know how many moves have been played since
+        if    self.probe_in_search
+           && self.board.details.halfmove_clock == 0
+        {
+            if let Some(syzygy) = self.syzygy.as_ref()
+            {
+                if let Some(wdl) = syzygy.probe_wdl(&mut self.board)
+                {
+                    self.tb_hits += 1;
+
+                    return match wdl
+                    {
+                        Wdl::Win  =>  TB_WIN - ply as i32,
+                        Wdl::Loss => -TB_WIN + ply as i32,
+                        other     =>  other.value(),
+                    };
+                }
+            }
+        }
+
+        let color = self.board.details.side_to_move;
+        let in_check = self.board.in_check(color);
+
+        // Check extension, a check has to be answered so there is no point in counting it
+        let depth = if in_check { depth + 1 } else { depth };
+
+        if depth == 0
+        {
+            return self.quiescence(alpha, beta, ply);
+        }
+
+        let mut tt_move = 0;
+
+        if let Some(entry) = self.tt.probe(key)
+        {
+            tt_move = entry.best_move;
+
+            if entry.depth >= depth
+            {
+                let score = score_from_tt(entry.score, ply);
+
+                let usable = match entry.bound
+                {
+                    Bound::Exact => true,
+                    Bound::Lower => score >= beta,
+                    Bound::Upper => score <= alpha,
+                };
+
+                if usable
+                {
+                    return score;
+                }
+            }
+        }
+
+        let mut moves = self.board.legal_moves();
+
+        if moves.is_empty()
+        {
+            return outcome_score(self.board.no_moves_outcome(), color, ply);
+        }
+
+        // Checked after looking for checkmate, a mate on the 100th half move still counts
+        if self.board.details.halfmove_clock >= 100
+        {
+            return 0;
+        }
+
+        self.order_moves(&mut moves, ply, tt_move);
+        self.keys.push(key);
+
+        let original_alpha = alpha;
+        let mut best_score = -INFINITY;
+        let mut best_move = 0;
+
+        for next_move in moves
+        {
+            self.board.make_move(next_move);
+            let score = self.child_score(color, depth - 1, alpha, beta, ply + 1);
+            self.board.unmake_move();
+
+            if self.aborted
+            {
+                self.keys.pop();
+                return 0;
+            }
+
+            if score > best_score
+            {
+                best_score = score;
+                best_move = pack_move(&next_move);
+            }
+
+            if score > alpha
+            {
+                alpha = score;
+                self.update_pv(ply, next_move);
+            }
+
+            if alpha >= beta
+            {
+                if next_move.capture == 0 && next_move.promotion == 0
+                {
+                    self.store_killer(next_move, ply);
+                    self.history[(next_move.piece + 6) as usize][next_move.target] += (depth * depth) as i32;
+                }
+
+                break;
+            }
+        }
+
+        self.keys.pop();
+
+        let bound = if best_score >= beta
+        {
+            Bound::Lower
+        }
+        else if best_score > original_alpha
+        {
+            Bound::Exact
+        }
+        else
+        {
+            Bound::Upper
+        };
+
+        // Without a cutoff or a better alpha no move is known to be the best one
+        if bound == Bound::Upper
+        {
+            best_move = 0;
+        }
+
+        self.tt.store(key, best_move, score_to_tt(best_score, ply), depth, bound);
+
+        return best_score;
+    }
+
+    // Only captures and promotions are searched, the side to move can also decide to not
+    // capture anything and keep the static evaluation ("stand pat")
+    fn quiescence(&mut self, mut alpha: i32, beta: i32, ply: usize) -> i32
+    {
+        if self.should_abort()
+        {
+            return 0;
+        }
+
+        self.nodes += 1;
+
+        if let Some(outcome) = self.board.variant_outcome()
+        {
+            return outcome_score(outcome, self.board.details.side_to_move, ply);
+        }
+
+        let stand_pat = evaluate(&self.board);
+
+        if ply >= MAX_PLY - 1 || stand_pat >= beta
+        {
+            return stand_pat;
+        }
+
+        if stand_pat > alpha
+        {
+            alpha = stand_pat;
+        }
+
+        let mut moves: Vec<Move> = self.board.legal_moves()
+                                             .into_iter()
+                                             .filter(|candidate| candidate.capture != 0 || candidate.promotion != 0)
+                                             .collect();
+
+        moves.sort_by_key(|candidate| -Self::capture_score(candidate));
+
+        let color = self.board.details.side_to_move;
+
+        for next_move in moves
+        {
+            self.board.make_move(next_move);
+
+            // In Duck chess the same player still has to move the duck
+            let score = if self.board.details.side_to_move == color
+            {
+                self.quiescence(alpha, beta, ply + 1)
+            } else
+            {
+                -self.quiescence(-beta, -alpha, ply + 1)
+            };
+
+            self.board.unmake_move();
+
+            if self.aborted
+            {
+                return 0;
+            }
+
+            if score >= beta
+            {
+                return score;
+            }
+
+            if score > alpha
+            {
+                alpha = score;
+            }
+        }
+
+        return alpha;
+    }
+
+    // MVV-LVA: the most valuable victim first, then the least valuable attacker
+    fn capture_score(candidate: &Move) -> i32
+    {
+        return   10 * PIECE_VALUES[candidate.capture.unsigned_abs() as usize]
+               + PIECE_VALUES[candidate.promotion.unsigned_abs() as usize]
+               - PIECE_VALUES[candidate.piece.unsigned_abs() as usize] / 10;
+    }
+
+    fn order_moves(&self, moves: &mut [Move], ply: usize, tt_move: u16)
+    {
+        let killers = self.killers[ply];
+
+        moves.sort_by_cached_key(|candidate| {
+            if tt_move != 0 && pack_move(candidate) == tt_move
+            {
+                return -2_000_000;
+            }
+
+            if candidate.capture != 0 || candidate.promotion != 0
+            {
+                return -(1_000_000 + Self::capture_score(candidate));
+            }
+
+            if killers[0] == Some(*candidate)
+            {
+                return -900_000;
+            }
+
+            if killers[1] == Some(*candidate)
+            {
+                return -800_000;
+            }
+
+            return -self.history[(candidate.piece + 6) as usize][candidate.target];
+        });
+    }
+
+    fn store_killer(&mut self, killer: Move, ply: usize)
+    {
+        if self.killers[ply][0] != Some(killer)
+        {
+            self.killers[ply][1] = self.killers[ply][0];
+            self.killers[ply][0] = Some(killer);
+        }
+    }
+}
@@ -0,0 +1,2575 @@
+// Probing Syzygy endgame tablebases from local files
+//
+// Syzygy tablebases are the standard endgame tablebases used by chess engines. Every material
+// combination (e.g. KRvK, white king and rook against the black king) comes with two files:
+//
+// KRvK.rtbw -> WDL, whether the side to move wins, draws or loses, used within the search
+// KRvK.rtbz -> DTZ, the distance (in plies) to the next zeroing move (capture or pawn move) that
+//              keeps the result, used at the root to make progress towards the win
+//
+// Both take the 50 move rule into account: a win that needs more than 50 moves without a capture
+// or pawn move is a "cursed win" and a loss that is saved by the 50 move rule a "blessed loss"
+//
+// The files are compressed and only contain one position out of every group of positions that are
+// mirror images of each other. The decoding below follows the original probing code by Ronald de
+// Man (and the Stockfish version of it), the names of the tables and steps are kept the same
+// so both can be compared
+//
+// Positions with castling rights are not part of the tables and are never probed
+
+use crate::chess::*;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+pub const WDL_EXTENSION: &str = "rtbw";
+pub const DTZ_EXTENSION: &str = "rtbz";
+
+const WDL_MAGIC: [u8; 4] = [0x71, 0xE8, 0x23, 0x5D];
+const DTZ_MAGIC: [u8; 4] = [0xD7, 0x66, 0x0C, 0xA5];
+
+// The largest tables that exist have 7 pieces
+const TB_PIECES: usize = 7;
+
+// Flags stored for every (part of a) table
+const FLAG_STM:          u8 = 1;
+const FLAG_MAPPED:       u8 = 2;
+const FLAG_WIN_PLIES:    u8 = 4;
+const FLAG_LOSS_PLIES:   u8 = 8;
+const FLAG_WIDE:         u8 = 16;
+const FLAG_SINGLE_VALUE: u8 = 128;
+
+// Flags stored in the header of the file
+const HEADER_SPLIT:     u8 = 1;
+const HEADER_HAS_PAWNS: u8 = 2;
+
+// Root moves are ranked by the DTZ, every win that can be reached within the 50 move rule
+// gets the same rank
+const MAX_DTZ: i32 = 1 << 18;
+
+// The result of a position from the point of view of the side to move
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Wdl
+{
+    Loss,
+    Blessed_Loss,
+    Draw,
+    Cursed_Win,
+    Win,
+}
+
+impl Wdl
+{
+    // The values used within the tables, -2 (loss) to 2 (win)
+    pub fn value(self) -> i32
+    {
+        return match self
+        {
+            Wdl::Loss         => -2,
+            Wdl::Blessed_Loss => -1,
+            Wdl::Draw         =>  0,
+            Wdl::Cursed_Win   =>  1,
+            Wdl::Win          =>  2,
+        };
+    }
+
+    fn from_value(value: i32) -> Self
+    {
+        return match value
+        {
+            i32::MIN..=-2 => Wdl::Loss,
+            -1            => Wdl::Blessed_Loss,
+            0             => Wdl::Draw,
+            1             => Wdl::Cursed_Win,
+            _             => Wdl::Win,
+        };
+    }
+}
+
+// The result of a full probe
+// dtz is positive when winning, negative when losing and 0 for a draw. Cursed wins and
+// blessed losses are offset by 100 (so a dtz above 100 can not be won with the 50 move rule)
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Syzygy_Probe
+{
+    pub wdl: Wdl,
+    pub dtz: i32,
+}
+
+// Lookup tables for turning a position into an index within a table
+// Only depend on the board geometry, so they are computed once when the tablebase is opened
+struct Index_Tables
+{
+    // Squares below the a1-h8 diagonal -> 0..27
+    map_b1h1h7: [u64; 64],
+    // Squares in the a1-d1-d4 triangle -> 0..9, the diagonal squares come last
+    map_a1d1d4: [u64; 64],
+    // The 462 placements of two kings where the first one is in the a1-d1-d4 triangle
+    map_kk: [[u64; 64]; 10],
+    // binomial[k][n] -> the number of ways to choose k out of n
+    binomial: [[u64; 64]; TB_PIECES],
+    // Squares a2-h7 -> 0..47, the leading pawn is the one with the highest value
+    map_pawns: [u64; 64],
+    lead_pawn_idx: [[u64; 64]; 6],
+    lead_pawns_size: [[u64; 4]; 6],
+}
+
+impl Index_Tables
+{
+    fn new() -> Self
+    {
+        let mut tables = Index_Tables
+        {
+            map_b1h1h7: [0; 64],
+            map_a1d1d4: [0; 64],
+            map_kk: [[0; 64]; 10],
+            binomial: [[0; 64]; TB_PIECES],
+            map_pawns: [0; 64],
+            lead_pawn_idx: [[0; 64]; 6],
+            lead_pawns_size: [[0; 4]; 6],
+        };
+
+        let mut code = 0;
+
+        for square in 0..64
+        {
+            if off_a1h8(square) < 0
+            {
+                tables.map_b1h1h7[square] = code;
+                code += 1;
+            }
+        }
+
+        let mut diagonal: Vec<usize> = vec![];
+        code = 0;
+
+        for square in 0..28
+        {
+            if off_a1h8(square) < 0 && Chess_Board::get_col(square) <= 3
+            {
+                tables.map_a1d1d4[square] = code;
+                code += 1;
+            } else if off_a1h8(square) == 0 && Chess_Board::get_col(square) <= 3
+            {
+                diagonal.push(square);
+            }
+        }
+
+        for square in diagonal
+        {
+            tables.map_a1d1d4[square] = code;
+            code += 1;
+        }
+
+        // Positions with both kings on the diagonal are encoded last
+        let mut both_on_diagonal: Vec<(usize, usize)> = vec![];
+        code = 0;
+
+        for k_idx in 0..10
+        {
+            for first in 0..28
+            {
+                // b1 is the only square of the triangle that is mapped to 0
+                if    tables.map_a1d1d4[first] != k_idx as u64
+                   || (k_idx == 0 && first != 1)
+                {
+                    continue;
+                }
+
+                for second in 0..64
+                {
+                    let row_distance = Chess_Board::get_row(first).abs_diff(Chess_Board::get_row(second));
+                    let col_distance = Chess_Board::get_col(first).abs_diff(Chess_Board::get_col(second));
+
+                    if row_distance <= 1 && col_distance <= 1
+                    {
+                        continue;
+                    }
+
+                    if off_a1h8(first) == 0 && off_a1h8(second) > 0
+                    {
+                        continue;
+                    }
+
+                    if off_a1h8(first) == 0 && off_a1h8(second) == 0
+                    {
+                        both_on_diagonal.push((k_idx, second));
+                        continue;
+                    }
+
+                    tables.map_kk[k_idx][second] = code;
+                    code += 1;
+                }
+            }
+        }
+
+        for (k_idx, second) in both_on_diagonal
+        {
+            tables.map_kk[k_idx][second] = code;
+            code += 1;
+        }
+
+        tables.binomial[0][0] = 1;
+
+        for n in 1..64
+        {
+            for k in 0..std::cmp::min(TB_PIECES, n + 1)
+            {
+                tables.binomial[k][n] =  if k > 0 { tables.binomial[k - 1][n - 1] } else { 0 }
+                                       + if k < n { tables.binomial[k][n - 1] } else { 0 };
+            }
+        }
+
+        // a2 -> 47, h2 -> 46, a3 -> 45, ... so the pawns closest to the edge and the lowest rank
+        // get the highest values
+        let mut available = 48;
+
+        for lead_count in 1..6
+        {
+            for file in 0..4
+            {
+                let mut index = 0;
+
+                for rank in 1..7
+                {
+                    let square = rank * 8 + file;
+
+                    if lead_count == 1
+                    {
+                        available -= 1;
+                        tables.map_pawns[square] = available;
+                        available -= 1;
+                        tables.map_pawns[square ^ 7] = available;
+                    }
+
+                    tables.lead_pawn_idx[lead_count][square] = index;
+                    index += tables.binomial[lead_count - 1][tables.map_pawns[square] as usize];
+                }
+
+                tables.lead_pawns_size[lead_count][file] = index;
+            }
+        }
+
+        return tables;
+    }
+}
+
+// The rank minus the file of a square: 0 on the a1-h8 diagonal, negative below and positive above
+fn off_a1h8(square: usize) -> i32
+{
+    return Chess_Board::get_row(square) as i32 - Chess_Board::get_col(square) as i32;
+}
+
+// Pieces within the files are stored as 1..6 for white and 9..14 for black
+fn tb_piece(piece: i8) -> u8
+{
+    return if piece > 0 { piece as u8 } else { 8 + piece.unsigned_abs() };
+}
+
+// The name of the pieces of one side as used within the file names, e.g. "KRP"
+fn side_name(board: &[i8; 64], color: i8) -> String
+{
+    let mut name = String::new();
+
+    for (piece, letter) in [(KING, 'K'), (QUEEN, 'Q'), (ROOK, 'R'), (BISHOP, 'B'), (KNIGHT, 'N'), (PAWN, 'P')]
+    {
+        let count = board.iter().filter(|&&square| square == color * piece).count();
+
+        for _ in 0..count
+        {
+            name.push(letter);
+        }
+    }
+
+    return name;
+}
+
+fn read_u16(data: &[u8], position: usize) -> Option<u16>
+{
+    let bytes = data.get(position..position + 2)?;
+    return Some(u16::from_le_bytes([bytes[0], bytes[1]]));
+}
+
+fn read_u32(data: &[u8], position: usize) -> Option<u32>
+{
+    let bytes = data.get(position..position + 4)?;
+    return Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]));
+}
+
+// The compressed data is read as big endian, missing bytes at the end of the file are zeros
+fn read_be(data: &[u8], position: usize, count: usize) -> u64
+{
+    let mut result = 0;
+
+    for b_idx in 0..count
+    {
+        result = (result << 8) | *data.get(position + b_idx).unwrap_or(&0) as u64;
+    }
+
+    return result;
+}
+
+// The decoding information for one part of a table
+// A table has a part for every side to move (WDL tables where the sides are different) and
+// for every file of the leading pawn (tables with pawns)
+// Positions within the data are offsets from the start of the file
+#[derive(Clone, Default)]
+struct Pairs_Data
+{
+    flags:     u8,
+    pieces:    [u8; TB_PIECES],
+    group_len: [usize; TB_PIECES + 1],
+    group_idx: [u64; TB_PIECES + 1],
+
+    block_size:        usize,
+    span:              u64,
+    sparse_index:      usize,
+    sparse_index_size: usize,
+    block_length:      usize,
+    block_length_size: usize,
+    blocks_num:        usize,
+    data:              usize,
+
+    min_sym_len: u8,
+    lowest_sym:  usize,
+    base64:      Vec<u64>,
+    symlen:      Vec<u8>,
+    btree:       usize,
+
+    // Start of the value maps of a DTZ table, one for every WDL result
+    map_idx: [usize; 4],
+}
+
+// A single WDL or DTZ file, loaded into memory
+struct Syzygy_Table
+{
+    data:   Vec<u8>,
+    is_dtz: bool,
+
+    piece_count:       usize,
+    has_pawns:         bool,
+    has_unique_pieces: bool,
+    // [leading color, other color], the leading color is the color with fewer pawns
+    pawn_count:        [usize; 2],
+    // Both sides have the same pieces (e.g. KRvKR)
+    symmetric:         bool,
+
+    // Indexed by [side to move][file of the leading pawn]
+    items: Vec<Vec<Pairs_Data>>,
+    map:   usize,
+}
+
+// The result of looking up a position within a DTZ table
+enum Dtz_Lookup
+{
+    Value(i32),
+    // DTZ tables only store one side to move, the other side has to be found with a search
+    Change_Side,
+}
+
+// Where a position is stored within a table: (side to move, file of the leading pawn, index)
+enum Table_Position
+{
+    Index(usize, usize, u64),
+    Change_Side,
+}
+
+impl Syzygy_Table
+{
+    // Loads and parses a file, name is the material without the extension, e.g. "KRvK"
+    fn load(path: &Path, name: &str, is_dtz: bool, index: &Index_Tables) -> Option<Self>
+    {
+        let mut table = Self::new(name, fs::read(path).ok()?, is_dtz)?;
+        table.parse(index)?;
+
+        return Some(table);
+    }
+
+    // A table of the material within name, the data is only read by parse
+    fn new(name: &str, data: Vec<u8>, is_dtz: bool) -> Option<Self>
+    {
+        let (white, black) = name.split_once('v')?;
+        let letters: Vec<char> = name.chars().filter(|letter| *letter != 'v').collect();
+
+        let count = |side: &str, letter: char| side.chars().filter(|other| *other == letter).count();
+
+        let white_pawns = count(white, 'P');
+        let black_pawns = count(black, 'P');
+
+        // The leading color is the side with fewer pawns, as long as it has pawns at all
+        let white_leads =    black_pawns == 0
+                          || (white_pawns > 0 && black_pawns >= white_pawns);
+
+        let has_unique_pieces = "QRBNP".chars().any(|letter| count(white, letter) == 1 || count(black, letter) == 1);
+
+        let table = Syzygy_Table
+        {
+            data,
+            is_dtz,
+            piece_count: letters.len(),
+            has_pawns: white_pawns + black_pawns > 0,
+            has_unique_pieces,
+            pawn_count: if white_leads { [white_pawns, black_pawns] } else { [black_pawns, white_pawns] },
+            symmetric: white == black,
+            items: vec![],
+            map: 0,
+        };
+
+        if table.piece_count > TB_PIECES
+        {
+            return None;
+        }
+
+        return Some(table);
+    }
+
+    fn parse(&mut self, index: &Index_Tables) -> Option<()>
+    {
+        let data = &self.data;
+        let magic = if self.is_dtz { DTZ_MAGIC } else { WDL_MAGIC };
+
+        if data.get(0..4)? != magic
+        {
+            return None;
+        }
+
+        let header = *data.get(4)?;
+
+        if (header & HEADER_HAS_PAWNS != 0) != self.has_pawns
+        {
+            return None;
+        }
+
+        // WDL tables store both sides to move unless both sides have the same pieces,
+        // DTZ tables always store a single side
+        let sides = if !self.is_dtz && header & HEADER_SPLIT != 0 { 2 } else { 1 };
+        let files = if self.has_pawns { 4 } else { 1 };
+        let both_pawns = self.has_pawns && self.pawn_count[1] > 0;
+
+        let mut items = vec![vec![Pairs_Data::default(); files]; sides];
+        let mut position = 5;
+
+        for file in 0..files
+        {
+            let first = *data.get(position)?;
+            let second = if both_pawns { *data.get(position + 1)? } else { 0xFF };
+
+            let order = [[first & 0xF, second & 0xF], [first >> 4, second >> 4]];
+            position += 1 + both_pawns as usize;
+
+            for p_idx in 0..self.piece_count
+            {
+                let pieces = *data.get(position)?;
+
+                for side in 0..sides
+                {
+                    items[side][file].pieces[p_idx] = if side == 1 { pieces >> 4 } else { pieces & 0xF };
+                }
+
+                position += 1;
+            }
+
+            for side in 0..sides
+            {
+                self.set_groups(&mut items[side][file], order[side], file, index);
+            }
+        }
+
+        position += position & 1;
+
+        for file in 0..files
+        {
+            for side in 0..sides
+            {
+                position = Self::set_sizes(&mut items[side][file], data, position)?;
+            }
+        }
+
+        if self.is_dtz
+        {
+            self.map = position;
+
+            for file in 0..files
+            {
+                let item = &mut items[0][file];
+
+                if item.flags & FLAG_MAPPED == 0
+                {
+                    continue;
+                }
+
+                if item.flags & FLAG_WIDE != 0
+                {
+                    position += position & 1;
+
+                    for m_idx in 0..4
+                    {
+                        item.map_idx[m_idx] = (position - self.map) / 2 + 1;
+                        position += 2 * read_u16(data, position)? as usize + 2;
+                    }
+                } else
+                {
+                    for m_idx in 0..4
+                    {
+                        item.map_idx[m_idx] = position - self.map + 1;
+                        position += *data.get(position)? as usize + 1;
+                    }
+                }
+            }
+
+            position += position & 1;
+        }
+
+        for file in 0..files
+        {
+            for side in 0..sides
+            {
+                items[side][file].sparse_index = position;
+                position += items[side][file].sparse_index_size * 6;
+            }
+        }
+
+        for file in 0..files
+        {
+            for side in 0..sides
+            {
+                items[side][file].block_length = position;
+                position += items[side][file].block_length_size * 2;
+            }
+        }
+
+        for file in 0..files
+        {
+            for side in 0..sides
+            {
+                position = (position + 0x3F) & !0x3F;
+                items[side][file].data = position;
+                position += items[side][file].blocks_num * items[side][file].block_size;
+            }
+        }
+
+        if position > data.len()
+        {
+            return None;
+        }
+
+        self.items = items;
+
+        return Some(());
+    }
+
+    // Splits the pieces into groups that are encoded together and computes the factor every
+    // group is multiplied with. In KRvKN the kings and the first piece are always encoded
+    // together, identical pieces next to each other also form a group
+    fn set_groups(&self, item: &mut Pairs_Data, order: [u8; 2], file: usize, index: &Index_Tables)
+    {
+        let mut n = 0;
+        let mut first_len: i32 = if self.has_pawns { 0 } else if self.has_unique_pieces { 3 } else { 2 };
+        item.group_len[0] = 1;
+
+        for p_idx in 1..self.piece_count
+        {
+            first_len -= 1;
+
+            if first_len > 0 || item.pieces[p_idx] == item.pieces[p_idx - 1]
+            {
+                item.group_len[n] += 1;
+            } else
+            {
+                n += 1;
+                item.group_len[n] = 1;
+            }
+        }
+
+        n += 1;
+        item.group_len[n] = 0;
+
+        // The order of the groups is stored in the file, order[0] is the position of the leading
+        // group and order[1] the position of the remaining pawns (if both sides have pawns)
+        let both_pawns = self.has_pawns && self.pawn_count[1] > 0;
+        let mut next = if both_pawns { 2 } else { 1 };
+        let mut free_squares = 64 - item.group_len[0] - if both_pawns { item.group_len[1] } else { 0 };
+        let mut idx: u64 = 1;
+        let mut k = 0;
+
+        while next < n || k == order[0] || k == order[1]
+        {
+            if k == order[0]
+            {
+                item.group_idx[0] = idx;
+                idx *= if self.has_pawns
+                {
+                    index.lead_pawns_size[item.group_len[0]][file]
+                } else if self.has_unique_pieces
+                {
+                    31332
+                } else
+                {
+                    462
+                };
+            } else if k == order[1]
+            {
+                item.group_idx[1] = idx;
+                idx *= index.binomial[item.group_len[1]][48 - item.group_len[0]];
+            } else
+            {
+                item.group_idx[next] = idx;
+                idx *= index.binomial[item.group_len[next]][free_squares];
+                free_squares -= item.group_len[next];
+                next += 1;
+            }
+
+            k += 1;
+        }
+
+        item.group_idx[n] = idx;
+    }
+
+    // Reads the sizes of the blocks and the Huffman code of a part of the table
+    fn set_sizes(item: &mut Pairs_Data, data: &[u8], mut position: usize) -> Option<usize>
+    {
+        item.flags = *data.get(position)?;
+        position += 1;
+
+        // All positions have the same value, which is stored in place of the symbol length
+        if item.flags & FLAG_SINGLE_VALUE != 0
+        {
+            item.min_sym_len = *data.get(position)?;
+            return Some(position + 1);
+        }
+
+        let groups = item.group_len.iter().position(|&length| length == 0).unwrap_or(TB_PIECES);
+        let table_size = item.group_idx[groups];
+
+        item.block_size = 1usize.checked_shl(*data.get(position)? as u32)?;
+        item.span = 1u64.checked_shl(*data.get(position + 1)? as u32)?;
+        item.sparse_index_size = table_size.div_ceil(item.span) as usize;
+
+        let padding = *data.get(position + 2)? as usize;
+        item.blocks_num = read_u32(data, position + 3)? as usize;
+        item.block_length_size = item.blocks_num + padding;
+
+        let max_sym_len = *data.get(position + 7)?;
+        item.min_sym_len = *data.get(position + 8)?;
+        item.lowest_sym = position + 9;
+
+        if    max_sym_len < item.min_sym_len
+           || item.min_sym_len == 0
+           || max_sym_len > 64
+        {
+            return None;
+        }
+
+        // Canonical Huffman code: longer codes have lower values, base64[i] is the lowest value
+        // of a code of length min_sym_len + i, left aligned within 64 bits
+        let lengths = (max_sym_len - item.min_sym_len) as usize + 1;
+        item.base64 = vec![0; lengths];
+
+        for l_idx in (0..lengths - 1).rev()
+        {
+            let lowest = read_u16(data, item.lowest_sym + 2 * l_idx)? as u64;
+            let next_lowest = read_u16(data, item.lowest_sym + 2 * l_idx + 2)? as u64;
+
+            item.base64[l_idx] = (item.base64[l_idx + 1] + lowest - next_lowest) / 2;
+        }
+
+        for l_idx in 0..lengths
+        {
+            item.base64[l_idx] = item.base64[l_idx].checked_shl(64 - l_idx as u32 - item.min_sym_len as u32).unwrap_or(0);
+        }
+
+        position = item.lowest_sym + 2 * lengths;
+
+        // Symbols are built by recursive pairing: every symbol is either a value or a pair of
+        // two other symbols stored within a binary tree
+        let symbols = read_u16(data, position)? as usize;
+        position += 2;
+
+        item.btree = position;
+        position += 3 * symbols + (symbols & 1);
+
+        if position > data.len()
+        {
+            return None;
+        }
+
+        item.symlen = vec![0; symbols];
+        let mut visited = vec![false; symbols];
+
+        for symbol in 0..symbols
+        {
+            if !visited[symbol]
+            {
+                item.symlen[symbol] = Self::set_symlen(item, data, symbol, &mut visited)?;
+            }
+        }
+
+        return Some(position);
+    }
+
+    // The number of values a symbol expands to, minus one
+    fn set_symlen(item: &mut Pairs_Data, data: &[u8], symbol: usize, visited: &mut Vec<bool>) -> Option<u8>
+    {
+        visited[symbol] = true;
+        let (left, right) = Self::btree_entry(item, data, symbol);
+
+        if right == 0xFFF
+        {
+            return Some(0);
+        }
+
+        for child in [left, right]
+        {
+            if child >= visited.len()
+            {
+                return None;
+            }
+
+            if !visited[child]
+            {
+                item.symlen[child] = Self::set_symlen(item, data, child, visited)?;
+            }
+        }
+
+        return Some(item.symlen[left].wrapping_add(item.symlen[right]).wrapping_add(1));
+    }
+
+    // Every entry of the tree holds two 12 bit symbols
+    fn btree_entry(item: &Pairs_Data, data: &[u8], symbol: usize) -> (usize, usize)
+    {
+        let bytes = &data[item.btree + 3 * symbol..item.btree + 3 * symbol + 3];
+
+        let left = ((bytes[1] as usize & 0xF) << 8) | bytes[0] as usize;
+        let right = ((bytes[2] as usize) << 4) | (bytes[1] as usize >> 4);
+
+        return (left, right);
+    }
+
+    // Finds the value stored at an index
+    fn decompress_pairs(&self, item: &Pairs_Data, idx: u64) -> Option<i32>
+    {
+        let data = &self.data;
+
+        if item.flags & FLAG_SINGLE_VALUE != 0
+        {
+            return Some(item.min_sym_len as i32);
+        }
+
+        // The sparse index stores the block and offset of every span-th value, the block of
+        // idx is found by walking the block lengths from there
+        let k = (idx / item.span) as usize;
+
+        if k >= item.sparse_index_size
+        {
+            return None;
+        }
+
+        let mut block = read_u32(data, item.sparse_index + 6 * k)? as usize;
+        let mut offset = read_u16(data, item.sparse_index + 6 * k + 4)? as i64;
+
+        offset += (idx % item.span) as i64 - (item.span / 2) as i64;
+
+        while offset < 0
+        {
+            if block == 0
+            {
+                return None;
+            }
+
+            block -= 1;
+            offset += read_u16(data, item.block_length + 2 * block)? as i64 + 1;
+        }
+
+        while offset > read_u16(data, item.block_length + 2 * block)? as i64
+        {
+            offset -= read_u16(data, item.block_length + 2 * block)? as i64 + 1;
+            block += 1;
+        }
+
+        // Read the symbols of the block until the one containing the offset is found
+        let mut pointer = item.data + block * item.block_size;
+        let mut buffer = read_be(data, pointer, 8);
+        let mut buffer_size = 64;
+        pointer += 8;
+
+        let mut symbol;
+
+        loop
+        {
+            let mut length = 0;
+
+            while buffer < item.base64[length]
+            {
+                length += 1;
+            }
+
+            let shift = 64 - length as u32 - item.min_sym_len as u32;
+            symbol = ((buffer - item.base64[length]) >> shift) as usize;
+            symbol += read_u16(data, item.lowest_sym + 2 * length)? as usize;
+
+            if symbol >= item.symlen.len()
+            {
+                return None;
+            }
+
+            if offset < item.symlen[symbol] as i64 + 1
+            {
+                break;
+            }
+
+            offset -= item.symlen[symbol] as i64 + 1;
+            length += item.min_sym_len as usize;
+            buffer = buffer.checked_shl(length as u32).unwrap_or(0);
+            buffer_size -= length as i32;
+
+            if buffer_size <= 32
+            {
+                buffer_size += 32;
+                buffer |= read_be(data, pointer, 4) << (64 - buffer_size);
+                pointer += 4;
+            }
+        }
+
+        // Expand the pairs down to a single value
+        while item.symlen[symbol] != 0
+        {
+            let (left, right) = Self::btree_entry(item, data, symbol);
+
+            if offset < item.symlen[left] as i64 + 1
+            {
+                symbol = left;
+            } else
+            {
+                offset -= item.symlen[left] as i64 + 1;
+                symbol = right;
+            }
+        }
+
+        return Some(Self::btree_entry(item, data, symbol).0 as i32);
+    }
+
+    // DTZ tables store the values through a map and either in moves or in plies,
+    // the result is always in plies
+    fn map_score(&self, file: usize, value: i32, wdl: i32) -> Option<i32>
+    {
+        let item = &self.items[0][file];
+        let mut value = value;
+
+        // Index of the map for a loss, blessed loss, cursed win and win
+        let wdl_map = [1, 3, 0, 2, 0];
+
+        if item.flags & FLAG_MAPPED != 0
+        {
+            let start = item.map_idx[wdl_map[(wdl + 2) as usize]] + value as usize;
+
+            value = if item.flags & FLAG_WIDE != 0
+            {
+                read_u16(&self.data, self.map + 2 * start)? as i32
+            } else
+            {
+                *self.data.get(self.map + start)? as i32
+            };
+        }
+
+        if    (wdl == 2 && item.flags & FLAG_WIN_PLIES == 0)
+           || (wdl == -2 && item.flags & FLAG_LOSS_PLIES == 0)
+           || wdl == 1
+           || wdl == -1
+        {
+            value *= 2;
+        }
+
+        return Some(value + 1);
+    }
+
+    // Looks up a position, black_stronger is set when the position has the pieces of the
+    // table with the colors swapped (e.g. a KvKR position in the KRvK table)
+    // Returns the value stored in the table: the WDL value for WDL tables, the DTZ in plies for
+    // DTZ tables (wdl is needed to decode it)
+    fn probe(&self, board: &Chess_Board, black_stronger: bool, wdl: i32, index: &Index_Tables) -> Option<Dtz_Lookup>
+    {
+        let (side, file, idx) = match self.encode(board, black_stronger, index)?
+        {
+            Table_Position::Index(side, file, idx) => (side, file, idx),
+            Table_Position::Change_Side            => return Some(Dtz_Lookup::Change_Side),
+        };
+
+        let value = self.decompress_pairs(&self.items[side][file], idx)?;
+
+        if self.is_dtz
+        {
+            return Some(Dtz_Lookup::Value(self.map_score(file, value, wdl)?));
+        }
+
+        return Some(Dtz_Lookup::Value(value - 2));
+    }
+
+    // Finds the part of the table and the index within it of a position
+    fn encode(&self, board: &Chess_Board, black_stronger: bool, index: &Index_Tables) -> Option<Table_Position>
+    {
+        let black_to_move = board.details.side_to_move == BLACK;
+
+        // Symmetric tables only store white to move, so the colors are swapped for black to move
+        let flip = black_stronger || (self.symmetric && black_to_move);
+        let flip_color: u8 = if flip { 8 } else { 0 };
+        let flip_squares: usize = if flip { 56 } else { 0 };
+        let stm = (flip != black_to_move) as usize;
+
+        let mut squares: Vec<usize> = vec![];
+        let mut pieces: Vec<u8> = vec![];
+        let mut lead_count = 0;
+        let mut tb_file = 0;
+        let mut lead_piece = 0;
+
+        // Tables with pawns are split by the file of the leading pawn
+        if self.has_pawns
+        {
+            lead_piece = self.items[0][0].pieces[0] ^ flip_color;
+            let lead_color = if lead_piece & 8 == 0 { WHITE } else { BLACK };
+
+            for square in 0..64
+            {
+                if board.board[square] == lead_color * PAWN
+                {
+                    squares.push(square ^ flip_squares);
+                    pieces.push(lead_piece ^ flip_color);
+                }
+            }
+
+            lead_count = squares.len();
+
+            let mut lead = 0;
+
+            for s_idx in 1..lead_count
+            {
+                if index.map_pawns[squares[s_idx]] > index.map_pawns[squares[lead]]
+                {
+                    lead = s_idx;
+                }
+            }
+
+            squares.swap(0, lead);
+
+            let col = Chess_Board::get_col(squares[0]);
+            tb_file = std::cmp::min(col, 7 - col);
+        }
+
+        let item = &self.items[stm % self.items.len()][tb_file];
+
+        // Symmetric tables without pawns look the same from both sides
+        if    self.is_dtz
+           && (item.flags & FLAG_STM) as usize != stm
+           && (!self.symmetric || self.has_pawns)
+        {
+            return Some(Table_Position::Change_Side);
+        }
+
+        for square in 0..64
+        {
+            let piece = board.board[square];
+
+            if    piece == 0
+               || (self.has_pawns && tb_piece(piece) == lead_piece)
+            {
+                continue;
+            }
+
+            squares.push(square ^ flip_squares);
+            pieces.push(tb_piece(piece) ^ flip_color);
+        }
+
+        let size = squares.len();
+
+        if size != self.piece_count
+        {
+            return None;
+        }
+
+        // Put the pieces into the order used by the table
+        for p_idx in lead_count..size.saturating_sub(1)
+        {
+            for o_idx in p_idx + 1..size
+            {
+                if item.pieces[p_idx] == pieces[o_idx]
+                {
+                    pieces.swap(p_idx, o_idx);
+                    squares.swap(p_idx, o_idx);
+                    break;
+                }
+            }
+        }
+
+        // Mirror so the leading piece is on the a-d files
+        if Chess_Board::get_col(squares[0]) > 3
+        {
+            for square in squares.iter_mut()
+            {
+                *square ^= 7;
+            }
+        }
+
+        let mut idx: u64;
+
+        if self.has_pawns
+        {
+            idx = index.lead_pawn_idx[lead_count][squares[0]];
+
+            squares[1..lead_count].sort_by_key(|&square| index.map_pawns[square]);
+
+            for s_idx in 1..lead_count
+            {
+                idx += index.binomial[s_idx][index.map_pawns[squares[s_idx]] as usize];
+            }
+        } else
+        {
+            // Without pawns the leading piece is also mirrored into the lower half of the board
+            // and below the a1-h8 diagonal
+            if Chess_Board::get_row(squares[0]) > 3
+            {
+                for square in squares.iter_mut()
+                {
+                    *square ^= 56;
+                }
+            }
+
+            for s_idx in 0..item.group_len[0]
+            {
+                if off_a1h8(squares[s_idx]) == 0
+                {
+                    continue;
+                }
+
+                if off_a1h8(squares[s_idx]) > 0
+                {
+                    for square in squares[s_idx..].iter_mut()
+                    {
+                        *square = ((*square >> 3) | (*square << 3)) & 63;
+                    }
+                }
+
+                break;
+            }
+
+            if self.has_unique_pieces
+            {
+                let adjust1 = (squares[1] > squares[0]) as u64;
+                let adjust2 = (squares[2] > squares[0]) as u64 + (squares[2] > squares[1]) as u64;
+
+                let rank = |square: usize| Chess_Board::get_row(square) as u64;
+
+                idx = if off_a1h8(squares[0]) != 0
+                {
+                      (index.map_a1d1d4[squares[0]] * 63 + (squares[1] as u64 - adjust1)) * 62
+                    + squares[2] as u64 - adjust2
+                } else if off_a1h8(squares[1]) != 0
+                {
+                      (6 * 63 + rank(squares[0]) * 28 + index.map_b1h1h7[squares[1]]) * 62
+                    + squares[2] as u64 - adjust2
+                } else if off_a1h8(squares[2]) != 0
+                {
+                      6 * 63 * 62 + 4 * 28 * 62
+                    + rank(squares[0]) * 7 * 28
+                    + (rank(squares[1]) - adjust1) * 28
+                    + index.map_b1h1h7[squares[2]]
+                } else
+                {
+                      6 * 63 * 62 + 4 * 28 * 62 + 4 * 7 * 28
+                    + rank(squares[0]) * 7 * 6
+                    + (rank(squares[1]) - adjust1) * 6
+                    + (rank(squares[2]) - adjust2)
+                };
+            } else
+            {
+                idx = index.map_kk[index.map_a1d1d4[squares[0]] as usize][squares[1]];
+            }
+        }
+
+        // Encode the remaining groups, every square is moved down by the number of squares of
+        // the earlier groups it comes after
+        idx *= item.group_idx[0];
+
+        let mut group_start = item.group_len[0];
+        let mut remaining_pawns = self.has_pawns && self.pawn_count[1] > 0;
+        let mut next = 1;
+
+        while item.group_len[next] != 0
+        {
+            let group_end = group_start + item.group_len[next];
+            squares[group_start..group_end].sort();
+
+            let mut n: u64 = 0;
+
+            for s_idx in group_start..group_end
+            {
+                let adjust = squares[..group_start].iter().filter(|&&other| squares[s_idx] > other).count();
+                let square = squares[s_idx] - adjust - if remaining_pawns { 8 } else { 0 };
+
+                n += index.binomial[s_idx - group_start + 1][square];
+            }
+
+            remaining_pawns = false;
+            idx += n * item.group_idx[next];
+            group_start = group_end;
+            next += 1;
+        }
+
+        return Some(Table_Position::Index(stm % self.items.len(), tb_file, idx));
+    }
+}
+
+// The files found for one material combination, loaded the first time they are needed
+struct Table_Entry
+{
+    wdl_path: Option<PathBuf>,
+    dtz_path: Option<PathBuf>,
+    wdl:      OnceLock<Option<Syzygy_Table>>,
+    dtz:      OnceLock<Option<Syzygy_Table>>,
+}
+
+// All Syzygy tables found within one or more directories
+pub struct Syzygy_Tablebase
+{
+    tables: HashMap<String, Table_Entry>,
+    index:  Index_Tables,
+
+    // The largest number of pieces (including kings) of the WDL tables found
+    max_pieces: usize,
+}
+
+impl Syzygy_Tablebase
+{
+    // Finds the tables within the directories, multiple directories are separated by ':' or
+    // ';' (as in the UCI SyzygyPath option). Files are only read once they are probed
+    pub fn open(paths: &str) -> Self
+    {
+        let mut tablebase = Syzygy_Tablebase
+        {
+            tables: HashMap::new(),
+            index: Index_Tables::new(),
+            max_pieces: 0,
+        };
+
+        for directory in paths.split([':', ';']).filter(|path| !path.is_empty())
+        {
+            let entries = match fs::read_dir(directory)
+            {
+                Ok(entries) => entries,
+                Err(_) => continue,
+            };
+
+            for entry in entries.flatten()
+            {
+                let path = entry.path();
+
+                let (name, extension) = match (path.file_stem().and_then(|name| name.to_str()),
+                                               path.extension().and_then(|extension| extension.to_str()))
+                {
+                    (Some(name), Some(extension)) => (name.to_string(), extension.to_string()),
+                    _ => continue,
+                };
+
+                if    extension != WDL_EXTENSION && extension != DTZ_EXTENSION
+                   || !name.contains('v')
+                {
+                    continue;
+                }
+
+                let table = tablebase.tables.entry(name.clone()).or_insert(Table_Entry
+                {
+                    wdl_path: None,
+                    dtz_path: None,
+                    wdl: OnceLock::new(),
+                    dtz: OnceLock::new(),
+                });
+
+                if extension == WDL_EXTENSION
+                {
+                    table.wdl_path = Some(path);
+                    tablebase.max_pieces = std::cmp::max(tablebase.max_pieces, name.len() - 1);
+                } else
+                {
+                    table.dtz_path = Some(path);
+                }
+            }
+        }
+
+        return tablebase;
+    }
+
+    // The largest number of pieces that can be probed, 0 if no tables were found
+    pub fn max_pieces(&self) -> usize
+    {
+        return self.max_pieces;
+    }
+
+    pub fn table_count(&self) -> usize
+    {
+        return self.tables.len();
+    }
+
+    // Whether a position can be looked up: no castling rights and not too many pieces
+    pub fn can_probe(&self, board: &Chess_Board) -> bool
+    {
+        let pieces = board.board.iter().filter(|&&piece| piece != 0).count();
+
+        return    pieces <= self.max_pieces
+               && !board.castling_rights().iter().any(|&right| right);
+    }
+
+    // Finds the table of the material on the board, the second value is true if the table has
+    // the colors of the position swapped
+    fn find_table(&self, board: &Chess_Board, is_dtz: bool) -> Option<(&Syzygy_Table, bool)>
+    {
+        let white = side_name(&board.board, WHITE);
+        let black = side_name(&board.board, BLACK);
+
+        let (name, black_stronger) = if self.tables.contains_key(&format!("{}v{}", white, black))
+        {
+            (format!("{}v{}", white, black), false)
+        } else
+        {
+            (format!("{}v{}", black, white), true)
+        };
+
+        let entry = self.tables.get(&name)?;
+
+        let table = if is_dtz
+        {
+            entry.dtz.get_or_init(|| Syzygy_Table::load(entry.dtz_path.as_ref()?, &name, true, &self.index))
+        } else
+        {
+            entry.wdl.get_or_init(|| Syzygy_Table::load(entry.wdl_path.as_ref()?, &name, false, &self.index))
+        };
+
+        return table.as_ref().map(|table| (table, black_stronger));
+    }
+
+    fn probe_wdl_table(&self, board: &Chess_Board) -> Option<i32>
+    {
+        // Only the two kings are left
+        if board.board.iter().filter(|&&piece| piece != 0).count() == 2
+        {
+            return Some(0);
+        }
+
+        let (table, black_stronger) = self.find_table(board, false)?;
+
+        return match table.probe(board, black_stronger, 0, &self.index)?
+        {
+            Dtz_Lookup::Value(value) => Some(value),
+            Dtz_Lookup::Change_Side  => None,
+        };
+    }
+
+    fn probe_dtz_table(&self, board: &Chess_Board, wdl: i32) -> Option<Dtz_Lookup>
+    {
+        let (table, black_stronger) = self.find_table(board, true)?;
+
+        return table.probe(board, black_stronger, wdl, &self.index);
+    }
+
+    // The WDL value of a position, the captures are searched first because the tables do not
+    // know about en passant and store "don't care" values where a capture is the best move
+    // The second value is true when the best move is a capture (or a pawn move with
+    // check_zeroing_moves), in which case the DTZ table can not be used
+    fn search_wdl(&self, board: &mut Chess_Board, check_zeroing_moves: bool) -> Option<(i32, bool)>
+    {
+        let moves = board.legal_moves();
+        let mut best_value = -2;
+        let mut move_count = 0;
+
+        for legal_move in moves.iter()
+        {
+            if    legal_move.capture == 0
+               && (!check_zeroing_moves || legal_move.piece.abs() != PAWN)
+            {
+                continue;
+            }
+
+            move_count += 1;
+
+            board.make_move(*legal_move);
+            let value = self.search_wdl(board, false).map(|(value, _)| -value);
+            board.unmake_move();
+
+            let value = value?;
+
+            if value > best_value
+            {
+                best_value = value;
+
+                if value >= 2
+                {
+                    return Some((value, true));
+                }
+            }
+        }
+
+        // If every legal move has been searched the table does not have to be probed
+        let no_more_moves = move_count > 0 && move_count == moves.len();
+
+        let value = if no_more_moves { best_value } else { self.probe_wdl_table(board)? };
+
+        if best_value >= value
+        {
+            return Some((best_value, best_value > 0 || no_more_moves));
+        }
+
+        return Some((value, false));
+    }
+
+    // The DTZ of a position with its WDL value already known
+    fn dtz_before_zeroing(wdl: i32) -> i32
+    {
+        return match wdl
+        {
+            2  =>  1,
+            1  =>  101,
+            -1 => -101,
+            -2 => -1,
+            _  =>  0,
+        };
+    }
+
+    fn probe_dtz_value(&self, board: &mut Chess_Board) -> Option<i32>
+    {
+        let (wdl, zeroing_best_move) = self.search_wdl(board, true)?;
+
+        // Draws are not stored within the DTZ tables
+        if wdl == 0
+        {
+            return Some(0);
+        }
+
+        if zeroing_best_move
+        {
+            return Some(Self::dtz_before_zeroing(wdl));
+        }
+
+        let sign = wdl.signum();
+
+        match self.probe_dtz_table(board, wdl)?
+        {
+            Dtz_Lookup::Value(dtz) =>
+            {
+                let cursed = if wdl == 1 || wdl == -1 { 100 } else { 0 };
+                return Some((dtz + cursed) * sign);
+            },
+            Dtz_Lookup::Change_Side => (),
+        }
+
+        // The table only stores the other side to move, so the DTZ is found with a search of
+        // one ply, picking the move with the lowest DTZ that keeps the result
+        let mut min_dtz = i32::MAX;
+
+        for legal_move in board.legal_moves()
+        {
+            let zeroing = legal_move.capture != 0 || legal_move.piece.abs() == PAWN;
+
+            board.make_move(legal_move);
+
+            // For zeroing moves the DTZ is the one of the move itself
+            let result = if zeroing
+            {
+                self.search_wdl(board, false).map(|(value, _)| -Self::dtz_before_zeroing(value))
+            } else
+            {
+                self.probe_dtz_value(board).map(|dtz| -dtz)
+            };
+
+            let mate =    result == Some(1)
+                       && board.in_check(board.details.side_to_move)
+                       && board.legal_moves().is_empty();
+
+            board.unmake_move();
+
+            let mut dtz = result?;
+
+            if mate
+            {
+                min_dtz = 1;
+            }
+
+            if !zeroing
+            {
+                dtz += dtz.signum();
+            }
+
+            if dtz < min_dtz && dtz.signum() == sign
+            {
+                min_dtz = dtz;
+            }
+        }
+
+        // Without legal moves the position is checkmate
+        return Some(if min_dtz == i32::MAX { -1 } else { min_dtz });
+    }
+
+    // The WDL result of a position, None if it can not be probed
+    pub fn probe_wdl(&self, board: &mut Chess_Board) -> Option<Wdl>
+    {
+        if !self.can_probe(board)
+        {
+            return None;
+        }
+
+        return self.search_wdl(board, false).map(|(value, _)| Wdl::from_value(value));
+    }
+
+    // The DTZ of a position in plies, None if it can not be probed
+    pub fn probe_dtz(&self, board: &mut Chess_Board) -> Option<i32>
+    {
+        if !self.can_probe(board)
+        {
+            return None;
+        }
+
+        return self.probe_dtz_value(board);
+    }
+
+    // Probes both the WDL and DTZ tables
+    pub fn probe(&self, board: &mut Chess_Board) -> Option<Syzygy_Probe>
+    {
+        let wdl = self.probe_wdl(board)?;
+        let dtz = self.probe_dtz(board)?;
+
+        return Some(Syzygy_Probe { wdl, dtz });
+    }
+
+    // Ranks the legal moves of a position, a higher rank is better for the side to move
+    // The DTZ tables are used if available, otherwise only the WDL tables (which does not
+    // guarantee progress towards the win). The second value tells which tables were used
+    pub fn rank_root_moves(&self, board: &mut Chess_Board) -> Option<(Vec<(Move, i32)>, bool)>
+    {
+        if !self.can_probe(board)
+        {
+            return None;
+        }
+
+        if let Some(ranked) = self.rank_by_dtz(board)
+        {
+            return Some((ranked, true));
+        }
+
+        let mut ranked: Vec<(Move, i32)> = vec![];
+
+        for legal_move in board.legal_moves()
+        {
+            board.make_move(legal_move);
+            let wdl = self.search_wdl(board, false).map(|(value, _)| -value);
+            board.unmake_move();
+
+            let rank = match wdl?
+            {
+                2  =>  1000,
+                1  =>  899,
+                0  =>  0,
+                -1 => -899,
+                _  => -1000,
+            };
+
+            ranked.push((legal_move, rank));
+        }
+
+        ranked.sort_by_key(|&(_, rank)| -rank);
+
+        return Some((ranked, false));
+    }
+
+    fn rank_by_dtz(&self, board: &mut Chess_Board) -> Option<Vec<(Move, i32)>>
+    {
+        let halfmove_clock = board.details.halfmove_clock as i32;
+        let mut ranked: Vec<(Move, i32)> = vec![];
+
+        for legal_move in board.legal_moves()
+        {
+            let zeroing = legal_move.capture != 0 || legal_move.piece.abs() == PAWN;
+
+            board.make_move(legal_move);
+
+            let mut dtz = if zeroing
+            {
+                self.search_wdl(board, false).map(|(value, _)| Self::dtz_before_zeroing(-value))
+            } else
+            {
+                self.probe_dtz_value(board).map(|dtz| -dtz + (-dtz).signum())
+            };
+
+            // A mating move gets a DTZ of 1
+            if    dtz == Some(2)
+               && board.in_check(board.details.side_to_move)
+               && board.legal_moves().is_empty()
+            {
+                dtz = Some(1);
+            }
+
+            board.unmake_move();
+
+            let dtz = dtz?;
+
+            // Every win within the 50 move rule gets the same rank, so the search can pick
+            // between them. Losses are ranked the same unless the 50 move rule can save them
+            let rank = if dtz > 0
+            {
+                if dtz + halfmove_clock <= 99 { MAX_DTZ } else { MAX_DTZ - (dtz + halfmove_clock) }
+            } else if dtz < 0
+            {
+                if -dtz * 2 + halfmove_clock < 100 { -MAX_DTZ } else { -MAX_DTZ + (-dtz + halfmove_clock) }
+            } else
+            {
+                0
+            };
+
+            ranked.push((legal_move, rank));
+        }
+
+        ranked.sort_by_key(|&(_, rank)| -rank);
+
+        return Some(ranked);
+    }
+
+    // The best moves of a position according to the tables, together with the result
+    // Only the moves with the best rank are kept, the search then picks between them
+    pub fn filter_root_moves(&self, board: &mut Chess_Board) -> Option<(Vec<Move>, Wdl, bool)>
+    {
+        let (ranked, used_dtz) = self.rank_root_moves(board)?;
+        let best_rank = ranked.first()?.1;
+        let wdl = Wdl::from_value(self.search_wdl(board, false)?.0);
+
+        let moves = ranked.into_iter()
+                          .filter(|&(_, rank)| rank == best_rank)
+                          .map(|(best_move, _)| best_move)
+                          .collect();
+
+        return Some((moves, wdl, used_dtz));
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::tablebase::{Endgame_Tablebase, Tb_Value};
+
+    use std::cmp::Reverse;
+    use std::collections::BinaryHeap;
+
+    // The fixtures are small tables written by write_fixtures (at the end of this file) from the
+    // tables of tablebase.rs, to write them again:
+    // cargo test --release -- --ignored write_fixtures
+    fn fixture_directory() -> PathBuf
+    {
+        return Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("syzygy");
+    }
+
+    fn fixtures() -> Syzygy_Tablebase
+    {
+        return Syzygy_Tablebase::open(fixture_directory().to_str().unwrap());
+    }
+
+    fn probe_wdl_fen(tablebase: &Syzygy_Tablebase, fen: &str) -> Wdl
+    {
+        return tablebase.probe_wdl(&mut Chess_Board::from_fen(fen).unwrap()).unwrap();
+    }
+
+    fn probe_fen(tablebase: &Syzygy_Tablebase, fen: &str) -> (Wdl, i32)
+    {
+        let mut board = Chess_Board::from_fen(fen).unwrap();
+
+        return (tablebase.probe_wdl(&mut board).unwrap(), tablebase.probe_dtz(&mut board).unwrap());
+    }
+
+    fn filtered_moves(tablebase: &Syzygy_Tablebase, fen: &str) -> (Vec<String>, Wdl, bool)
+    {
+        let mut board = Chess_Board::from_fen(fen).unwrap();
+        let (moves, wdl, used_dtz) = tablebase.filter_root_moves(&mut board).unwrap();
+
+        return (moves.into_iter().map(|best_move| best_move.to_uci()).collect(), wdl, used_dtz);
+    }
+
+    #[test]
+    fn fixtures_are_found()
+    {
+        let tablebase = fixtures();
+
+        assert_eq!(tablebase.table_count(), FIXTURES.len());
+        assert_eq!(tablebase.max_pieces(), 4);
+    }
+
+    // The values checked by the probe_* tests hold for any correct table, they are checked
+    // against the published tables as well when SYZYGY_PATH points to them:
+    // SYZYGY_PATH=/path/to/syzygy cargo test -- --ignored published_tables
+    fn check_kqvk(tablebase: &Syzygy_Tablebase)
+    {
+
+        // Qf8 mates
+        assert_eq!(probe_fen(tablebase, "7k/8/6K1/8/8/8/8/5Q2 w - - 0 1"), (Wdl::Win, 1));
+        // Checkmate and stalemate
+        assert_eq!(probe_fen(tablebase, "5Q1k/8/6K1/8/8/8/8/8 b - - 0 1"), (Wdl::Loss, -1));
+        assert_eq!(probe_fen(tablebase, "7k/5Q2/6K1/8/8/8/8/8 b - - 0 1"), (Wdl::Draw, 0));
+        // The king takes the queen
+        assert_eq!(probe_fen(tablebase, "8/8/8/8/8/8/1k6/Q3K3 b - - 0 1"), (Wdl::Draw, 0));
+        // The longest win, mate in 10
+        assert_eq!(probe_fen(tablebase, "8/8/8/5k2/8/8/1Q6/K7 w - - 0 1").1, 19);
+
+        // The same positions with the colors swapped are found in the same table
+        assert_eq!(probe_fen(tablebase, "5q2/8/8/8/8/6k1/8/7K b - - 0 1"), (Wdl::Win, 1));
+        assert_eq!(probe_fen(tablebase, "8/8/8/8/8/8/1K6/q3k3 w - - 0 1"), (Wdl::Draw, 0));
+    }
+
+    fn check_krvk(tablebase: &Syzygy_Tablebase)
+    {
+
+        assert_eq!(probe_fen(tablebase, "7k/8/6K1/8/8/8/8/R7 w - - 0 1"), (Wdl::Win, 1));
+        // Kb8 is the only move, then Rh8 mates
+        assert_eq!(probe_fen(tablebase, "k7/8/1K6/8/8/8/8/7R b - - 0 1"), (Wdl::Loss, -2));
+        // The longest win, mate in 16
+        assert_eq!(probe_fen(tablebase, "8/8/8/8/8/2k5/1R6/K7 w - - 0 1").1, 31);
+    }
+
+    fn check_kpvk(tablebase: &Syzygy_Tablebase)
+    {
+
+        // e8=Q is a zeroing move that wins
+        assert_eq!(probe_fen(tablebase, "8/4P1k1/4K3/8/8/8/8/8 w - - 0 1"), (Wdl::Win, 1));
+        // Stalemate
+        assert_eq!(probe_fen(tablebase, "4k3/4P3/4K3/8/8/8/8/8 b - - 0 1"), (Wdl::Draw, 0));
+        // The king in front of the pawn on the 6th rank wins, whoever is to move
+        assert_eq!(probe_fen(tablebase, "4k3/8/4K3/4P3/8/8/8/8 w - - 0 1").0, Wdl::Win);
+        assert_eq!(probe_fen(tablebase, "4k3/8/4K3/4P3/8/8/8/8 b - - 0 1").0, Wdl::Loss);
+        // A rook pawn with the king in the corner is a draw
+        assert_eq!(probe_fen(tablebase, "7k/8/8/8/8/8/7P/7K w - - 0 1"), (Wdl::Draw, 0));
+        // Black pawns are found in the same table
+        assert_eq!(probe_fen(tablebase, "8/8/8/8/8/4k3/4p1K1/8 b - - 0 1"), (Wdl::Win, 1));
+        assert_eq!(probe_fen(tablebase, "8/8/8/8/4p3/4k3/8/4K3 w - - 0 1").0, Wdl::Loss);
+    }
+
+    fn check_krvkn(tablebase: &Syzygy_Tablebase)
+    {
+        // Rh8 mates, the knight can not get to the eighth rank
+        assert_eq!(probe_wdl_fen(tablebase, "k7/8/1K6/8/8/8/8/4n2R w - - 0 1"), Wdl::Win);
+        // Checkmate
+        assert_eq!(probe_wdl_fen(tablebase, "R6k/8/7K/8/8/8/8/n7 b - - 0 1"), Wdl::Loss);
+        // The knight takes the rook
+        assert_eq!(probe_wdl_fen(tablebase, "8/8/8/4k3/8/1n6/8/R6K b - - 0 1"), Wdl::Draw);
+        // The first position with the colors swapped, Rh1 mates
+        assert_eq!(probe_wdl_fen(tablebase, "4N2r/8/8/8/8/1k6/8/K7 b - - 0 1"), Wdl::Win);
+    }
+
+    fn check_kpvkp(tablebase: &Syzygy_Tablebase)
+    {
+        // Stalemate, the pawns block each other
+        assert_eq!(probe_wdl_fen(tablebase, "k7/p1K5/P7/8/8/8/8/8 b - - 0 1"), Wdl::Draw);
+        // Kxd6 leaves the king on a key square of the pawn
+        assert_eq!(probe_wdl_fen(tablebase, "4k3/8/3pK3/4P3/8/8/8/8 w - - 0 1"), Wdl::Win);
+        // exd6 en passant, the black king is too far away from the pawn
+        assert_eq!(probe_wdl_fen(tablebase, "8/8/8/3pP3/8/8/k7/4K3 w - d6 0 1"), Wdl::Win);
+        assert_eq!(probe_wdl_fen(tablebase, "4k3/K7/8/8/3Pp3/8/8/8 b - d3 0 1"), Wdl::Win);
+    }
+
+    #[test]
+    fn probe_kqvk()
+    {
+        check_kqvk(&fixtures());
+    }
+
+    #[test]
+    fn probe_krvk()
+    {
+        check_krvk(&fixtures());
+    }
+
+    #[test]
+    fn probe_kpvk()
+    {
+        check_kpvk(&fixtures());
+    }
+
+    #[test]
+    fn probe_krvkn()
+    {
+        check_krvkn(&fixtures());
+    }
+
+    #[test]
+    fn probe_kpvkp()
+    {
+        check_kpvkp(&fixtures());
+    }
+
+    // The tables with four pieces of the fixtures have no DTZ table, root moves are filtered by
+    // their WDL
+    #[test]
+    fn filter_root_moves_without_dtz()
+    {
+        let tablebase = fixtures();
+
+        let (moves, wdl, used_dtz) = filtered_moves(&tablebase, "8/8/8/3pP3/8/8/k7/4K3 w - d6 0 1");
+
+        assert_eq!((wdl, used_dtz), (Wdl::Win, false));
+        assert!(moves.contains(&String::from("e5d6")));
+
+        let (moves, wdl, _) = filtered_moves(&tablebase, "8/8/8/4k3/8/1n6/8/R6K b - - 0 1");
+
+        assert_eq!(wdl, Wdl::Draw);
+        assert!(moves.contains(&String::from("b3a1")));
+    }
+
+    #[test]
+    #[ignore]
+    fn published_tables()
+    {
+        let Some(path) = std::env::var_os("SYZYGY_PATH") else { panic!("SYZYGY_PATH is not set") };
+        let tablebase = Syzygy_Tablebase::open(path.to_str().unwrap());
+
+        check_kqvk(&tablebase);
+        check_krvk(&tablebase);
+        check_kpvk(&tablebase);
+        check_krvkn(&tablebase);
+        check_kpvkp(&tablebase);
+
+        // The published tables also have the DTZ of four pieces
+        assert_eq!(probe_fen(&tablebase, "k7/8/1K6/8/8/8/8/4n2R w - - 0 1"), (Wdl::Win, 1));
+        assert_eq!(probe_fen(&tablebase, "R6k/8/7K/8/8/8/8/n7 b - - 0 1"), (Wdl::Loss, -1));
+        assert_eq!(probe_fen(&tablebase, "8/8/8/3pP3/8/8/k7/4K3 w - d6 0 1"), (Wdl::Win, 1));
+    }
+
+    #[test]
+    fn filter_root_moves_keeps_the_wins()
+    {
+        let tablebase = fixtures();
+
+        // Qf8 mates and Qf7 stalemates
+        let (moves, wdl, used_dtz) = filtered_moves(&tablebase, "7k/8/6K1/8/8/8/8/5Q2 w - - 0 1");
+
+        assert_eq!((wdl, used_dtz), (Wdl::Win, true));
+        assert!(moves.contains(&String::from("f1f8")));
+        assert!(!moves.contains(&String::from("f1f7")));
+
+        // Taking the opposition is the only move that wins
+        let (moves, wdl, _) = filtered_moves(&tablebase, "4k3/8/3K4/4P3/8/8/8/8 w - - 0 1");
+
+        assert_eq!(wdl, Wdl::Win);
+        assert_eq!(moves, vec![String::from("d6e6")]);
+
+        // The only move loses
+        let (moves, wdl, _) = filtered_moves(&tablebase, "k7/8/1K6/8/8/8/8/7R b - - 0 1");
+
+        assert_eq!(wdl, Wdl::Loss);
+        assert_eq!(moves, vec![String::from("a8b8")]);
+    }
+
+    // The result of a position worked out from the results of its moves, the way the tables
+    // are defined: the DTZ counts the plies until the next capture or pawn move (or mate)
+    fn search_one_ply(tablebase: &Syzygy_Tablebase, board: &mut Chess_Board) -> (i32, i32)
+    {
+        let moves = board.legal_moves();
+
+        if moves.is_empty()
+        {
+            return if board.in_check(board.details.side_to_move) { (-2, -1) } else { (0, 0) };
+        }
+
+        let mut best_wdl = -2;
+        let mut win_dtz = i32::MAX;
+        let mut loss_dtz = 0;
+
+        for legal_move in moves
+        {
+            let zeroing = legal_move.capture != 0 || legal_move.piece.abs() == PAWN;
+
+            board.make_move(legal_move);
+
+            let mate = board.in_check(board.details.side_to_move) && board.legal_moves().is_empty();
+            let wdl = -tablebase.probe_wdl(board).unwrap().value();
+            let dtz = if zeroing || mate { 1 } else { 1 + tablebase.probe_dtz(board).unwrap().abs() };
+
+            board.unmake_move();
+
+            best_wdl = std::cmp::max(best_wdl, wdl);
+
+            if wdl == 2
+            {
+                win_dtz = std::cmp::min(win_dtz, dtz);
+            }
+
+            loss_dtz = std::cmp::max(loss_dtz, dtz);
+        }
+
+        return match best_wdl
+        {
+            2  => (2, win_dtz),
+            0  => (0, 0),
+            _  => (-2, -loss_dtz),
+        };
+    }
+
+    // Every value of the tables agrees with the values of the positions one move later
+    #[test]
+    fn tables_agree_with_one_ply_search()
+    {
+        let tablebase = fixtures();
+
+        for (name, pieces, _) in FIXTURES.iter().filter(|(_, _, dtz_flags)| dtz_flags.is_some())
+        {
+            let mut checked = 0;
+
+            for position in (0..position_count(pieces)).step_by(997)
+            {
+                let Some(mut board) = position_board(pieces, position) else { continue };
+
+                let wdl = tablebase.probe_wdl(&mut board).unwrap().value();
+                let dtz = tablebase.probe_dtz(&mut board).unwrap();
+
+                assert_eq!((wdl, dtz), search_one_ply(&tablebase, &mut board), "{} {}", name, board.to_fen());
+                checked += 1;
+            }
+
+            assert!(checked > 100, "{}", name);
+        }
+    }
+
+    // The WDL of the tables without DTZ agrees with the positions one move later, positions
+    // with a promotion are left out as their tables are not within the fixtures
+    #[test]
+    fn wdl_tables_agree_with_one_ply_search()
+    {
+        let tablebase = fixtures();
+
+        for (name, pieces, _) in FIXTURES.iter().filter(|(_, _, dtz_flags)| dtz_flags.is_none())
+        {
+            let mut checked = 0;
+
+            'positions: for position in (0..position_count(pieces)).step_by(99_991)
+            {
+                let Some(mut board) = position_board(pieces, position) else { continue };
+
+                // Checkmate and stalemate without moves
+                let moves = board.legal_moves();
+                let mut best_wdl = if moves.is_empty() && !board.in_check(board.details.side_to_move) { 0 } else { -2 };
+
+                for legal_move in moves
+                {
+                    board.make_move(legal_move);
+                    let wdl = tablebase.probe_wdl(&mut board);
+                    board.unmake_move();
+
+                    let Some(wdl) = wdl else { continue 'positions };
+                    best_wdl = std::cmp::max(best_wdl, -wdl.value());
+                }
+
+                assert_eq!(tablebase.probe_wdl(&mut board).unwrap().value(), best_wdl, "{} {}", name, board.to_fen());
+                checked += 1;
+            }
+
+            assert!(checked > 100, "{}", name);
+        }
+    }
+
+    // ---- Writing the fixtures ----
+
+    // The material of every fixture in the order the pieces are stored within the table, and
+    // the flags of the DTZ table (which side to move it stores and whether it uses value maps)
+    // The tables with four pieces only have a WDL table, working out their DTZ takes too long
+    const FIXTURES: [(&str, &[u8], Option<u8>); 7] =
+    [
+        ("KQvK",  &[6, 5, 14],     Some(0)),
+        ("KRvK",  &[6, 4, 14],     Some(FLAG_MAPPED)),
+        ("KBvK",  &[6, 3, 14],     Some(0)),
+        ("KNvK",  &[6, 2, 14],     Some(0)),
+        ("KPvK",  &[1, 6, 14],     Some(FLAG_STM | FLAG_MAPPED)),
+        ("KRvKN", &[6, 4, 14, 10], None),
+        ("KPvKP", &[1, 9, 6, 14],  None),
+    ];
+
+    // 64 byte blocks and an entry within the sparse index for every 1024 values
+    const BLOCK_SIZE_BITS: u8 = 6;
+    const SPAN_BITS:       u8 = 10;
+    const MAX_CODE_LENGTH: u32 = 24;
+
+    fn chess_piece(piece: u8) -> i8
+    {
+        return if piece & 8 == 0 { piece as i8 } else { -((piece & 7) as i8) };
+    }
+
+    // Positions are numbered by the squares of the pieces and the side to move
+    fn position_count(pieces: &[u8]) -> usize
+    {
+        return 64usize.pow(pieces.len() as u32) * 2;
+    }
+
+    fn position_number(pieces: &[u8], board: &Chess_Board) -> usize
+    {
+        let mut number = 0;
+
+        for &piece in pieces
+        {
+            let square = board.board.iter().position(|&other| other == chess_piece(piece)).unwrap();
+            number = number * 64 + square;
+        }
+
+        return number * 2 + (board.details.side_to_move == BLACK) as usize;
+    }
+
+    // The board of a position, None if the position can not happen
+    fn position_board(pieces: &[u8], number: usize) -> Option<Chess_Board>
+    {
+        let mut board = Chess_Board::new();
+        board.board = [0; 64];
+        board.details.rooks_have_moved = [true; 4];
+        board.details.kings_have_moved = [true; 2];
+        board.details.en_passant = None;
+        board.details.side_to_move = if number % 2 == 0 { WHITE } else { BLACK };
+
+        let mut rest = number / 2;
+
+        for &piece in pieces.iter().rev()
+        {
+            let square = rest % 64;
+            rest /= 64;
+
+            let last_row = Chess_Board::get_row(square) == 0 || Chess_Board::get_row(square) == 7;
+
+            if    board.board[square] != 0
+               || (piece & 7 == PAWN as u8 && last_row)
+            {
+                return None;
+            }
+
+            board.board[square] = chess_piece(piece);
+        }
+
+        if board.in_check(-board.details.side_to_move)
+        {
+            return None;
+        }
+
+        return Some(board);
+    }
+
+    // What is known about a position while the DTZ values are worked out
+    struct Solved_Position
+    {
+        wdl: i32,
+        dtz: i32,
+        // A zeroing move or a mate that keeps the win, or (when losing) any zeroing move
+        zeroing: bool,
+        // The positions reached by the other moves
+        children: Vec<usize>,
+    }
+
+    // The WDL of a position from the tables of tablebase.rs
+    fn generated_wdl(generated: &Endgame_Tablebase, board: &Chess_Board) -> i32
+    {
+        if board.board.iter().filter(|&&piece| piece != 0).count() == 2
+        {
+            return 0;
+        }
+
+        return match generated.probe(board).unwrap()
+        {
+            Tb_Value::Win(_)  =>  2,
+            Tb_Value::Draw    =>  0,
+            Tb_Value::Loss(_) => -2,
+        };
+    }
+
+    // Only the WDL of every position, for the tables without a DTZ table. The tables of
+    // tablebase.rs do not know the 50 move rule, so every win has to be a mate within 50 moves
+    // for the WDL to be the same as with the rule (there are no cursed wins or blessed losses)
+    fn solve_wdl(pieces: &[u8], generated: &Endgame_Tablebase) -> Vec<Option<Solved_Position>>
+    {
+        return (0..position_count(pieces)).map(|number|
+        {
+            let board = position_board(pieces, number)?;
+
+            if let Some(Tb_Value::Win(plies) | Tb_Value::Loss(plies)) = generated.probe(&board)
+            {
+                assert!(plies < 100, "{} is decided after more than 50 moves", board.to_fen());
+            }
+
+            Some(Solved_Position { wdl: generated_wdl(generated, &board), dtz: 0, zeroing: false, children: vec![] })
+        }).collect();
+    }
+
+    // The WDL and DTZ of every position of a material, the WDL comes from the tables of
+    // tablebase.rs and the DTZ is worked out one ply at a time from the zeroing moves
+    fn solve(pieces: &[u8], generated: &Endgame_Tablebase) -> Vec<Option<Solved_Position>>
+    {
+        let wdl_of = |board: &Chess_Board| generated_wdl(generated, board);
+
+        let mut positions: Vec<Option<Solved_Position>> = vec![];
+
+        for number in 0..position_count(pieces)
+        {
+            let Some(mut board) = position_board(pieces, number) else
+            {
+                positions.push(None);
+                continue;
+            };
+
+            let wdl = wdl_of(&board);
+            let mut zeroing = false;
+            let mut children = vec![];
+
+            for legal_move in board.legal_moves()
+            {
+                board.make_move(legal_move);
+
+                if legal_move.capture != 0 || legal_move.piece.abs() == PAWN
+                {
+                    zeroing |= wdl == -2 || wdl_of(&board) == -2;
+                } else if board.in_check(board.details.side_to_move) && board.legal_moves().is_empty()
+                {
+                    zeroing = true;
+                } else
+                {
+                    children.push(position_number(pieces, &board));
+                }
+
+                board.unmake_move();
+            }
+
+            positions.push(Some(Solved_Position { wdl, dtz: 0, zeroing, children }));
+        }
+
+        // Wins and losses without quiet moves to wait for are done after one ply
+        for position in positions.iter_mut().flatten()
+        {
+            if position.wdl == 2 && position.zeroing
+            {
+                position.dtz = 1;
+            } else if position.wdl == -2 && position.children.is_empty()
+            {
+                position.dtz = -1;
+            }
+        }
+
+        // A win takes one ply more than the quickest loss it can move into, a loss one ply more
+        // than the slowest win of the opponent
+        for plies in 2..
+        {
+            let dtz: Vec<i32> = positions.iter().map(|position| position.as_ref().map_or(0, |position| position.dtz)).collect();
+            let mut unsolved = 0;
+
+            for position in positions.iter_mut().flatten()
+            {
+                if position.wdl == 0 || position.dtz != 0
+                {
+                    continue;
+                }
+
+                if position.wdl == 2
+                {
+                    if position.children.iter().any(|&child| dtz[child] == -(plies - 1))
+                    {
+                        position.dtz = plies;
+                    }
+                } else if position.children.iter().all(|&child| dtz[child] > 0)
+                {
+                    let slowest = position.children.iter().map(|&child| dtz[child] + 1).max().unwrap();
+                    position.dtz = -std::cmp::max(slowest, position.zeroing as i32);
+                }
+
+                unsolved += (position.dtz == 0) as usize;
+            }
+
+            if unsolved == 0
+            {
+                break;
+            }
+
+            assert!(plies < 200, "unsolved positions");
+        }
+
+        return positions;
+    }
+
+    // The lengths of a Huffman code for the frequencies, rare symbols get more common until the
+    // longest code fits the limit
+    fn code_lengths(frequencies: &[u64]) -> Vec<u32>
+    {
+        let mut frequencies = frequencies.to_vec();
+
+        loop
+        {
+            let mut parents = vec![usize::MAX; frequencies.len()];
+            let mut heap: BinaryHeap<Reverse<(u64, usize)>> = frequencies.iter().enumerate().map(|(node, &frequency)| Reverse((frequency, node))).collect();
+
+            while heap.len() > 1
+            {
+                let Reverse((first, first_node)) = heap.pop().unwrap();
+                let Reverse((second, second_node)) = heap.pop().unwrap();
+
+                parents.push(usize::MAX);
+                parents[first_node] = parents.len() - 1;
+                parents[second_node] = parents.len() - 1;
+
+                heap.push(Reverse((first + second, parents.len() - 1)));
+            }
+
+            let lengths: Vec<u32> = (0..frequencies.len()).map(|symbol|
+            {
+                let mut length = 0;
+                let mut node = symbol;
+
+                while parents[node] != usize::MAX
+                {
+                    node = parents[node];
+                    length += 1;
+                }
+
+                length
+            }).collect();
+
+            if lengths.iter().all(|&length| length <= MAX_CODE_LENGTH)
+            {
+                return lengths;
+            }
+
+            for frequency in frequencies.iter_mut()
+            {
+                *frequency = frequency.div_ceil(2);
+            }
+        }
+    }
+
+    // One compressed part of a table, in the sections it is split into within the file
+    #[derive(Default)]
+    struct Compressed_Part
+    {
+        sizes:         Vec<u8>,
+        sparse_index:  Vec<u8>,
+        block_lengths: Vec<u8>,
+        data:          Vec<u8>,
+    }
+
+    // Compresses the values of a part the way set_sizes and decompress_pairs read them
+    fn compress(values: &[u16], flags: u8) -> Compressed_Part
+    {
+        if values.iter().all(|&value| value == values[0])
+        {
+            return Compressed_Part { sizes: vec![flags | FLAG_SINGLE_VALUE, values[0] as u8], ..Default::default() };
+        }
+
+        // The symbols are the values followed by pairs of symbols: (value or left, right)
+        let mut leaves: Vec<u16> = values.to_vec();
+        leaves.sort();
+        leaves.dedup();
+
+        let mut tree: Vec<(usize, usize)> = leaves.iter().map(|&value| (value as usize, 0xFFF)).collect();
+        let mut lengths: Vec<usize> = vec![1; leaves.len()];
+        let mut sequence: Vec<usize> = values.iter().map(|value| leaves.binary_search(value).unwrap()).collect();
+
+        // The most common pair of symbols next to each other becomes a new symbol, over and over
+        while tree.len() < 1024
+        {
+            let mut counts: HashMap<(usize, usize), usize> = HashMap::new();
+
+            for pair in sequence.windows(2)
+            {
+                *counts.entry((pair[0], pair[1])).or_insert(0) += 1;
+            }
+
+            let best = counts.into_iter()
+                             .filter(|&((left, right), count)| count >= 8 && lengths[left] + lengths[right] <= 256)
+                             .max_by_key(|&(pair, count)| (count, Reverse(pair)));
+
+            let Some(((left, right), _)) = best else { break };
+
+            let symbol = tree.len();
+            let mut paired = vec![];
+            let mut s_idx = 0;
+
+            while s_idx < sequence.len()
+            {
+                if s_idx + 1 < sequence.len() && sequence[s_idx] == left && sequence[s_idx + 1] == right
+                {
+                    paired.push(symbol);
+                    s_idx += 2;
+                } else
+                {
+                    paired.push(sequence[s_idx]);
+                    s_idx += 1;
+                }
+            }
+
+            // The Huffman code needs at least two symbols
+            if paired.iter().all(|&other| other == paired[0])
+            {
+                break;
+            }
+
+            tree.push((left, right));
+            lengths.push(lengths[left] + lengths[right]);
+            sequence = paired;
+        }
+
+        // Symbols are numbered from the longest code to the shortest, the symbols that are only
+        // used within pairs come last
+        let mut frequencies = vec![0; tree.len()];
+
+        for &symbol in &sequence
+        {
+            frequencies[symbol] += 1;
+        }
+
+        let used: Vec<usize> = (0..tree.len()).filter(|&symbol| frequencies[symbol] > 0).collect();
+        let used_lengths = code_lengths(&used.iter().map(|&symbol| frequencies[symbol]).collect::<Vec<u64>>());
+
+        let mut code_length = vec![0; tree.len()];
+
+        for (u_idx, &symbol) in used.iter().enumerate()
+        {
+            code_length[symbol] = used_lengths[u_idx];
+        }
+
+        let mut order: Vec<usize> = (0..tree.len()).collect();
+        order.sort_by_key(|&symbol| (frequencies[symbol] == 0, Reverse(code_length[symbol]), symbol));
+
+        let mut number = vec![0; tree.len()];
+
+        for (o_idx, &symbol) in order.iter().enumerate()
+        {
+            number[symbol] = o_idx;
+        }
+
+        let min_length = *used_lengths.iter().min().unwrap();
+        let max_length = *used_lengths.iter().max().unwrap();
+        let count = (max_length - min_length + 1) as usize;
+
+        let mut counts = vec![0u64; count];
+
+        for &length in &used_lengths
+        {
+            counts[(length - min_length) as usize] += 1;
+        }
+
+        let mut lowest = vec![0u64; count];
+        let mut base = vec![0u64; count];
+
+        for l_idx in (0..count - 1).rev()
+        {
+            lowest[l_idx] = lowest[l_idx + 1] + counts[l_idx + 1];
+            base[l_idx] = (base[l_idx + 1] + counts[l_idx + 1]) / 2;
+        }
+
+        // The blocks are filled with whole symbols
+        let block_bits = 8 << BLOCK_SIZE_BITS;
+        let max_block_values = 65536 - (1 << SPAN_BITS);
+
+        let mut blocks: Vec<Vec<bool>> = vec![vec![]];
+        let mut block_values: Vec<usize> = vec![0];
+
+        for &symbol in &sequence
+        {
+            let length = code_length[symbol];
+            let l_idx = (length - min_length) as usize;
+            let code = base[l_idx] + number[symbol] as u64 - lowest[l_idx];
+
+            if    blocks.last().unwrap().len() + length as usize > block_bits
+               || block_values.last().unwrap() + lengths[symbol] > max_block_values
+            {
+                blocks.push(vec![]);
+                block_values.push(0);
+            }
+
+            blocks.last_mut().unwrap().extend((0..length).rev().map(|bit| code >> bit & 1 == 1));
+            *block_values.last_mut().unwrap() += lengths[symbol];
+        }
+
+        let mut part = Compressed_Part::default();
+
+        part.sizes.extend([flags, BLOCK_SIZE_BITS, SPAN_BITS, 0]);
+        part.sizes.extend((blocks.len() as u32).to_le_bytes());
+        part.sizes.extend([max_length as u8, min_length as u8]);
+
+        for &value in &lowest
+        {
+            part.sizes.extend((value as u16).to_le_bytes());
+        }
+
+        part.sizes.extend((tree.len() as u16).to_le_bytes());
+
+        for &symbol in &order
+        {
+            let (left, right) = tree[symbol];
+            let (left, right) = if right == 0xFFF { (left, right) } else { (number[left], number[right]) };
+
+            part.sizes.extend([left as u8, (left >> 8) as u8 | (right << 4) as u8, (right >> 4) as u8]);
+        }
+
+        if tree.len() % 2 == 1
+        {
+            part.sizes.push(0);
+        }
+
+        // The block and offset of the value in the middle of every span
+        let mut starts = vec![0];
+
+        for &values in &block_values
+        {
+            starts.push(starts.last().unwrap() + values);
+        }
+
+        let span = 1 << SPAN_BITS;
+
+        for k in 0..values.len().div_ceil(span)
+        {
+            let middle = k * span + span / 2;
+            let block = (0..blocks.len()).rev().find(|&block| starts[block] <= middle).unwrap();
+
+            part.sparse_index.extend((block as u32).to_le_bytes());
+            part.sparse_index.extend(((middle - starts[block]) as u16).to_le_bytes());
+        }
+
+        for (block, &values) in blocks.iter().zip(&block_values)
+        {
+            part.block_lengths.extend(((values - 1) as u16).to_le_bytes());
+
+            let mut bytes = vec![0u8; block_bits / 8];
+
+            for (b_idx, &bit) in block.iter().enumerate()
+            {
+                bytes[b_idx / 8] |= (bit as u8) << (7 - b_idx % 8);
+            }
+
+            part.data.extend(bytes);
+        }
+
+        return part;
+    }
+
+    // The (WDL, value) of every index of a part, None where no position is stored
+    type Part_Values = Vec<Option<(i32, u16)>>;
+
+    // Writes a WDL or DTZ table with the values of the solved positions
+    fn write_table(name: &str, pieces: &[u8], dtz_flags: Option<u8>, positions: &[Option<Solved_Position>], index: &Index_Tables) -> Vec<u8>
+    {
+        let is_dtz = dtz_flags.is_some();
+        let mut table = Syzygy_Table::new(name, vec![], is_dtz).unwrap();
+
+        let sides = if is_dtz || table.symmetric { 1 } else { 2 };
+        let files = if table.has_pawns { 4 } else { 1 };
+
+        // With pawns on both sides the pawns of the other side are the second group
+        let both_pawns = table.has_pawns && table.pawn_count[1] > 0;
+        let order = [0, if both_pawns { 1 } else { 0xF }];
+
+        let mut items = vec![vec![Pairs_Data::default(); files]; sides];
+
+        for file in 0..files
+        {
+            for item in items.iter_mut()
+            {
+                item[file].pieces[..pieces.len()].copy_from_slice(pieces);
+                item[file].flags = dtz_flags.map_or(0, |flags| flags | FLAG_WIN_PLIES | FLAG_LOSS_PLIES);
+                table.set_groups(&mut item[file], order, file, index);
+            }
+        }
+
+        table.items = items;
+
+        // The values of every part, DTZ values are kept with their WDL for the maps
+        let mut parts: Vec<Vec<Part_Values>> = table.items.iter().map(|side| side.iter().map(|item|
+        {
+            let groups = item.group_len.iter().position(|&length| length == 0).unwrap();
+            vec![None; item.group_idx[groups] as usize]
+        }).collect()).collect();
+
+        for (number, position) in positions.iter().enumerate()
+        {
+            let Some(position) = position else { continue };
+            let board = position_board(pieces, number).unwrap();
+
+            let Some(Table_Position::Index(side, file, idx)) = table.encode(&board, false, index) else { continue };
+
+            let value = match is_dtz
+            {
+                false                     => Some((0, (position.wdl + 2) as u16)),
+                true if position.wdl == 0 => None,
+                true                      => Some((position.wdl, (position.dtz.abs() - 1) as u16)),
+            };
+
+            let stored = &mut parts[side][file][idx as usize];
+
+            if value.is_some()
+            {
+                assert!(stored.is_none() || *stored == value, "{} has two values at index {}", name, idx);
+                *stored = value;
+            }
+        }
+
+        // The maps of a DTZ table: every WDL result has its own list of the values that occur
+        let mapped = dtz_flags.is_some_and(|flags| flags & FLAG_MAPPED != 0);
+        let mut maps: Vec<[Vec<u16>; 4]> = vec![Default::default(); files];
+
+        if mapped
+        {
+            for file in 0..files
+            {
+                for &(wdl, value) in parts[0][file].iter().flatten()
+                {
+                    let map = &mut maps[file][if wdl > 0 { 0 } else { 1 }];
+
+                    if !map.contains(&value)
+                    {
+                        map.push(value);
+                    }
+                }
+
+                for map in maps[file].iter_mut()
+                {
+                    map.sort();
+                }
+            }
+        }
+
+        let mut compressed = vec![];
+
+        for file in 0..files
+        {
+            for side in 0..sides
+            {
+                let mut values: Vec<u16> = vec![];
+                let mut previous = parts[side][file].iter().flatten().next().map_or(0, |&(_, value)| value);
+
+                for &value in &parts[side][file]
+                {
+                    // Indices without a position repeat the value before, which compresses best
+                    if let Some((wdl, value)) = value
+                    {
+                        previous = if mapped { maps[file][if wdl > 0 { 0 } else { 1 }].binary_search(&value).unwrap() as u16 } else { value };
+                    }
+
+                    values.push(previous);
+                }
+
+                compressed.push(compress(&values, table.items[side][file].flags));
+            }
+        }
+
+        let mut bytes = if is_dtz { DTZ_MAGIC.to_vec() } else { WDL_MAGIC.to_vec() };
+
+        bytes.push(if sides == 2 { HEADER_SPLIT } else { 0 } | if table.has_pawns { HEADER_HAS_PAWNS } else { 0 });
+
+        for _ in 0..files
+        {
+            bytes.extend(order.iter().take(1 + both_pawns as usize).map(|&group| group | group << 4));
+            bytes.extend(pieces.iter().map(|&piece| piece | piece << 4));
+        }
+
+        bytes.resize(bytes.len() + bytes.len() % 2, 0);
+
+        for part in &compressed
+        {
+            bytes.extend(&part.sizes);
+        }
+
+        if is_dtz
+        {
+            for file_maps in maps.iter().filter(|_| mapped)
+            {
+                for map in file_maps
+                {
+                    bytes.push(map.len() as u8);
+                    bytes.extend(map.iter().map(|&value| value as u8));
+                }
+            }
+
+            bytes.resize(bytes.len() + bytes.len() % 2, 0);
+        }
+
+        for part in &compressed
+        {
+            bytes.extend(&part.sparse_index);
+        }
+
+        for part in &compressed
+        {
+            bytes.extend(&part.block_lengths);
+        }
+
+        for part in &compressed
+        {
+            bytes.resize((bytes.len() + 0x3F) & !0x3F, 0);
+            bytes.extend(&part.data);
+        }
+
+        return bytes;
+    }
+
+    // Every position has to come back out of the files
+    fn check_written(pieces: &[u8], positions: &[Option<Solved_Position>], with_dtz: bool)
+    {
+        let tablebase = fixtures();
+
+        for (number, position) in positions.iter().enumerate()
+        {
+            let Some(position) = position else { continue };
+            let mut board = position_board(pieces, number).unwrap();
+
+            assert_eq!(tablebase.probe_wdl(&mut board).unwrap().value(), position.wdl, "{}", board.to_fen());
+
+            if with_dtz
+            {
+                assert_eq!(tablebase.probe_dtz(&mut board).unwrap(), position.dtz, "{}", board.to_fen());
+            }
+        }
+    }
+
+    // The tables of tablebase.rs are generated again every time, unless INTERMEZZO_TABLES is set
+    // to a directory where they are kept (generating those for KPvKP takes a while)
+    #[test]
+    #[ignore]
+    fn write_fixtures()
+    {
+        let mut generated = Endgame_Tablebase::new(std::env::var_os("INTERMEZZO_TABLES").map(PathBuf::from));
+        let index = Index_Tables::new();
+        let directory = fixture_directory();
+
+        fs::create_dir_all(&directory).unwrap();
+
+        for (name, pieces, dtz_flags) in FIXTURES
+        {
+            generated.generate(name).unwrap();
+
+            let Some(dtz_flags) = dtz_flags else
+            {
+                let positions = solve_wdl(pieces, &generated);
+
+                fs::write(directory.join(format!("{}.{}", name, WDL_EXTENSION)), write_table(name, pieces, None, &positions, &index)).unwrap();
+                check_written(pieces, &positions, false);
+                continue;
+            };
+
+            let positions = solve(pieces, &generated);
+
+            // Without pawns the DTZ is the distance to mate
+            for (number, position) in positions.iter().enumerate()
+            {
+                let Some(position) = position else { continue };
+
+                if !name.contains('P')
+                {
+                    let board = position_board(pieces, number).unwrap();
+
+                    let expected = match generated.probe(&board).unwrap()
+                    {
+                        Tb_Value::Win(plies)  => plies as i32,
+                        Tb_Value::Draw        => 0,
+                        Tb_Value::Loss(plies) => -std::cmp::max(plies as i32, 1),
+                    };
+
+                    assert_eq!(position.dtz, expected, "{} {}", name, board.to_fen());
+                }
+            }
+
+            fs::write(directory.join(format!("{}.{}", name, WDL_EXTENSION)), write_table(name, pieces, None, &positions, &index)).unwrap();
+            fs::write(directory.join(format!("{}.{}", name, DTZ_EXTENSION)), write_table(name, pieces, Some(dtz_flags), &positions, &index)).unwrap();
+            check_written(pieces, &positions, true);
+        }
+    }
+}
@@ -0,0 +1,950 @@
+// Endgame tablebases for up to four pieces, generated by retrograde analysis
+//
+// A table holds the result (win / draw / loss) and the distance to mate of every position with a
+// given material, e.g. KQvK (white king and queen against the black king)
+//
+// Generation works backwards from the checkmates:
+// 1. Every position is set up on a Chess_Board and its legal moves are generated. Positions without
+//    moves are checkmates or stalemates. Captures and promotions leave the table, their results
+//    are looked up within the smaller tables (which are generated first)
+// 2. Starting from the checkmates the positions are resolved one ply at a time. Every position that
+//    can move into a lost position is won. A position where every move leads into a won position
+//    (for the opponent) is lost. The positions leading into a position are found by taking moves back
+// 3. Whatever is left unresolved at the end is a draw
+//
+// Positions with en passant or castling rights are not part of the tables, such positions are
+// looked up as if the right did not exist
+//
+// The tables only store one position out of every group of positions that are mirror images of
+// each other. Tables without pawns use all 8 symmetries of the board (the white king is always
+// moved into the a1-d1-d4 triangle), tables with pawns only use the left-right mirror
+
+use crate::chess::*;
+use crate::notation::piece_to_char;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+pub const MAX_PIECES: usize = 4;
+
+// The file extension and header of tables stored on disk
+pub const TABLE_EXTENSION: &str = "iztb";
+const MAGIC: &[u8; 4] = b"IZTB";
+const VERSION: u8 = 1;
+
+// The values stored for every position
+// DRAW        -> the position is a draw
+// ILLEGAL     -> the position can not happen (pieces on the same square, side not to move in check, ...)
+// 2 + plies   -> the number of plies until mate, even for the side to move losing, odd for winning
+const DRAW:       u8 = 0;
+const ILLEGAL:    u8 = 1;
+const DTM_OFFSET: u8 = 2;
+const MAX_PLIES:  usize = 252;
+
+// Only used while generating a table
+const UNRESOLVED: u8 = 255;
+
+// The result of a position from the point of view of the side to move
+// Win and Loss hold the number of plies until checkmate
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Tb_Value
+{
+    Win(u32),
+    Draw,
+    Loss(u32),
+}
+
+impl Tb_Value
+{
+    fn from_code(code: u8) -> Option<Self>
+    {
+        return match code
+        {
+            DRAW    => Some(Tb_Value::Draw),
+            ILLEGAL => None,
+            _ =>
+            {
+                let plies = (code - DTM_OFFSET) as u32;
+                Some(if plies % 2 == 0 { Tb_Value::Loss(plies) } else { Tb_Value::Win(plies) })
+            },
+        };
+    }
+
+    // The number of full moves until mate as it would be written as "mate in n"
+    pub fn moves_to_mate(&self) -> Option<u32>
+    {
+        return match self
+        {
+            Tb_Value::Win(plies)  => Some(plies.div_ceil(2)),
+            Tb_Value::Loss(plies) => Some(plies / 2),
+            Tb_Value::Draw        => None,
+        };
+    }
+}
+
+// The squares of the a1-d1-d4 triangle the white king is moved into for tables without pawns
+const TRIANGLE: [usize; 10] = [0, 1, 2, 3, 9, 10, 11, 18, 19, 27];
+
+// Applies one of the 8 symmetries of the board to a square
+// bit 0 -> mirror the files, bit 1 -> mirror the rows, bit 2 -> mirror along the a1-h8 diagonal
+fn transform(square: usize, symmetry: usize) -> usize
+{
+    let mut col = Chess_Board::get_col(square);
+    let mut row = Chess_Board::get_row(square);
+
+    if symmetry & 1 != 0
+    {
+        col = 7 - col;
+    }
+
+    if symmetry & 2 != 0
+    {
+        row = 7 - row;
+    }
+
+    if symmetry & 4 != 0
+    {
+        std::mem::swap(&mut col, &mut row);
+    }
+
+    return row * 8 + col;
+}
+
+// The value of a piece used to decide which side is the stronger side of a table
+fn piece_value(piece: i8) -> u32
+{
+    return match piece.abs()
+    {
+        QUEEN  => 9,
+        ROOK   => 5,
+        BISHOP => 3,
+        KNIGHT => 3,
+        PAWN   => 1,
+        _      => 0,
+    };
+}
+
+// The order of the pieces within a signature: K Q R B N P
+fn piece_order(piece: i8) -> usize
+{
+    return match piece.abs()
+    {
+        KING   => 0,
+        QUEEN  => 1,
+        ROOK   => 2,
+        BISHOP => 3,
+        KNIGHT => 4,
+        _      => 5,
+    };
+}
+
+// Writes the pieces of one side in signature order, e.g. [6, 1, 5] -> "KQP"
+fn side_signature(pieces: &[i8]) -> String
+{
+    let mut sorted = pieces.to_vec();
+    sorted.sort_by_key(|piece| piece_order(*piece));
+
+    return sorted.iter().map(|piece| piece_to_char(piece.abs())).collect();
+}
+
+// Returns the signature of the table that holds the material on the board and whether the
+// colors have to be swapped to find the position within it
+// The stronger side is always the white side of a table, KvKQ is found within KQvK
+pub fn material_signature(board: &[i8; 64]) -> (String, bool)
+{
+    let white: Vec<i8> = board.iter().copied().filter(|piece| *piece > 0).collect();
+    let black: Vec<i8> = board.iter().copied().filter(|piece| *piece < 0).collect();
+
+    let white_signature = side_signature(&white);
+    let black_signature = side_signature(&black);
+
+    let white_key = (white.iter().map(|piece| piece_value(*piece)).sum::<u32>(), white.len(), white_signature.clone());
+    let black_key = (black.iter().map(|piece| piece_value(*piece)).sum::<u32>(), black.len(), black_signature.clone());
+
+    if white_key >= black_key
+    {
+        return (format!("{}v{}", white_signature, black_signature), false);
+    }
+
+    return (format!("{}v{}", black_signature, white_signature), true);
+}
+
+// Parses a signature such as "KQvK" or "KQK" into the pieces of the table, white pieces first
+fn parse_signature(signature: &str) -> Result<Vec<i8>, String>
+{
+    let (white, black) = match signature.split_once('v')
+    {
+        Some(sides) => sides,
+        None =>
+        {
+            // Without a 'v' the black side starts at the second king
+            match signature[1..].find('K')
+            {
+                Some(position) => signature.split_at(position + 1),
+                None => return Err(format!("invalid signature \"{}\"", signature)),
+            }
+        },
+    };
+
+    let mut pieces: Vec<i8> = vec![];
+
+    for (side, color) in [(white, WHITE), (black, BLACK)]
+    {
+        let mut side_pieces: Vec<i8> = vec![];
+
+        for letter in side.chars()
+        {
+            let piece = match letter
+            {
+                'K' => KING,
+                'Q' => QUEEN,
+                'R' => ROOK,
+                'B' => BISHOP,
+                'N' => KNIGHT,
+                'P' => PAWN,
+                _   => return Err(format!("unknown piece '{}' in signature \"{}\"", letter, signature)),
+            };
+
+            side_pieces.push(piece * color);
+        }
+
+        if side_pieces.iter().filter(|piece| piece.abs() == KING).count() != 1
+        {
+            return Err(format!("every side needs exactly one king in \"{}\"", signature));
+        }
+
+        side_pieces.sort_by_key(|piece| piece_order(*piece));
+        pieces.extend(side_pieces);
+    }
+
+    if pieces.len() > MAX_PIECES
+    {
+        return Err(format!("\"{}\" has more than {} pieces", signature, MAX_PIECES));
+    }
+
+    return Ok(pieces);
+}
+
+// Mirrors the board vertically and swaps the colors of the pieces
+fn flip_colors(board: &[i8; 64]) -> [i8; 64]
+{
+    let mut flipped = [0; 64];
+
+    for square in 0..64
+    {
+        flipped[transform(square, 2)] = -board[square];
+    }
+
+    return flipped;
+}
+
+// A single table such as KQvK
+pub struct Endgame_Table
+{
+    pub signature: String,
+    // The pieces of the table, the white pieces (starting with the king) then the black pieces
+    pub pieces:    Vec<i8>,
+    pub has_pawns: bool,
+    pub values:    Vec<u8>,
+}
+
+impl Endgame_Table
+{
+    fn new(signature: &str) -> Result<Self, String>
+    {
+        let pieces = parse_signature(signature)?;
+        let has_pawns = pieces.iter().any(|piece| piece.abs() == PAWN);
+        let white: Vec<i8> = pieces.iter().copied().filter(|piece| *piece > 0).collect();
+        let black: Vec<i8> = pieces.iter().copied().filter(|piece| *piece < 0).collect();
+
+        return Ok(Endgame_Table
+        {
+            signature: format!("{}v{}", side_signature(&white), side_signature(&black)),
+            pieces,
+            has_pawns,
+            values: vec![],
+        });
+    }
+
+    // The number of squares the white king can be on after applying the symmetries
+    fn king_squares(&self) -> usize
+    {
+        return if self.has_pawns { 32 } else { 10 };
+    }
+
+    // The number of entries of the table
+    pub fn size(&self) -> usize
+    {
+        return self.king_squares() * 64usize.pow(self.pieces.len() as u32 - 1) * 2;
+    }
+
+    fn king_index(&self, square: usize) -> Option<usize>
+    {
+        if self.has_pawns
+        {
+            let col = Chess_Board::get_col(square);
+            return if col < 4 { Some(Chess_Board::get_row(square) * 4 + col) } else { None };
+        }
+
+        return TRIANGLE.iter().position(|triangle_square| *triangle_square == square);
+    }
+
+    fn king_from_index(&self, index: usize) -> usize
+    {
+        if self.has_pawns
+        {
+            return (index / 4) * 8 + index % 4;
+        }
+
+        return TRIANGLE[index];
+    }
+
+    // Computes the index of a position within the table
+    // Out of all the mirror images of the position the one with the lowest index is used, so every
+    // group of equivalent positions has exactly one index
+    pub fn encode(&self, board: &[i8; 64], side_to_move: i8) -> Option<usize>
+    {
+        let king = board.iter().position(|piece| *piece == KING)?;
+        let symmetries: &[usize] = if self.has_pawns { &[0, 1] } else { &[0, 1, 2, 3, 4, 5, 6, 7] };
+        let mut best: Option<usize> = None;
+
+        for symmetry in symmetries
+        {
+            let mut index = match self.king_index(transform(king, *symmetry))
+            {
+                Some(king_index) => king_index,
+                None => continue,
+            };
+
+            // Identical pieces (two knights) are stored with their squares in ascending order
+            let mut p_idx = 1;
+
+            while p_idx < self.pieces.len()
+            {
+                let piece = self.pieces[p_idx];
+                let count = self.pieces[p_idx..].iter().take_while(|other| **other == piece).count();
+
+                let mut squares: Vec<usize> = (0..64).filter(|square| board[*square] == piece)
+                                                     .map(|square| transform(square, *symmetry))
+                                                     .collect();
+
+                if squares.len() != count
+                {
+                    return None;
+                }
+
+                squares.sort();
+
+                for square in squares
+                {
+                    index = index * 64 + square;
+                }
+
+                p_idx += count;
+            }
+
+            index = index * 2 + if side_to_move == WHITE { 0 } else { 1 };
+            best = Some(best.map_or(index, |current| std::cmp::min(current, index)));
+        }
+
+        return best;
+    }
+
+    // Turns an index back into a position, None if two pieces would be on the same square
+    pub fn decode(&self, index: usize) -> Option<([i8; 64], i8)>
+    {
+        let side_to_move = if index % 2 == 0 { WHITE } else { BLACK };
+        let mut rest = index / 2;
+        let mut squares = vec![0; self.pieces.len()];
+
+        for p_idx in (1..self.pieces.len()).rev()
+        {
+            squares[p_idx] = rest % 64;
+            rest /= 64;
+        }
+
+        squares[0] = self.king_from_index(rest);
+
+        let mut board = [0; 64];
+
+        for p_idx in 0..self.pieces.len()
+        {
+            if board[squares[p_idx]] != 0
+            {
+                return None;
+            }
+
+            board[squares[p_idx]] = self.pieces[p_idx];
+        }
+
+        return Some((board, side_to_move));
+    }
+
+    // Looks up a position, the board has to hold exactly the material of the table
+    pub fn probe(&self, board: &[i8; 64], side_to_move: i8) -> Option<Tb_Value>
+    {
+        let index = self.encode(board, side_to_move)?;
+        return Tb_Value::from_code(*self.values.get(index)?);
+    }
+
+    // Writes the table in the on disk format
+    // header: "IZTB", version, length of the signature, signature
+    // data:   run length encoded values, (value, length of the run as a LEB128 number)
+    pub fn to_bytes(&self) -> Vec<u8>
+    {
+        let mut bytes: Vec<u8> = vec![];
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.push(self.signature.len() as u8);
+        bytes.extend_from_slice(self.signature.as_bytes());
+
+        let mut v_idx = 0;
+
+        while v_idx < self.values.len()
+        {
+            let value = self.values[v_idx];
+            let mut run = self.values[v_idx..].iter().take_while(|other| **other == value).count();
+            v_idx += run;
+
+            bytes.push(value);
+
+            loop
+            {
+                let low = (run & 0x7F) as u8;
+                run >>= 7;
+
+                if run == 0
+                {
+                    bytes.push(low);
+                    break;
+                }
+
+                bytes.push(low | 0x80);
+            }
+        }
+
+        return bytes;
+    }
+
+    // Reads a table written by to_bytes
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String>
+    {
+        if bytes.len() < 6 || &bytes[0..4] != MAGIC
+        {
+            return Err("not a tablebase file".to_string());
+        }
+
+        if bytes[4] != VERSION
+        {
+            return Err(format!("unsupported tablebase version {}", bytes[4]));
+        }
+
+        let length = bytes[5] as usize;
+
+        let signature = match bytes.get(6..6 + length).map(std::str::from_utf8)
+        {
+            Some(Ok(signature)) => signature,
+            _ => return Err("invalid signature in tablebase file".to_string()),
+        };
+
+        let mut table = Endgame_Table::new(signature)?;
+        let mut values: Vec<u8> = Vec::with_capacity(table.size());
+        let mut b_idx = 6 + length;
+
+        while b_idx < bytes.len()
+        {
+            let value = bytes[b_idx];
+            b_idx += 1;
+
+            let mut run = 0;
+            let mut shift = 0;
+
+            loop
+            {
+                let byte = match bytes.get(b_idx)
+                {
+                    Some(byte) => *byte,
+                    None => return Err("truncated tablebase file".to_string()),
+                };
+
+                b_idx += 1;
+                run |= ((byte & 0x7F) as usize) << shift;
+                shift += 7;
+
+                if byte & 0x80 == 0
+                {
+                    break;
+                }
+            }
+
+            values.extend(std::iter::repeat_n(value, run));
+        }
+
+        if values.len() != table.size()
+        {
+            return Err(format!("expected {} entries in {} but found {}", table.size(), signature, values.len()));
+        }
+
+        table.values = values;
+
+        return Ok(table);
+    }
+}
+
+// A collection of tables, tables are generated on demand or loaded from a directory
+pub struct Endgame_Tablebase
+{
+    pub directory: Option<PathBuf>,
+    pub tables:    HashMap<String, Endgame_Table>,
+
+    // A board reused for setting up positions while generating
+    scratch: Chess_Board,
+}
+
+impl Endgame_Tablebase
+{
+    pub fn new(directory: Option<PathBuf>) -> Self
+    {
+        let mut scratch = Chess_Board::new();
+        scratch.details.rooks_have_moved = [true; 4];
+        scratch.details.kings_have_moved = [true; 2];
+
+        return Endgame_Tablebase
+        {
+            directory,
+            tables: HashMap::new(),
+            scratch,
+        };
+    }
+
+    fn table_path(&self, signature: &str) -> Option<PathBuf>
+    {
+        return self.directory.as_ref().map(|directory| directory.join(format!("{}.{}", signature, TABLE_EXTENSION)));
+    }
+
+    // Loads a table from the directory, returns false if the file does not exist
+    pub fn load(&mut self, signature: &str) -> Result<bool, String>
+    {
+        let path = match self.table_path(signature)
+        {
+            Some(path) if path.exists() => path,
+            _ => return Ok(false),
+        };
+
+        let bytes = fs::read(&path).map_err(|error| format!("{}: {}", path.display(), error))?;
+        let table = Endgame_Table::from_bytes(&bytes)?;
+        self.tables.insert(table.signature.clone(), table);
+
+        return Ok(true);
+    }
+
+    // Makes sure a table and every smaller table it depends on is available
+    // Tables are loaded from the directory if possible, otherwise they are generated (and saved)
+    pub fn generate(&mut self, signature: &str) -> Result<(), String>
+    {
+        // KvKQ is generated as KQvK
+        let table = Endgame_Table::new(&Self::signature_of(&parse_signature(signature)?))?;
+
+        if self.tables.contains_key(&table.signature) || self.load(&table.signature)?
+        {
+            return Ok(());
+        }
+
+        for dependency in Self::dependencies(&table.pieces)
+        {
+            self.generate(&dependency)?;
+        }
+
+        let table = self.generate_table(table)?;
+
+        if let Some(path) = self.table_path(&table.signature)
+        {
+            fs::write(&path, table.to_bytes()).map_err(|error| format!("{}: {}", path.display(), error))?;
+        }
+
+        self.tables.insert(table.signature.clone(), table);
+
+        return Ok(());
+    }
+
+    // The signature of the table holding a list of pieces
+    fn signature_of(material: &[i8]) -> String
+    {
+        let mut board = [0; 64];
+
+        for (square, piece) in material.iter().enumerate()
+        {
+            board[square] = *piece;
+        }
+
+        return material_signature(&board).0;
+    }
+
+    // The signatures of the tables reached by a capture or a promotion
+    fn dependencies(pieces: &[i8]) -> Vec<String>
+    {
+        let mut results: Vec<String> = vec![];
+
+        for p_idx in 0..pieces.len()
+        {
+            if pieces[p_idx].abs() == KING
+            {
+                continue;
+            }
+
+            let mut captured = pieces.to_vec();
+            captured.remove(p_idx);
+            results.push(Self::signature_of(&captured));
+
+            if pieces[p_idx].abs() == PAWN
+            {
+                for promotion in [KNIGHT, BISHOP, ROOK, QUEEN]
+                {
+                    let mut promoted = pieces.to_vec();
+                    promoted[p_idx] = promotion * pieces[p_idx].signum();
+                    results.push(Self::signature_of(&promoted));
+
+                    // A promotion can also capture a piece at the same time
+                    for c_idx in 0..pieces.len()
+                    {
+                        if    c_idx != p_idx
+                           && pieces[c_idx].abs() != KING
+                           && pieces[c_idx].signum() != pieces[p_idx].signum()
+                        {
+                            let mut promoted_capture = promoted.clone();
+                            promoted_capture.remove(c_idx);
+                            results.push(Self::signature_of(&promoted_capture));
+                        }
+                    }
+                }
+            }
+        }
+
+        results.sort();
+        results.dedup();
+
+        return results;
+    }
+
+    // Sets up the scratch board with a position
+    fn setup(&mut self, board: &[i8; 64], side_to_move: i8)
+    {
+        self.scratch.board = *board;
+        self.scratch.moves.clear();
+        self.scratch.previous_details.clear();
+        self.scratch.details.side_to_move = side_to_move;
+        self.scratch.details.en_passant = None;
+    }
+
+    // Finds the canonical indices of every position from which the side that is not to move
+    // could have moved into the position (captures and promotions are not taken back since they
+    // come from other tables)
+    fn predecessors(&mut self, table: &Endgame_Table, board: &[i8; 64], side_to_move: i8) -> Vec<usize>
+    {
+        let mover = -side_to_move;
+        let mut results: Vec<usize> = vec![];
+
+        self.setup(board, side_to_move);
+
+        for square in 0..64
+        {
+            let piece = board[square];
+
+            if !Chess_Board::match_color(mover, piece)
+            {
+                continue;
+            }
+
+            let mut origins: Vec<usize> = vec![];
+
+            if piece.abs() == PAWN
+            {
+                let row = Chess_Board::get_row(square);
+                let behind = (square as i8 - mover * 8) as usize;
+                let (single_row, double_row) = if mover == WHITE { (2..=6, 3) } else { (1..=5, 4) };
+
+                if single_row.contains(&row) && board[behind] == 0
+                {
+                    origins.push(behind);
+
+                    let two_behind = (behind as i8 - mover * 8) as usize;
+
+                    if row == double_row && board[two_behind] == 0
+                    {
+                        origins.push(two_behind);
+                    }
+                }
+            } else
+            {
+                // Moves of pieces other than pawns are reversible, the squares a piece can move
+                // to are the squares it can have come from
+                origins = self.scratch.piece_moves(square)
+                                      .into_iter()
+                                      .filter(|origin| *origin < 64 && board[*origin] == 0)
+                                      .collect();
+            }
+
+            for origin in origins
+            {
+                let mut previous = *board;
+                previous[origin] = piece;
+                previous[square] = 0;
+
+                // The side that did not move can not have been in check
+                self.scratch.board = previous;
+
+                if !self.scratch.in_check(side_to_move)
+                {
+                    if let Some(index) = table.encode(&previous, mover)
+                    {
+                        results.push(index);
+                    }
+                }
+            }
+
+            self.scratch.board = *board;
+        }
+
+        results.sort();
+        results.dedup();
+
+        return results;
+    }
+
+    fn generate_table(&mut self, mut table: Endgame_Table) -> Result<Endgame_Table, String>
+    {
+        let size = table.size();
+
+        let mut values    = vec![UNRESOLVED; size];
+        let mut remaining = vec![0u8; size];
+        let mut can_lose  = vec![true; size];
+        let mut exit_loss = vec![0u8; size];
+        let mut done      = vec![false; size];
+        let mut levels: Vec<Vec<usize>> = vec![vec![]; MAX_PLIES + 2];
+
+        // Step 1: find the checkmates and the results of captures and promotions
+        for index in 0..size
+        {
+            let (board, side_to_move) = match table.decode(index)
+            {
+                Some(position) => position,
+                None =>
+                {
+                    values[index] = ILLEGAL;
+                    continue;
+                },
+            };
+
+            let pawn_on_last_row = (0..8).chain(56..64).any(|square| board[square].abs() == PAWN);
+
+            if    pawn_on_last_row
+               || table.encode(&board, side_to_move) != Some(index)
+            {
+                values[index] = ILLEGAL;
+                continue;
+            }
+
+            self.setup(&board, side_to_move);
+
+            if self.scratch.in_check(-side_to_move)
+            {
+                values[index] = ILLEGAL;
+                continue;
+            }
+
+            let moves = self.scratch.legal_moves();
+
+            if moves.is_empty()
+            {
+                if self.scratch.in_check(side_to_move)
+                {
+                    values[index] = DTM_OFFSET;
+                    levels[0].push(index);
+                } else
+                {
+                    values[index] = DRAW;
+                }
+
+                continue;
+            }
+
+            let mut children: Vec<usize> = vec![];
+            let mut best_exit_win: Option<usize> = None;
+
+            for legal_move in moves
+            {
+                self.scratch.make_move(legal_move);
+
+                if legal_move.capture != 0 || legal_move.promotion != 0
+                {
+                    match self.probe(&self.scratch)
+                    {
+                        Some(Tb_Value::Loss(plies)) =>
+                        {
+                            let plies = plies as usize + 1;
+                            best_exit_win = Some(best_exit_win.map_or(plies, |best| std::cmp::min(best, plies)));
+                        },
+                        Some(Tb_Value::Win(plies)) => exit_loss[index] = std::cmp::max(exit_loss[index], plies as u8 + 1),
+                        Some(Tb_Value::Draw) => can_lose[index] = false,
+                        None =>
+                        {
+                            let (missing, _) = material_signature(&self.scratch.board);
+                            return Err(format!("table {} is needed to generate {}", missing, table.signature));
+                        },
+                    }
+                } else if let Some(child) = table.encode(&self.scratch.board, -side_to_move)
+                {
+                    children.push(child);
+                }
+
+                self.scratch.unmake_move();
+            }
+
+            // Only the different positions count, moves reaching mirror images of the same
+            // position are only counted once
+            children.sort();
+            children.dedup();
+            remaining[index] = children.len() as u8;
+
+            if let Some(plies) = best_exit_win
+            {
+                can_lose[index] = false;
+                levels[plies].push(index);
+            } else if remaining[index] == 0
+            {
+                if can_lose[index]
+                {
+                    values[index] = DTM_OFFSET + exit_loss[index];
+                    levels[exit_loss[index] as usize].push(index);
+                } else
+                {
+                    values[index] = DRAW;
+                }
+            }
+        }
+
+        // Step 2: work backwards one ply at a time
+        for ply in 0..=MAX_PLIES
+        {
+            let current = std::mem::take(&mut levels[ply]);
+
+            for index in current
+            {
+                if done[index]
+                {
+                    continue;
+                }
+
+                // A win through a capture or promotion that was not found to be faster within the table
+                if values[index] == UNRESOLVED
+                {
+                    values[index] = DTM_OFFSET + ply as u8;
+                }
+
+                if values[index] != DTM_OFFSET + ply as u8
+                {
+                    continue;
+                }
+
+                done[index] = true;
+
+                let (board, side_to_move) = table.decode(index).unwrap();
+
+                for previous in self.predecessors(&table, &board, side_to_move)
+                {
+                    if values[previous] != UNRESOLVED
+                    {
+                        continue;
+                    }
+
+                    if ply % 2 == 0
+                    {
+                        // Moving into a lost position wins
+                        if ply + 1 > MAX_PLIES
+                        {
+                            return Err(format!("distance to mate in {} is too long to be stored", table.signature));
+                        }
+
+                        values[previous] = DTM_OFFSET + ply as u8 + 1;
+                        levels[ply + 1].push(previous);
+                    } else
+                    {
+                        // Once every move leads into a won position the position is lost
+                        remaining[previous] = remaining[previous].saturating_sub(1);
+
+                        if remaining[previous] == 0 && can_lose[previous]
+                        {
+                            let plies = std::cmp::max(ply + 1, exit_loss[previous] as usize);
+
+                            if plies > MAX_PLIES
+                            {
+                                return Err(format!("distance to mate in {} is too long to be stored", table.signature));
+                            }
+
+                            values[previous] = DTM_OFFSET + plies as u8;
+                            levels[plies].push(previous);
+                        }
+                    }
+                }
+            }
+        }
+
+        // Step 3: everything else is a draw
+        for value in values.iter_mut()
+        {
+            if *value == UNRESOLVED
+            {
+                *value = DRAW;
+            }
+        }
+
+        table.values = values;
+
+        return Ok(table);
+    }
+
+    // Looks up the result of a position from the point of view of the side to move
+    // None if the material is not covered by a loaded table
+    pub fn probe(&self, board: &Chess_Board) -> Option<Tb_Value>
+    {
+        let (signature, flipped) = material_signature(&board.board);
+        let table = self.tables.get(&signature)?;
+
+        if flipped
+        {
+            return table.probe(&flip_colors(&board.board), -board.details.side_to_move);
+        }
+
+        return table.probe(&board.board, board.details.side_to_move);
+    }
+
+    // Picks the best move of a position: the fastest mate when winning, a move that keeps the
+    // draw when drawn and the slowest mate when losing
+    pub fn best_move(&self, board: &mut Chess_Board) -> Option<Move>
+    {
+        let mut best: Option<(Move, i64)> = None;
+
+        for legal_move in board.legal_moves()
+        {
+            board.make_move(legal_move);
+            let value = self.probe(board);
+            board.unmake_move();
+
+            // A higher score is better for the side to move
+            let score = match value?
+            {
+                Tb_Value::Loss(plies) => 1000 - plies as i64,
+                Tb_Value::Draw        => 0,
+                Tb_Value::Win(plies)  => -1000 + plies as i64,
+            };
+
+            if best.is_none_or(|(_, best_score)| score > best_score)
+            {
+                best = Some((legal_move, score));
+            }
+        }
+
+        return best.map(|(best_move, _)| best_move);
+    }
+}
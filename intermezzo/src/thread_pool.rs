@@ -0,0 +1,204 @@
+// Multi-threaded search (Lazy SMP)
+//
+// Every thread runs its own search of the same position with its own killer moves and history.
+// The threads do not communicate apart from the shared transposition table: a position that has
+// been searched by one thread is found in the table by all other threads, so together they get
+// deeper than a single thread would. To keep the threads from searching the exact same tree the
+// helper threads skip some of the depths (see skip_depth in search.rs)
+//
+// Only the main thread (thread 0) manages the time and reports its progress, when it is done the
+// helper threads are stopped. The move that is played is decided by a vote, every thread votes
+// for its best move with a weight depending on its score and the depth it completed
+//
+// With MultiPV only the main thread searches more than one line and its result is always used,
+// the helpers only fill the transposition table
+
+use crate::chess::*;
+use crate::search::*;
+use crate::syzygy::*;
+use crate::time_manager::*;
+use crate::transposition::*;
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+
+pub struct Thread_Pool
+{
+    pub stop:          Arc<AtomicBool>,
+    pub ponder:        Arc<AtomicBool>,
+    pub syzygy:        Option<Arc<Syzygy_Tablebase>>,
+    pub move_overhead: u64,
+    pub multi_pv:      usize,
+
+    tt:           Arc<Transposition_Table>,
+    shared_nodes: Arc<AtomicU64>,
+    searches:     Vec<Search>,
+}
+
+impl Thread_Pool
+{
+    pub fn new(threads: usize, hash_mb: usize) -> Self
+    {
+        let mut pool = Thread_Pool
+        {
+            stop: Arc::new(AtomicBool::new(false)),
+            ponder: Arc::new(AtomicBool::new(false)),
+            syzygy: None,
+            move_overhead: DEFAULT_MOVE_OVERHEAD,
+            multi_pv: 1,
+            tt: Arc::new(Transposition_Table::new(hash_mb)),
+            shared_nodes: Arc::new(AtomicU64::new(0)),
+            searches: vec![],
+        };
+
+        pool.set_threads(threads);
+
+        return pool;
+    }
+
+    pub fn set_threads(&mut self, threads: usize)
+    {
+        let threads = std::cmp::max(threads, 1);
+
+        self.searches.truncate(threads);
+
+        while self.searches.len() < threads
+        {
+            let mut search = Search::with_table(self.tt.clone());
+            search.thread_id = self.searches.len();
+            self.searches.push(search);
+        }
+    }
+
+    // Replaces the transposition table with an empty one of the given size
+    pub fn set_hash_size(&mut self, hash_mb: usize)
+    {
+        self.tt = Arc::new(Transposition_Table::new(hash_mb));
+
+        for search in self.searches.iter_mut()
+        {
+            search.tt = self.tt.clone();
+        }
+    }
+
+    // Forgets everything from earlier searches, e.g. when a new game starts
+    pub fn clear(&mut self)
+    {
+        self.tt.clear();
+    }
+
+    pub fn hashfull(&self) -> u32
+    {
+        return self.tt.hashfull();
+    }
+
+    // Searches the position with all threads, report is only called by the main thread
+    // The stop flag is cleared again once the search is done, so a stop that arrives before the
+    // threads have started is not lost
+    pub fn search(&mut self, board: &Chess_Board, limits: &Search_Limits, report: &mut dyn FnMut(&Search_Info)) -> Search_Info
+    {
+        self.shared_nodes.store(0, Ordering::Relaxed);
+        self.tt.new_search();
+
+        for search in self.searches.iter_mut()
+        {
+            search.stop = self.stop.clone();
+            search.ponder = self.ponder.clone();
+            search.syzygy = self.syzygy.clone();
+            search.move_overhead = self.move_overhead;
+            search.shared_nodes = self.shared_nodes.clone();
+            search.multi_pv = 1;
+        }
+
+        self.searches[0].multi_pv = self.multi_pv;
+
+        // The helpers search until they are stopped by the main thread
+        let helper_limits = Search_Limits { time: None, ..*limits };
+        let stop = self.stop.clone();
+
+        let (main, helpers) = self.searches.split_at_mut(1);
+
+        let results = std::thread::scope(|scope| {
+            let handles: Vec<_> = helpers.iter_mut()
+                                         .map(|helper| scope.spawn(move || helper.search(board, &helper_limits, &mut |_| {})))
+                                         .collect();
+
+            let main_result = main[0].search(board, limits, report);
+            stop.store(true, Ordering::Relaxed);
+
+            let mut results = vec![main_result];
+            results.extend(handles.into_iter().map(|handle| handle.join().unwrap()));
+
+            return results;
+        });
+
+        self.stop.store(false, Ordering::Relaxed);
+
+        let best_index = if self.multi_pv > 1 { 0 } else { Self::vote(&results) };
+        let mut best = results[best_index].clone();
+        best.nodes = self.shared_nodes.load(Ordering::Relaxed);
+        best.time = results[0].time;
+
+        // The main thread has already reported its own result
+        if best_index != 0
+        {
+            report(&best);
+        }
+
+        return best;
+    }
+
+    // Every thread votes for its best move weighted by how much better its score is than the
+    // worst score of all threads and by the depth it completed. The thread with the most votes
+    // for its move is picked, a thread that found a mate is always preferred. Threads that did
+    // not get as deep as the main thread still vote but are never picked themselves
+    fn vote(results: &[Search_Info]) -> usize
+    {
+        let min_score = results.iter().map(|result| result.score).min().unwrap_or(0);
+        let mut votes: HashMap<u16, i64> = HashMap::new();
+        let key = |result: &Search_Info| result.best_move().map(|best_move| pack_move(&best_move)).unwrap_or(0);
+
+        for result in results
+        {
+            *votes.entry(key(result)).or_insert(0) += (result.score - min_score + 14) as i64 * result.depth as i64;
+        }
+
+        let mut best_index = 0;
+
+        for r_idx in 1..results.len()
+        {
+            let best = &results[best_index];
+            let candidate = &results[r_idx];
+
+            if    candidate.best_move().is_none()
+               || candidate.depth < results[0].depth
+            {
+                continue;
+            }
+
+            let better = if is_mate_score(best.score)
+            {
+                // A shorter mate is better, and so is a longer way to get mated
+                candidate.score > best.score
+            }
+            else if is_mate_score(candidate.score) && candidate.score > 0
+            {
+                true
+            }
+            else
+            {
+                   votes[&key(candidate)] > votes[&key(best)]
+                || (   votes[&key(candidate)] == votes[&key(best)]
+                    && candidate.score > best.score)
+            };
+
+            if better
+            {
+                best_index = r_idx;
+            }
+        }
+
+        return best_index;
+    }
+}
@@ -0,0 +1,140 @@
+// Three-check
+//
+// Normal chess, but a player also wins by giving check for the third time. The checks given are
+// stored within the Board_Details (checks_given) so they are taken back with the moves
+//
+// In FEN the checks are written as the number of checks each side still has to give, white first,
+// as an extra field after the en passant square:
+// rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 3+3 0 1
+//
+// The lichess notation with the checks already given at the end is read as well:
+// rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 +0+0
+//
+// Even a lone knight can give three checks, so there are no draws because of material
+
+use crate::chess::*;
+use crate::variant::*;
+
+pub const CHECKS_TO_WIN: u8 = 3;
+
+// Mixed into the position key for the number of checks given by white and black
+const CHECK_KEYS: [[u64; CHECKS_TO_WIN as usize + 1]; 2] = [
+    [0, 0x1c4f_0b7e_89a2_d3f1, 0x6d2e_93b4_f1c8_a507, 0x93a1_57e2_0cd4_6b8f],
+    [0, 0x4be8_26f9_d370_15ac, 0xa7d3_c18e_652b_9f04, 0x2f91_e46a_b8c7_3d52],
+];
+
+pub struct Three_Check;
+
+impl Rules for Three_Check
+{
+    fn name(&self) -> &'static str
+    {
+        return "Three-check";
+    }
+
+    fn aliases(&self) -> &'static [&'static str]
+    {
+        return &["3check"];
+    }
+
+    fn start_fen(&self) -> &'static str
+    {
+        return "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 3+3 0 1";
+    }
+
+    fn parse_fen(&self, fen: &str) -> Result<Chess_Board, String>
+    {
+        let mut fields: Vec<&str> = fen.split_whitespace().collect();
+        let mut checks_given = [0, 0];
+
+        // "W+B" after the en passant square, the checks that are left
+        if let Some(remaining) = fields.get(4).and_then(|field| parse_checks(field))
+        {
+            checks_given = [CHECKS_TO_WIN - remaining[0], CHECKS_TO_WIN - remaining[1]];
+            fields.remove(4);
+        // "+W+B" at the end, the checks that have been given
+        } else if let Some(given) = fields.last().and_then(|field| field.strip_prefix('+')).and_then(parse_checks)
+        {
+            checks_given = given;
+            fields.pop();
+        }
+
+        let mut new_board = Chess_Board::from_fen(&fields.join(" "))?;
+        new_board.details.checks_given = checks_given;
+
+        return Ok(new_board);
+    }
+
+    fn write_fen(&self, board: &Chess_Board, shredder: bool) -> String
+    {
+        let standard = board.standard_fen(shredder);
+        let mut fields: Vec<&str> = standard.split_whitespace().collect();
+
+        let [white, black] = board.details.checks_given;
+        let remaining = format!("{}+{}", CHECKS_TO_WIN.saturating_sub(white), CHECKS_TO_WIN.saturating_sub(black));
+
+        fields.insert(4, &remaining);
+
+        return fields.join(" ");
+    }
+
+    fn variant_key(&self, board: &Chess_Board) -> u64
+    {
+        let [white, black] = board.details.checks_given;
+
+        return   CHECK_KEYS[0][white.min(CHECKS_TO_WIN) as usize]
+               ^ CHECK_KEYS[1][black.min(CHECKS_TO_WIN) as usize];
+    }
+
+    fn make_move(&self, board: &mut Chess_Board, new_move: Move)
+    {
+        board.standard_make_move(new_move);
+
+        // is_check is the color giving check
+        if board.details.is_check != 0
+        {
+            let index = if board.details.is_check == WHITE { 0 } else { 1 };
+            board.details.checks_given[index] += 1;
+        }
+    }
+
+    fn variant_outcome(&self, board: &Chess_Board) -> Option<Outcome>
+    {
+        let [white, black] = board.details.checks_given;
+
+        if white >= CHECKS_TO_WIN
+        {
+            return Some(Outcome::Variant_Win(WHITE));
+        }
+
+        if black >= CHECKS_TO_WIN
+        {
+            return Some(Outcome::Variant_Win(BLACK));
+        }
+
+        return None;
+    }
+
+    fn material_draws(&self) -> bool
+    {
+        return false;
+    }
+}
+
+pub static THREE_CHECK: Three_Check = Three_Check;
+
+// Reads "2+3" into [2, 3]
+fn parse_checks(text: &str) -> Option<[u8; 2]>
+{
+    let (white, black) = text.split_once('+')?;
+    let white = white.parse::<u8>().ok()?;
+    let black = black.parse::<u8>().ok()?;
+
+    if    white > CHECKS_TO_WIN
+       || black > CHECKS_TO_WIN
+    {
+        return None;
+    }
+
+    return Some([white, black]);
+}
@@ -0,0 +1,292 @@
+// Time management for the engine
+//
+// Given the state of the clock the Time_Manager decides how long the engine should think
+// on the current move. Two limits are computed:
+//
+// soft_limit -> checked between iterations of the search, no new iteration is started once
+//               it has passed. It is scaled up or down while searching depending on how
+//               stable the best move and the score are
+// hard_limit -> checked inside the search itself, the search is aborted once it has passed
+//               no matter what
+//
+// All times are in milliseconds
+
+use std::time::Instant;
+
+// The default safety margin subtracted from the clock to account for communication
+// delays between the engine and the GUI / server
+pub const DEFAULT_MOVE_OVERHEAD: u64 = 30;
+
+// When the time control does not specify the number of moves until the next time control
+// (sudden death or increment only) we assume the game will last this many more moves
+const DEFAULT_MOVES_TO_GO: u64 = 40;
+
+// The largest number of moves_to_go taken into account, anything larger is treated the same
+const MAX_MOVES_TO_GO: u64 = 50;
+
+// Never plan to spend more than these fractions (in percent) of the remaining clock on a single move
+const MAX_SOFT_USAGE: u64 = 50;
+const MAX_HARD_USAGE: u64 = 80;
+
+// The hard limit is allowed to be this many times larger than the soft limit
+const HARD_LIMIT_FACTOR: u64 = 5;
+
+// Number of consecutive iterations with the same best move before the move is treated as obvious
+const OBVIOUS_MOVE_STABILITY: u32 = 6;
+
+// A drop in score (in centipawns) between iterations that is large enough to think longer
+const SCORE_DROP_MARGIN: i32 = 30;
+
+// The clock as seen by the side to move
+// moves_to_go  -> None for sudden death or increment based time controls
+// move_time    -> a fixed amount of time per move (UCI "go movetime"), overrides everything else
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Time_Controls
+{
+    pub remaining:   u64,
+    pub increment:   u64,
+    pub moves_to_go: Option<u64>,
+    pub move_time:   Option<u64>,
+}
+
+pub struct Time_Manager
+{
+    start: Instant,
+
+    // The limits computed from the clock when the move started
+    soft_limit: u64,
+    hard_limit: u64,
+
+    // Running statistics about the search that scale the soft limit
+    // best_move_changes decays every iteration so older changes matter less
+    best_move_changes: f64,
+    stability:         u32,
+    previous_score:    Option<i32>,
+    score_drop:        i32,
+    completed_depth:   u32,
+
+    // Set when there is only a single legal move, there is nothing to think about
+    forced: bool,
+}
+
+impl Time_Manager
+{
+    // Constructor that starts the clock and allocates the limits for the current move
+    pub fn new(controls: &Time_Controls, move_overhead: u64) -> Self
+    {
+        let (soft_limit, hard_limit) = Self::allocate(controls, move_overhead);
+
+        return Time_Manager
+        {
+            start: Instant::now(),
+            soft_limit,
+            hard_limit,
+            best_move_changes: 0.0,
+            stability: 0,
+            previous_score: None,
+            score_drop: 0,
+            completed_depth: 0,
+            forced: false,
+        };
+    }
+
+    // Computes the (soft, hard) limits for a move
+    //
+    // For a fixed move time both limits are the same
+    // Otherwise the remaining time (minus the overhead) is split between the moves that are
+    // still to be played and most of the increment is added on top, as the increment will
+    // be given back after the move anyway
+    pub fn allocate(controls: &Time_Controls, move_overhead: u64) -> (u64, u64)
+    {
+        if let Some(move_time) = controls.move_time
+        {
+            let limit = std::cmp::max(move_time.saturating_sub(move_overhead), 1);
+            return (limit, limit);
+        }
+
+        let available = controls.remaining.saturating_sub(move_overhead);
+
+        // Make sure the engine always gets at least a millisecond to find a move
+        if available == 0
+        {
+            return (1, 1);
+        }
+
+        let moves_to_go = controls.moves_to_go
+                                  .unwrap_or(DEFAULT_MOVES_TO_GO)
+                                  .clamp(1, MAX_MOVES_TO_GO);
+
+        let soft_cap = available * MAX_SOFT_USAGE / 100;
+        let hard_cap = available * MAX_HARD_USAGE / 100;
+
+        // With only one move left before the time control there is no reason to save time
+        // for later, both limits go up to their caps and the rest stays on the clock as a reserve
+        if moves_to_go == 1
+        {
+            return (std::cmp::max(soft_cap, 1), std::cmp::max(hard_cap, 1));
+        }
+
+        let base = available / moves_to_go + controls.increment * 3 / 4;
+
+        let soft_limit = std::cmp::max(std::cmp::min(base, soft_cap), 1);
+        let hard_limit = std::cmp::max(std::cmp::min(base * HARD_LIMIT_FACTOR, hard_cap), soft_limit);
+
+        return (soft_limit, hard_limit);
+    }
+
+    // Milliseconds since the move started
+    pub fn elapsed(&self) -> u64
+    {
+        return self.start.elapsed().as_millis() as u64;
+    }
+
+    // Starts the clock again but keeps the statistics of the search so far
+    // Used when pondering turns into a real search ("ponderhit"), the time spent pondering was
+    // on the opponent's clock
+    pub fn restart(&mut self)
+    {
+        self.start = Instant::now();
+    }
+
+    // Tells the time manager that the position only has a single legal move
+    pub fn set_forced(&mut self)
+    {
+        self.forced = true;
+    }
+
+    // Called by the search after every completed iteration
+    // best_move_changed -> whether this iteration picked a different best move than the last
+    // score             -> the score of the best move in centipawns
+    pub fn update(&mut self, depth: u32, best_move_changed: bool, score: i32)
+    {
+        self.completed_depth = depth;
+        self.best_move_changes /= 2.0;
+
+        if best_move_changed
+        {
+            self.best_move_changes += 1.0;
+            self.stability = 0;
+        } else
+        {
+            self.stability += 1;
+        }
+
+        self.score_drop = match self.previous_score
+        {
+            Some(previous) => previous - score,
+            None           => 0,
+        };
+
+        self.previous_score = Some(score);
+    }
+
+    // The soft limit after taking into account how the search has been going
+    //
+    // An unstable best move (changed in recent iterations) or a dropping score means the
+    // position is complicated and deserves more time
+    // A best move that has not changed in many iterations is considered obvious and the
+    // engine can move early
+    pub fn adjusted_soft_limit(&self) -> u64
+    {
+        let mut scale = 1.0 + self.best_move_changes;
+
+        if self.score_drop > SCORE_DROP_MARGIN
+        {
+            scale *= if self.score_drop > 4 * SCORE_DROP_MARGIN { 1.6 } else { 1.3 };
+        }
+
+        if self.stability >= OBVIOUS_MOVE_STABILITY
+        {
+            scale *= 0.5;
+        }
+
+        let adjusted = (self.soft_limit as f64 * scale) as u64;
+
+        return std::cmp::min(adjusted, self.hard_limit);
+    }
+
+    // Checked between iterations, returns true if the search should not start another one
+    pub fn should_stop(&self) -> bool
+    {
+        // With a single legal move the first iteration is enough to have a move to play
+        if    self.forced
+           && self.completed_depth >= 1
+        {
+            return true;
+        }
+
+        return self.elapsed() >= self.adjusted_soft_limit();
+    }
+
+    // Checked inside the search, returns true if the search has to be aborted immediately
+    pub fn should_abort(&self) -> bool
+    {
+        return self.elapsed() >= self.hard_limit;
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn clock(remaining: u64, increment: u64, moves_to_go: Option<u64>) -> Time_Controls
+    {
+        return Time_Controls { remaining, increment, moves_to_go, move_time: None };
+    }
+
+    #[test]
+    fn fixed_move_time()
+    {
+        let controls = Time_Controls { move_time: Some(1000), ..Default::default() };
+        assert_eq!(Time_Manager::allocate(&controls, 30), (970, 970));
+
+        // The overhead never takes away the last millisecond
+        let controls = Time_Controls { move_time: Some(10), ..Default::default() };
+        assert_eq!(Time_Manager::allocate(&controls, 30), (1, 1));
+    }
+
+    #[test]
+    fn last_move_before_the_time_control_stays_within_the_caps()
+    {
+        // 10 seconds after the overhead, the soft limit may use half and the hard limit 80%
+        assert_eq!(Time_Manager::allocate(&clock(10_030, 0, Some(1)), 30), (5000, 8000));
+        assert_eq!(Time_Manager::allocate(&clock(10_030, 5000, Some(1)), 30), (5000, 8000));
+    }
+
+    #[test]
+    fn almost_no_time_left()
+    {
+        assert_eq!(Time_Manager::allocate(&clock(0, 0, None), 30), (1, 1));
+        assert_eq!(Time_Manager::allocate(&clock(30, 0, Some(1)), 30), (1, 1));
+        assert_eq!(Time_Manager::allocate(&clock(40, 0, None), 30), (1, 1));
+        assert_eq!(Time_Manager::allocate(&clock(40, 0, Some(1)), 30), (5, 8));
+    }
+
+    #[test]
+    fn a_large_increment_is_capped_by_the_clock()
+    {
+        assert_eq!(Time_Manager::allocate(&clock(1000, 10_000, None), 0), (500, 800));
+    }
+
+    #[test]
+    fn limits_never_go_over_the_caps()
+    {
+        for remaining in [0, 1, 31, 100, 1000, 60_000, 3_600_000]
+        {
+            for increment in [0, 100, 2000, 30_000]
+            {
+                for moves_to_go in [None, Some(0), Some(1), Some(2), Some(10), Some(40), Some(100)]
+                {
+                    let controls = clock(remaining, increment, moves_to_go);
+                    let (soft_limit, hard_limit) = Time_Manager::allocate(&controls, 30);
+                    let available = remaining.saturating_sub(30);
+
+                    assert!(1 <= soft_limit && soft_limit <= hard_limit, "{:?}", controls);
+                    assert!(soft_limit <= std::cmp::max(available * MAX_SOFT_USAGE / 100, 1), "{:?}", controls);
+                    assert!(hard_limit <= std::cmp::max(available * MAX_HARD_USAGE / 100, 1), "{:?}", controls);
+                }
+            }
+        }
+    }
+}
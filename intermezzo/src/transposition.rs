@@ -0,0 +1,220 @@
+// The transposition table, a cache of positions that have already been searched
+//
+// The same position is often reached through different move orders (transpositions). For every
+// searched position the table stores the best move, the score and the depth it was searched to,
+// so the search can reuse the result or at least try the best move first
+//
+// The table is shared between the search threads without any locks. Every entry is stored as
+// two 64 bit values: the data and the key xor'ed with the data. When two threads write the same
+// entry at the same time the key no longer matches the data and the entry is simply ignored
+//
+// The data of an entry is packed into 64 bits:
+// bits  0-15 -> best move (origin, target and promotion piece)
+// bits 16-31 -> score
+// bits 32-39 -> depth
+// bits 40-41 -> bound
+// bits 42-49 -> generation, the search the entry was written in
+
+use crate::chess::*;
+
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+
+pub const DEFAULT_HASH_MB: usize = 16;
+
+// Whether the stored score is exact or only a bound on the real score
+// Lower -> the search failed high (score >= beta), the real score may be higher
+// Upper -> the search failed low (score <= alpha), the real score may be lower
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Bound
+{
+    Exact,
+    Lower,
+    Upper,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Tt_Entry
+{
+    pub best_move: u16,
+    pub score:     i32,
+    pub depth:     u32,
+    pub bound:     Bound,
+    generation:    u8,
+}
+
+struct Slot
+{
+    key:  AtomicU64,
+    data: AtomicU64,
+}
+
+pub struct Transposition_Table
+{
+    slots:      Vec<Slot>,
+    generation: AtomicU8,
+}
+
+// Packs a move into 16 bits: 6 bits origin, 6 bits target, 3 bits promotion piece and 1 bit for
+// castling, as in Chess960 a castling move can go to the same square as a normal king move
+// Drops have the same origin and target, the 3 bits hold the dropped piece (or the duck) instead
+pub fn pack_move(packed: &Move) -> u16
+{
+    let castle = packed.kind == Move_Kind::Short_Castle || packed.kind == Move_Kind::Long_Castle;
+    let piece = if packed.kind == Move_Kind::Drop || packed.kind == Move_Kind::Duck { packed.piece } else { packed.promotion };
+
+    return    packed.origin as u16
+           | (packed.target as u16) << 6
+           | (piece.unsigned_abs() as u16) << 12
+           | (castle as u16) << 15;
+}
+
+// Finds the move stored in an entry within a list of moves
+pub fn find_move(moves: &[Move], packed: u16) -> Option<Move>
+{
+    if packed == 0
+    {
+        return None;
+    }
+
+    return moves.iter().find(|candidate| pack_move(candidate) == packed).copied();
+}
+
+impl Transposition_Table
+{
+    // Creates a table using about the given number of megabytes
+    pub fn new(megabytes: usize) -> Self
+    {
+        let count = std::cmp::max(megabytes * 1024 * 1024 / std::mem::size_of::<Slot>(), 1);
+        let mut slots = Vec::with_capacity(count);
+
+        for _ in 0..count
+        {
+            slots.push(Slot { key: AtomicU64::new(0), data: AtomicU64::new(0) });
+        }
+
+        return Transposition_Table
+        {
+            slots,
+            generation: AtomicU8::new(0),
+        };
+    }
+
+    // Forgets every stored position, e.g. when a new game starts
+    pub fn clear(&self)
+    {
+        for slot in self.slots.iter()
+        {
+            slot.key.store(0, Ordering::Relaxed);
+            slot.data.store(0, Ordering::Relaxed);
+        }
+
+        self.generation.store(0, Ordering::Relaxed);
+    }
+
+    // Called before every search, entries from older searches are replaced first
+    pub fn new_search(&self)
+    {
+        let next = self.generation.load(Ordering::Relaxed).wrapping_add(1);
+        self.generation.store(next, Ordering::Relaxed);
+    }
+
+    fn slot(&self, key: u64) -> &Slot
+    {
+        return &self.slots[(key % self.slots.len() as u64) as usize];
+    }
+
+    pub fn probe(&self, key: u64) -> Option<Tt_Entry>
+    {
+        let slot = self.slot(key);
+        let data = slot.data.load(Ordering::Relaxed);
+
+        if    data == 0
+           || slot.key.load(Ordering::Relaxed) ^ data != key
+        {
+            return None;
+        }
+
+        return Some(Self::unpack(data));
+    }
+
+    pub fn store(&self, key: u64, best_move: u16, score: i32, depth: u32, bound: Bound)
+    {
+        let slot = self.slot(key);
+        let generation = self.generation.load(Ordering::Relaxed);
+        let old_data = slot.data.load(Ordering::Relaxed);
+
+        // Deeper results of other positions from the current search are kept
+        if    old_data != 0
+           && slot.key.load(Ordering::Relaxed) ^ old_data != key
+        {
+            let old = Self::unpack(old_data);
+
+            if    old.generation == generation
+               && old.depth > depth + 2
+            {
+                return;
+            }
+        }
+
+        // Keep the best move of the position when the new result does not have one
+        let mut best_move = best_move;
+
+        if best_move == 0
+        {
+            if let Some(old) = self.probe(key)
+            {
+                best_move = old.best_move;
+            }
+        }
+
+        let bound_bits: u64 = match bound
+        {
+            Bound::Exact => 0,
+            Bound::Lower => 1,
+            Bound::Upper => 2,
+        };
+
+        let data =    best_move as u64
+                   | (score as i16 as u16 as u64) << 16
+                   | (std::cmp::min(depth, 255) as u64) << 32
+                   | bound_bits << 40
+                   | (generation as u64) << 42;
+
+        slot.key.store(key ^ data, Ordering::Relaxed);
+        slot.data.store(data, Ordering::Relaxed);
+    }
+
+    fn unpack(data: u64) -> Tt_Entry
+    {
+        return Tt_Entry
+        {
+            best_move:  (data & 0xFFFF) as u16,
+            score:      ((data >> 16) & 0xFFFF) as u16 as i16 as i32,
+            depth:      ((data >> 32) & 0xFF) as u32,
+            bound:      match (data >> 40) & 3
+            {
+                0 => Bound::Exact,
+                1 => Bound::Lower,
+                _ => Bound::Upper,
+            },
+            generation: ((data >> 42) & 0xFF) as u8,
+        };
+    }
+
+    // How full the table is in permille, as reported by UCI "hashfull"
+    // Only the first thousand entries are looked at
+    pub fn hashfull(&self) -> u32
+    {
+        let generation = self.generation.load(Ordering::Relaxed);
+        let sample = std::cmp::min(1000, self.slots.len());
+
+        let used = self.slots[..sample].iter()
+                                       .filter(|slot| {
+                                           let data = slot.data.load(Ordering::Relaxed);
+                                           data != 0 && Self::unpack(data).generation == generation
+                                       })
+                                       .count();
+
+        return (used * 1000 / sample) as u32;
+    }
+}
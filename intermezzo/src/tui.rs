@@ -0,0 +1,857 @@
+// A full screen terminal UI, started with "intermezzo tui [minutes] [increment in seconds]"
+//
+// The board is drawn with colored squares and Unicode pieces on the alternate screen, next to it a
+// panel with the clocks, the engine evaluation and the moves of the game. Everything is written with
+// ANSI escape codes and the terminal is put in raw mode with stty, so it works over SSH without
+// anything else installed (but not on the Windows console)
+//
+// Selecting a piece shows the squares it can move to, selecting one of those plays the move:
+// arrows       -> move the cursor
+// enter/space  -> select the piece under the cursor or play the selected piece to the cursor
+// mouse click  -> the same as moving the cursor there and pressing enter
+// escape       -> unselect
+// :            -> type a move in SAN or coordinates
+// u            -> undo (against the engine until it is your turn again)
+// f            -> flip the board
+// e            -> change the sides the engine plays (off, black, white, both)
+// g            -> let the engine play the side to move
+// n            -> new game
+// q            -> quit
+//
+// The clocks only run when minutes are given. The engine searches on its own thread so the clocks
+// and the evaluation keep being updated while it thinks
+
+use crate::chess::*;
+use crate::game::*;
+use crate::render::figurine;
+use crate::search::*;
+use crate::time_manager::*;
+
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+// Where the board starts on the screen (1 based like the terminal), every square is 3 columns wide
+const BOARD_TOP: usize = 2;
+const BOARD_LEFT: usize = 4;
+const SQUARE_WIDTH: usize = 3;
+const PANEL_LEFT: usize = BOARD_LEFT + 8 * SQUARE_WIDTH + 4;
+const PANEL_MOVE_ROWS: usize = 10;
+
+// Background colors from the 256 color palette
+const LIGHT_SQUARE: u8 = 180;
+const DARK_SQUARE: u8 = 137;
+const LAST_MOVE_LIGHT: u8 = 186;
+const LAST_MOVE_DARK: u8 = 143;
+const SELECTED_SQUARE: u8 = 71;
+const CURSOR_SQUARE: u8 = 33;
+const CHECK_SQUARE: u8 = 160;
+// A piece that can be captured by the selected piece
+const TARGET_SQUARE: u8 = 107;
+
+// How often the screen is drawn again while waiting for input, for the clocks
+const REFRESH: Duration = Duration::from_millis(100);
+
+// Without clocks the engine thinks this long on every move
+const ENGINE_MOVE_TIME: u64 = 1000;
+
+enum Key
+{
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+    Escape,
+    Backspace,
+    // Ctrl-C, quits even while typing a move
+    Interrupt,
+    Click(usize, usize),
+    Char(char),
+}
+
+enum Event
+{
+    Input(Vec<u8>),
+    // The depth and score of an iteration of the engine
+    Engine_Info(u32, i32),
+}
+
+// Puts the terminal in raw mode on the alternate screen and restores it when dropped, also when
+// the program panics
+struct Terminal
+{
+    saved_mode: String,
+}
+
+impl Terminal
+{
+    fn new() -> Result<Self, String>
+    {
+        let output = Command::new("stty").arg("-g").stdin(Stdio::inherit()).output().map_err(|error| format!("could not run stty: {}", error))?;
+
+        if !output.status.success()
+        {
+            return Err(String::from("the input is not a terminal"));
+        }
+
+        let saved_mode = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+        Command::new("stty").args(["raw", "-echo"]).stdin(Stdio::inherit()).status().map_err(|error| error.to_string())?;
+
+        // Alternate screen, hidden cursor and mouse clicks reported as "\x1b[<b;x;yM"
+        print!("\x1b[?1049h\x1b[?25l\x1b[?1000h\x1b[?1006h");
+        std::io::stdout().flush().ok();
+
+        return Ok(Terminal { saved_mode });
+    }
+}
+
+impl Drop for Terminal
+{
+    fn drop(&mut self)
+    {
+        print!("\x1b[?1006l\x1b[?1000l\x1b[?25h\x1b[?1049l");
+        std::io::stdout().flush().ok();
+
+        Command::new("stty").arg(&self.saved_mode).stdin(Stdio::inherit()).status().ok();
+    }
+}
+
+// The time left of both players in milliseconds, the clock of the side to move runs from
+// turn_start on
+struct Clocks
+{
+    start:      u64,
+    remaining:  [u64; 2],
+    increment:  u64,
+    turn_start: Instant,
+}
+
+impl Clocks
+{
+    fn left(&self, index: usize, running: bool) -> u64
+    {
+        if !running
+        {
+            return self.remaining[index];
+        }
+
+        return self.remaining[index].saturating_sub(self.turn_start.elapsed().as_millis() as u64);
+    }
+
+    // Stops the clock of the player that just moved and starts the other one
+    fn press(&mut self, index: usize)
+    {
+        self.remaining[index] = self.left(index, true) + self.increment;
+        self.turn_start = Instant::now();
+    }
+}
+
+pub struct Tui
+{
+    game:      Game,
+    flipped:   bool,
+    // Whether the engine plays [white, black]
+    engine:    [bool; 2],
+    cursor:    usize,
+    selected:  Option<usize>,
+    // The moves that go to the same square and only differ in the promotion piece, waiting for a choice
+    promotion: Vec<Move>,
+    // The text typed after ':', None when not typing
+    typed:     Option<String>,
+    message:   String,
+    clocks:    Option<Clocks>,
+    // The depth and score of the last search, the score is from white's point of view
+    eval:      Option<(u32, i32)>,
+
+    // The search is None while it is searching on the search thread
+    search:    Option<Search>,
+    thinking:  Option<JoinHandle<(Search, Search_Info)>>,
+    // The stop flag of the search, kept here as the search itself is on the search thread
+    stop:      Arc<AtomicBool>,
+    events:    Sender<Event>,
+}
+
+impl Tui
+{
+    // Minutes and increment are for the clocks, None to play without them
+    fn new(minutes: Option<f64>, increment: u64) -> (Self, Receiver<Event>)
+    {
+        let (events, receiver) = channel();
+        let search = Search::new();
+
+        let clocks = minutes.map(|minutes| Clocks
+        {
+            start:      (minutes * 60_000.0) as u64,
+            remaining:  [(minutes * 60_000.0) as u64; 2],
+            increment:  increment * 1000,
+            turn_start: Instant::now(),
+        });
+
+        let tui = Tui
+        {
+            game: Game::new(),
+            flipped: false,
+            engine: [false, false],
+            cursor: 12,
+            selected: None,
+            promotion: vec![],
+            typed: None,
+            message: String::new(),
+            clocks,
+            eval: None,
+            stop: search.stop.clone(),
+            search: Some(search),
+            thinking: None,
+            events,
+        };
+
+        return (tui, receiver);
+    }
+
+    // intermezzo tui [minutes] [increment in seconds]
+    pub fn run_from_args(args: &[String])
+    {
+        let minutes = args.first().and_then(|minutes| minutes.parse::<f64>().ok());
+        let increment = args.get(1).and_then(|increment| increment.parse::<u64>().ok()).unwrap_or(0);
+
+        let (mut tui, receiver) = Tui::new(minutes, increment);
+
+        if let Err(error) = tui.run(receiver)
+        {
+            println!("{}", error);
+        }
+    }
+
+    fn engine_index(color: i8) -> usize
+    {
+        return if color == WHITE { 0 } else { 1 };
+    }
+
+    fn run(&mut self, receiver: Receiver<Event>) -> Result<(), String>
+    {
+        let terminal = Terminal::new()?;
+
+        // The input is read on its own thread so the screen can be drawn while waiting
+        let input = self.events.clone();
+
+        std::thread::spawn(move || {
+            let mut buffer = [0u8; 64];
+
+            while let Ok(count) = std::io::stdin().read(&mut buffer)
+            {
+                if count == 0 || input.send(Event::Input(buffer[..count].to_vec())).is_err()
+                {
+                    break;
+                }
+            }
+        });
+
+        loop
+        {
+            self.check_engine();
+            self.check_clocks();
+            self.draw();
+
+            let keys = match receiver.recv_timeout(REFRESH)
+            {
+                Ok(Event::Input(bytes))             => parse_keys(&bytes),
+                Ok(Event::Engine_Info(depth, score)) =>
+                {
+                    let white_score = if self.game.side_to_move() == WHITE { score } else { -score };
+                    self.eval = Some((depth, white_score));
+                    continue;
+                },
+                Err(_)                              => continue,
+            };
+
+            for key in keys
+            {
+                if !self.handle_key(key)
+                {
+                    self.stop_engine();
+                    drop(terminal);
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    // Returns false to quit
+    fn handle_key(&mut self, key: Key) -> bool
+    {
+        if let Key::Interrupt = key
+        {
+            return false;
+        }
+
+        if let Some(mut text) = self.typed.take()
+        {
+            match key
+            {
+                Key::Enter      => self.play_text(&text),
+                Key::Escape     => {},
+                Key::Backspace  =>
+                {
+                    text.pop();
+                    self.typed = Some(text);
+                },
+                Key::Char(letter) =>
+                {
+                    text.push(letter);
+                    self.typed = Some(text);
+                },
+                _               => self.typed = Some(text),
+            }
+
+            return true;
+        }
+
+        if !self.promotion.is_empty()
+        {
+            let choice = match key
+            {
+                Key::Char(letter) => crate::notation::piece_from_char(letter.to_ascii_uppercase()),
+                _                 => None,
+            };
+
+            let found = self.promotion.iter().copied().find(|candidate| Some(candidate.promotion.abs()) == choice);
+            self.promotion.clear();
+
+            if let Some(chosen) = found
+            {
+                self.play(chosen);
+            }
+
+            return true;
+        }
+
+        let (step_col, step_row) = if self.flipped { (-1, -1) } else { (1, 1) };
+
+        match key
+        {
+            Key::Up              => self.move_cursor(0, step_row),
+            Key::Down            => self.move_cursor(0, -step_row),
+            Key::Left            => self.move_cursor(-step_col, 0),
+            Key::Right           => self.move_cursor(step_col, 0),
+            Key::Enter           => self.select(self.cursor),
+            Key::Char(' ')       => self.select(self.cursor),
+            Key::Escape          => self.selected = None,
+            Key::Backspace       => {},
+            Key::Interrupt       => {},
+            Key::Click(x, y)     =>
+            {
+                if let Some(square) = self.square_at(x, y)
+                {
+                    self.cursor = square;
+                    self.select(square);
+                }
+            },
+            Key::Char('q')       => return false,
+            Key::Char(':')       => self.typed = Some(String::new()),
+            Key::Char('f')       => self.flipped = !self.flipped,
+            Key::Char('u')       => self.undo(),
+            Key::Char('n')       =>
+            {
+                self.stop_engine();
+                self.game = Game::with_rules(self.game.rules());
+                self.selected = None;
+                self.eval = None;
+                self.message = String::from("new game");
+
+                if let Some(clocks) = &mut self.clocks
+                {
+                    clocks.remaining = [clocks.start; 2];
+                    clocks.turn_start = Instant::now();
+                }
+            },
+            Key::Char('e')       =>
+            {
+                self.engine = match self.engine
+                {
+                    [false, false] => [false, true],
+                    [false, true]  => [true, false],
+                    [true, false]  => [true, true],
+                    _              => [false, false],
+                };
+            },
+            Key::Char('g')       => self.start_engine(),
+            Key::Char(_)         => {},
+        }
+
+        return true;
+    }
+
+    fn move_cursor(&mut self, col_step: i32, row_step: i32)
+    {
+        let col = (Chess_Board::get_col(self.cursor) as i32 + col_step).clamp(0, 7);
+        let row = (Chess_Board::get_row(self.cursor) as i32 + row_step).clamp(0, 7);
+
+        self.cursor = (row * 8 + col) as usize;
+    }
+
+    // The square under a position on the screen
+    fn square_at(&self, x: usize, y: usize) -> Option<usize>
+    {
+        if    x < BOARD_LEFT
+           || y < BOARD_TOP
+        {
+            return None;
+        }
+
+        let screen_col = (x - BOARD_LEFT) / SQUARE_WIDTH;
+        let screen_row = y - BOARD_TOP;
+
+        if screen_col > 7 || screen_row > 7
+        {
+            return None;
+        }
+
+        let (col, row) = if self.flipped { (7 - screen_col, screen_row) } else { (screen_col, 7 - screen_row) };
+
+        return Some(row * 8 + col);
+    }
+
+    // The legal moves a human can make now, none while the engine is to move
+    fn human_moves(&mut self) -> Vec<Move>
+    {
+        if    self.game.is_over()
+           || self.thinking.is_some()
+           || self.engine[Self::engine_index(self.game.side_to_move())]
+        {
+            return vec![];
+        }
+
+        return self.game.legal_moves();
+    }
+
+    fn select(&mut self, square: usize)
+    {
+        let moves = self.human_moves();
+
+        // The duck is placed by selecting the square it goes to
+        if    !moves.is_empty()
+           && moves.iter().all(|candidate| candidate.kind == Move_Kind::Duck)
+        {
+            if let Some(&duck_move) = moves.iter().find(|candidate| candidate.target == square)
+            {
+                self.play(duck_move);
+            }
+
+            return;
+        }
+
+        if let Some(origin) = self.selected
+        {
+            let matching: Vec<Move> = moves.iter().copied().filter(|candidate| candidate.origin == origin && candidate.target == square && candidate.kind != Move_Kind::Drop).collect();
+
+            match matching.len()
+            {
+                0 => {},
+                1 =>
+                {
+                    self.play(matching[0]);
+                    return;
+                },
+                _ =>
+                {
+                    self.message = String::from("promote to (q, r, b, n)?");
+                    self.promotion = matching;
+                    return;
+                },
+            }
+        }
+
+        let has_moves = moves.iter().any(|candidate| candidate.origin == square && candidate.kind != Move_Kind::Drop);
+
+        self.selected = if has_moves && self.selected != Some(square) { Some(square) } else { None };
+    }
+
+    fn play_text(&mut self, text: &str)
+    {
+        if self.human_moves().is_empty()
+        {
+            self.message = String::from("it is not your turn");
+            return;
+        }
+
+        let mut board = self.game.board().clone();
+
+        match board.parse_san(text).or_else(|| board.parse_uci_move(text))
+        {
+            Some(parsed) => self.play(parsed),
+            None         => self.message = format!("{} is not a legal move", text),
+        }
+    }
+
+    fn play(&mut self, new_move: Move)
+    {
+        let mover = self.game.side_to_move();
+        let san = self.game.board().clone().move_to_san(new_move);
+
+        if let Err(error) = self.game.play(new_move)
+        {
+            self.message = error;
+            return;
+        }
+
+        // A duck chess turn has two moves, the clock stops after the second
+        if let Some(clocks) = &mut self.clocks
+        {
+            if self.game.side_to_move() != mover
+            {
+                clocks.press(Self::engine_index(mover));
+            }
+        }
+
+        self.selected = None;
+        self.message = san;
+    }
+
+    fn undo(&mut self)
+    {
+        self.stop_engine();
+
+        if self.game.undo().is_none()
+        {
+            self.message = String::from("there is no move to take back");
+            return;
+        }
+
+        while    self.engine != [true, true]
+              && self.engine[Self::engine_index(self.game.side_to_move())]
+              && self.game.undo().is_some()
+        {
+        }
+
+        self.selected = None;
+        self.message = String::from("move taken back");
+    }
+
+    // Starts a search for the side to move on the search thread
+    fn start_engine(&mut self)
+    {
+        if self.game.is_over() || self.thinking.is_some()
+        {
+            return;
+        }
+
+        let Some(mut search) = self.search.take() else { return };
+
+        let time = match &self.clocks
+        {
+            Some(clocks) => Time_Controls
+            {
+                remaining: clocks.left(Self::engine_index(self.game.side_to_move()), true),
+                increment: clocks.increment,
+                ..Default::default()
+            },
+            None         => Time_Controls { move_time: Some(ENGINE_MOVE_TIME), ..Default::default() },
+        };
+
+        let limits = Search_Limits { time: Some(time), ..Default::default() };
+        let board = self.game.board().clone();
+        let events = self.events.clone();
+
+        self.selected = None;
+        self.message = String::from("thinking...");
+
+        self.thinking = Some(std::thread::spawn(move || {
+            let info = search.search(&board, &limits, &mut |info| { events.send(Event::Engine_Info(info.depth, info.score)).ok(); });
+            return (search, info);
+        }));
+    }
+
+    // Stops the search without playing its move
+    fn stop_engine(&mut self)
+    {
+        let Some(thinking) = self.thinking.take() else { return };
+
+        self.stop.store(true, Ordering::Relaxed);
+
+        if let Ok((search, _)) = thinking.join()
+        {
+            self.search = Some(search);
+        }
+
+        self.stop.store(false, Ordering::Relaxed);
+        self.message = String::new();
+    }
+
+    // Plays the move of a finished search, and starts one when the engine is to move
+    fn check_engine(&mut self)
+    {
+        if self.thinking.as_ref().is_some_and(|thinking| thinking.is_finished())
+        {
+            let Some(thinking) = self.thinking.take() else { return };
+
+            if let Ok((search, info)) = thinking.join()
+            {
+                self.search = Some(search);
+
+                if let Some(best_move) = info.best_move()
+                {
+                    self.play(best_move);
+                }
+            }
+        }
+
+        if    self.thinking.is_none()
+           && !self.game.is_over()
+           && self.engine[Self::engine_index(self.game.side_to_move())]
+        {
+            self.start_engine();
+        }
+    }
+
+    // Draws the whole screen, every line is cleared and written again so nothing flickers
+    fn draw(&mut self)
+    {
+        let mut screen = String::new();
+
+        let line = |screen: &mut String, y: usize, x: usize, text: &str| {
+            screen.push_str(&format!("\x1b[{};{}H{}\x1b[0m", y, x, text));
+        };
+
+        for y in 1..=BOARD_TOP + PANEL_MOVE_ROWS + 14
+        {
+            screen.push_str(&format!("\x1b[{};1H\x1b[2K", y));
+        }
+
+        line(&mut screen, 1, BOARD_LEFT, &format!("\x1b[1mintermezzo\x1b[0m  {}", self.game.rules().name()));
+
+        // The board, with the rank numbers on the left and the files below
+        let targets = match self.selected
+        {
+            Some(origin) => self.human_moves().iter().filter(|candidate| candidate.origin == origin).fold(0u64, |targets, candidate| targets | 1 << candidate.target),
+            None         => 0,
+        };
+
+        let board = self.game.board();
+        let last_move = board.moves.last().copied();
+        let checked_king = if board.details.is_check != 0 { board.king_square(board.details.side_to_move) } else { None };
+
+        for screen_row in 0..8
+        {
+            let row = if self.flipped { screen_row } else { 7 - screen_row };
+            let mut text = format!("{} ", row + 1);
+
+            for screen_col in 0..8
+            {
+                let col = if self.flipped { 7 - screen_col } else { screen_col };
+                let square = row * 8 + col;
+                let piece = board.board[square];
+                let light = (row + col) % 2 == 1;
+                let is_target = targets & (1 << square) != 0;
+                let moved = last_move.is_some_and(|last| last.origin == square || last.target == square);
+
+                let background = if checked_king == Some(square)
+                {
+                    CHECK_SQUARE
+                } else if self.cursor == square
+                {
+                    CURSOR_SQUARE
+                } else if self.selected == Some(square)
+                {
+                    SELECTED_SQUARE
+                } else if is_target && piece != 0
+                {
+                    TARGET_SQUARE
+                } else if moved
+                {
+                    if light { LAST_MOVE_LIGHT } else { LAST_MOVE_DARK }
+                } else
+                {
+                    if light { LIGHT_SQUARE } else { DARK_SQUARE }
+                };
+
+                let glyph = match piece
+                {
+                    0 if is_target => String::from("\x1b[38;5;22m•"),
+                    0              => String::from(" "),
+                    DUCK           => String::from("\x1b[38;5;226m●"),
+                    _              =>
+                    {
+                        let foreground = if piece > 0 { "\x1b[1;38;5;231m" } else { "\x1b[38;5;16m" };
+                        format!("{}{}", foreground, figurine(piece, true))
+                    },
+                };
+
+                text.push_str(&format!("\x1b[48;5;{}m {} \x1b[0m", background, glyph));
+            }
+
+            line(&mut screen, BOARD_TOP + screen_row, BOARD_LEFT - 2, &text);
+        }
+
+        let files: String = (0..8).map(|screen_col| {
+            let col = if self.flipped { 7 - screen_col } else { screen_col };
+            format!(" {} ", (b'a' + col as u8) as char)
+        }).collect();
+
+        line(&mut screen, BOARD_TOP + 8, BOARD_LEFT, &files);
+
+        // The panel: clocks, evaluation and the moves
+        let side = self.game.side_to_move();
+        let running = !self.game.is_over();
+
+        for (index, color, name) in [(0, WHITE, "White"), (1, BLACK, "Black")]
+        {
+            let clock = match &self.clocks
+            {
+                Some(clocks) => format_clock(clocks.left(index, running && side == color)),
+                None         => String::from("--:--"),
+            };
+
+            let marker = if running && side == color { "\x1b[1m>" } else { " " };
+            let player = if self.engine[index] { "engine" } else { "human" };
+
+            line(&mut screen, BOARD_TOP + index, PANEL_LEFT, &format!("{} {}  {:>9}  {}", marker, name, clock, player));
+        }
+
+        let eval = match self.eval
+        {
+            Some((depth, score)) if is_mate_score(score) => format!("{}  depth {}", score_to_uci(score), depth),
+            Some((depth, score))                         => format!("{:+.2}  depth {}", score as f64 / 100.0, depth),
+            None                                         => String::from("-"),
+        };
+
+        line(&mut screen, BOARD_TOP + 3, PANEL_LEFT, &format!("  Eval   {}{}", eval, if self.thinking.is_some() { "  (thinking)" } else { "" }));
+
+        let san_moves = self.game.san_moves();
+        let turns: Vec<String> = san_moves.chunks(2).enumerate().map(|(index, pair)| {
+            format!("{:>4}. {:<8} {}", index + 1, pair[0], pair.get(1).map_or("", |black| black.as_str()))
+        }).collect();
+
+        let first = turns.len().saturating_sub(PANEL_MOVE_ROWS);
+
+        for (index, turn) in turns[first..].iter().enumerate()
+        {
+            line(&mut screen, BOARD_TOP + 5 + index, PANEL_LEFT, turn);
+        }
+
+        // Below the board: the state of the game, the last message and the keys
+        let status_top = BOARD_TOP + PANEL_MOVE_ROWS + 6;
+
+        let status = match self.game.termination()
+        {
+            Some(termination) => format!("\x1b[1mgame over: {} by {}", self.game.result().to_pgn(), termination.description()),
+            None              => format!("{} to move{}", if side == WHITE { "white" } else { "black" }, if checked_king.is_some() { ", check" } else { "" }),
+        };
+
+        line(&mut screen, status_top, BOARD_LEFT, &status);
+
+        match &self.typed
+        {
+            Some(text) => line(&mut screen, status_top + 1, BOARD_LEFT, &format!("move: {}_", text)),
+            None       => line(&mut screen, status_top + 1, BOARD_LEFT, &self.message),
+        }
+
+        line(&mut screen, status_top + 3, BOARD_LEFT, "\x1b[2marrows/click select  : type a move  u undo  f flip  e engine  g go  n new  q quit");
+
+        print!("{}", screen);
+        std::io::stdout().flush().ok();
+    }
+
+    fn check_clocks(&mut self)
+    {
+        if self.game.is_over()
+        {
+            return;
+        }
+
+        let side = self.game.side_to_move();
+        let flagged = self.clocks.as_ref().is_some_and(|clocks| clocks.left(Self::engine_index(side), true) == 0);
+
+        if flagged
+        {
+            self.stop_engine();
+
+            if let Some(clocks) = &mut self.clocks
+            {
+                clocks.remaining[Self::engine_index(side)] = 0;
+            }
+
+            self.game.timeout(side).ok();
+        }
+    }
+}
+
+// Minutes, seconds and tenths of a second below a minute
+fn format_clock(milliseconds: u64) -> String
+{
+    let seconds = milliseconds / 1000;
+
+    if seconds < 60
+    {
+        return format!("{}.{}", seconds, milliseconds % 1000 / 100);
+    }
+
+    return format!("{}:{:02}", seconds / 60, seconds % 60);
+}
+
+// Splits what was read from the terminal into keys, mouse clicks come as "\x1b[<0;x;yM"
+fn parse_keys(bytes: &[u8]) -> Vec<Key>
+{
+    let mut keys = vec![];
+    let mut index = 0;
+
+    while index < bytes.len()
+    {
+        let byte = bytes[index];
+        index += 1;
+
+        if byte != 0x1b
+        {
+            match byte
+            {
+                b'\r' | b'\n' => keys.push(Key::Enter),
+                0x7f | 0x08   => keys.push(Key::Backspace),
+                0x03          => keys.push(Key::Interrupt),
+                0x20..=0x7e   => keys.push(Key::Char(byte as char)),
+                _             => {},
+            }
+
+            continue;
+        }
+
+        if bytes.get(index) != Some(&b'[')
+        {
+            keys.push(Key::Escape);
+            continue;
+        }
+
+        // A control sequence ends with a byte from '@' to '~'
+        let start = index + 1;
+        let Some(length) = bytes[start..].iter().position(|byte| (0x40..=0x7e).contains(byte)) else { break };
+        let end = start + length;
+
+        let sequence = String::from_utf8_lossy(&bytes[start..end]);
+        index = end + 1;
+
+        match bytes[end]
+        {
+            b'A' => keys.push(Key::Up),
+            b'B' => keys.push(Key::Down),
+            b'C' => keys.push(Key::Right),
+            b'D' => keys.push(Key::Left),
+            b'M' if sequence.starts_with('<') =>
+            {
+                let numbers: Vec<usize> = sequence[1..].split(';').filter_map(|number| number.parse().ok()).collect();
+
+                // Only presses of the left button
+                if let [0, x, y] = numbers[..]
+                {
+                    keys.push(Key::Click(x, y));
+                }
+            },
+            _ => {},
+        }
+    }
+
+    return keys;
+}
@@ -0,0 +1,527 @@
+// The Universal Chess Interface (UCI), the text protocol chess GUIs use to talk to engines
+//
+// Commands are read line by line from stdin and answers are written to stdout. The search runs
+// on a separate thread so that "stop", "isready" and "quit" are handled while it is thinking. The
+// Thread_Pool is moved into that thread and handed back once the search is done
+//
+// With "go ponder" the engine searches the position after the move it expects the opponent to
+// play, without any time limit. On "ponderhit" the same search continues as a normal timed search,
+// on "stop" (the opponent played something else) it is abandoned. The best move of an infinite or
+// ponder search is only printed once the GUI asks for it, even if the search finished earlier
+//
+// Supported options:
+// Hash           -> size of the transposition table in megabytes
+// Threads        -> number of search threads
+// MultiPV        -> number of lines to search and report
+// Move Overhead  -> milliseconds subtracted from the clock for communication delays
+// SyzygyPath     -> directories with Syzygy tablebases, separated by ':' or ';'
+// Ponder         -> only tells the engine that the GUI may send "go ponder", nothing changes
+// OwnBook        -> plays the moves of the Polyglot book in Book File without searching, only in
+//                   normal chess and not for infinite or ponder searches
+// Book File      -> the Polyglot book (.bin, see polyglot.rs)
+// Best Book Move -> always the move with the highest weight instead of a weighted random choice
+// UCI_Chess960   -> castling moves are sent and received as the king capturing its own rook
+// UCI_Variant    -> the rules the games are played with (see variant.rs), Capablanca and Gothic
+//                   are played on a Fairy_Board and searched by fairy_search.rs, which ignores the
+//                   other options apart from Move Overhead
+
+use crate::chess::*;
+use crate::fairy::*;
+use crate::fairy_search::*;
+use crate::polyglot::*;
+use crate::search::*;
+use crate::syzygy::*;
+use crate::thread_pool::*;
+use crate::time_manager::*;
+use crate::transposition::*;
+use crate::variant::*;
+
+use std::io::BufRead;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+const ENGINE_NAME: &str = "Intermezzo";
+const ENGINE_AUTHOR: &str = "the OpenGLChess authors";
+
+const MAX_HASH_MB: usize = 4096;
+const MAX_THREADS: usize = 256;
+const MAX_MULTI_PV: usize = 256;
+
+pub struct Uci
+{
+    board:  Chess_Board,
+    stop:   Arc<AtomicBool>,
+    ponder: Arc<AtomicBool>,
+    // Set during infinite and ponder searches, the best move is held back while it is set
+    waiting: Arc<AtomicBool>,
+    chess960: bool,
+    variant: &'static dyn Rules,
+    // Set while a variant on a Fairy_Board is played instead of board and variant
+    fairy:   Option<(&'static Fairy_Variant, Fairy_Board)>,
+    own_book: bool,
+    book:     Option<Polyglot_Book>,
+    book_selection: Book_Selection,
+
+    // The pool is None while it is searching on the search thread
+    pool:   Option<Thread_Pool>,
+    search: Option<JoinHandle<Thread_Pool>>,
+}
+
+impl Uci
+{
+    pub fn new() -> Self
+    {
+        let pool = Thread_Pool::new(1, DEFAULT_HASH_MB);
+
+        return Uci
+        {
+            board: Chess_Board::new(),
+            stop: pool.stop.clone(),
+            ponder: pool.ponder.clone(),
+            waiting: Arc::new(AtomicBool::new(false)),
+            chess960: false,
+            variant: &STANDARD,
+            fairy: None,
+            own_book: false,
+            book: None,
+            book_selection: Book_Selection::Weighted,
+            pool: Some(pool),
+            search: None,
+        };
+    }
+
+    // Reads commands from stdin until "quit" or the end of the input
+    pub fn run(&mut self)
+    {
+        let stdin = std::io::stdin();
+
+        for line in stdin.lock().lines()
+        {
+            let Ok(line) = line else { break };
+
+            if !self.handle_command(&line)
+            {
+                break;
+            }
+        }
+
+        self.stop_search();
+    }
+
+    // Returns false once the engine should quit
+    pub fn handle_command(&mut self, line: &str) -> bool
+    {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+
+        if tokens.is_empty()
+        {
+            return true;
+        }
+
+        match tokens[0]
+        {
+            "uci"        => self.identify(),
+            "isready"    => println!("readyok"),
+            "setoption"  => self.set_option(&tokens[1..]),
+            "ucinewgame" =>
+            {
+                self.stop_search();
+                self.pool_mut().clear();
+                self.board = Chess_Board::with_rules(self.variant);
+                self.board.chess960 = self.chess960;
+                self.start_fairy_variant();
+            },
+            "position"   => self.set_position(&tokens[1..]),
+            "go"         => self.go(&tokens[1..]),
+            "stop"       => self.stop_search(),
+            "ponderhit"  =>
+            {
+                self.ponder.store(false, Ordering::Relaxed);
+                self.waiting.store(false, Ordering::Relaxed);
+            },
+            "quit"       => return false,
+            _            => println!("info string unknown command: {}", line),
+        }
+
+        return true;
+    }
+
+    fn identify(&self)
+    {
+        println!("id name {}", ENGINE_NAME);
+        println!("id author {}", ENGINE_AUTHOR);
+        println!("option name Hash type spin default {} min 1 max {}", DEFAULT_HASH_MB, MAX_HASH_MB);
+        println!("option name Threads type spin default 1 min 1 max {}", MAX_THREADS);
+        println!("option name MultiPV type spin default 1 min 1 max {}", MAX_MULTI_PV);
+        println!("option name Move Overhead type spin default {} min 0 max 5000", DEFAULT_MOVE_OVERHEAD);
+        println!("option name SyzygyPath type string default <empty>");
+        println!("option name Ponder type check default false");
+        println!("option name OwnBook type check default false");
+        println!("option name Book File type string default <empty>");
+        println!("option name Best Book Move type check default false");
+        println!("option name UCI_Chess960 type check default false");
+
+        let variants: Vec<String> = VARIANTS.iter()
+                                            .map(|rules| rules.name())
+                                            .chain(FAIRY_VARIANTS.iter().map(|fairy| fairy.name))
+                                            .map(|name| format!("var {}", variant_key_name(name)))
+                                            .collect();
+
+        println!("option name UCI_Variant type combo default {} {}", variant_key_name(STANDARD.name()), variants.join(" "));
+        println!("uciok");
+    }
+
+    // The pool, waiting for a running search to finish first
+    fn pool_mut(&mut self) -> &mut Thread_Pool
+    {
+        if let Some(handle) = self.search.take()
+        {
+            self.pool = Some(handle.join().unwrap());
+        }
+
+        return self.pool.as_mut().unwrap();
+    }
+
+    fn stop_search(&mut self)
+    {
+        if self.search.is_some()
+        {
+            self.waiting.store(false, Ordering::Relaxed);
+            self.stop.store(true, Ordering::Relaxed);
+            self.pool_mut();
+            self.stop.store(false, Ordering::Relaxed);
+            self.ponder.store(false, Ordering::Relaxed);
+        }
+    }
+
+    // setoption name <name> [value <value>], names can contain spaces
+    fn set_option(&mut self, tokens: &[&str])
+    {
+        let value_index = tokens.iter().position(|&token| token == "value").unwrap_or(tokens.len());
+
+        if tokens.first() != Some(&"name")
+        {
+            return;
+        }
+
+        let name = tokens[1..value_index].join(" ");
+        let value = tokens[std::cmp::min(value_index + 1, tokens.len())..].join(" ");
+        let number = value.parse::<usize>().ok();
+
+        self.stop_search();
+        let pool = self.pool_mut();
+
+        match name.to_lowercase().as_str()
+        {
+            "hash"           => if let Some(megabytes) = number { pool.set_hash_size(megabytes.clamp(1, MAX_HASH_MB)) },
+            "threads"        => if let Some(threads) = number { pool.set_threads(threads.clamp(1, MAX_THREADS)) },
+            "multipv"        => if let Some(lines) = number { pool.multi_pv = lines.clamp(1, MAX_MULTI_PV) },
+            "ponder"         => (),
+            "ownbook"        => self.own_book = value == "true",
+            "best book move" => self.book_selection = if value == "true" { Book_Selection::Best } else { Book_Selection::Weighted },
+            "book file"      =>
+            {
+                if value.is_empty() || value == "<empty>"
+                {
+                    self.book = None;
+                }
+                else
+                {
+                    match Polyglot_Book::open(&value)
+                    {
+                        Ok(book) =>
+                        {
+                            println!("info string found {} book entries", book.entries.len());
+                            self.book = Some(book);
+                        },
+                        Err(error) => println!("info string could not read {}: {}", value, error),
+                    }
+                }
+            },
+            "uci_chess960"   =>
+            {
+                self.chess960 = value == "true";
+                self.board.chess960 = self.chess960;
+            },
+            "uci_variant"    => match (find_variant(&value), find_fairy_variant(&value))
+            {
+                (Some(rules), _) =>
+                {
+                    self.variant = rules;
+                    self.fairy = None;
+                    self.board = Chess_Board::with_rules(rules);
+                    self.board.chess960 = self.chess960;
+                },
+                (None, Some(fairy)) =>
+                {
+                    self.fairy = Some((fairy, Fairy_Board::from_fen(fairy.start_fen).unwrap()));
+                },
+                (None, None) => println!("info string unknown variant: {}", value),
+            },
+            "move overhead"  => if let Some(overhead) = number { pool.move_overhead = overhead as u64 },
+            "syzygypath"     =>
+            {
+                if value.is_empty() || value == "<empty>"
+                {
+                    pool.syzygy = None;
+                }
+                else
+                {
+                    let tablebase = Syzygy_Tablebase::open(&value);
+                    println!("info string found {} tablebases", tablebase.table_count());
+                    pool.syzygy = Some(Arc::new(tablebase));
+                }
+            },
+            _                => println!("info string unknown option: {}", name),
+        }
+    }
+
+    // Puts the Fairy_Board of the fairy variant being played, if any, back to its start position
+    fn start_fairy_variant(&mut self)
+    {
+        if let Some((fairy, board)) = self.fairy.as_mut()
+        {
+            *board = Fairy_Board::from_fen(fairy.start_fen).unwrap();
+        }
+    }
+
+    // position [startpos | fen <fen>] [moves <move>...]
+    fn set_position(&mut self, tokens: &[&str])
+    {
+        let moves_index = tokens.iter().position(|&token| token == "moves").unwrap_or(tokens.len());
+
+        if let Some((fairy, fairy_board)) = self.fairy.as_mut()
+        {
+            let board = match tokens.first()
+            {
+                Some(&"startpos") => Fairy_Board::from_fen(fairy.start_fen),
+                Some(&"fen")      => Fairy_Board::from_fen(&tokens[1..moves_index].join(" ")),
+                _                 => Err(String::from("expected startpos or fen")),
+            };
+
+            let mut board = match board
+            {
+                Ok(board) => board,
+                Err(error) =>
+                {
+                    println!("info string invalid position: {}", error);
+                    return;
+                },
+            };
+
+            for &text in tokens.iter().skip(moves_index + 1)
+            {
+                match board.parse_uci_move(text)
+                {
+                    Some(next_move) => board.make_move(next_move),
+                    None =>
+                    {
+                        println!("info string illegal move: {}", text);
+                        return;
+                    },
+                }
+            }
+
+            *fairy_board = board;
+            return;
+        }
+
+        let board = match tokens.first()
+        {
+            Some(&"startpos") => Ok(Chess_Board::with_rules(self.variant)),
+            Some(&"fen")      => Chess_Board::from_variant_fen(&tokens[1..moves_index].join(" "), self.variant),
+            _                 => Err(String::from("expected startpos or fen")),
+        };
+
+        let mut board = match board
+        {
+            Ok(mut board) =>
+            {
+                board.chess960 = self.chess960;
+                board
+            },
+            Err(error) =>
+            {
+                println!("info string invalid position: {}", error);
+                return;
+            },
+        };
+
+        for &text in tokens.iter().skip(moves_index + 1)
+        {
+            match board.parse_uci_move(text)
+            {
+                Some(next_move) => board.make_move(next_move),
+                None =>
+                {
+                    println!("info string illegal move: {}", text);
+                    return;
+                },
+            }
+        }
+
+        self.board = board;
+    }
+
+    // go [wtime <ms>] [btime <ms>] [winc <ms>] [binc <ms>] [movestogo <n>] [movetime <ms>]
+    //    [depth <n>] [nodes <n>] [infinite] [ponder]
+    fn go(&mut self, tokens: &[&str])
+    {
+        self.stop_search();
+
+        let mut limits = Search_Limits::default();
+        let mut clock = [None, None];
+        let mut increment = [0, 0];
+        let mut moves_to_go = None;
+        let mut move_time = None;
+        let mut infinite = false;
+        let mut ponder = false;
+
+        let mut t_idx = 0;
+
+        while t_idx < tokens.len()
+        {
+            let value = tokens.get(t_idx + 1).and_then(|value| value.parse::<u64>().ok());
+
+            match tokens[t_idx]
+            {
+                "wtime"     => clock[0] = value,
+                "btime"     => clock[1] = value,
+                "winc"      => increment[0] = value.unwrap_or(0),
+                "binc"      => increment[1] = value.unwrap_or(0),
+                "movestogo" => moves_to_go = value,
+                "movetime"  => move_time = value,
+                "depth"     => limits.depth = value.map(|depth| depth as u32),
+                "nodes"     => limits.nodes = value,
+                "infinite"  =>
+                {
+                    infinite = true;
+                    t_idx += 1;
+                    continue;
+                },
+                "ponder"    =>
+                {
+                    ponder = true;
+                    t_idx += 1;
+                    continue;
+                },
+                _           =>
+                {
+                    t_idx += 1;
+                    continue;
+                },
+            }
+
+            t_idx += 2;
+        }
+
+        let side_to_move = match &self.fairy
+        {
+            Some((_, board)) => board.details.side_to_move,
+            None             => self.board.details.side_to_move,
+        };
+
+        let side = if side_to_move == WHITE { 0 } else { 1 };
+
+        if clock[side].is_some() || move_time.is_some()
+        {
+            limits.time = Some(Time_Controls
+            {
+                remaining: clock[side].unwrap_or(0),
+                increment: increment[side],
+                moves_to_go,
+                move_time,
+            });
+        }
+
+        // The book only knows positions of normal chess
+        let book = self.book.as_ref().filter(|_| self.own_book && self.fairy.is_none() && self.board.is_standard() && !ponder && !infinite);
+
+        if let Some(book_move) = book.and_then(|book| book.select_move(&mut self.board.clone(), self.book_selection))
+        {
+            println!("bestmove {}", self.board.move_to_uci(book_move));
+            return;
+        }
+
+        self.ponder.store(ponder, Ordering::Relaxed);
+        self.waiting.store(ponder || infinite, Ordering::Relaxed);
+
+        let mut pool = self.pool.take().unwrap();
+        let waiting = self.waiting.clone();
+
+        // The fairy search has no pondering, its move is held back until "ponderhit" the same way
+        if let Some((_, fairy_board)) = &self.fairy
+        {
+            let board = fairy_board.clone();
+
+            self.search = Some(std::thread::spawn(move || {
+                let mut search = Fairy_Search::new(&board);
+                search.stop = pool.stop.clone();
+                search.move_overhead = pool.move_overhead;
+
+                let result = search.search(&limits, &mut |info| {
+                    let pv: Vec<String> = info.pv.iter().map(|pv_move| board.move_to_uci(*pv_move)).collect();
+                    print_info_line(info, 1, info.score, &pv);
+                });
+
+                while waiting.load(Ordering::Relaxed)
+                {
+                    std::thread::sleep(Duration::from_millis(1));
+                }
+
+                match result.best_move()
+                {
+                    Some(best_move) => println!("bestmove {}", board.move_to_uci(best_move)),
+                    None            => println!("bestmove 0000"),
+                }
+
+                return pool;
+            }));
+
+            return;
+        }
+
+        let board = self.board.clone();
+
+        self.search = Some(std::thread::spawn(move || {
+            let result = pool.search(&board, &limits, &mut |info| print_info(&board, info));
+            println!("info hashfull {}", pool.hashfull());
+
+            while waiting.load(Ordering::Relaxed)
+            {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+
+            match (result.pv.first(), result.pv.get(1))
+            {
+                (Some(best_move), Some(ponder_move)) => println!("bestmove {} ponder {}", board.move_to_uci(*best_move), board.move_to_uci(*ponder_move)),
+                (Some(best_move), None)              => println!("bestmove {}", board.move_to_uci(*best_move)),
+                _                                    => println!("bestmove 0000"),
+            }
+
+            return pool;
+        }));
+    }
+}
+
+// Prints one info line for every line of the search
+fn print_info(board: &Chess_Board, info: &Search_Info)
+{
+    for (l_idx, line) in info.lines.iter().enumerate()
+    {
+        let pv: Vec<String> = line.pv.iter().map(|pv_move| board.move_to_uci(*pv_move)).collect();
+
+        print_info_line(info, l_idx + 1, line.score, &pv);
+    }
+}
+
+fn print_info_line(info: &Search_Info, multi_pv: usize, score: i32, pv: &[String])
+{
+    let nps = (info.nodes * 1000).checked_div(info.time).unwrap_or(0);
+
+    println!("info depth {} multipv {} score {} nodes {} nps {} time {} tbhits {} pv {}",
+             info.depth, multi_pv, score_to_uci(score), info.nodes, nps, info.time, info.tb_hits,
+             pv.join(" "));
+}
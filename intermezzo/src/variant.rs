@@ -0,0 +1,262 @@
+// Rule variants
+//
+// Everything that differs between variants of chess goes through the Rules trait: the starting
+// position, move generation, making and taking back moves and how a game ends. A Chess_Board holds
+// a reference to the rules it is played with and calls the trait for these, so a variant only has
+// to override the parts that differ and everything else (search, notation, games) keeps working
+//
+// The default implementations of the trait are the rules of normal chess, Standard_Chess does not
+// override anything. The standard_* functions of Chess_Board are the normal rules, variants can
+// call them and change the result, e.g. filter the legal moves
+//
+// Rules are stateless, anything a variant needs to remember about a position has to be stored
+// within the board so that it is taken back together with the moves
+//
+// Adding a variant:
+// 1. a unit struct implementing Rules with a static instance
+// 2. an entry within VARIANTS so it can be found by name (UCI_Variant, the PGN Variant tag)
+
+use crate::antichess::*;
+use crate::atomic::*;
+use crate::chess::*;
+use crate::crazyhouse::*;
+use crate::draw::Draw_Rule;
+use crate::duck::*;
+use crate::fog_of_war::*;
+use crate::horde::*;
+use crate::king_of_the_hill::*;
+use crate::notation::START_FEN;
+use crate::polyglot::polyglot_key;
+use crate::racing_kings::*;
+use crate::three_check::*;
+
+// How a game ended according to the rules, from the point of view of the position
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Outcome
+{
+    // The side to move is checkmated
+    Checkmate,
+    // The side to move has no legal moves but is not in check
+    Stalemate,
+    // A win for the given color because of a rule of the variant, e.g. a king reaching the center
+    Variant_Win(i8),
+    // A draw because of a rule of the variant
+    Variant_Draw,
+    // A draw that ends the game immediately (draw.rs), only used for a Fairy_Board, the draws of a
+    // Chess_Board are kept by Game
+    Draw(Draw_Rule),
+}
+
+impl Outcome
+{
+    // The winning color or 0 for a draw, the side to move is needed for checkmate
+    pub fn winner(self, side_to_move: i8) -> i8
+    {
+        return match self
+        {
+            Outcome::Checkmate          => -side_to_move,
+            Outcome::Variant_Win(color) => color,
+            _                           => 0,
+        };
+    }
+}
+
+pub trait Rules: Sync
+{
+    // The name as used within the PGN Variant tag, e.g. "King of the Hill"
+    fn name(&self) -> &'static str;
+
+    // Other names the variant is known by, e.g. for UCI_Variant
+    fn aliases(&self) -> &'static [&'static str]
+    {
+        return &[];
+    }
+
+    fn start_fen(&self) -> &'static str
+    {
+        return START_FEN;
+    }
+
+    // Reading and writing FEN, variants that store more about a position than normal chess add
+    // their own fields
+    fn parse_fen(&self, fen: &str) -> Result<Chess_Board, String>
+    {
+        return Chess_Board::from_fen(fen);
+    }
+
+    fn write_fen(&self, board: &Chess_Board, shredder: bool) -> String
+    {
+        return board.standard_fen(shredder);
+    }
+
+    // Mixed into the key of a position within the search, for whatever the variant stores about
+    // a position that is not part of the Polyglot key
+    fn variant_key(&self, _board: &Chess_Board) -> u64
+    {
+        return 0;
+    }
+
+    // Move generation hooks, legal_moves is expected to make and take back the candidates with
+    // the make_move and unmake_move of the board so that the changes of the variant are included
+    // legal_moves is not called once variant_outcome ends the game
+    fn pseudo_legal_moves(&self, board: &Chess_Board, color: i8) -> Vec<Move>
+    {
+        return board.standard_pseudo_legal_moves(color);
+    }
+
+    fn legal_moves(&self, board: &mut Chess_Board) -> Vec<Move>
+    {
+        return board.standard_legal_moves();
+    }
+
+    fn make_move(&self, board: &mut Chess_Board, new_move: Move)
+    {
+        board.standard_make_move(new_move);
+    }
+
+    fn unmake_move(&self, board: &mut Chess_Board)
+    {
+        board.standard_unmake_move();
+    }
+
+    // Whether the king of the given color is in check, a king that is not on the board is not
+    fn in_check(&self, board: &Chess_Board, color: i8) -> bool
+    {
+        return board.standard_in_check(color);
+    }
+
+    // A game that is over before looking at the moves of the side to move, e.g. because the other
+    // side reached a goal. Checked in every position, so it should be cheap
+    fn variant_outcome(&self, _board: &Chess_Board) -> Option<Outcome>
+    {
+        return None;
+    }
+
+    // The result when the side to move has no legal moves
+    fn no_moves_outcome(&self, board: &Chess_Board) -> Outcome
+    {
+        return if board.in_check(board.details.side_to_move) { Outcome::Checkmate } else { Outcome::Stalemate };
+    }
+
+    // Whether positions without enough material to checkmate are draws (insufficient material,
+    // dead positions), not the case for variants that are not won by checkmate
+    fn material_draws(&self) -> bool
+    {
+        return true;
+    }
+}
+
+// Normal chess, everything uses the default implementations
+pub struct Standard_Chess;
+
+impl Rules for Standard_Chess
+{
+    fn name(&self) -> &'static str
+    {
+        return "Standard";
+    }
+
+    fn aliases(&self) -> &'static [&'static str]
+    {
+        return &["chess", "normal"];
+    }
+}
+
+pub static STANDARD: Standard_Chess = Standard_Chess;
+
+// Every variant that can be played, the first one is the default
+pub static VARIANTS: [&dyn Rules; 11] = [
+    &STANDARD,
+    &KING_OF_THE_HILL,
+    &THREE_CHECK,
+    &RACING_KINGS,
+    &CRAZYHOUSE,
+    &BUGHOUSE,
+    &ATOMIC,
+    &ANTICHESS,
+    &HORDE,
+    &FOG_OF_WAR,
+    &DUCK_CHESS,
+];
+
+// The name of a variant without spaces, dashes and uppercase letters, as used by UCI
+// "King of the Hill" -> "kingofthehill"
+pub fn variant_key_name(name: &str) -> String
+{
+    return name.chars()
+               .filter(|letter| letter.is_ascii_alphanumeric())
+               .map(|letter| letter.to_ascii_lowercase())
+               .collect();
+}
+
+// Finds a variant by its name or one of its aliases, ignoring case, spaces and dashes
+// "King of the Hill", "king-of-the-hill" and "kingofthehill" are all the same
+pub fn find_variant(name: &str) -> Option<&'static dyn Rules>
+{
+    let name = variant_key_name(name);
+
+    return VARIANTS.iter()
+                   .find(|rules| {
+                       return    variant_key_name(rules.name()) == name
+                              || rules.aliases().iter().any(|&alias| variant_key_name(alias) == name);
+                   })
+                   .copied();
+}
+
+impl Chess_Board
+{
+    // The starting position of a variant
+    pub fn with_rules(rules: &'static dyn Rules) -> Self
+    {
+        return Self::from_variant_fen(rules.start_fen(), rules).unwrap();
+    }
+
+    // A position of a variant, from_fen only reads normal FEN
+    pub fn from_variant_fen(fen: &str, rules: &'static dyn Rules) -> Result<Self, String>
+    {
+        let mut new_board = rules.parse_fen(fen)?;
+        new_board.rules = rules;
+
+        let color = new_board.details.side_to_move;
+        new_board.details.is_check = if new_board.in_check(color) { -color } else { 0 };
+
+        return Ok(new_board);
+    }
+
+    // The key of the position used by the search for the transposition table and repetitions
+    pub fn position_key(&self) -> u64
+    {
+        return polyglot_key(self) ^ self.rules.variant_key(self);
+    }
+
+    pub fn is_standard(&self) -> bool
+    {
+        return self.rules.name() == STANDARD.name();
+    }
+
+    // Whether the game is over in the current position and how
+    pub fn outcome(&mut self) -> Option<Outcome>
+    {
+        if let Some(outcome) = self.variant_outcome()
+        {
+            return Some(outcome);
+        }
+
+        if self.legal_moves().is_empty()
+        {
+            return Some(self.rules.no_moves_outcome(self));
+        }
+
+        return None;
+    }
+
+    pub fn variant_outcome(&self) -> Option<Outcome>
+    {
+        return self.rules.variant_outcome(self);
+    }
+
+    pub fn no_moves_outcome(&self) -> Outcome
+    {
+        return self.rules.no_moves_outcome(self);
+    }
+}
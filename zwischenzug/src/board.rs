@@ -51,6 +51,142 @@ impl Chess_Board
     }
 }
 
+// Options for drawing the board as text with Chess_Board::render
+// figurines    -> Unicode chess pieces instead of letters
+// colors       -> light and dark squares as ANSI background colors instead of a grid
+// coordinates  -> rank numbers on the left and files below the board
+// flipped      -> black at the bottom
+// last_move    -> the origin and target of a move to highlight, the board does not keep its moves
+// check        -> the square of a king in check to highlight
+#[derive(Clone, Copy, Default)]
+pub struct Render_Options
+{
+    pub figurines:   bool,
+    pub colors:      bool,
+    pub coordinates: bool,
+    pub flipped:     bool,
+    pub last_move:   Option<(Square, Square)>,
+    pub check:       Option<Square>,
+}
+
+impl Chess_Board
+{
+    // Draws the board into a String, with the default options it looks like this:
+    // + --- + --- + --- + --- + --- + --- + --- + --- +
+    // |  r  |  n  |  b  |  q  |  k  |  b  |  n  |  r  |
+    // + --- + --- + --- + --- + --- + --- + --- + --- +
+    // ...
+    // Without colors the last move is drawn as "[ P ]" and the king in check as "! K !"
+    pub fn render(&self, options: &Render_Options) -> String
+    {
+        let rows: Vec<usize> = if options.flipped { (0..8).rev().collect() } else { (0..8).collect() };
+        let cols: Vec<usize> = if options.flipped { (0..8).rev().collect() } else { (0..8).collect() };
+
+        let grid_line = if options.coordinates { "  +" } else { "+" }.to_string() + &" --- +".repeat(8) + "\n";
+        let mut result = String::new();
+
+        if !options.colors
+        {
+            result.push_str(&grid_line);
+        }
+
+        for &row in &rows
+        {
+            // Row 0 is the 8th rank
+            if options.coordinates
+            {
+                result.push_str(&format!("{} ", 8 - row));
+            }
+
+            if !options.colors
+            {
+                result.push('|');
+            }
+
+            for &col in &cols
+            {
+                let square = Square::new(row, col);
+                let piece = self[square];
+                let glyph = piece.to_char(options.figurines && !options.colors, options.figurines);
+
+                let moved = options.last_move.is_some_and(|(origin, target)| origin == square || target == square);
+                let checked = options.check.is_some_and(|king| king == square);
+
+                if options.colors
+                {
+                    let light = (row + col) % 2 == 0;
+
+                    let background = if checked
+                    {
+                        160
+                    } else if moved
+                    {
+                        if light { 186 } else { 143 }
+                    } else
+                    {
+                        if light { 180 } else { 137 }
+                    };
+
+                    let foreground = match piece.color
+                    {
+                        Player::White => "\x1b[1;38;5;231m",
+                        _             => "\x1b[38;5;16m",
+                    };
+
+                    result.push_str(&format!("\x1b[48;5;{}m{} {} \x1b[0m", background, foreground, glyph));
+                    continue;
+                }
+
+                let cell = if checked
+                {
+                    format!("! {} !", glyph)
+                } else if moved
+                {
+                    format!("[ {} ]", glyph)
+                } else
+                {
+                    format!("  {}  ", glyph)
+                };
+
+                result.push_str(&cell);
+                result.push('|');
+            }
+
+            result.push('\n');
+
+            if !options.colors
+            {
+                result.push_str(&grid_line);
+            }
+        }
+
+        if options.coordinates
+        {
+            let width = if options.colors { 3 } else { 6 };
+            result.push_str(if options.colors { "  " } else { "   " });
+
+            for &col in &cols
+            {
+                result.push_str(&format!("{:^width$}", (b'a' + col as u8) as char, width = width));
+            }
+
+            result.push('\n');
+        }
+
+        return result;
+    }
+}
+
+// Allows the board to be printed directly with println!("{}", board), as a grid with coordinates
+impl std::fmt::Display for Chess_Board
+{
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result
+    {
+        let options = Render_Options { coordinates: true, ..Default::default() };
+        return write!(formatter, "{}", self.render(&options));
+    }
+}
+
 // Implements the operator overload for indexing
 // Allows for the following
 // sample_board[(2, 1)]
@@ -33,6 +33,7 @@ impl Player
 }
 
 // Square object representing a square on the chess board
+#[derive(Clone, Copy)]
 pub struct Square
 {
     pub row: usize,
@@ -6,6 +6,9 @@
 #![allow(dead_code)]
 #![allow(unused)]
 #![allow(non_camel_case_types)]
+#![allow(clippy::needless_return)]
+#![allow(clippy::partialeq_ne_impl)]
+#![allow(clippy::suspicious_else_formatting)]
 
 use board::piece::chess_core::*;
 use board::piece::*;
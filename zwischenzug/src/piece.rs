@@ -32,6 +32,37 @@ impl Piece
         return Piece { piece_type, color, has_moved: false };
     }
 
+    // The letter of the piece, uppercase for white, or its Unicode figurine
+    // With outlined the white figurines are the outlined ones, otherwise both colors use the
+    // filled figurines and the color has to be shown some other way
+    pub fn to_char(self, outlined: bool, figurine: bool) -> char
+    {
+        let index = match self.piece_type
+        {
+            Piece_Type::King   => 0,
+            Piece_Type::Queen  => 1,
+            Piece_Type::Rook   => 2,
+            Piece_Type::Bishop => 3,
+            Piece_Type::Knight => 4,
+            Piece_Type::Pawn   => 5,
+            Piece_Type::Empty  => return ' ',
+        };
+
+        let white = matches!(self.color, chess_core::Player::White);
+
+        if figurine
+        {
+            let outlined_figurines = ['♔', '♕', '♖', '♗', '♘', '♙'];
+            let filled_figurines   = ['♚', '♛', '♜', '♝', '♞', '♟'];
+
+            return if white && outlined { outlined_figurines[index] } else { filled_figurines[index] };
+        }
+
+        let letter = ['k', 'q', 'r', 'b', 'n', 'p'][index];
+
+        return if white { letter.to_ascii_uppercase() } else { letter };
+    }
+
     // Generating moves based on the piece type
     pub fn generate_moves()
     {